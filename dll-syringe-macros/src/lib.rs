@@ -0,0 +1,114 @@
+//! Derive macros for [`dll-syringe`](https://docs.rs/dll-syringe).
+//!
+//! This crate should not be used directly. Enable the `process-memory` feature of `dll-syringe` instead.
+
+#![warn(missing_docs, rust_2018_idioms)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Error, Index, Member, Meta, NestedMeta,
+};
+
+/// Derives `RemoteStruct` for a `#[repr(C)]` or `#[repr(transparent)]` struct.
+///
+/// This generates a companion struct named `<Name>Fields` with the same visibility as the struct,
+/// containing a `FieldOffset` for each field. Tuple struct fields are named `_0`, `_1`, ...
+#[proc_macro_derive(RemoteStruct)]
+pub fn derive_remote_struct(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_remote_struct(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_remote_struct(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "RemoteStruct can only be derived for structs",
+            ))
+        }
+    };
+    if !has_defined_layout(&input.attrs) {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "RemoteStruct requires a defined layout, add `#[repr(C)]` or `#[repr(transparent)]`",
+        ));
+    }
+
+    let vis = &input.vis;
+    let name = &input.ident;
+    let fields_name = format_ident!("{}Fields", name);
+    let generics = &input.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let field_offset = quote!(::dll_syringe::process::memory::FieldOffset);
+
+    let mut declarations = Vec::new();
+    let mut initializers = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let (ident, member) = match &field.ident {
+            Some(ident) => (ident.clone(), Member::Named(ident.clone())),
+            None => (format_ident!("_{}", i), Member::Unnamed(Index::from(i))),
+        };
+        let field_vis = &field.vis;
+        let ty = &field.ty;
+        let doc = format!("The offset of the field `{}`.", quote!(#member));
+        declarations.push(quote! {
+            #[doc = #doc]
+            #field_vis #ident: #field_offset<#name #ty_generics, #ty>
+        });
+        initializers.push(quote! {
+            #ident: unsafe {
+                #field_offset::new_unchecked(
+                    ::core::ptr::addr_of!((*base).#member) as usize - base as usize
+                )
+            }
+        });
+    }
+
+    let doc = format!(
+        "The field offsets of [`{}`] used for projecting remote pointers.",
+        name
+    );
+    Ok(quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone, Copy)]
+        #vis struct #fields_name #generics #where_clause {
+            #(#declarations,)*
+        }
+
+        unsafe impl #impl_generics ::dll_syringe::process::memory::RemoteStruct for #name #ty_generics #where_clause {
+            type Fields = #fields_name #ty_generics;
+
+            fn fields() -> Self::Fields {
+                let uninit = ::core::mem::MaybeUninit::<Self>::uninit();
+                let base = uninit.as_ptr();
+                #fields_name {
+                    #(#initializers,)*
+                }
+            }
+        }
+    })
+}
+
+/// Returns whether the given attributes contain `#[repr(C)]` or `#[repr(transparent)]`.
+fn has_defined_layout(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("repr"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .any(|meta| match meta {
+            NestedMeta::Meta(Meta::Path(path)) => {
+                path.is_ident("C") || path.is_ident("transparent")
+            }
+            _ => false,
+        })
+}
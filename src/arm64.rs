@@ -0,0 +1,395 @@
+//! A minimal encoder for the subset of `AArch64` instructions needed by the generated stubs.
+//!
+//! In contrast to `iced-x86` for `x86` and `x64`, there is no suitable assembler crate for `ARM64`.
+
+/// A 64-bit general purpose register (`x0`-`x30`) or the stack pointer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct XReg(u8);
+
+/// A 64-bit floating point register (`d0`-`d31`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DReg(u8);
+
+#[allow(dead_code)]
+pub(crate) mod registers {
+    use super::{DReg, XReg};
+
+    pub(crate) const X0: XReg = XReg(0);
+    pub(crate) const X1: XReg = XReg(1);
+    pub(crate) const X2: XReg = XReg(2);
+    pub(crate) const X3: XReg = XReg(3);
+    pub(crate) const X4: XReg = XReg(4);
+    pub(crate) const X5: XReg = XReg(5);
+    pub(crate) const X6: XReg = XReg(6);
+    pub(crate) const X7: XReg = XReg(7);
+    pub(crate) const X9: XReg = XReg(9);
+    pub(crate) const X10: XReg = XReg(10);
+    /// Intra-procedure-call scratch register.
+    pub(crate) const X16: XReg = XReg(16);
+    /// Intra-procedure-call scratch register.
+    pub(crate) const X17: XReg = XReg(17);
+    /// Callee-saved register.
+    pub(crate) const X19: XReg = XReg(19);
+    /// Callee-saved register.
+    pub(crate) const X20: XReg = XReg(20);
+    /// Callee-saved register.
+    pub(crate) const X21: XReg = XReg(21);
+    /// Callee-saved register.
+    pub(crate) const X22: XReg = XReg(22);
+    /// Frame pointer.
+    pub(crate) const X29: XReg = XReg(29);
+    /// Link register.
+    pub(crate) const X30: XReg = XReg(30);
+    /// Stack pointer (shares its encoding with the zero register, which is not supported).
+    pub(crate) const SP: XReg = XReg(31);
+
+    /// Integer argument and result registers of the AAPCS64 calling convention.
+    pub(crate) const ARGUMENT_REGISTERS: [XReg; 8] = [X0, X1, X2, X3, X4, X5, X6, X7];
+
+    pub(crate) const D0: DReg = DReg(0);
+    pub(crate) const D1: DReg = DReg(1);
+    pub(crate) const D2: DReg = DReg(2);
+    pub(crate) const D3: DReg = DReg(3);
+    pub(crate) const D4: DReg = DReg(4);
+    pub(crate) const D5: DReg = DReg(5);
+    pub(crate) const D6: DReg = DReg(6);
+    pub(crate) const D7: DReg = DReg(7);
+
+    /// Floating point argument and result registers of the AAPCS64 calling convention.
+    pub(crate) const FLOAT_ARGUMENT_REGISTERS: [DReg; 8] = [D0, D1, D2, D3, D4, D5, D6, D7];
+}
+
+/// A branch target in the code of an [`Arm64Assembler`].
+#[derive(Debug)]
+pub(crate) struct Label(usize);
+
+#[derive(Debug, Clone, Copy)]
+enum BranchKind {
+    /// `b`, with a 26-bit offset.
+    Unconditional,
+    /// `cbz`/`cbnz`, with a 19-bit offset.
+    Compare,
+}
+
+/// Assembles `AArch64` instructions into position independent code.
+///
+/// Invalid operands (e.g. an unaligned or out of range offset) are considered bugs and cause a panic.
+#[derive(Debug, Default)]
+pub(crate) struct Arm64Assembler {
+    instructions: Vec<u32>,
+    labels: Vec<Option<usize>>,
+    branches: Vec<(usize, usize, BranchKind)>,
+}
+
+#[allow(dead_code)]
+impl Arm64Assembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn emit(&mut self, instruction: u32) {
+        self.instructions.push(instruction);
+    }
+
+    /// Creates a new label that has to be bound using [`Arm64Assembler::set_label`] before assembling.
+    pub fn create_label(&mut self) -> Label {
+        self.labels.push(None);
+        Label(self.labels.len() - 1)
+    }
+
+    /// Binds the given label to the next emitted instruction.
+    pub fn set_label(&mut self, label: &Label) {
+        let target = &mut self.labels[label.0];
+        assert!(target.is_none(), "label bound twice");
+        *target = Some(self.instructions.len());
+    }
+
+    /// `mov xd, xn` (also supports `sp` as either operand)
+    pub fn mov(&mut self, rd: XReg, rn: XReg) {
+        if rd == registers::SP || rn == registers::SP {
+            self.add(rd, rn, 0);
+        } else {
+            // orr xd, xzr, xn
+            self.emit(0xAA00_03E0 | u32::from(rn.0) << 16 | u32::from(rd.0));
+        }
+    }
+
+    /// Loads the given 64-bit immediate into the given register.
+    ///
+    /// Always emits a `movz` followed by three `movk`, so that the length of the code does not depend on the value.
+    pub fn mov_imm64(&mut self, rd: XReg, imm: u64) {
+        assert_ne!(rd, registers::SP);
+        for shift in 0..4 {
+            let opcode = if shift == 0 { 0xD280_0000 } else { 0xF280_0000 };
+            let imm16 = (imm >> (shift * 16)) as u16;
+            self.emit(opcode | shift << 21 | u32::from(imm16) << 5 | u32::from(rd.0));
+        }
+    }
+
+    /// `mov wd, #imm` (zero extends into `xd`)
+    pub fn mov_imm32(&mut self, rd: XReg, imm: u16) {
+        assert_ne!(rd, registers::SP);
+        // movz wd, #imm
+        self.emit(0x5280_0000 | u32::from(imm) << 5 | u32::from(rd.0));
+    }
+
+    /// `add xd, xn, #imm`
+    pub fn add(&mut self, rd: XReg, rn: XReg, imm: u32) {
+        assert!(imm < (1 << 12), "immediate out of range");
+        self.emit(0x9100_0000 | imm << 10 | u32::from(rn.0) << 5 | u32::from(rd.0));
+    }
+
+    /// `sub xd, xn, #imm`
+    pub fn sub(&mut self, rd: XReg, rn: XReg, imm: u32) {
+        assert!(imm < (1 << 12), "immediate out of range");
+        self.emit(0xD100_0000 | imm << 10 | u32::from(rn.0) << 5 | u32::from(rd.0));
+    }
+
+    /// `ldr xt, [xn, #offset]`
+    pub fn ldr(&mut self, rt: XReg, rn: XReg, offset: u32) {
+        self.emit(
+            0xF940_0000
+                | Self::scaled_offset(offset) << 10
+                | u32::from(rn.0) << 5
+                | u32::from(rt.0),
+        );
+    }
+
+    /// `str xt, [xn, #offset]`
+    pub fn str(&mut self, rt: XReg, rn: XReg, offset: u32) {
+        self.emit(
+            0xF900_0000
+                | Self::scaled_offset(offset) << 10
+                | u32::from(rn.0) << 5
+                | u32::from(rt.0),
+        );
+    }
+
+    /// `ldr dt, [xn, #offset]`
+    pub fn ldr_d(&mut self, rt: DReg, rn: XReg, offset: u32) {
+        self.emit(
+            0xFD40_0000
+                | Self::scaled_offset(offset) << 10
+                | u32::from(rn.0) << 5
+                | u32::from(rt.0),
+        );
+    }
+
+    fn scaled_offset(offset: u32) -> u32 {
+        assert!(offset & 7 == 0, "offset is not 8 byte aligned");
+        assert!(offset / 8 < (1 << 12), "offset out of range");
+        offset / 8
+    }
+
+    /// `fmov xd, dn`
+    pub fn fmov_from_d(&mut self, rd: XReg, rn: DReg) {
+        assert_ne!(rd, registers::SP);
+        self.emit(0x9E66_0000 | u32::from(rn.0) << 5 | u32::from(rd.0));
+    }
+
+    /// `stp xt1, xt2, [xn, #offset]!`
+    pub fn stp_pre_index(&mut self, rt1: XReg, rt2: XReg, rn: XReg, offset: i32) {
+        self.emit(
+            0xA980_0000
+                | Self::scaled_pair_offset(offset) << 15
+                | u32::from(rt2.0) << 10
+                | u32::from(rn.0) << 5
+                | u32::from(rt1.0),
+        );
+    }
+
+    /// `ldp xt1, xt2, [xn], #offset`
+    pub fn ldp_post_index(&mut self, rt1: XReg, rt2: XReg, rn: XReg, offset: i32) {
+        self.emit(
+            0xA8C0_0000
+                | Self::scaled_pair_offset(offset) << 15
+                | u32::from(rt2.0) << 10
+                | u32::from(rn.0) << 5
+                | u32::from(rt1.0),
+        );
+    }
+
+    fn scaled_pair_offset(offset: i32) -> u32 {
+        assert!(offset & 7 == 0, "offset is not 8 byte aligned");
+        assert!((-64..64).contains(&(offset / 8)), "offset out of range");
+        (offset / 8) as u32 & 0x7F
+    }
+
+    /// `blr xn`
+    pub fn blr(&mut self, rn: XReg) {
+        self.emit(0xD63F_0000 | u32::from(rn.0) << 5);
+    }
+
+    /// `ret`
+    pub fn ret(&mut self) {
+        self.emit(0xD65F_03C0);
+    }
+
+    /// `b label`
+    pub fn b(&mut self, label: &Label) {
+        self.branches
+            .push((self.instructions.len(), label.0, BranchKind::Unconditional));
+        self.emit(0x1400_0000);
+    }
+
+    /// `cbz xt, label`
+    pub fn cbz(&mut self, rt: XReg, label: &Label) {
+        assert_ne!(rt, registers::SP);
+        self.branches
+            .push((self.instructions.len(), label.0, BranchKind::Compare));
+        self.emit(0xB400_0000 | u32::from(rt.0));
+    }
+
+    /// `cbnz xt, label`
+    pub fn cbnz(&mut self, rt: XReg, label: &Label) {
+        assert_ne!(rt, registers::SP);
+        self.branches
+            .push((self.instructions.len(), label.0, BranchKind::Compare));
+        self.emit(0xB500_0000 | u32::from(rt.0));
+    }
+
+    /// Resolves all branches and returns the encoded instructions.
+    ///
+    /// # Panics
+    /// This method panics if a used label was never bound.
+    pub fn assemble(mut self) -> Vec<u8> {
+        for &(index, label, kind) in &self.branches {
+            let target = self.labels[label].expect("label was never bound");
+            let offset = target as i64 - index as i64;
+            self.instructions[index] |= match kind {
+                BranchKind::Unconditional => {
+                    assert!((-(1 << 25)..(1 << 25)).contains(&offset));
+                    offset as u32 & 0x03FF_FFFF
+                }
+                BranchKind::Compare => {
+                    assert!((-(1 << 18)..(1 << 18)).contains(&offset));
+                    (offset as u32 & 0x7_FFFF) << 5
+                }
+            };
+        }
+
+        self.instructions
+            .iter()
+            .flat_map(|instruction| instruction.to_le_bytes())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{registers::*, Arm64Assembler};
+
+    // The expected encodings were generated using `llvm-mc -triple=aarch64 -show-encoding`.
+
+    fn assemble(f: impl FnOnce(&mut Arm64Assembler)) -> Vec<u8> {
+        let mut asm = Arm64Assembler::new();
+        f(&mut asm);
+        asm.assemble()
+    }
+
+    #[test]
+    fn encodes_moves() {
+        assert_eq!(assemble(|asm| asm.mov(X9, X0)), [0xE9, 0x03, 0x00, 0xAA]);
+        assert_eq!(assemble(|asm| asm.mov(X29, SP)), [0xFD, 0x03, 0x00, 0x91]);
+        assert_eq!(assemble(|asm| asm.mov(SP, X29)), [0xBF, 0x03, 0x00, 0x91]);
+        assert_eq!(
+            assemble(|asm| asm.mov_imm32(X0, 0)),
+            [0x00, 0x00, 0x80, 0x52]
+        );
+        assert_eq!(
+            assemble(|asm| asm.mov_imm64(X16, 0xDEF0_9ABC_5678_1234)),
+            [
+                0x90, 0x46, 0x82, 0xD2, // movz x16, #0x1234
+                0x10, 0xCF, 0xAA, 0xF2, // movk x16, #0x5678, lsl #16
+                0x90, 0x57, 0xD3, 0xF2, // movk x16, #0x9abc, lsl #32
+                0x10, 0xDE, 0xFB, 0xF2, // movk x16, #0xdef0, lsl #48
+            ]
+        );
+        assert_eq!(
+            assemble(|asm| asm.fmov_from_d(X0, D0)),
+            [0x00, 0x00, 0x66, 0x9E]
+        );
+    }
+
+    #[test]
+    fn mov_imm64_has_fixed_length() {
+        assert_eq!(assemble(|asm| asm.mov_imm64(X17, 0)).len(), 16);
+        assert_eq!(assemble(|asm| asm.mov_imm64(X17, u64::MAX)).len(), 16);
+    }
+
+    #[test]
+    fn encodes_arithmetic() {
+        assert_eq!(
+            assemble(|asm| asm.sub(SP, SP, 32)),
+            [0xFF, 0x83, 0x00, 0xD1]
+        );
+        assert_eq!(
+            assemble(|asm| asm.add(SP, SP, 32)),
+            [0xFF, 0x83, 0x00, 0x91]
+        );
+    }
+
+    #[test]
+    fn encodes_loads_and_stores() {
+        assert_eq!(assemble(|asm| asm.ldr(X1, X0, 8)), [0x01, 0x04, 0x40, 0xF9]);
+        assert_eq!(assemble(|asm| asm.ldr(X0, X0, 0)), [0x00, 0x00, 0x40, 0xF9]);
+        assert_eq!(
+            assemble(|asm| asm.str(X0, X17, 0)),
+            [0x20, 0x02, 0x00, 0xF9]
+        );
+        assert_eq!(
+            assemble(|asm| asm.str(X10, SP, 24)),
+            [0xEA, 0x0F, 0x00, 0xF9]
+        );
+        assert_eq!(
+            assemble(|asm| asm.ldr_d(D7, X9, 88)),
+            [0x27, 0x2D, 0x40, 0xFD]
+        );
+        assert_eq!(
+            assemble(|asm| asm.stp_pre_index(X29, X30, SP, -16)),
+            [0xFD, 0x7B, 0xBF, 0xA9]
+        );
+        assert_eq!(
+            assemble(|asm| asm.ldp_post_index(X29, X30, SP, 16)),
+            [0xFD, 0x7B, 0xC1, 0xA8]
+        );
+    }
+
+    #[test]
+    fn encodes_calls_and_returns() {
+        assert_eq!(assemble(|asm| asm.blr(X16)), [0x00, 0x02, 0x3F, 0xD6]);
+        assert_eq!(assemble(Arm64Assembler::ret), [0xC0, 0x03, 0x5F, 0xD6]);
+    }
+
+    #[test]
+    fn resolves_forward_and_backward_branches() {
+        let code = assemble(|asm| {
+            let start = asm.create_label();
+            let end = asm.create_label();
+            asm.set_label(&start);
+            asm.cbnz(X0, &end);
+            asm.cbz(X20, &end);
+            asm.b(&start);
+            asm.set_label(&end);
+            asm.ret();
+        });
+        assert_eq!(
+            code,
+            [
+                0x60, 0x00, 0x00, 0xB5, // cbnz x0, #12
+                0x54, 0x00, 0x00, 0xB4, // cbz x20, #8
+                0xFE, 0xFF, 0xFF, 0x17, // b #-8
+                0xC0, 0x03, 0x5F, 0xD6, // ret
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn assemble_panics_on_unbound_label() {
+        assemble(|asm| {
+            let label = asm.create_label();
+            asm.b(&label);
+        });
+    }
+}
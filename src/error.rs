@@ -1,10 +1,11 @@
-use std::{
-    fmt::{self, Display},
-    io,
-};
+use std::io;
+#[cfg(windows)]
+use std::fmt::{self, Display};
 
+#[cfg(windows)]
 use num_enum::{IntoPrimitive, TryFromPrimitive, TryFromPrimitiveError};
 use thiserror::Error;
+#[cfg(windows)]
 use winapi::um::{
     minwinbase::{
         EXCEPTION_ACCESS_VIOLATION, EXCEPTION_ARRAY_BOUNDS_EXCEEDED, EXCEPTION_BREAKPOINT,
@@ -19,9 +20,16 @@ use winapi::um::{
     winnt::STATUS_UNWIND_CONSOLIDATE,
 };
 
-#[cfg(feature = "syringe")]
+#[cfg(windows)]
 use winapi::shared::winerror::ERROR_PARTIAL_COPY;
 
+#[cfg(windows)]
+use crate::process::ProcessAccess;
+
+#[cfg(all(windows, feature = "syringe"))]
+use crate::{process::ProcessArchitecture, InjectFailureDiagnosis};
+
+#[cfg(windows)]
 #[derive(Debug, Error)]
 /// Error enum representing either a windows api error or a nul error from an invalid interior nul.
 pub enum IoOrNulError {
@@ -33,6 +41,7 @@ pub enum IoOrNulError {
     Io(#[from] io::Error),
 }
 
+#[cfg(windows)]
 /// Error enum for errors during a call to [`ProcessModule::get_local_procedure_address`].
 ///
 /// [`ProcessModule::get_local_procedure_address`]: crate::process::ProcessModule::get_local_procedure_address
@@ -49,6 +58,7 @@ pub enum GetLocalProcedureAddressError {
     UnsupportedRemoteTarget,
 }
 
+#[cfg(windows)]
 #[derive(
     Debug, TryFromPrimitive, IntoPrimitive, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash,
 )]
@@ -109,6 +119,7 @@ pub enum ExceptionCode {
     UnwindConsolidate = STATUS_UNWIND_CONSOLIDATE,
 }
 
+#[cfg(windows)]
 impl ExceptionCode {
     /// Try to interpret the given code as a windows exception code.
     pub fn try_from_code(code: u32) -> Result<Self, TryFromPrimitiveError<Self>> {
@@ -121,6 +132,7 @@ impl ExceptionCode {
     }
 }
 
+#[cfg(windows)]
 impl Display for ExceptionCode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // These error messages were collected using https://stackoverflow.com/a/43961146/6304917 and https://stackoverflow.com/a/7915329/6304917
@@ -155,6 +167,7 @@ impl Display for ExceptionCode {
     }
 }
 
+#[cfg(windows)]
 #[derive(Debug, Error)]
 /// An error representing either an unhandled exception or an io error.
 pub enum ExceptionOrIoError {
@@ -166,9 +179,169 @@ pub enum ExceptionOrIoError {
     Exception(ExceptionCode),
 }
 
+/// Error enum for errors while parsing a pe image.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PeError {
+    /// Variant representing a missing or malformed dos header.
+    #[error("invalid dos header")]
+    InvalidDosHeader,
+    /// Variant representing missing or malformed nt headers.
+    #[error("invalid nt headers")]
+    InvalidNtHeaders,
+    /// Variant representing an optional header with an unknown magic value.
+    #[error("unsupported optional header magic {:#x}", _0)]
+    UnsupportedOptionalHeader(u16),
+    /// Variant representing a relative virtual address that lies outside of the image.
+    #[error("invalid relative virtual address {:#x}", _0)]
+    InvalidRva(u32),
+    /// Variant representing an absolute address that lies outside of the image.
+    #[error("invalid address {:#x}", _0)]
+    InvalidAddress(u64),
+    /// Variant representing a base relocation of an unsupported type.
+    #[error("unsupported relocation type {}", _0)]
+    UnsupportedRelocation(u8),
+    /// Variant representing an image that has to be relocated but does not contain relocation information.
+    #[error("image has to be relocated but relocations were stripped")]
+    RelocationsStripped,
+    /// Variant representing a malformed forwarder string of an exported symbol.
+    #[error("invalid export forwarder at {:#x}", _0)]
+    InvalidForwarder(u32),
+}
+
+/// Error enum for errors while reading the image of a module using [`ProcessModule::image`](crate::process::ProcessModule::image)
+/// or [`ModuleImage::from_file`](crate::process::ModuleImage::from_file).
+#[derive(Debug, Error)]
+pub enum ReadImageError {
+    /// Variant representing an io error.
+    #[error("io error: {}", _0)]
+    Io(io::Error),
+    /// Variant representing an inaccessible module.
+    /// This can occur if the module was unloaded or if the process it was loaded in crashed or was terminated.
+    #[error("inaccessible module")]
+    ModuleInaccessible,
+    /// Variant representing an invalid or unsupported image.
+    #[error("invalid image: {}", _0)]
+    Pe(#[from] PeError),
+}
+
+impl From<io::Error> for ReadImageError {
+    fn from(err: io::Error) -> Self {
+        #[cfg(windows)]
+        if err.raw_os_error() == Some(ERROR_PARTIAL_COPY as _) {
+            return Self::ModuleInaccessible;
+        }
+        Self::Io(err)
+    }
+}
+
+#[cfg(windows)]
+/// Error representing a process handle that lacks the access rights required for an operation.
+///
+/// Operations on a process return this error (wrapped in an [`io::Error`] of kind [`PermissionDenied`](io::ErrorKind::PermissionDenied))
+/// before calling into the operating system if the handle was opened without the rights they require (see [`OwnedProcess::open`](crate::process::OwnedProcess::open)).
+/// The error enums of this crate report it as their `MissingAccess` variant instead of an inaccessible process.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq, Hash)]
+#[error("process handle is missing the access rights {:?}", _0)]
+pub struct MissingAccess(pub ProcessAccess);
+
+#[cfg(windows)]
+impl MissingAccess {
+    /// Returns the access rights that are missing.
+    #[must_use]
+    pub fn missing(&self) -> ProcessAccess {
+        self.0
+    }
+
+    /// Returns the [`MissingAccess`] error wrapped in the given [`io::Error`], if any.
+    #[must_use]
+    pub fn from_io_error(err: &io::Error) -> Option<Self> {
+        err.get_ref()
+            .and_then(|inner| inner.downcast_ref::<Self>())
+            .copied()
+    }
+}
+
+#[cfg(windows)]
+impl From<MissingAccess> for io::Error {
+    fn from(err: MissingAccess) -> Self {
+        io::Error::new(io::ErrorKind::PermissionDenied, err)
+    }
+}
+
+/// Error enum for errors while parsing a [`Pattern`](crate::process::Pattern).
+#[derive(Debug, Error, Clone, PartialEq, Eq, Hash)]
+pub enum ParsePatternError {
+    /// Variant representing a pattern without any bytes.
+    #[error("pattern is empty")]
+    Empty,
+    /// Variant representing a token that is neither a hexadecimal byte nor a wildcard.
+    #[error("invalid pattern byte {:?} at position {}", token, position)]
+    InvalidByte {
+        /// The invalid token.
+        token: String,
+        /// The index of the invalid token in the pattern.
+        position: usize,
+    },
+}
+
+/// Error enum for errors while resolving a [`PointerChain`](crate::process::memory::PointerChain).
+///
+/// The hops of a chain are numbered starting from zero, where hop `n` is the dereference of the address
+/// that the `n`-th offset is added to.
+#[derive(Debug, Error)]
+#[cfg(all(windows, feature = "process-memory"))]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "process-memory")))]
+pub enum PointerChainError {
+    /// Variant representing an io error while looking up the base module or the architecture of the target process.
+    #[error("io error: {}", _0)]
+    Io(#[from] io::Error),
+    /// Variant representing a base module that is not loaded in the target process.
+    #[error("module {} not found", _0)]
+    ModuleNotFound(String),
+    /// Variant representing a hop that read a null pointer.
+    #[error("hop {} read a null pointer at {:#x}", hop, address)]
+    NullPointer {
+        /// The index of the hop.
+        hop: usize,
+        /// The address the pointer was read from.
+        address: usize,
+    },
+    /// Variant representing a hop whose pointer could not be read.
+    #[error("hop {} could not read a pointer at {:#x}: {}", hop, address, error)]
+    Unreadable {
+        /// The index of the hop.
+        hop: usize,
+        /// The address the pointer was read from.
+        address: usize,
+        /// The error that occurred while reading the pointer.
+        error: io::Error,
+    },
+    /// Variant representing a hop whose pointer does not fit into the address space of the current process.
+    /// This can occur if the current process is 32-bit and the target process is 64-bit.
+    #[error(
+        "hop {} read the pointer {:#x} that cannot be represented in the current process",
+        hop,
+        pointer
+    )]
+    UnsupportedPointer {
+        /// The index of the hop.
+        hop: usize,
+        /// The pointer that was read.
+        pointer: u64,
+    },
+    /// Variant representing an offset that moved an address outside of the address space.
+    #[error("offset {:#x} overflowed the address {:#x}", offset, address)]
+    Overflow {
+        /// The address the offset was added to.
+        address: usize,
+        /// The offset that was added.
+        offset: isize,
+    },
+}
+
 /// Error enum for errors during [`Syringe::load_inject_help_data_for_process`](crate::Syringe::load_inject_help_data_for_process).
 #[derive(Debug, Error)]
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "syringe")))]
 pub(crate) enum LoadInjectHelpDataError {
     /// Variant representing an io error.
@@ -177,22 +350,38 @@ pub(crate) enum LoadInjectHelpDataError {
     /// Variant representing an unsupported target process.
     #[error("unsupported target process")]
     UnsupportedTarget,
+    /// Variant representing a target process whose architecture is not supported from the architecture of the current process.
+    #[error("cannot target {} processes from {} processes", target, injector)]
+    UnsupportedArchitecture {
+        /// The architecture of the current process.
+        injector: ProcessArchitecture,
+        /// The architecture of the target process.
+        target: ProcessArchitecture,
+    },
     /// Variant representing an inaccessible target process.
     /// This can occur if it crashed or was terminated.
     #[error("inaccessible target process")]
     ProcessInaccessible,
+    /// Variant representing a handle to the target process that lacks the access rights required for the operation.
+    #[error("{}", _0)]
+    MissingAccess(#[from] MissingAccess),
     /// Variant representing an error while loading an pe file.
-    #[cfg(target_arch = "x86_64")]
-    #[cfg(feature = "into-x86-from-x64")]
+    #[cfg(any(
+        all(target_arch = "x86_64", feature = "into-x86-from-x64"),
+        all(target_arch = "x86", feature = "into-x64-from-x86")
+    ))]
     #[error("failed to load pe file: {}", _0)]
     Goblin(#[from] goblin::error::Error),
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<io::Error> for LoadInjectHelpDataError {
     fn from(err: io::Error) -> Self {
-        if err.raw_os_error() == Some(ERROR_PARTIAL_COPY as _)
+        if let Some(missing) = MissingAccess::from_io_error(&err) {
+            Self::MissingAccess(missing)
+        } else if err.raw_os_error() == Some(ERROR_PARTIAL_COPY as _)
             || err.kind() == io::ErrorKind::PermissionDenied
+            || err.kind() == io::ErrorKind::BrokenPipe
         {
             Self::ProcessInaccessible
         } else {
@@ -203,7 +392,7 @@ impl From<io::Error> for LoadInjectHelpDataError {
 
 /// Error enum for errors during [`Syringe::inject`](crate::Syringe::inject).
 #[derive(Debug, Error)]
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "syringe")))]
 pub enum InjectError {
     /// Variant representing an illegal interior nul value in the module path.
@@ -215,9 +404,25 @@ pub enum InjectError {
     /// Variant representing an unsupported target process.
     #[error("unsupported target process")]
     UnsupportedTarget,
+    /// Variant representing a target process whose architecture is not supported from the architecture of the current process.
+    #[error("cannot target {} processes from {} processes", target, injector)]
+    UnsupportedArchitecture {
+        /// The architecture of the current process.
+        injector: ProcessArchitecture,
+        /// The architecture of the target process.
+        target: ProcessArchitecture,
+    },
     /// Variant representing an io error inside the target process.
     #[error("remote io error: {}", _0)]
     RemoteIo(io::Error),
+    /// Variant representing a module that failed to load inside the target process together with a diagnosis of its dependencies.
+    #[error("remote io error: {} ({})", error, diagnosis)]
+    ModuleLoadFailed {
+        /// The error reported by the target process.
+        error: io::Error,
+        /// The diagnosis of the dependencies of the module.
+        diagnosis: InjectFailureDiagnosis,
+    },
     /// Variant representing an unhandled exception inside the target process.
     #[error("remote exception: {}", _0)]
     RemoteException(ExceptionCode),
@@ -225,18 +430,29 @@ pub enum InjectError {
     /// This can occur if it crashed or was terminated.
     #[error("inaccessible target process")]
     ProcessInaccessible,
+    /// Variant representing a handle to the target process that lacks the access rights required for the operation.
+    #[error("{}", _0)]
+    MissingAccess(#[from] MissingAccess),
+    /// Variant representing a payload that was built for a different architecture than the target process.
+    #[error("payload architecture does not match the target process")]
+    ArchitectureMismatch,
     /// Variant representing an error while loading an pe file.
-    #[cfg(target_arch = "x86_64")]
-    #[cfg(feature = "into-x86-from-x64")]
+    #[cfg(any(
+        all(target_arch = "x86_64", feature = "into-x86-from-x64"),
+        all(target_arch = "x86", feature = "into-x64-from-x86")
+    ))]
     #[error("failed to load pe file: {}", _0)]
     Goblin(#[from] goblin::error::Error),
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<io::Error> for InjectError {
     fn from(err: io::Error) -> Self {
-        if err.raw_os_error() == Some(ERROR_PARTIAL_COPY as _)
+        if let Some(missing) = MissingAccess::from_io_error(&err) {
+            Self::MissingAccess(missing)
+        } else if err.raw_os_error() == Some(ERROR_PARTIAL_COPY as _)
             || err.kind() == io::ErrorKind::PermissionDenied
+            || err.kind() == io::ErrorKind::BrokenPipe
         {
             Self::ProcessInaccessible
         } else {
@@ -245,14 +461,14 @@ impl From<io::Error> for InjectError {
     }
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<ExceptionCode> for InjectError {
     fn from(err: ExceptionCode) -> Self {
         Self::RemoteException(err)
     }
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<IoOrNulError> for InjectError {
     fn from(err: IoOrNulError) -> Self {
         match err {
@@ -262,7 +478,7 @@ impl From<IoOrNulError> for InjectError {
     }
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<ExceptionOrIoError> for InjectError {
     fn from(err: ExceptionOrIoError) -> Self {
         match err {
@@ -272,15 +488,21 @@ impl From<ExceptionOrIoError> for InjectError {
     }
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<LoadInjectHelpDataError> for InjectError {
     fn from(err: LoadInjectHelpDataError) -> Self {
         match err {
             LoadInjectHelpDataError::Io(e) => Self::Io(e),
             LoadInjectHelpDataError::UnsupportedTarget => Self::UnsupportedTarget,
+            LoadInjectHelpDataError::UnsupportedArchitecture { injector, target } => {
+                Self::UnsupportedArchitecture { injector, target }
+            }
             LoadInjectHelpDataError::ProcessInaccessible => Self::ProcessInaccessible,
-            #[cfg(target_arch = "x86_64")]
-            #[cfg(feature = "into-x86-from-x64")]
+            LoadInjectHelpDataError::MissingAccess(e) => Self::MissingAccess(e),
+            #[cfg(any(
+                all(target_arch = "x86_64", feature = "into-x86-from-x64"),
+                all(target_arch = "x86", feature = "into-x64-from-x86")
+            ))]
             LoadInjectHelpDataError::Goblin(e) => Self::Goblin(e),
         }
     }
@@ -288,7 +510,7 @@ impl From<LoadInjectHelpDataError> for InjectError {
 
 /// Error enum for errors during [`Syringe::eject`](crate::Syringe::eject).
 #[derive(Debug, Error)]
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "syringe")))]
 pub enum EjectError {
     /// Variant representing an io error.
@@ -297,6 +519,14 @@ pub enum EjectError {
     /// Variant representing an unsupported target process.
     #[error("unsupported target process")]
     UnsupportedTarget,
+    /// Variant representing a target process whose architecture is not supported from the architecture of the current process.
+    #[error("cannot target {} processes from {} processes", target, injector)]
+    UnsupportedArchitecture {
+        /// The architecture of the current process.
+        injector: ProcessArchitecture,
+        /// The architecture of the target process.
+        target: ProcessArchitecture,
+    },
     /// Variant representing an io error inside the target process.
     #[error("remote io error: {}", _0)]
     RemoteIo(io::Error),
@@ -307,36 +537,57 @@ pub enum EjectError {
     /// This can occur if it crashed or was terminated.
     #[error("inaccessible target process")]
     ProcessInaccessible,
+    /// Variant representing a handle to the target process that lacks the access rights required for the operation.
+    #[error("{}", _0)]
+    MissingAccess(#[from] MissingAccess),
     /// Variant representing an inaccessible target module.
     /// This can occur if the target module was ejected or unloaded.
     #[error("inaccessible target module")]
     ModuleInaccessible,
+    /// Variant representing a module that is still loaded after releasing all references that could be released.
+    /// This can occur if the module is pinned or statically imported by the target process.
+    #[error("module still loaded after releasing {} references", released)]
+    ModuleStillLoaded {
+        /// The number of references that were released.
+        released: usize,
+    },
     /// Variant representing an error while loading an pe file.
-    #[cfg(target_arch = "x86_64")]
-    #[cfg(feature = "into-x86-from-x64")]
+    #[cfg(any(
+        all(target_arch = "x86_64", feature = "into-x86-from-x64"),
+        all(target_arch = "x86", feature = "into-x64-from-x86")
+    ))]
     #[error("failed to load pe file: {}", _0)]
     Goblin(#[from] goblin::error::Error),
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<LoadInjectHelpDataError> for EjectError {
     fn from(err: LoadInjectHelpDataError) -> Self {
         match err {
             LoadInjectHelpDataError::Io(e) => Self::Io(e),
             LoadInjectHelpDataError::UnsupportedTarget => Self::UnsupportedTarget,
+            LoadInjectHelpDataError::UnsupportedArchitecture { injector, target } => {
+                Self::UnsupportedArchitecture { injector, target }
+            }
             LoadInjectHelpDataError::ProcessInaccessible => Self::ProcessInaccessible,
-            #[cfg(target_arch = "x86_64")]
-            #[cfg(feature = "into-x86-from-x64")]
+            LoadInjectHelpDataError::MissingAccess(e) => Self::MissingAccess(e),
+            #[cfg(any(
+                all(target_arch = "x86_64", feature = "into-x86-from-x64"),
+                all(target_arch = "x86", feature = "into-x64-from-x86")
+            ))]
             LoadInjectHelpDataError::Goblin(e) => Self::Goblin(e),
         }
     }
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<io::Error> for EjectError {
     fn from(err: io::Error) -> Self {
-        if err.raw_os_error() == Some(ERROR_PARTIAL_COPY as _)
+        if let Some(missing) = MissingAccess::from_io_error(&err) {
+            Self::MissingAccess(missing)
+        } else if err.raw_os_error() == Some(ERROR_PARTIAL_COPY as _)
             || err.kind() == io::ErrorKind::PermissionDenied
+            || err.kind() == io::ErrorKind::BrokenPipe
         {
             Self::ProcessInaccessible
         } else {
@@ -345,14 +596,14 @@ impl From<io::Error> for EjectError {
     }
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<ExceptionCode> for EjectError {
     fn from(err: ExceptionCode) -> Self {
         Self::RemoteException(err)
     }
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<ExceptionOrIoError> for EjectError {
     fn from(err: ExceptionOrIoError) -> Self {
         match err {
@@ -364,7 +615,7 @@ impl From<ExceptionOrIoError> for EjectError {
 
 /// Error enum for errors during procedure loading.
 #[derive(Debug, Error)]
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "syringe")))]
 pub enum LoadProcedureError {
     /// Variant representing an io error.
@@ -373,6 +624,14 @@ pub enum LoadProcedureError {
     /// Variant representing an unsupported target process.
     #[error("unsupported target process")]
     UnsupportedTarget,
+    /// Variant representing a target process whose architecture is not supported from the architecture of the current process.
+    #[error("cannot target {} processes from {} processes", target, injector)]
+    UnsupportedArchitecture {
+        /// The architecture of the current process.
+        injector: ProcessArchitecture,
+        /// The architecture of the target process.
+        target: ProcessArchitecture,
+    },
     /// Variant representing an io error inside the target process.
     #[error("remote io error: {}", _0)]
     RemoteIo(io::Error),
@@ -383,36 +642,50 @@ pub enum LoadProcedureError {
     /// This can occur if it crashed or was terminated.
     #[error("inaccessible target process")]
     ProcessInaccessible,
+    /// Variant representing a handle to the target process that lacks the access rights required for the operation.
+    #[error("{}", _0)]
+    MissingAccess(#[from] MissingAccess),
     /// Variant representing an inaccessible target module.
     /// This can occur if the target module was ejected or unloaded.
     #[error("inaccessible target module")]
     ModuleInaccessible,
     /// Variant representing an error while loading an pe file.
-    #[cfg(target_arch = "x86_64")]
-    #[cfg(feature = "into-x86-from-x64")]
+    #[cfg(any(
+        all(target_arch = "x86_64", feature = "into-x86-from-x64"),
+        all(target_arch = "x86", feature = "into-x64-from-x86")
+    ))]
     #[error("failed to load pe file: {}", _0)]
     Goblin(#[from] goblin::error::Error),
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<LoadInjectHelpDataError> for LoadProcedureError {
     fn from(err: LoadInjectHelpDataError) -> Self {
         match err {
             LoadInjectHelpDataError::Io(e) => Self::Io(e),
             LoadInjectHelpDataError::UnsupportedTarget => Self::UnsupportedTarget,
+            LoadInjectHelpDataError::UnsupportedArchitecture { injector, target } => {
+                Self::UnsupportedArchitecture { injector, target }
+            }
             LoadInjectHelpDataError::ProcessInaccessible => Self::ProcessInaccessible,
-            #[cfg(target_arch = "x86_64")]
-            #[cfg(feature = "into-x86-from-x64")]
+            LoadInjectHelpDataError::MissingAccess(e) => Self::MissingAccess(e),
+            #[cfg(any(
+                all(target_arch = "x86_64", feature = "into-x86-from-x64"),
+                all(target_arch = "x86", feature = "into-x64-from-x86")
+            ))]
             LoadInjectHelpDataError::Goblin(e) => Self::Goblin(e),
         }
     }
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<io::Error> for LoadProcedureError {
     fn from(err: io::Error) -> Self {
-        if err.raw_os_error() == Some(ERROR_PARTIAL_COPY as _)
+        if let Some(missing) = MissingAccess::from_io_error(&err) {
+            Self::MissingAccess(missing)
+        } else if err.raw_os_error() == Some(ERROR_PARTIAL_COPY as _)
             || err.kind() == io::ErrorKind::PermissionDenied
+            || err.kind() == io::ErrorKind::BrokenPipe
         {
             Self::ProcessInaccessible
         } else {
@@ -421,14 +694,14 @@ impl From<io::Error> for LoadProcedureError {
     }
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<ExceptionCode> for LoadProcedureError {
     fn from(err: ExceptionCode) -> Self {
         Self::RemoteException(err)
     }
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<ExceptionOrIoError> for LoadProcedureError {
     fn from(err: ExceptionOrIoError) -> Self {
         match err {
@@ -438,9 +711,138 @@ impl From<ExceptionOrIoError> for LoadProcedureError {
     }
 }
 
+/// Error enum for errors during [`Syringe::inject_manual_map`](crate::Syringe::inject_manual_map).
+#[derive(Debug, Error)]
+#[cfg(all(windows, feature = "manual-map"))]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "manual-map")))]
+pub enum ManualMapError {
+    /// Variant representing an io error.
+    #[error("io error: {}", _0)]
+    Io(io::Error),
+    /// Variant representing an unsupported target process.
+    #[error("unsupported target process")]
+    UnsupportedTarget,
+    /// Variant representing a target process whose architecture is not supported from the architecture of the current process.
+    #[error("cannot target {} processes from {} processes", target, injector)]
+    UnsupportedArchitecture {
+        /// The architecture of the current process.
+        injector: ProcessArchitecture,
+        /// The architecture of the target process.
+        target: ProcessArchitecture,
+    },
+    /// Variant representing an io error inside the target process.
+    #[error("remote io error: {}", _0)]
+    RemoteIo(io::Error),
+    /// Variant representing an unhandled exception inside the target process.
+    #[error("remote exception: {}", _0)]
+    RemoteException(ExceptionCode),
+    /// Variant representing an inaccessible target process.
+    /// This can occur if it crashed or was terminated.
+    #[error("inaccessible target process")]
+    ProcessInaccessible,
+    /// Variant representing a handle to the target process that lacks the access rights required for the operation.
+    #[error("{}", _0)]
+    MissingAccess(#[from] MissingAccess),
+    /// Variant representing an inaccessible target module.
+    /// This can occur if the target module was ejected or unloaded.
+    #[error("inaccessible target module")]
+    ModuleInaccessible,
+    /// Variant representing an invalid or unsupported payload image.
+    #[error("invalid payload image: {}", _0)]
+    Pe(#[from] PeError),
+    /// Variant representing a payload that was built for a different architecture than the target process.
+    #[error("payload architecture does not match the target process")]
+    ArchitectureMismatch,
+    /// Variant representing a dependency of the payload that could not be loaded into the target process.
+    #[error("failed to load dependency {}: {}", module, source)]
+    DependencyLoad {
+        /// The name of the dependency.
+        module: String,
+        /// The error that occured while loading the dependency.
+        source: Box<InjectError>,
+    },
+    /// Variant representing an import of the payload that could not be resolved.
+    #[error("unresolved import {}!{}", module, symbol)]
+    UnresolvedImport {
+        /// The name of the module the symbol is imported from.
+        module: String,
+        /// The name or ordinal of the imported symbol.
+        symbol: String,
+    },
+    /// Variant representing an entry point of the payload that reported a failed initialization.
+    #[error("payload entry point returned false")]
+    EntryPointFailed,
+    /// Variant representing an error while loading an pe file.
+    #[cfg(any(
+        all(target_arch = "x86_64", feature = "into-x86-from-x64"),
+        all(target_arch = "x86", feature = "into-x64-from-x86")
+    ))]
+    #[error("failed to load pe file: {}", _0)]
+    Goblin(#[from] goblin::error::Error),
+}
+
+#[cfg(all(windows, feature = "manual-map"))]
+impl From<io::Error> for ManualMapError {
+    fn from(err: io::Error) -> Self {
+        if let Some(missing) = MissingAccess::from_io_error(&err) {
+            Self::MissingAccess(missing)
+        } else if err.raw_os_error() == Some(ERROR_PARTIAL_COPY as _)
+            || err.kind() == io::ErrorKind::PermissionDenied
+            || err.kind() == io::ErrorKind::BrokenPipe
+        {
+            Self::ProcessInaccessible
+        } else {
+            Self::Io(err)
+        }
+    }
+}
+
+#[cfg(all(windows, feature = "manual-map"))]
+impl From<ExceptionCode> for ManualMapError {
+    fn from(err: ExceptionCode) -> Self {
+        Self::RemoteException(err)
+    }
+}
+
+#[cfg(all(windows, feature = "manual-map"))]
+impl From<LoadProcedureError> for ManualMapError {
+    fn from(err: LoadProcedureError) -> Self {
+        match err {
+            LoadProcedureError::Io(e) => Self::Io(e),
+            LoadProcedureError::UnsupportedTarget => Self::UnsupportedTarget,
+            LoadProcedureError::UnsupportedArchitecture { injector, target } => {
+                Self::UnsupportedArchitecture { injector, target }
+            }
+            LoadProcedureError::RemoteIo(e) => Self::RemoteIo(e),
+            LoadProcedureError::RemoteException(e) => Self::RemoteException(e),
+            LoadProcedureError::ProcessInaccessible => Self::ProcessInaccessible,
+            LoadProcedureError::MissingAccess(e) => Self::MissingAccess(e),
+            LoadProcedureError::ModuleInaccessible => Self::ModuleInaccessible,
+            #[cfg(any(
+                all(target_arch = "x86_64", feature = "into-x86-from-x64"),
+                all(target_arch = "x86", feature = "into-x64-from-x86")
+            ))]
+            LoadProcedureError::Goblin(e) => Self::Goblin(e),
+        }
+    }
+}
+
+#[cfg(all(windows, feature = "manual-map"))]
+impl From<crate::rpc::RawRpcError> for ManualMapError {
+    fn from(err: crate::rpc::RawRpcError) -> Self {
+        match err {
+            crate::rpc::RawRpcError::Io(e) => Self::Io(e),
+            crate::rpc::RawRpcError::RemoteException(e) => Self::RemoteException(e),
+            crate::rpc::RawRpcError::ProcessInaccessible => Self::ProcessInaccessible,
+            crate::rpc::RawRpcError::MissingAccess(e) => Self::MissingAccess(e),
+            crate::rpc::RawRpcError::ModuleInaccessible => Self::ModuleInaccessible,
+        }
+    }
+}
+
 /// Error enum encompassing all errors during [`Syringe`](crate::Syringe) operations.
 #[derive(Debug, Error)]
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "syringe")))]
 pub enum SyringeError {
     /// Variant representing an illegal interior nul value in the module path.
@@ -452,9 +854,25 @@ pub enum SyringeError {
     /// Variant representing an unsupported target process.
     #[error("unsupported target process")]
     UnsupportedTarget,
+    /// Variant representing a target process whose architecture is not supported from the architecture of the current process.
+    #[error("cannot target {} processes from {} processes", target, injector)]
+    UnsupportedArchitecture {
+        /// The architecture of the current process.
+        injector: ProcessArchitecture,
+        /// The architecture of the target process.
+        target: ProcessArchitecture,
+    },
     /// Variant representing an io error inside the target process.
     #[error("remote io error: {}", _0)]
     RemoteIo(io::Error),
+    /// Variant representing a module that failed to load inside the target process together with a diagnosis of its dependencies.
+    #[error("remote io error: {} ({})", error, diagnosis)]
+    ModuleLoadFailed {
+        /// The error reported by the target process.
+        error: io::Error,
+        /// The diagnosis of the dependencies of the module.
+        diagnosis: InjectFailureDiagnosis,
+    },
     /// Variant representing an unhandled exception inside the target process.
     #[error("remote exception: {}", _0)]
     RemoteException(ExceptionCode),
@@ -462,10 +880,23 @@ pub enum SyringeError {
     /// This can occur if it crashed or was terminated.
     #[error("inaccessible target process")]
     ProcessInaccessible,
+    /// Variant representing a handle to the target process that lacks the access rights required for the operation.
+    #[error("{}", _0)]
+    MissingAccess(#[from] MissingAccess),
     /// Variant representing an inaccessible target module.
     /// This can occur if the target module was ejected or unloaded.
     #[error("inaccessible target module")]
     ModuleInaccessible,
+    /// Variant representing a module that is still loaded after releasing all references that could be released.
+    /// This can occur if the module is pinned or statically imported by the target process.
+    #[error("module still loaded after releasing {} references", released)]
+    ModuleStillLoaded {
+        /// The number of references that were released.
+        released: usize,
+    },
+    /// Variant representing a payload that was built for a different architecture than the target process.
+    #[error("payload architecture does not match the target process")]
+    ArchitectureMismatch,
     /// Variant representing an error while serializing or deserializing.
     #[cfg(feature = "rpc-payload")]
     #[error("serde error: {}", _0)]
@@ -475,17 +906,22 @@ pub enum SyringeError {
     #[error("remote payload error: {}", _0)]
     RemotePayloadProcedure(String),
     /// Variant representing an error while loading an pe file.
-    #[cfg(target_arch = "x86_64")]
-    #[cfg(feature = "into-x86-from-x64")]
+    #[cfg(any(
+        all(target_arch = "x86_64", feature = "into-x86-from-x64"),
+        all(target_arch = "x86", feature = "into-x64-from-x86")
+    ))]
     #[error("failed to load pe file: {}", _0)]
     Goblin(#[from] goblin::error::Error),
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<io::Error> for SyringeError {
     fn from(err: io::Error) -> Self {
-        if err.raw_os_error() == Some(ERROR_PARTIAL_COPY as _)
+        if let Some(missing) = MissingAccess::from_io_error(&err) {
+            Self::MissingAccess(missing)
+        } else if err.raw_os_error() == Some(ERROR_PARTIAL_COPY as _)
             || err.kind() == io::ErrorKind::PermissionDenied
+            || err.kind() == io::ErrorKind::BrokenPipe
         {
             Self::ProcessInaccessible
         } else {
@@ -494,14 +930,14 @@ impl From<io::Error> for SyringeError {
     }
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<ExceptionCode> for SyringeError {
     fn from(err: ExceptionCode) -> Self {
         Self::RemoteException(err)
     }
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<IoOrNulError> for SyringeError {
     fn from(err: IoOrNulError) -> Self {
         match err {
@@ -511,7 +947,7 @@ impl From<IoOrNulError> for SyringeError {
     }
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<ExceptionOrIoError> for SyringeError {
     fn from(err: ExceptionOrIoError) -> Self {
         match err {
@@ -521,58 +957,81 @@ impl From<ExceptionOrIoError> for SyringeError {
     }
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<InjectError> for SyringeError {
     fn from(err: InjectError) -> Self {
         match err {
             InjectError::IllegalPath(e) => Self::IllegalPath(e),
             InjectError::Io(e) => Self::Io(e),
             InjectError::UnsupportedTarget => Self::UnsupportedTarget,
+            InjectError::UnsupportedArchitecture { injector, target } => {
+                Self::UnsupportedArchitecture { injector, target }
+            }
             InjectError::RemoteIo(e) => Self::RemoteIo(e),
+            InjectError::ModuleLoadFailed { error, diagnosis } => {
+                Self::ModuleLoadFailed { error, diagnosis }
+            }
             InjectError::RemoteException(e) => Self::RemoteException(e),
             InjectError::ProcessInaccessible => Self::ProcessInaccessible,
-            #[cfg(target_arch = "x86_64")]
-            #[cfg(feature = "into-x86-from-x64")]
+            InjectError::MissingAccess(e) => Self::MissingAccess(e),
+            InjectError::ArchitectureMismatch => Self::ArchitectureMismatch,
+            #[cfg(any(
+                all(target_arch = "x86_64", feature = "into-x86-from-x64"),
+                all(target_arch = "x86", feature = "into-x64-from-x86")
+            ))]
             InjectError::Goblin(e) => Self::Goblin(e),
         }
     }
 }
 
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 impl From<EjectError> for SyringeError {
     fn from(err: EjectError) -> Self {
         match err {
             EjectError::Io(e) => Self::Io(e),
             EjectError::UnsupportedTarget => Self::UnsupportedTarget,
+            EjectError::UnsupportedArchitecture { injector, target } => {
+                Self::UnsupportedArchitecture { injector, target }
+            }
             EjectError::RemoteIo(e) => Self::RemoteIo(e),
             EjectError::RemoteException(e) => Self::RemoteException(e),
             EjectError::ProcessInaccessible => Self::ProcessInaccessible,
+            EjectError::MissingAccess(e) => Self::MissingAccess(e),
             EjectError::ModuleInaccessible => Self::ModuleInaccessible,
-            #[cfg(target_arch = "x86_64")]
-            #[cfg(feature = "into-x86-from-x64")]
+            EjectError::ModuleStillLoaded { released } => Self::ModuleStillLoaded { released },
+            #[cfg(any(
+                all(target_arch = "x86_64", feature = "into-x86-from-x64"),
+                all(target_arch = "x86", feature = "into-x64-from-x86")
+            ))]
             EjectError::Goblin(e) => Self::Goblin(e),
         }
     }
 }
 
-#[cfg(feature = "rpc-core")]
+#[cfg(all(windows, feature = "rpc-core"))]
 impl From<LoadProcedureError> for SyringeError {
     fn from(err: LoadProcedureError) -> Self {
         match err {
             LoadProcedureError::Io(e) => Self::Io(e),
             LoadProcedureError::UnsupportedTarget => Self::UnsupportedTarget,
+            LoadProcedureError::UnsupportedArchitecture { injector, target } => {
+                Self::UnsupportedArchitecture { injector, target }
+            }
             LoadProcedureError::RemoteIo(e) => Self::RemoteIo(e),
             LoadProcedureError::RemoteException(e) => Self::RemoteException(e),
             LoadProcedureError::ProcessInaccessible => Self::ProcessInaccessible,
+            LoadProcedureError::MissingAccess(e) => Self::MissingAccess(e),
             LoadProcedureError::ModuleInaccessible => Self::ModuleInaccessible,
-            #[cfg(target_arch = "x86_64")]
-            #[cfg(feature = "into-x86-from-x64")]
+            #[cfg(any(
+                all(target_arch = "x86_64", feature = "into-x86-from-x64"),
+                all(target_arch = "x86", feature = "into-x64-from-x86")
+            ))]
             LoadProcedureError::Goblin(e) => Self::Goblin(e),
         }
     }
 }
 
-#[cfg(feature = "rpc-core")]
+#[cfg(all(windows, feature = "rpc-core"))]
 #[cfg_attr(all(feature = "rpc-core", not(feature = "rpc-raw")), doc(hidden))]
 impl From<crate::rpc::RawRpcError> for SyringeError {
     fn from(err: crate::rpc::RawRpcError) -> Self {
@@ -580,12 +1039,13 @@ impl From<crate::rpc::RawRpcError> for SyringeError {
             crate::rpc::RawRpcError::Io(err) => Self::Io(err),
             crate::rpc::RawRpcError::RemoteException(code) => Self::RemoteException(code),
             crate::rpc::RawRpcError::ProcessInaccessible => Self::ProcessInaccessible,
+            crate::rpc::RawRpcError::MissingAccess(e) => Self::MissingAccess(e),
             crate::rpc::RawRpcError::ModuleInaccessible => Self::ModuleInaccessible,
         }
     }
 }
 
-#[cfg(feature = "rpc-payload")]
+#[cfg(all(windows, feature = "rpc-payload"))]
 #[cfg_attr(all(feature = "rpc-core", not(feature = "rpc-raw")), doc(hidden))]
 impl From<crate::rpc::PayloadRpcError> for SyringeError {
     fn from(err: crate::rpc::PayloadRpcError) -> Self {
@@ -593,6 +1053,7 @@ impl From<crate::rpc::PayloadRpcError> for SyringeError {
             crate::rpc::PayloadRpcError::Io(e) => Self::Io(e),
             crate::rpc::PayloadRpcError::RemoteException(e) => Self::RemoteException(e),
             crate::rpc::PayloadRpcError::ProcessInaccessible => Self::ProcessInaccessible,
+            crate::rpc::PayloadRpcError::MissingAccess(e) => Self::MissingAccess(e),
             crate::rpc::PayloadRpcError::ModuleInaccessible => Self::ModuleInaccessible,
             crate::rpc::PayloadRpcError::RemoteProcedure(e) => Self::RemotePayloadProcedure(e),
             crate::rpc::PayloadRpcError::Serde(e) => Self::Serde(e),
@@ -602,7 +1063,7 @@ impl From<crate::rpc::PayloadRpcError> for SyringeError {
 
 /// Error enum encompassing all errors during syringe operations in a nested format.
 #[derive(Debug, Error)]
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "syringe")))]
 pub enum SyringeOperationError {
     /// Variant representing an error while injecting a module.
@@ -623,4 +1084,8 @@ pub enum SyringeOperationError {
     #[cfg(feature = "rpc-core")]
     #[error("procedure load error: {}", _0)]
     ProcedureLoad(#[from] LoadProcedureError),
+    /// Variant representing an error while manually mapping a module.
+    #[cfg(feature = "manual-map")]
+    #[error("manual map error: {}", _0)]
+    ManualMap(#[from] ManualMapError),
 }
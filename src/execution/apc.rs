@@ -0,0 +1,200 @@
+use std::{io, mem, os::windows::prelude::AsRawHandle, ptr, time::Duration};
+
+use cstr::cstr;
+
+use iced_x86::{
+    code_asm::{
+        dword_ptr, qword_ptr,
+        registers::{gpr32::*, gpr64::*},
+        CodeAssembler,
+    },
+    IcedError,
+};
+use widestring::u16cstr;
+use winapi::{
+    shared::ntdef::{HANDLE, NTSTATUS, PVOID},
+    um::{
+        processthreadsapi::QueueUserAPC,
+        winnt::{THREAD_QUERY_LIMITED_INFORMATION, THREAD_SET_CONTEXT},
+    },
+};
+
+use crate::{
+    execution::RemoteCall,
+    process::{
+        memory::{RemoteAllocation, RemoteBox, RemoteBoxAllocator},
+        BorrowedProcessModule, Process, ProcessArchitecture, ThreadHandle,
+    },
+};
+
+type NtQueueApcThreadFn = unsafe extern "system" fn(HANDLE, PVOID, PVOID, PVOID, PVOID) -> NTSTATUS;
+
+/// Executes thread procedures on an existing thread of the target process by queueing an asynchronous procedure call (APC) to it.
+///
+/// The queued wrapper calls the procedure stored in a [`RemoteCall`] and marks it as done,
+/// so that it can be awaited without creating a new thread.
+#[derive(Debug)]
+pub(crate) struct ApcQueuer {
+    code: RemoteAllocation,
+    call: RemoteBox<RemoteCall>,
+    /// `NtQueueApcThread` of the current process if the target is a wow64 process.
+    nt_queue_apc_thread: Option<NtQueueApcThreadFn>,
+}
+
+impl ApcQueuer {
+    pub fn build(remote_allocator: &RemoteBoxAllocator) -> Result<Self, io::Error> {
+        let architecture = remote_allocator.process_architecture()?;
+        if architecture == ProcessArchitecture::Arm64 {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "apc execution is not supported for ARM64 processes",
+            ));
+        }
+
+        let call = remote_allocator.alloc_and_copy(&RemoteCall::default())?;
+
+        let is_x86 = architecture.is_x86();
+        let nt_queue_apc_thread = if is_x86 && cfg!(target_arch = "x86_64") {
+            // QueueUserAPC always queues a 64-bit routine, so wow64 routines have to be queued using the native api.
+            let ntdll =
+                BorrowedProcessModule::find_local_by_name_or_abs_path_wstr(u16cstr!("ntdll.dll"))?
+                    .unwrap();
+            let nt_queue_apc_thread =
+                ntdll.get_local_procedure_address_cstr(cstr!("NtQueueApcThread"))?;
+            Some(unsafe { mem::transmute::<_, NtQueueApcThreadFn>(nt_queue_apc_thread) })
+        } else {
+            None
+        };
+
+        let code = if is_x86 {
+            // QueueUserAPC routines take a single argument, while native apc routines take three.
+            let argument_count = if nt_queue_apc_thread.is_some() { 3 } else { 1 };
+            Self::build_code_x86(argument_count).unwrap()
+        } else {
+            Self::build_code_x64().unwrap()
+        };
+        let code = remote_allocator.alloc_and_copy_buf(code.as_slice())?;
+        code.memory().flush_instruction_cache()?;
+
+        Ok(Self {
+            code,
+            call,
+            nt_queue_apc_thread,
+        })
+    }
+
+    /// Queues the given thread procedure to the thread with the given id and waits for its result.
+    ///
+    /// The procedure only runs once the thread enters an alertable wait state.
+    /// Returns `None` if the thread did not run the call before the given timeout elapsed. A queued apc cannot be
+    /// cancelled, so in that case the queuer must neither be used again nor dropped as the apc may still run later.
+    pub fn run(
+        &self,
+        thread_id: u32,
+        procedure: usize,
+        parameter: usize,
+        timeout: Option<Duration>,
+    ) -> Result<Option<u32>, io::Error> {
+        let thread = ThreadHandle::open(
+            thread_id,
+            THREAD_SET_CONTEXT | THREAD_QUERY_LIMITED_INFORMATION,
+        )?;
+        // the queued code only exists in the target process.
+        if thread.process_id()? != self.code.process().pid()? {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "thread does not belong to the target process",
+            ));
+        }
+
+        self.call.write(&RemoteCall::new(procedure, parameter))?;
+
+        if let Some(nt_queue_apc_thread) = self.nt_queue_apc_thread {
+            // wow64 expects the address of 32-bit apc routines to be encoded like this.
+            let routine = (self.code.as_raw_ptr() as usize)
+                .wrapping_shl(2)
+                .wrapping_neg();
+            let status = unsafe {
+                nt_queue_apc_thread(
+                    thread.as_raw_handle(),
+                    routine as PVOID,
+                    self.call.as_raw_ptr().cast(),
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                )
+            };
+            if status < 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!(
+                        "failed to queue apc to wow64 thread (NTSTATUS {:#x})",
+                        status
+                    ),
+                ));
+            }
+        } else {
+            let result = unsafe {
+                QueueUserAPC(
+                    Some(mem::transmute(self.code.as_raw_ptr())),
+                    thread.as_raw_handle(),
+                    self.call.as_raw_ptr() as usize,
+                )
+            };
+            if result == 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        RemoteCall::wait_for_completion_with_timeout(&self.call, timeout)
+    }
+
+    fn build_code_x86(argument_count: u32) -> Result<Vec<u8>, IcedError> {
+        let mut asm = CodeAssembler::new(32)?;
+
+        // void __stdcall apc(RemoteCall* call, ...)
+        asm.push(ebx)?;
+        asm.mov(ebx, dword_ptr(esp + 8))?; // call
+        asm.push(dword_ptr(ebx + RemoteCall::PARAMETER_OFFSET))?;
+        asm.mov(eax, dword_ptr(ebx + RemoteCall::PROCEDURE_OFFSET))?;
+        asm.call(eax)?; // stdcall, so the parameter is popped by the callee
+        asm.mov(dword_ptr(ebx + RemoteCall::RESULT_OFFSET), eax)?;
+        asm.mov(dword_ptr(ebx + RemoteCall::DONE_OFFSET), 1)?;
+        asm.pop(ebx)?;
+        asm.ret_1(argument_count * 4)?; // Restore stack ptr. (Callee cleanup)
+
+        let code = asm.assemble(0x1234_5678)?;
+        debug_assert_eq!(
+            code,
+            asm.assemble(0x1111_2222)?,
+            "apc x86 stub is not location independent"
+        );
+
+        Ok(code)
+    }
+
+    fn build_code_x64() -> Result<Vec<u8>, IcedError> {
+        let mut asm = CodeAssembler::new(64)?;
+
+        // void apc(RemoteCall* call)
+        asm.push(rbx)?; // also re-aligns the stack to a 16 byte boundary
+        asm.sub(rsp, 32)?; // shadow space
+        asm.mov(rbx, rcx)?; // call
+        asm.mov(rcx, qword_ptr(rbx + RemoteCall::PARAMETER_OFFSET))?;
+        asm.mov(rax, qword_ptr(rbx + RemoteCall::PROCEDURE_OFFSET))?;
+        asm.call(rax)?;
+        asm.mov(dword_ptr(rbx + RemoteCall::RESULT_OFFSET), eax)?;
+        asm.mov(dword_ptr(rbx + RemoteCall::DONE_OFFSET), 1)?;
+        asm.add(rsp, 32)?;
+        asm.pop(rbx)?;
+        asm.ret()?;
+
+        let code = asm.assemble(0x1234_5678)?;
+        debug_assert_eq!(
+            code,
+            asm.assemble(0x1111_2222)?,
+            "apc x64 stub is not location independent"
+        );
+
+        Ok(code)
+    }
+}
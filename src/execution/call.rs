@@ -0,0 +1,71 @@
+use std::{
+    io, thread,
+    time::{Duration, Instant},
+};
+
+use crate::process::{memory::RemoteBox, Process};
+
+/// A call of a thread procedure that is picked up by the wrapper code of an existing thread in the target process.
+/// The layout is shared with the generated wrapper code.
+#[derive(Debug, Clone, Copy, Default)]
+#[repr(C)]
+pub(crate) struct RemoteCall {
+    procedure: u64,
+    parameter: u64,
+    result: u32,
+    done: u32,
+}
+
+impl RemoteCall {
+    pub const PROCEDURE_OFFSET: i32 = 0;
+    pub const PARAMETER_OFFSET: i32 = 8;
+    pub const RESULT_OFFSET: i32 = 16;
+    pub const DONE_OFFSET: i32 = 20;
+
+    const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+    pub fn new(procedure: usize, parameter: usize) -> Self {
+        Self {
+            procedure: procedure as u64,
+            parameter: parameter as u64,
+            result: 0,
+            done: 0,
+        }
+    }
+
+    /// Returns whether the wrapper code marked this call as done.
+    pub fn is_done(&self) -> bool {
+        self.done != 0
+    }
+
+    /// Waits until the wrapper code marked the given call as done and returns the result of the procedure.
+    pub fn wait_for_completion(call: &RemoteBox<Self>) -> Result<u32, io::Error> {
+        Self::wait_for_completion_with_timeout(call, None)
+            .map(|result| result.expect("wait without timeout ended without result"))
+    }
+
+    /// Waits until the wrapper code marked the given call as done and returns the result of the procedure.
+    /// Returns `None` if the call was not done before the given timeout elapsed.
+    pub fn wait_for_completion_with_timeout(
+        call: &RemoteBox<Self>,
+        timeout: Option<Duration>,
+    ) -> Result<Option<u32>, io::Error> {
+        let start = Instant::now();
+        loop {
+            let state = call.read()?;
+            if state.is_done() {
+                return Ok(Some(state.result));
+            }
+            if !call.process().is_alive() {
+                return Err(io::Error::new(
+                    io::ErrorKind::BrokenPipe,
+                    "target process terminated while running remote call",
+                ));
+            }
+            if timeout.map_or(false, |timeout| start.elapsed() >= timeout) {
+                return Ok(None);
+            }
+            thread::sleep(Self::POLL_INTERVAL);
+        }
+    }
+}
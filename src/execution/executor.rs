@@ -0,0 +1,147 @@
+use std::{
+    cell::{Cell, OnceCell, RefCell},
+    io, mem,
+    rc::Rc,
+    time::Duration,
+};
+
+use crate::{
+    execution::{ApcQueuer, ThreadHijacker},
+    process::{memory::RemoteBoxAllocator, BorrowedProcess, Process},
+};
+
+/// The mechanism used to execute code inside the target process of a [`Syringe`](crate::Syringe).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "syringe")))]
+pub enum ExecutionStrategy {
+    /// Creates a new thread in the target process using `CreateRemoteThread` for every remote call.
+    #[default]
+    RemoteThread,
+    /// Suspends an existing thread of the target process, redirects it to the remote code and restores its original context afterwards.
+    ///
+    /// # Note
+    /// A thread that is blocked inside a system call (e.g. waiting or sleeping) only picks up the redirection once the call returns.
+    /// This strategy is not supported for ARM64 processes.
+    ThreadHijacking {
+        /// The id of the thread to hijack or `None` to use the first thread of the target process (usually the main thread).
+        /// The thread has to belong to the target process.
+        thread_id: Option<u32>,
+        /// The maximum time to wait for the thread to pick up a remote call or `None` to wait indefinitely.
+        ///
+        /// If the thread did not pick up the call in time, its original context is restored and the call fails with
+        /// an error of kind [`TimedOut`](io::ErrorKind::TimedOut). A call that was already picked up is always awaited.
+        timeout: Option<Duration>,
+    },
+    /// Queues an asynchronous procedure call (APC) to the given thread of the target process using `QueueUserAPC`.
+    ///
+    /// This allows running code on a specific thread, which is required for thread-affine apis (e.g. on a ui or render thread).
+    ///
+    /// # Note
+    /// The queued code only runs once the thread enters an alertable wait state (e.g. through `SleepEx` or `WaitForSingleObjectEx`).
+    /// This strategy is not supported for ARM64 processes.
+    Apc {
+        /// The id of the thread to queue the remote calls to, which has to belong to the target process.
+        thread_id: u32,
+        /// The maximum time to wait for the thread to run a remote call or `None` to wait indefinitely.
+        ///
+        /// If the thread did not run the call in time, the call fails with an error of kind [`TimedOut`](io::ErrorKind::TimedOut).
+        /// A queued apc cannot be cancelled, so the procedure may still run once the thread enters an alertable wait state.
+        /// The memory used by the timed out call is therefore leaked and stays allocated until the target process exits.
+        timeout: Option<Duration>,
+    },
+}
+
+/// Executes code in a target process according to an [`ExecutionStrategy`].
+/// Cloned executors share their strategy.
+#[derive(Debug, Clone)]
+pub(crate) struct RemoteExecutor(Rc<RemoteExecutorInner>);
+
+#[derive(Debug)]
+struct RemoteExecutorInner {
+    remote_allocator: RemoteBoxAllocator,
+    strategy: Cell<ExecutionStrategy>,
+    thread_hijacker: OnceCell<ThreadHijacker>,
+    /// The queuer is replaced after a timed out call, as its memory has to be leaked (see [`ExecutionStrategy::Apc`]).
+    apc_queuer: RefCell<Option<ApcQueuer>>,
+}
+
+impl RemoteExecutor {
+    pub fn new(remote_allocator: RemoteBoxAllocator, strategy: ExecutionStrategy) -> Self {
+        Self(Rc::new(RemoteExecutorInner {
+            remote_allocator,
+            strategy: Cell::new(strategy),
+            thread_hijacker: OnceCell::new(),
+            apc_queuer: RefCell::new(None),
+        }))
+    }
+
+    pub fn process(&self) -> BorrowedProcess<'_> {
+        self.0.remote_allocator.process()
+    }
+
+    pub fn remote_allocator(&self) -> &RemoteBoxAllocator {
+        &self.0.remote_allocator
+    }
+
+    pub fn strategy(&self) -> ExecutionStrategy {
+        self.0.strategy.get()
+    }
+
+    pub fn set_strategy(&self, strategy: ExecutionStrategy) {
+        self.0.strategy.set(strategy);
+    }
+
+    /// Runs the given thread procedure with the given parameter in the target process and returns its exit code.
+    pub fn run<T>(
+        &self,
+        remote_fn: extern "system" fn(*mut T) -> u32,
+        parameter: *mut T,
+    ) -> Result<u32, io::Error> {
+        match self.strategy() {
+            ExecutionStrategy::RemoteThread => {
+                self.process().run_remote_thread(remote_fn, parameter)
+            }
+            ExecutionStrategy::ThreadHijacking { thread_id, timeout } => {
+                let thread_id = match thread_id {
+                    Some(thread_id) => thread_id,
+                    None => self
+                        .process()
+                        .thread_ids()?
+                        .first()
+                        .copied()
+                        .ok_or_else(|| {
+                            io::Error::new(io::ErrorKind::NotFound, "target process has no threads")
+                        })?,
+                };
+                let hijacker = self
+                    .0
+                    .thread_hijacker
+                    .get_or_try_init(|| ThreadHijacker::build(self.remote_allocator()))?;
+                hijacker.run(thread_id, remote_fn as usize, parameter as usize, timeout)
+            }
+            ExecutionStrategy::Apc { thread_id, timeout } => {
+                let mut apc_queuer = self.0.apc_queuer.borrow_mut();
+                if apc_queuer.is_none() {
+                    *apc_queuer = Some(ApcQueuer::build(self.remote_allocator())?);
+                }
+                let result = apc_queuer.as_ref().unwrap().run(
+                    thread_id,
+                    remote_fn as usize,
+                    parameter as usize,
+                    timeout,
+                )?;
+                match result {
+                    Some(result) => Ok(result),
+                    None => {
+                        // the apc stays queued, so the code and the call it refers to must never be freed.
+                        mem::forget(apc_queuer.take());
+                        Err(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            "thread did not run the queued remote call in time",
+                        ))
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,505 @@
+use std::{io, mem, thread, time::Duration};
+
+use iced_x86::{
+    code_asm::{
+        dword_ptr, ptr, qword_ptr,
+        registers::{gpr32::*, gpr64::*},
+        CodeAssembler,
+    },
+    IcedError,
+};
+use winapi::um::winnt::{
+    THREAD_GET_CONTEXT, THREAD_QUERY_LIMITED_INFORMATION, THREAD_SET_CONTEXT, THREAD_SUSPEND_RESUME,
+};
+
+use crate::{
+    execution::RemoteCall,
+    process::{
+        memory::{ProcessMemorySlice, RemoteAllocation, RemoteBox, RemoteBoxAllocator},
+        BorrowedProcess, Process, ProcessArchitecture, ThreadHandle,
+    },
+};
+
+/// Executes thread procedures on an existing thread of the target process by temporarily redirecting its instruction pointer.
+///
+/// The hijacked thread is redirected to a wrapper that saves all registers, flags and the extended processor state
+/// (using `xsave` if available, so that the upper halves of the AVX registers are preserved as well),
+/// calls the procedure stored in a [`RemoteCall`], stores the result, restores the saved state
+/// and finally returns to the original instruction pointer, which is pushed onto the stack of the thread by the injector.
+#[derive(Debug)]
+pub(crate) struct ThreadHijacker {
+    code: RemoteAllocation,
+    call: RemoteBox<RemoteCall>,
+}
+
+/// The instruction and stack pointer of a thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ControlRegisters {
+    instruction_ptr: u64,
+    stack_ptr: u64,
+}
+
+impl ThreadHijacker {
+    pub fn build(remote_allocator: &RemoteBoxAllocator) -> Result<Self, io::Error> {
+        let architecture = remote_allocator.process_architecture()?;
+        if architecture == ProcessArchitecture::Arm64 {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "thread hijacking is not supported for ARM64 processes",
+            ));
+        }
+
+        let call = remote_allocator.alloc_and_copy(&RemoteCall::default())?;
+
+        let xsave_area_size = Self::xsave_area_size()?;
+        let code = if architecture.is_x86() {
+            Self::build_code_x86(call.as_ptr().as_ptr(), xsave_area_size).unwrap()
+        } else {
+            Self::build_code_x64(call.as_ptr().as_ptr(), xsave_area_size).unwrap()
+        };
+        let code = remote_allocator.alloc_and_copy_buf(code.as_slice())?;
+        code.memory().flush_instruction_cache()?;
+
+        Ok(Self { code, call })
+    }
+
+    fn process(&self) -> BorrowedProcess<'_> {
+        self.code.process()
+    }
+
+    /// Runs the given thread procedure on the thread with the given id and returns its result.
+    ///
+    /// If the thread does not pick up the call before the given timeout elapses, the redirection is undone and an error
+    /// of kind [`TimedOut`](io::ErrorKind::TimedOut) is returned.
+    pub fn run(
+        &self,
+        thread_id: u32,
+        procedure: usize,
+        parameter: usize,
+        timeout: Option<Duration>,
+    ) -> Result<u32, io::Error> {
+        let thread = ThreadHandle::open(
+            thread_id,
+            THREAD_SUSPEND_RESUME
+                | THREAD_GET_CONTEXT
+                | THREAD_SET_CONTEXT
+                | THREAD_QUERY_LIMITED_INFORMATION,
+        )?;
+        // redirecting a thread of another process would corrupt that process.
+        if thread.process_id()? != self.process().pid()? {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "thread does not belong to the target process",
+            ));
+        }
+
+        self.call.write(&RemoteCall::new(procedure, parameter))?;
+
+        let suspended = thread.suspend()?;
+        let (original, redirected) = self.redirect(&thread)?;
+        suspended.resume()?;
+
+        let result = match RemoteCall::wait_for_completion_with_timeout(&self.call, timeout)? {
+            Some(result) => result,
+            None => {
+                let suspended = thread.suspend()?;
+                if !self.call.read()?.is_done() && self.control_registers(&thread)? == redirected {
+                    self.set_control_registers(&thread, original)?;
+                    suspended.resume()?;
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "hijacked thread did not pick up the remote call in time",
+                    ));
+                }
+
+                // the thread already picked up the call, so it cannot be cancelled anymore.
+                suspended.resume()?;
+                RemoteCall::wait_for_completion(&self.call)?
+            }
+        };
+
+        self.wait_for_return(&thread)?;
+
+        Ok(result)
+    }
+
+    /// Waits until the given thread has left the wrapper code.
+    ///
+    /// The wrapper marks the call as done before it restores the interrupted state, so the thread may still be executing
+    /// its epilogue. The wrapper must not be freed or reused before the thread has returned to its original instruction pointer.
+    fn wait_for_return(&self, thread: &ThreadHandle) -> Result<(), io::Error> {
+        let code_start = self.code.as_raw_ptr() as u64;
+        let code = code_start..code_start + self.code.len() as u64;
+        loop {
+            let suspended = thread.suspend()?;
+            let registers = self.control_registers(thread)?;
+            suspended.resume()?;
+
+            if !code.contains(&registers.instruction_ptr) {
+                return Ok(());
+            }
+            thread::yield_now();
+        }
+    }
+
+    /// Returns the size of the `xsave` area for the state components enabled by the os
+    /// or `None` if `xsave` is not supported, in which case only the legacy fpu/sse state is saved using `fxsave`.
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    fn xsave_area_size() -> Result<Option<u32>, io::Error> {
+        #[cfg(target_arch = "x86")]
+        use std::arch::x86::{__cpuid, __cpuid_count};
+        #[cfg(target_arch = "x86_64")]
+        use std::arch::x86_64::{__cpuid, __cpuid_count};
+
+        // CPUID.01H:ECX.OSXSAVE[bit 27] indicates that the os has enabled xsave.
+        if unsafe { __cpuid(1) }.ecx & (1 << 27) == 0 {
+            return Ok(None);
+        }
+        // CPUID.0DH:EBX is the size of the xsave area for the features currently enabled in XCR0,
+        // which is the same for all processes on the system.
+        Ok(Some(unsafe { __cpuid_count(0xD, 0) }.ebx))
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    fn xsave_area_size() -> Result<Option<u32>, io::Error> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "thread hijacking is not supported from ARM64 processes",
+        ))
+    }
+
+    /// Pushes the current instruction pointer of the given (suspended) thread onto its stack and points it to the wrapper code.
+    /// Returns the original and the redirected control registers.
+    fn redirect(
+        &self,
+        thread: &ThreadHandle,
+    ) -> Result<(ControlRegisters, ControlRegisters), io::Error> {
+        let original = self.control_registers(thread)?;
+
+        let redirected = if self.code.process_architecture()?.is_x86() {
+            let stack_ptr = original.stack_ptr - mem::size_of::<u32>() as u64;
+            self.stack_slot(stack_ptr, mem::size_of::<u32>())
+                .write_struct(0, &(original.instruction_ptr as u32))?;
+            ControlRegisters {
+                instruction_ptr: self.code.as_raw_ptr() as u64,
+                stack_ptr,
+            }
+        } else {
+            let stack_ptr = original.stack_ptr - mem::size_of::<u64>() as u64;
+            self.stack_slot(stack_ptr, mem::size_of::<u64>())
+                .write_struct(0, &original.instruction_ptr)?;
+            ControlRegisters {
+                instruction_ptr: self.code.as_raw_ptr() as u64,
+                stack_ptr,
+            }
+        };
+
+        self.set_control_registers(thread, redirected)?;
+        Ok((original, redirected))
+    }
+
+    fn control_registers(&self, thread: &ThreadHandle) -> Result<ControlRegisters, io::Error> {
+        let mut registers = None;
+        self.update_control_registers(thread, |current| {
+            registers = Some(*current);
+            false
+        })?;
+        Ok(registers.unwrap())
+    }
+
+    fn set_control_registers(
+        &self,
+        thread: &ThreadHandle,
+        registers: ControlRegisters,
+    ) -> Result<(), io::Error> {
+        self.update_control_registers(thread, |current| {
+            *current = registers;
+            true
+        })
+    }
+
+    /// Reads the control registers of the given (suspended) thread and writes them back if the given closure returns `true`.
+    fn update_control_registers(
+        &self,
+        thread: &ThreadHandle,
+        update: impl FnOnce(&mut ControlRegisters) -> bool,
+    ) -> Result<(), io::Error> {
+        #[cfg(target_arch = "x86_64")]
+        if self.code.process_architecture()?.is_x86() {
+            return Self::update_control_registers_wow64(thread, update);
+        }
+
+        Self::update_control_registers_native(thread, update)
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    fn update_control_registers_native(
+        thread: &ThreadHandle,
+        update: impl FnOnce(&mut ControlRegisters) -> bool,
+    ) -> Result<(), io::Error> {
+        use std::os::windows::prelude::AsRawHandle;
+        use winapi::um::{
+            processthreadsapi::{GetThreadContext, SetThreadContext},
+            winnt::{CONTEXT, CONTEXT_CONTROL},
+        };
+
+        // CONTEXT has to be 16 byte aligned on x64.
+        #[repr(C, align(16))]
+        struct AlignedContext(CONTEXT);
+
+        let mut context: AlignedContext = unsafe { mem::zeroed() };
+        context.0.ContextFlags = CONTEXT_CONTROL;
+        if unsafe { GetThreadContext(thread.as_raw_handle(), &mut context.0) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut registers = ControlRegisters {
+            instruction_ptr: context.0.Rip,
+            stack_ptr: context.0.Rsp,
+        };
+        if !update(&mut registers) {
+            return Ok(());
+        }
+        context.0.Rip = registers.instruction_ptr;
+        context.0.Rsp = registers.stack_ptr;
+
+        if unsafe { SetThreadContext(thread.as_raw_handle(), &context.0) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    #[cfg(target_arch = "x86")]
+    fn update_control_registers_native(
+        thread: &ThreadHandle,
+        update: impl FnOnce(&mut ControlRegisters) -> bool,
+    ) -> Result<(), io::Error> {
+        use std::os::windows::prelude::AsRawHandle;
+        use winapi::um::{
+            processthreadsapi::{GetThreadContext, SetThreadContext},
+            winnt::{CONTEXT, CONTEXT_CONTROL},
+        };
+
+        let mut context: CONTEXT = unsafe { mem::zeroed() };
+        context.ContextFlags = CONTEXT_CONTROL;
+        if unsafe { GetThreadContext(thread.as_raw_handle(), &mut context) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut registers = ControlRegisters {
+            instruction_ptr: u64::from(context.Eip),
+            stack_ptr: u64::from(context.Esp),
+        };
+        if !update(&mut registers) {
+            return Ok(());
+        }
+        context.Eip = registers.instruction_ptr as u32;
+        context.Esp = registers.stack_ptr as u32;
+
+        if unsafe { SetThreadContext(thread.as_raw_handle(), &context) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    fn update_control_registers_native(
+        _thread: &ThreadHandle,
+        _update: impl FnOnce(&mut ControlRegisters) -> bool,
+    ) -> Result<(), io::Error> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "thread hijacking is not supported from ARM64 processes",
+        ))
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    fn update_control_registers_wow64(
+        thread: &ThreadHandle,
+        update: impl FnOnce(&mut ControlRegisters) -> bool,
+    ) -> Result<(), io::Error> {
+        use std::os::windows::prelude::AsRawHandle;
+        use winapi::um::{
+            winbase::{Wow64GetThreadContext, Wow64SetThreadContext},
+            winnt::{WOW64_CONTEXT, WOW64_CONTEXT_CONTROL},
+        };
+
+        let mut context: WOW64_CONTEXT = unsafe { mem::zeroed() };
+        context.ContextFlags = WOW64_CONTEXT_CONTROL;
+        if unsafe { Wow64GetThreadContext(thread.as_raw_handle(), &mut context) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut registers = ControlRegisters {
+            instruction_ptr: u64::from(context.Eip),
+            stack_ptr: u64::from(context.Esp),
+        };
+        if !update(&mut registers) {
+            return Ok(());
+        }
+        context.Eip = registers.instruction_ptr as u32;
+        context.Esp = registers.stack_ptr as u32;
+
+        if unsafe { Wow64SetThreadContext(thread.as_raw_handle(), &context) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Offset of the header in the xsave area, which follows the legacy fxsave region.
+    const XSAVE_HEADER_OFFSET: i32 = 512;
+    /// Size of the legacy region and the header of the xsave area.
+    const XSAVE_AREA_MIN_SIZE: i32 = 576;
+
+    /// Rounds the given xsave area size up to a multiple of 64 bytes to keep the stack aligned.
+    fn align_xsave_area_size(size: u32) -> i32 {
+        ((size as i32).max(Self::XSAVE_AREA_MIN_SIZE) + 63) & !63
+    }
+
+    fn stack_slot(&self, address: u64, len: usize) -> ProcessMemorySlice<'_> {
+        unsafe {
+            ProcessMemorySlice::from_raw_parts(address as usize as *mut u8, len, self.process())
+        }
+    }
+
+    #[allow(clippy::fn_to_numeric_cast, clippy::fn_to_numeric_cast_with_truncation)]
+    fn build_code_x86(
+        call: *mut RemoteCall,
+        xsave_area_size: Option<u32>,
+    ) -> Result<Vec<u8>, IcedError> {
+        assert!(!call.is_null());
+        assert_eq!(call as u32 as usize, call as usize);
+
+        let mut asm = CodeAssembler::new(32)?;
+
+        // save the interrupted state (the original eip was pushed by the injector)
+        asm.pushfd()?;
+        asm.pushad()?;
+        asm.cld()?;
+        asm.mov(ebp, esp)?;
+        match xsave_area_size {
+            Some(size) => {
+                asm.and(esp, -64)?; // align stack for xsave
+                asm.sub(esp, Self::align_xsave_area_size(size))?;
+                // xrstor faults on a garbage xsave header, but xsave only writes its first 8 bytes.
+                asm.xor(eax, eax)?;
+                for offset in (Self::XSAVE_HEADER_OFFSET..Self::XSAVE_AREA_MIN_SIZE).step_by(4) {
+                    asm.mov(dword_ptr(esp + offset), eax)?;
+                }
+                asm.mov(eax, -1)?; // save all state components enabled in XCR0
+                asm.mov(edx, -1)?;
+                asm.xsave(ptr(esp))?;
+            }
+            None => {
+                asm.and(esp, -16)?; // align stack for fxsave
+                asm.sub(esp, 512)?;
+                asm.fxsave(ptr(esp))?;
+            }
+        }
+
+        // call the procedure like a thread entry point
+        asm.mov(ebx, call as u32)?;
+        asm.push(dword_ptr(ebx + RemoteCall::PARAMETER_OFFSET))?;
+        asm.mov(eax, dword_ptr(ebx + RemoteCall::PROCEDURE_OFFSET))?;
+        asm.call(eax)?; // stdcall, so the parameter is popped by the callee
+        asm.mov(dword_ptr(ebx + RemoteCall::RESULT_OFFSET), eax)?;
+
+        // restore the interrupted state
+        if xsave_area_size.is_some() {
+            asm.mov(eax, -1)?;
+            asm.mov(edx, -1)?;
+            asm.xrstor(ptr(esp))?;
+        } else {
+            asm.fxrstor(ptr(esp))?;
+        }
+        asm.mov(dword_ptr(ebx + RemoteCall::DONE_OFFSET), 1)?;
+        asm.mov(esp, ebp)?;
+        asm.popad()?;
+        asm.popfd()?;
+        asm.ret()?; // return to the original eip
+
+        let code = asm.assemble(0x1234_5678)?;
+        debug_assert_eq!(
+            code,
+            asm.assemble(0x1111_2222)?,
+            "thread hijacking x86 stub is not location independent"
+        );
+
+        Ok(code)
+    }
+
+    #[allow(clippy::fn_to_numeric_cast, clippy::fn_to_numeric_cast_with_truncation)]
+    fn build_code_x64(
+        call: *mut RemoteCall,
+        xsave_area_size: Option<u32>,
+    ) -> Result<Vec<u8>, IcedError> {
+        assert!(!call.is_null());
+
+        let mut asm = CodeAssembler::new(64)?;
+
+        // save the interrupted state (the original rip was pushed by the injector)
+        asm.pushfq()?;
+        for register in [
+            rax, rcx, rdx, rbx, rbp, rsi, rdi, r8, r9, r10, r11, r12, r13, r14, r15,
+        ] {
+            asm.push(register)?;
+        }
+        asm.cld()?;
+        asm.mov(rbp, rsp)?;
+        match xsave_area_size {
+            Some(size) => {
+                asm.and(rsp, -64)?; // align stack for xsave and the call
+                asm.sub(rsp, Self::align_xsave_area_size(size))?;
+                // xrstor faults on a garbage xsave header, but xsave only writes its first 8 bytes.
+                asm.xor(eax, eax)?;
+                for offset in (Self::XSAVE_HEADER_OFFSET..Self::XSAVE_AREA_MIN_SIZE).step_by(8) {
+                    asm.mov(qword_ptr(rsp + offset), rax)?;
+                }
+                asm.mov(eax, -1)?; // save all state components enabled in XCR0
+                asm.mov(edx, -1)?;
+                asm.xsave64(ptr(rsp))?;
+            }
+            None => {
+                asm.and(rsp, -16)?; // align stack for fxsave and the call
+                asm.sub(rsp, 512)?;
+                asm.fxsave(ptr(rsp))?;
+            }
+        }
+
+        // call the procedure like a thread entry point
+        asm.mov(rbx, call as u64)?;
+        asm.mov(rcx, qword_ptr(rbx + RemoteCall::PARAMETER_OFFSET))?;
+        asm.mov(rax, qword_ptr(rbx + RemoteCall::PROCEDURE_OFFSET))?;
+        asm.sub(rsp, 32)?; // shadow space
+        asm.call(rax)?;
+        asm.add(rsp, 32)?;
+        asm.mov(dword_ptr(rbx + RemoteCall::RESULT_OFFSET), eax)?;
+
+        // restore the interrupted state
+        if xsave_area_size.is_some() {
+            asm.mov(eax, -1)?;
+            asm.mov(edx, -1)?;
+            asm.xrstor64(ptr(rsp))?;
+        } else {
+            asm.fxrstor(ptr(rsp))?;
+        }
+        asm.mov(dword_ptr(rbx + RemoteCall::DONE_OFFSET), 1)?;
+        asm.mov(rsp, rbp)?;
+        for register in [
+            r15, r14, r13, r12, r11, r10, r9, r8, rdi, rsi, rbp, rbx, rdx, rcx, rax,
+        ] {
+            asm.pop(register)?;
+        }
+        asm.popfq()?;
+        asm.ret()?; // return to the original rip
+
+        let code = asm.assemble(0x1234_5678)?;
+        debug_assert_eq!(
+            code,
+            asm.assemble(0x1111_2222)?,
+            "thread hijacking x64 stub is not location independent"
+        );
+
+        Ok(code)
+    }
+}
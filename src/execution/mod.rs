@@ -0,0 +1,11 @@
+mod executor;
+pub use executor::*;
+
+mod call;
+pub(crate) use call::*;
+
+mod hijack;
+pub(crate) use hijack::*;
+
+mod apc;
+pub(crate) use apc::*;
@@ -0,0 +1,479 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    env,
+    fmt::{self, Display},
+    io,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use winapi::{
+    shared::{minwindef::UINT, ntdef::LPWSTR},
+    um::{
+        sysinfoapi::{GetSystemDirectoryW, GetWindowsDirectoryW},
+        wow64apiset::GetSystemWow64DirectoryW,
+    },
+};
+
+use crate::{
+    error::ReadImageError,
+    inject_options::{InjectOptions, LoadLibraryFlags},
+    payload_info::is_compatible_machine,
+    process::{
+        is_api_set_name, ApiSetSchema, BorrowedProcess, BorrowedProcessModule, ImportedSymbol,
+        ModuleImage, Process, ProcessArchitecture,
+    },
+    utils::{win_fill_path_buf_helper, FillPathBufResult},
+};
+
+/// A diagnosis of why a payload could not be loaded into a target process.
+///
+/// The diagnosis is created by walking the import graph of the payload and checking each dependency against the
+/// modules loaded in the target process and the directories the loader is likely to search.
+///
+/// # Note
+/// The search directories are approximated from the point of view of the current process, so the `PATH` of the
+/// current process is used and the current directory of the target process is not considered.
+/// Delay loaded imports are not checked as they do not prevent the payload from being loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InjectFailureDiagnosis {
+    issues: Vec<DependencyIssue>,
+}
+
+impl InjectFailureDiagnosis {
+    /// Diagnoses the dependencies of the payload at the given path for being loaded into the given process
+    /// like by [`Syringe::inject`](crate::Syringe::inject).
+    pub fn diagnose(
+        process: BorrowedProcess<'_>,
+        payload_path: impl AsRef<Path>,
+    ) -> Result<Self, ReadImageError> {
+        Self::diagnose_with_search_path(process, payload_path.as_ref(), &[], false)
+    }
+
+    /// Diagnoses the dependencies of the payload at the given path for being loaded into the given process
+    /// like by [`Syringe::inject_with_options`](crate::Syringe::inject_with_options).
+    pub fn diagnose_with_options(
+        process: BorrowedProcess<'_>,
+        payload_path: impl AsRef<Path>,
+        options: &InjectOptions,
+    ) -> Result<Self, ReadImageError> {
+        let searches_payload_dir = options.flags().intersects(
+            LoadLibraryFlags::LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR
+                | LoadLibraryFlags::LOAD_WITH_ALTERED_SEARCH_PATH,
+        );
+        Self::diagnose_with_search_path(
+            process,
+            payload_path.as_ref(),
+            options.dll_directories(),
+            searches_payload_dir,
+        )
+    }
+
+    fn diagnose_with_search_path(
+        process: BorrowedProcess<'_>,
+        payload_path: &Path,
+        dll_directories: &[PathBuf],
+        searches_payload_dir: bool,
+    ) -> Result<Self, ReadImageError> {
+        let payload = ModuleImage::from_file(payload_path)?;
+        let payload_name = payload_path
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+
+        let mut walker =
+            DependencyWalker::new(process, payload_path, dll_directories, searches_payload_dir)?;
+        walker.walk(payload_name, payload);
+
+        Ok(Self {
+            issues: walker.issues,
+        })
+    }
+
+    /// Returns the issues that were found, in the order they were encountered while walking the import graph.
+    #[must_use]
+    pub fn issues(&self) -> &[DependencyIssue] {
+        &self.issues
+    }
+
+    /// Returns whether no issues were found.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl Display for InjectFailureDiagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.issues.is_empty() {
+            return write!(f, "no dependency issues found");
+        }
+
+        for (i, issue) in self.issues.iter().enumerate() {
+            if i != 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+/// An issue with a dependency that prevents a payload from being loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyIssue {
+    /// A module that could not be found in the target process or any of the search directories.
+    MissingModule {
+        /// The name of the module that imports the missing module.
+        importer: String,
+        /// The name of the missing module.
+        module: String,
+    },
+    /// A module that was only found in the directory of the payload, which is not searched by the loader.
+    /// This can be solved by using [`Syringe::inject_with_options`](crate::Syringe::inject_with_options)
+    /// with [`LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR`](LoadLibraryFlags::LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR).
+    NotInSearchPath {
+        /// The name of the module that imports the module.
+        importer: String,
+        /// The name of the module.
+        module: String,
+        /// The path the module was found at.
+        path: PathBuf,
+    },
+    /// A module that was only found built for a different architecture than the target process.
+    ArchitectureMismatch {
+        /// The name of the module that imports the module.
+        importer: String,
+        /// The name of the module.
+        module: String,
+        /// The path the module was found at.
+        path: PathBuf,
+    },
+    /// A symbol that is imported from a module but not exported by it.
+    MissingImport {
+        /// The name of the module that imports the symbol.
+        importer: String,
+        /// The name of the module the symbol is imported from.
+        module: String,
+        /// The missing symbol.
+        symbol: ImportedSymbol,
+    },
+}
+
+impl Display for DependencyIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingModule { importer, module } => {
+                write!(f, "{} imported by {} was not found", module, importer)
+            }
+            Self::NotInSearchPath {
+                importer,
+                module,
+                path,
+            } => write!(
+                f,
+                "{} imported by {} was found at {} which is not searched by the loader",
+                module,
+                importer,
+                path.display()
+            ),
+            Self::ArchitectureMismatch {
+                importer,
+                module,
+                path,
+            } => write!(
+                f,
+                "{} imported by {} was found at {} but does not match the target architecture",
+                module,
+                importer,
+                path.display()
+            ),
+            Self::MissingImport {
+                importer,
+                module,
+                symbol,
+            } => write!(
+                f,
+                "{} imported by {} is not exported by {}",
+                symbol, importer, module
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Resolution {
+    /// The module was found. The image is `None` if it could not be read.
+    Found {
+        image: Option<Rc<ModuleImage>>,
+        is_loaded: bool,
+    },
+    NotFound,
+    NotInSearchPath(PathBuf),
+    ArchitectureMismatch(PathBuf),
+}
+
+struct DependencyWalker<'a> {
+    architecture: ProcessArchitecture,
+    /// The modules loaded in the target process by their lowercase name.
+    loaded_modules: HashMap<String, Vec<BorrowedProcessModule<'a>>>,
+    search_dirs: Vec<PathBuf>,
+    /// The directory of the payload, if it is not part of the search directories.
+    unsearched_payload_dir: Option<PathBuf>,
+    api_set_schema: Option<ApiSetSchema<'static>>,
+    resolved: HashMap<String, Resolution>,
+    issues: Vec<DependencyIssue>,
+}
+
+impl<'a> DependencyWalker<'a> {
+    fn new(
+        process: BorrowedProcess<'a>,
+        payload_path: &Path,
+        dll_directories: &[PathBuf],
+        searches_payload_dir: bool,
+    ) -> Result<Self, io::Error> {
+        let architecture = process.architecture()?;
+
+        let mut loaded_modules = HashMap::<_, Vec<_>>::new();
+        for module in process.modules()? {
+            // skip modules that were unloaded in the meantime.
+            if let Ok(name) = module.base_name() {
+                let name = name.to_string_lossy().to_ascii_lowercase();
+                loaded_modules.entry(name).or_default().push(module);
+            }
+        }
+
+        let payload_dir = payload_path.parent().map(Path::to_path_buf);
+        let mut search_dirs = Vec::new();
+        if searches_payload_dir {
+            search_dirs.extend(payload_dir.clone());
+        }
+        search_dirs.extend(dll_directories.iter().cloned());
+        search_dirs.extend(process.path()?.parent().map(Path::to_path_buf));
+        search_dirs.push(system_dir(architecture)?);
+        search_dirs.push(windows_dir()?);
+        if let Some(paths) = env::var_os("PATH") {
+            search_dirs.extend(env::split_paths(&paths));
+        }
+
+        Ok(Self {
+            architecture,
+            loaded_modules,
+            search_dirs,
+            unsearched_payload_dir: payload_dir.filter(|_| !searches_payload_dir),
+            // api sets are resolved using the schema of the current process, which is the same for all processes.
+            api_set_schema: ApiSetSchema::current().ok().flatten(),
+            resolved: HashMap::new(),
+            issues: Vec::new(),
+        })
+    }
+
+    fn walk(&mut self, payload_name: String, payload: ModuleImage) {
+        let mut visited = HashSet::new();
+        visited.insert(payload_name.to_ascii_lowercase());
+        let mut queue = VecDeque::new();
+        queue.push_back((payload_name, Rc::new(payload)));
+
+        while let Some((importer, image)) = queue.pop_front() {
+            for import in image.imports() {
+                let module = if is_api_set_name(import.module()) {
+                    let schema = match &self.api_set_schema {
+                        Some(schema) => schema,
+                        None => continue,
+                    };
+                    match schema.resolve(import.module(), Some(&importer)) {
+                        Some(host) => host,
+                        None => {
+                            self.issues.push(DependencyIssue::MissingModule {
+                                importer: importer.clone(),
+                                module: import.module().to_owned(),
+                            });
+                            continue;
+                        }
+                    }
+                } else {
+                    import.module().to_owned()
+                };
+
+                match self.resolve(&module) {
+                    Resolution::Found {
+                        image: Some(dependency),
+                        is_loaded,
+                    } => {
+                        for symbol in import.symbols() {
+                            if dependency.find_export(symbol.export_ref()).is_none() {
+                                self.issues.push(DependencyIssue::MissingImport {
+                                    importer: importer.clone(),
+                                    module: module.clone(),
+                                    symbol: symbol.clone(),
+                                });
+                            }
+                        }
+
+                        // the dependencies of loaded modules are already satisfied.
+                        if !is_loaded && visited.insert(module.to_ascii_lowercase()) {
+                            queue.push_back((module, dependency));
+                        }
+                    }
+                    Resolution::Found { image: None, .. } => {}
+                    Resolution::NotFound => self.issues.push(DependencyIssue::MissingModule {
+                        importer: importer.clone(),
+                        module,
+                    }),
+                    Resolution::NotInSearchPath(path) => {
+                        self.issues.push(DependencyIssue::NotInSearchPath {
+                            importer: importer.clone(),
+                            module,
+                            path,
+                        });
+                    }
+                    Resolution::ArchitectureMismatch(path) => {
+                        self.issues.push(DependencyIssue::ArchitectureMismatch {
+                            importer: importer.clone(),
+                            module,
+                            path,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    fn resolve(&mut self, module: &str) -> Resolution {
+        let key = module.to_ascii_lowercase();
+        if let Some(resolution) = self.resolved.get(&key) {
+            return resolution.clone();
+        }
+
+        let resolution = self.resolve_uncached(module, &key);
+        self.resolved.insert(key, resolution.clone());
+        resolution
+    }
+
+    fn resolve_uncached(&self, module: &str, key: &str) -> Resolution {
+        if let Some(loaded_modules) = self.loaded_modules.get(key) {
+            // wow64 processes also contain 64-bit modules with the same name as their 32-bit counterparts.
+            let mut is_unreadable = false;
+            for loaded_module in loaded_modules {
+                match loaded_module.image() {
+                    Ok(image) if is_compatible_machine(image.machine(), self.architecture) => {
+                        return Resolution::Found {
+                            image: Some(Rc::new(image)),
+                            is_loaded: true,
+                        };
+                    }
+                    Ok(_) => {}
+                    Err(_) => is_unreadable = true,
+                }
+            }
+            if is_unreadable {
+                return Resolution::Found {
+                    image: None,
+                    is_loaded: true,
+                };
+            }
+        }
+
+        let mut mismatched_path = None;
+        for dir in &self.search_dirs {
+            let path = dir.join(module);
+            if !path.is_file() {
+                continue;
+            }
+
+            match ModuleImage::from_file(&path) {
+                Ok(image) if is_compatible_machine(image.machine(), self.architecture) => {
+                    return Resolution::Found {
+                        image: Some(Rc::new(image)),
+                        is_loaded: false,
+                    };
+                }
+                Ok(_) => {
+                    mismatched_path.get_or_insert(path);
+                }
+                Err(_) => {
+                    return Resolution::Found {
+                        image: None,
+                        is_loaded: false,
+                    };
+                }
+            }
+        }
+
+        if let Some(path) = mismatched_path {
+            return Resolution::ArchitectureMismatch(path);
+        }
+
+        if let Some(payload_dir) = &self.unsearched_payload_dir {
+            let path = payload_dir.join(module);
+            if path.is_file() {
+                return Resolution::NotInSearchPath(path);
+            }
+        }
+
+        Resolution::NotFound
+    }
+}
+
+/// Returns the system directory used by processes of the given architecture.
+fn system_dir(architecture: ProcessArchitecture) -> Result<PathBuf, io::Error> {
+    if architecture.is_x86() {
+        // fails on 32-bit windows, where there is only one system directory.
+        if let Ok(wow64_dir) = windows_path(GetSystemWow64DirectoryW) {
+            return Ok(wow64_dir);
+        }
+    }
+    windows_path(GetSystemDirectoryW)
+}
+
+fn windows_dir() -> Result<PathBuf, io::Error> {
+    windows_path(GetWindowsDirectoryW)
+}
+
+fn windows_path(
+    get_path: unsafe extern "system" fn(LPWSTR, UINT) -> UINT,
+) -> Result<PathBuf, io::Error> {
+    win_fill_path_buf_helper(|buf_ptr, buf_size| {
+        let result = unsafe { get_path(buf_ptr, buf_size as UINT) } as usize;
+        if result == 0 {
+            FillPathBufResult::Error(io::Error::last_os_error())
+        } else if result >= buf_size {
+            // the returned size includes the terminating nul if the buffer is too small.
+            FillPathBufResult::BufTooSmall {
+                size_hint: Some(result),
+            }
+        } else {
+            FillPathBufResult::Success { actual_len: result }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_lists_all_issues() {
+        let diagnosis = InjectFailureDiagnosis {
+            issues: vec![
+                DependencyIssue::MissingModule {
+                    importer: "payload.dll".to_string(),
+                    module: "missing.dll".to_string(),
+                },
+                DependencyIssue::MissingImport {
+                    importer: "payload.dll".to_string(),
+                    module: "dependency.dll".to_string(),
+                    symbol: ImportedSymbol::Ordinal(7),
+                },
+            ],
+        };
+
+        assert_eq!(
+            diagnosis.to_string(),
+            "missing.dll imported by payload.dll was not found; #7 imported by payload.dll is not exported by dependency.dll"
+        );
+        assert_eq!(
+            InjectFailureDiagnosis { issues: Vec::new() }.to_string(),
+            "no dependency issues found"
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+
+use bitflags::bitflags;
+use winapi::um::libloaderapi::{
+    LOAD_IGNORE_CODE_AUTHZ_LEVEL, LOAD_LIBRARY_REQUIRE_SIGNED_TARGET,
+    LOAD_LIBRARY_SAFE_CURRENT_DIRS, LOAD_LIBRARY_SEARCH_APPLICATION_DIR,
+    LOAD_LIBRARY_SEARCH_DEFAULT_DIRS, LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR,
+    LOAD_LIBRARY_SEARCH_SYSTEM32, LOAD_LIBRARY_SEARCH_USER_DIRS, LOAD_WITH_ALTERED_SEARCH_PATH,
+};
+
+bitflags! {
+    /// Flags passed to [`LoadLibraryExW`](https://docs.microsoft.com/en-us/windows/win32/api/libloaderapi/nf-libloaderapi-loadlibraryexw)
+    /// when injecting a module using [`Syringe::inject_with_options`](crate::Syringe::inject_with_options).
+    ///
+    /// Only flags that load the module as executable code are supported.
+    #[derive(Default)]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "syringe")))]
+    pub struct LoadLibraryFlags: u32 {
+        /// Searches the dependencies of the module in the directory of the module instead of the directory of the executable.
+        const LOAD_WITH_ALTERED_SEARCH_PATH = LOAD_WITH_ALTERED_SEARCH_PATH;
+        /// Does not check AppLocker rules or Software Restriction Policies for the module.
+        const LOAD_IGNORE_CODE_AUTHZ_LEVEL = LOAD_IGNORE_CODE_AUTHZ_LEVEL;
+        /// Only loads the module if it is signed.
+        const LOAD_LIBRARY_REQUIRE_SIGNED_TARGET = LOAD_LIBRARY_REQUIRE_SIGNED_TARGET;
+        /// Adds the directory of the module to the directories searched for its dependencies.
+        const LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR = LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR;
+        /// Adds the directory of the executable of the target process to the searched directories.
+        const LOAD_LIBRARY_SEARCH_APPLICATION_DIR = LOAD_LIBRARY_SEARCH_APPLICATION_DIR;
+        /// Adds the directories registered using `AddDllDirectory` (e.g. using [`InjectOptions::with_dll_directory`]) to the searched directories.
+        const LOAD_LIBRARY_SEARCH_USER_DIRS = LOAD_LIBRARY_SEARCH_USER_DIRS;
+        /// Adds the `System32` directory to the searched directories.
+        const LOAD_LIBRARY_SEARCH_SYSTEM32 = LOAD_LIBRARY_SEARCH_SYSTEM32;
+        /// Searches the same directories as [`LOAD_LIBRARY_SEARCH_APPLICATION_DIR`](Self::LOAD_LIBRARY_SEARCH_APPLICATION_DIR),
+        /// [`LOAD_LIBRARY_SEARCH_SYSTEM32`](Self::LOAD_LIBRARY_SEARCH_SYSTEM32) and
+        /// [`LOAD_LIBRARY_SEARCH_USER_DIRS`](Self::LOAD_LIBRARY_SEARCH_USER_DIRS) combined.
+        const LOAD_LIBRARY_SEARCH_DEFAULT_DIRS = LOAD_LIBRARY_SEARCH_DEFAULT_DIRS;
+        /// Only searches the current directory if it is in the safe load list.
+        const LOAD_LIBRARY_SAFE_CURRENT_DIRS = LOAD_LIBRARY_SAFE_CURRENT_DIRS;
+    }
+}
+
+/// Options controlling how a module is loaded by [`Syringe::inject_with_options`](crate::Syringe::inject_with_options).
+///
+/// # Example
+/// ```no_run
+/// use dll_syringe::{InjectOptions, LoadLibraryFlags, Syringe, process::OwnedProcess};
+///
+/// let syringe = Syringe::for_process(OwnedProcess::find_first_by_name("target_process").unwrap().unwrap());
+/// let options = InjectOptions::new()
+///     .with_flags(LoadLibraryFlags::LOAD_LIBRARY_SEARCH_DEFAULT_DIRS | LoadLibraryFlags::LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR)
+///     .with_dll_directory("C:\\payload\\dependencies");
+/// syringe.inject_with_options("C:\\payload\\payload.dll", &options).unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "syringe")))]
+pub struct InjectOptions {
+    flags: LoadLibraryFlags,
+    dll_directories: Vec<PathBuf>,
+}
+
+impl InjectOptions {
+    /// Creates new options without any flags or additional directories, which loads modules like [`Syringe::inject`](crate::Syringe::inject).
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the flags passed to `LoadLibraryExW`.
+    #[must_use]
+    pub fn with_flags(mut self, flags: LoadLibraryFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Adds a directory that is registered in the target process using `AddDllDirectory` before the module is loaded.
+    ///
+    /// # Note
+    /// The registered directories are only searched if [`LoadLibraryFlags::LOAD_LIBRARY_SEARCH_USER_DIRS`] or
+    /// [`LoadLibraryFlags::LOAD_LIBRARY_SEARCH_DEFAULT_DIRS`] is set. They are removed again using `RemoveDllDirectory`
+    /// once `LoadLibraryExW` returned, so they are not searched for modules loaded later on (e.g. delay loaded dependencies).
+    #[must_use]
+    pub fn with_dll_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.dll_directories.push(directory.into());
+        self
+    }
+
+    /// Returns the flags passed to `LoadLibraryExW`.
+    #[must_use]
+    pub fn flags(&self) -> LoadLibraryFlags {
+        self.flags
+    }
+
+    /// Returns the directories registered in the target process while the module is loaded.
+    #[must_use]
+    pub fn dll_directories(&self) -> &[PathBuf] {
+        &self.dll_directories
+    }
+}
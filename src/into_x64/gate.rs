@@ -0,0 +1,177 @@
+use std::{io, mem};
+
+use iced_x86::{
+    code_asm::{
+        dword_ptr, qword_ptr,
+        registers::{gpr16::*, gpr32::*, gpr64::*, segment::*},
+        CodeAssembler,
+    },
+    Code, IcedError, Instruction,
+};
+
+use crate::process::{memory::ProcessMemoryBuffer, BorrowedProcess, Process};
+
+/// Code segment selector of 64-bit code under WOW64.
+const CS_X64: u32 = 0x33;
+/// Code segment selector of 32-bit code under WOW64.
+const CS_X86: u32 = 0x23;
+
+/// The maximum number of arguments that can be passed to a function called through an [`X64Gate`].
+const MAX_ARG_COUNT: usize = 12;
+
+/// The function and arguments of a call through an [`X64Gate`], which are read by the thunk from memory.
+#[repr(C)]
+struct X64Call {
+    function: u64,
+    args: [u64; MAX_ARG_COUNT],
+}
+
+/// A thunk in the current WOW64 process for calling 64-bit functions.
+///
+/// Calls are performed by temporarily switching the current thread into 64-bit mode (also known as "heaven's gate").
+/// The thunk is location independent and does not depend on the called function, so it is built once and reused
+/// for all calls.
+#[derive(Debug)]
+pub(crate) struct X64Gate {
+    thunk: ProcessMemoryBuffer<'static>,
+}
+
+impl X64Gate {
+    /// Builds the thunk in the current process.
+    pub fn build() -> Result<Self, io::Error> {
+        let code = build_call_x64().map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+
+        let thunk = ProcessMemoryBuffer::allocate_code(BorrowedProcess::current(), code.len())?;
+        thunk.write(0, &code)?;
+        thunk.flush_instruction_cache()?;
+
+        Ok(Self { thunk })
+    }
+
+    /// Calls the 64-bit function at the given address with the given arguments and returns its result.
+    ///
+    /// # Safety
+    /// The function has to be a valid 64-bit function that takes the given arguments in the `x64` calling convention
+    /// and must not touch any state of the 32-bit side of the current process.
+    pub unsafe fn call(&self, function: u64, args: &[u64]) -> Result<u64, io::Error> {
+        if args.len() > MAX_ARG_COUNT {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "at most {} arguments can be passed to a 64-bit function",
+                    MAX_ARG_COUNT
+                ),
+            ));
+        }
+
+        // unused arguments are zeroed and ignored by the called function.
+        let mut call = X64Call {
+            function,
+            args: [0; MAX_ARG_COUNT],
+        };
+        call.args[..args.len()].copy_from_slice(args);
+
+        let thunk: extern "C" fn(*const X64Call) -> u64 =
+            unsafe { mem::transmute(self.thunk.as_ptr()) };
+        Ok(thunk(&call))
+    }
+}
+
+fn build_call_x64() -> Result<Vec<u8>, IcedError> {
+    let mut code = enter_x64()?;
+    code.extend(call_function_x64()?);
+    code.extend(leave_x64()?);
+    Ok(code)
+}
+
+/// Saves the callee-saved registers, loads the pointer to the [`X64Call`] into `esi` and switches the current thread into 64-bit mode.
+fn enter_x64() -> Result<Vec<u8>, IcedError> {
+    let mut asm = CodeAssembler::new(32)?;
+
+    asm.push(ebp)?;
+    asm.mov(ebp, esp)?;
+    asm.push(ebx)?;
+    asm.push(esi)?;
+    asm.push(edi)?;
+    asm.mov(esi, dword_ptr(ebp + 8))?; // pointer to the X64Call
+    asm.and(esp, -16)?; // align stack for the 64-bit call
+
+    // far return into the 64-bit code segment right after this sequence.
+    let mut next = asm.create_label();
+    asm.push(CS_X64)?;
+    asm.call(next)?;
+    asm.set_label(&mut next)?;
+    asm.add(dword_ptr(esp), 5)?; // skip this add (4 bytes) and the retf (1 byte)
+    asm.retf()?;
+
+    assemble_location_independent(&mut asm)
+}
+
+/// Calls the function of the [`X64Call`] pointed to by `esi` in 64-bit mode and moves its result into `edx:eax`.
+fn call_function_x64() -> Result<Vec<u8>, IcedError> {
+    let mut asm = CodeAssembler::new(64)?;
+
+    // the upper halves of rsp and rsi are undefined after the mode switch.
+    asm.mov(esp, esp)?;
+    asm.mov(esi, esi)?;
+
+    let stack_arg_count = MAX_ARG_COUNT - 4;
+    let frame_size = ((32 + stack_arg_count * 8 + 15) & !15) as i32;
+    asm.sub(rsp, frame_size)?;
+
+    let arg_offset = |i: usize| (8 + i * 8) as i32;
+    let arg_registers = [rcx, rdx, r8, r9];
+    for (i, register) in arg_registers.into_iter().enumerate() {
+        asm.mov(register, qword_ptr(rsi + arg_offset(i)))?;
+    }
+    for i in arg_registers.len()..MAX_ARG_COUNT {
+        asm.mov(rax, qword_ptr(rsi + arg_offset(i)))?;
+        asm.mov(qword_ptr(rsp + (i * 8) as i32), rax)?;
+    }
+
+    asm.mov(rax, qword_ptr(rsi))?;
+    asm.call(rax)?;
+    asm.add(rsp, frame_size)?;
+
+    // 64-bit results are returned in edx:eax on x86.
+    asm.mov(rdx, rax)?;
+    asm.shr(rdx, 32)?;
+
+    // far return into the 32-bit code segment right after this sequence.
+    let mut next = asm.create_label();
+    asm.call(next)?;
+    asm.set_label(&mut next)?;
+    asm.mov(dword_ptr(rsp + 4), CS_X86)?;
+    asm.add(dword_ptr(rsp), 13)?; // skip the mov above (8 bytes), this add (4 bytes) and the retf (1 byte)
+    asm.add_instruction(Instruction::with(Code::Retfd))?; // retf with 32-bit operands
+
+    assemble_location_independent(&mut asm)
+}
+
+/// Restores the stack segment and the callee-saved registers and returns.
+fn leave_x64() -> Result<Vec<u8>, IcedError> {
+    let mut asm = CodeAssembler::new(32)?;
+
+    // some cpus require ss to be reloaded after switching back.
+    asm.mov(cx, ds)?;
+    asm.mov(ss, cx)?;
+
+    asm.lea(esp, dword_ptr(ebp - 12))?;
+    asm.pop(edi)?;
+    asm.pop(esi)?;
+    asm.pop(ebx)?;
+    asm.pop(ebp)?;
+    asm.ret()?;
+
+    assemble_location_independent(&mut asm)
+}
+
+fn assemble_location_independent(asm: &mut CodeAssembler) -> Result<Vec<u8>, IcedError> {
+    let code = asm.assemble(0x1234_5678)?;
+    debug_assert_eq!(
+        code,
+        asm.assemble(0x1111_2222)?,
+        "x64 call thunk is not location independent"
+    );
+    Ok(code)
+}
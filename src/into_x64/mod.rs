@@ -0,0 +1,246 @@
+use std::{
+    cell::OnceCell,
+    convert::TryInto,
+    fs, io,
+    mem::MaybeUninit,
+    os::windows::{
+        io::{FromRawHandle, OwnedHandle},
+        prelude::AsRawHandle,
+    },
+    path::{Path, PathBuf},
+    ptr,
+    time::Duration,
+};
+
+use goblin::pe::PE;
+use path_absolutize::Absolutize;
+use widestring::{U16CString, U16Str};
+use winapi::{
+    shared::{
+        minwindef::{BOOL, FALSE, MAX_PATH},
+        ntdef::NTSTATUS,
+    },
+    um::sysinfoapi::GetSystemWindowsDirectoryW,
+};
+
+use crate::{
+    error::{EjectError, InjectError, LoadInjectHelpDataError},
+    process::{
+        memory::{RemoteAllocation, RemoteBox, RemoteBoxAllocator},
+        wait_for_thread_exit_code, BorrowedProcess, Process, ProcessArchitecture,
+    },
+    syringe::LoadLibraryWStub,
+    utils::retry_faillable_until_some_with_timeout,
+    Syringe,
+};
+
+mod gate;
+mod peb;
+use gate::X64Gate;
+use peb::{nt_status_to_result, Wow64Memory};
+
+/// Injects modules into `x64` processes from the current `x86` process running under WOW64.
+///
+/// The target process is only accessed using its 64-bit address space, as none of the 32-bit apis (e.g. `CreateRemoteThread`)
+/// can reach 64-bit code. Remote threads are created using the 64-bit `RtlCreateUserThread` of the current process, which is
+/// called by switching the current thread into 64-bit mode (see [`X64Gate`]).
+#[derive(Debug)]
+pub(crate) struct X64Injector {
+    kernel32_module: u64,
+    free_library_offset: u64,
+    rtl_create_user_thread: u64,
+    load_library_w_stub: RemoteAllocation,
+    result: RemoteBox<u64>,
+    gate: OnceCell<X64Gate>,
+}
+
+impl X64Injector {
+    pub fn build(remote_allocator: &RemoteBoxAllocator) -> Result<Self, LoadInjectHelpDataError> {
+        let process = remote_allocator.process();
+        let injector = BorrowedProcess::current().architecture()?;
+        let target = remote_allocator.process_architecture()?;
+        // switching into 64-bit mode is only possible under WOW64 on x64 windows.
+        if injector != ProcessArchitecture::X86 || target != ProcessArchitecture::X64 {
+            return Err(LoadInjectHelpDataError::UnsupportedArchitecture { injector, target });
+        }
+
+        let memory = Wow64Memory::load()?;
+
+        // get kernel32 base of target process (may fail if target process is currently starting and has not loaded kernel32 yet)
+        let kernel32_module = retry_faillable_until_some_with_timeout(
+            || memory.find_module_by_name(process, "kernel32.dll"),
+            Duration::from_secs(1),
+        )?
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "kernel32.dll is not loaded in the target process",
+            )
+        })?;
+
+        // the 64-bit ntdll is also loaded into the current process to implement wow64.
+        let ntdll_module = memory
+            .find_module_by_name(BorrowedProcess::current(), "ntdll.dll")?
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "64-bit ntdll.dll is not loaded in the current process",
+                )
+            })?;
+
+        let native_dir = Self::native_system_dir()?;
+        let kernel32_exports = Self::export_rvas(
+            native_dir.join("kernel32.dll"),
+            ["LoadLibraryW", "FreeLibrary", "GetLastError"],
+        )?;
+        let [load_library_offset, free_library_offset, get_last_error_offset] = kernel32_exports;
+        let [rtl_create_user_thread_offset] =
+            Self::export_rvas(native_dir.join("ntdll.dll"), ["RtlCreateUserThread"])?;
+
+        let result = remote_allocator.alloc_uninit::<u64>()?;
+        let code = LoadLibraryWStub::build_code_x64(
+            kernel32_module + load_library_offset,
+            result.as_raw_ptr() as usize as u64,
+            kernel32_module + get_last_error_offset,
+        )
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        let load_library_w_stub = remote_allocator.alloc_and_copy_buf(code.as_slice())?;
+        load_library_w_stub.memory().flush_instruction_cache()?;
+
+        Ok(Self {
+            kernel32_module,
+            free_library_offset,
+            rtl_create_user_thread: ntdll_module + rtl_create_user_thread_offset,
+            load_library_w_stub,
+            result,
+            gate: OnceCell::new(),
+        })
+    }
+
+    /// Returns the `System32` directory containing the native 64-bit system modules as seen from the current WOW64 process.
+    fn native_system_dir() -> Result<PathBuf, io::Error> {
+        let mut path_buf = MaybeUninit::uninit_array::<MAX_PATH>();
+        let path_buf_len: u32 = path_buf.len().try_into().unwrap();
+        let result = unsafe { GetSystemWindowsDirectoryW(path_buf[0].as_mut_ptr(), path_buf_len) };
+        if result == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let path_len = result as usize;
+        let path = unsafe { MaybeUninit::slice_assume_init_ref(&path_buf[..path_len]) };
+        let mut path = PathBuf::from(U16Str::from_slice(path).to_os_string());
+        // System32 is redirected to SysWOW64 for wow64 processes, Sysnative is not.
+        path.push("Sysnative");
+        Ok(path)
+    }
+
+    fn export_rvas<const N: usize>(
+        module_path: impl AsRef<Path>,
+        names: [&str; N],
+    ) -> Result<[u64; N], LoadInjectHelpDataError> {
+        let module_file_buffer = fs::read(module_path)?;
+        let pe = PE::parse(&module_file_buffer)?;
+
+        let mut rvas = [0; N];
+        for (rva, name) in rvas.iter_mut().zip(names) {
+            let export = pe
+                .exports
+                .iter()
+                .find(|export| export.name == Some(name))
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("{} is not exported by the native module", name),
+                    )
+                })?;
+            // the rva of a forwarded export points to the forwarder string instead of code.
+            if export.reexport.is_some() {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("{} is forwarded to another module", name),
+                )
+                .into());
+            }
+            *rva = export.rva as u64;
+        }
+        Ok(rvas)
+    }
+
+    /// Loads the module with the given path into the target process and returns its base address.
+    pub fn inject(
+        &self,
+        process: BorrowedProcess<'_>,
+        remote_allocator: &RemoteBoxAllocator,
+        payload_path: &Path,
+    ) -> Result<u64, InjectError> {
+        let module_path = payload_path.absolutize()?;
+        let wide_module_path =
+            U16CString::from_os_str(module_path.as_os_str())?.into_vec_with_nul();
+        let remote_wide_module_path =
+            remote_allocator.alloc_and_copy_buf(wide_module_path.as_slice())?;
+
+        let exit_code = self.run_remote_thread(
+            process,
+            self.load_library_w_stub.as_raw_ptr() as usize as u64,
+            remote_wide_module_path.as_raw_ptr() as usize as u64,
+        )?;
+        Syringe::remote_exit_code_to_error_or_exception(exit_code)?;
+
+        let module = self.result.read()?;
+        assert_ne!(module, 0);
+
+        Ok(module)
+    }
+
+    /// Unloads the module with the given base address from the target process.
+    pub fn eject(&self, process: BorrowedProcess<'_>, module: u64) -> Result<(), EjectError> {
+        let exit_code = self.run_remote_thread(
+            process,
+            self.kernel32_module + self.free_library_offset,
+            module,
+        )?;
+
+        if exit_code as BOOL == FALSE {
+            return Err(EjectError::RemoteIo(io::Error::new(
+                io::ErrorKind::Other,
+                "failed to eject module from process",
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Runs the 64-bit thread procedure at the given address in the target process and returns its exit code.
+    fn run_remote_thread(
+        &self,
+        process: BorrowedProcess<'_>,
+        start_address: u64,
+        parameter: u64,
+    ) -> Result<u32, io::Error> {
+        let gate = self.gate.get_or_try_init(X64Gate::build)?;
+
+        let mut thread_handle = 0u64;
+        let status = unsafe {
+            gate.call(
+                self.rtl_create_user_thread,
+                &[
+                    process.as_raw_handle() as usize as u64, // ProcessHandle
+                    0,                                       // SecurityDescriptor
+                    0,                                       // CreateSuspended
+                    0,                                       // StackZeroBits
+                    0,                                       // StackReserved
+                    0,                                       // StackCommit
+                    start_address,                           // StartAddress
+                    parameter,                               // StartParameter
+                    ptr::addr_of_mut!(thread_handle) as usize as u64, // ThreadHandle
+                    0,                                       // ClientId
+                ],
+            )?
+        };
+        nt_status_to_result(status as NTSTATUS, "RtlCreateUserThread")?;
+
+        // the handle was created in the handle table of the current process, so it can be used from the 32-bit side as well.
+        let thread_handle = unsafe { OwnedHandle::from_raw_handle(thread_handle as usize as _) };
+        wait_for_thread_exit_code(thread_handle.as_raw_handle())
+    }
+}
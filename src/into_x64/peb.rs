@@ -0,0 +1,181 @@
+use std::{
+    ffi::OsString,
+    io, mem,
+    os::windows::prelude::{AsRawHandle, OsStringExt},
+    path::Path,
+    ptr,
+};
+
+use cstr::cstr;
+use widestring::u16cstr;
+use winapi::shared::{
+    minwindef::ULONG,
+    ntdef::{HANDLE, NTSTATUS, PVOID},
+};
+
+use crate::process::{BorrowedProcess, BorrowedProcessModule};
+
+type NtWow64QueryInformationProcess64Fn =
+    unsafe extern "system" fn(HANDLE, ULONG, PVOID, ULONG, *mut ULONG) -> NTSTATUS;
+type NtWow64ReadVirtualMemory64Fn =
+    unsafe extern "system" fn(HANDLE, u64, PVOID, u64, *mut u64) -> NTSTATUS;
+
+const PROCESS_BASIC_INFORMATION_CLASS: ULONG = 0;
+
+// offsets into the 64-bit versions of PEB, PEB_LDR_DATA and LDR_DATA_TABLE_ENTRY
+const PEB64_LDR_OFFSET: u64 = 0x18;
+const PEB_LDR_DATA64_IN_LOAD_ORDER_MODULE_LIST_OFFSET: u64 = 0x10;
+const LDR_DATA_TABLE_ENTRY64_DLL_BASE_OFFSET: u64 = 0x30;
+const LDR_DATA_TABLE_ENTRY64_BASE_DLL_NAME_OFFSET: u64 = 0x58;
+
+#[derive(Debug, Default, Clone, Copy)]
+#[repr(C)]
+struct ProcessBasicInformation64 {
+    exit_status: u64,
+    peb_base_address: u64,
+    affinity_mask: u64,
+    base_priority: u64,
+    unique_process_id: u64,
+    inherited_from_unique_process_id: u64,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+#[repr(C)]
+struct UnicodeString64 {
+    length: u16,
+    maximum_length: u16,
+    buffer: u64,
+}
+
+/// Provides access to the 64-bit address space of processes from the current WOW64 process
+/// using the `NtWow64*64` functions of the 32-bit `ntdll.dll`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Wow64Memory {
+    query_information_process: NtWow64QueryInformationProcess64Fn,
+    read_virtual_memory: NtWow64ReadVirtualMemory64Fn,
+}
+
+impl Wow64Memory {
+    pub fn load() -> Result<Self, io::Error> {
+        let ntdll =
+            BorrowedProcessModule::find_local_by_name_or_abs_path_wstr(u16cstr!("ntdll.dll"))?
+                .unwrap();
+        let query_information_process =
+            ntdll.get_local_procedure_address_cstr(cstr!("NtWow64QueryInformationProcess64"))?;
+        let read_virtual_memory =
+            ntdll.get_local_procedure_address_cstr(cstr!("NtWow64ReadVirtualMemory64"))?;
+
+        Ok(Self {
+            query_information_process: unsafe { mem::transmute(query_information_process) },
+            read_virtual_memory: unsafe { mem::transmute(read_virtual_memory) },
+        })
+    }
+
+    /// Reads the memory at the given 64-bit address of the given process into the given buffer.
+    pub fn read(
+        &self,
+        process: BorrowedProcess<'_>,
+        address: u64,
+        buf: &mut [u8],
+    ) -> Result<(), io::Error> {
+        let mut bytes_read = 0;
+        let status = unsafe {
+            (self.read_virtual_memory)(
+                process.as_raw_handle(),
+                address,
+                buf.as_mut_ptr().cast(),
+                buf.len() as u64,
+                &mut bytes_read,
+            )
+        };
+        nt_status_to_result(status, "NtWow64ReadVirtualMemory64")?;
+        if bytes_read != buf.len() as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to read full buffer from 64-bit process",
+            ));
+        }
+        Ok(())
+    }
+
+    fn read_struct<T: Copy + Default>(
+        &self,
+        process: BorrowedProcess<'_>,
+        address: u64,
+    ) -> Result<T, io::Error> {
+        let mut value = T::default();
+        let buf = unsafe {
+            std::slice::from_raw_parts_mut(ptr::addr_of_mut!(value).cast(), mem::size_of::<T>())
+        };
+        self.read(process, address, buf)?;
+        Ok(value)
+    }
+
+    /// Returns the address of the 64-bit process environment block of the given process.
+    fn peb_address(&self, process: BorrowedProcess<'_>) -> Result<u64, io::Error> {
+        let mut info = ProcessBasicInformation64::default();
+        let status = unsafe {
+            (self.query_information_process)(
+                process.as_raw_handle(),
+                PROCESS_BASIC_INFORMATION_CLASS,
+                ptr::addr_of_mut!(info).cast(),
+                mem::size_of::<ProcessBasicInformation64>() as ULONG,
+                ptr::null_mut(),
+            )
+        };
+        nt_status_to_result(status, "NtWow64QueryInformationProcess64")?;
+        Ok(info.peb_base_address)
+    }
+
+    /// Searches the 64-bit modules of the given process for one with the given name and returns its base address.
+    /// The comparison of names is case-insensitive.
+    ///
+    /// # Note
+    /// For the current process this finds the 64-bit modules that implement WOW64 (e.g. the 64-bit `ntdll.dll`).
+    pub fn find_module_by_name(
+        &self,
+        process: BorrowedProcess<'_>,
+        module_name: impl AsRef<Path>,
+    ) -> Result<Option<u64>, io::Error> {
+        let module_name = module_name.as_ref().as_os_str();
+
+        let peb = self.peb_address(process)?;
+        let ldr: u64 = self.read_struct(process, peb + PEB64_LDR_OFFSET)?;
+        if ldr == 0 {
+            // the loader of the process is not initialized yet.
+            return Ok(None);
+        }
+
+        let list_head = ldr + PEB_LDR_DATA64_IN_LOAD_ORDER_MODULE_LIST_OFFSET;
+        let mut entry: u64 = self.read_struct(process, list_head)?;
+        while entry != list_head && entry != 0 {
+            let name: UnicodeString64 =
+                self.read_struct(process, entry + LDR_DATA_TABLE_ENTRY64_BASE_DLL_NAME_OFFSET)?;
+            let mut name_buf = vec![0u16; usize::from(name.length) / 2];
+            self.read(process, name.buffer, unsafe {
+                std::slice::from_raw_parts_mut(name_buf.as_mut_ptr().cast(), name_buf.len() * 2)
+            })?;
+
+            if OsString::from_wide(&name_buf).eq_ignore_ascii_case(module_name) {
+                let base =
+                    self.read_struct(process, entry + LDR_DATA_TABLE_ENTRY64_DLL_BASE_OFFSET)?;
+                return Ok(Some(base));
+            }
+
+            // InLoadOrderLinks is the first field of the entry, so its flink points to the next entry.
+            entry = self.read_struct(process, entry)?;
+        }
+
+        Ok(None)
+    }
+}
+
+pub(crate) fn nt_status_to_result(status: NTSTATUS, function: &str) -> Result<(), io::Error> {
+    if status < 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("{} failed with NTSTATUS {:#010x}", function, status),
+        ));
+    }
+    Ok(())
+}
@@ -1,4 +1,3 @@
-#![cfg(windows)]
 #![feature(
     maybe_uninit_uninit_array,
     maybe_uninit_slice,
@@ -35,32 +34,70 @@
 #![cfg_attr(not(feature = "doc-cfg"), allow(missing_docs))]
 #![cfg_attr(feature = "doc-cfg", feature(doc_cfg))]
 
-#[cfg(feature = "syringe")]
+// Everything that interacts with processes requires windows, the pe parser, the arm64 encoder, the pattern matching
+// engine and `PayloadInfo` have no such dependency and are also built (and tested) on other hosts.
+#[cfg(all(windows, feature = "syringe"))]
 mod syringe;
-#[cfg(feature = "syringe")]
+#[cfg(all(windows, feature = "syringe"))]
 pub use syringe::*;
 
+#[cfg(all(windows, feature = "syringe"))]
+mod inject_options;
+#[cfg(all(windows, feature = "syringe"))]
+pub use inject_options::*;
+
+#[cfg(all(windows, feature = "syringe"))]
+mod inject_diagnosis;
+#[cfg(all(windows, feature = "syringe"))]
+pub use inject_diagnosis::*;
+
+#[cfg(all(windows, feature = "syringe"))]
+mod execution;
+
+#[cfg(all(windows, feature = "syringe"))]
+pub use execution::*;
+
+#[cfg(feature = "syringe")]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) mod arm64;
+
+#[cfg(all(windows, target_arch = "x86", feature = "into-x64-from-x86"))]
+mod into_x64;
+
+#[cfg(all(windows, feature = "manual-map"))]
+mod manual_map;
+#[cfg(all(windows, feature = "manual-map"))]
+pub use manual_map::*;
+
 /// Module containing process abstractions and utilities.
 pub mod process;
 
-#[cfg(feature = "rpc-core")]
+mod payload_info;
+pub use payload_info::*;
+
+#[cfg(all(windows, feature = "rpc-core"))]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "rpc-core")))]
 /// Module containing traits and structs regarding remote procedures.
 pub mod rpc;
 
+#[cfg(windows)]
 pub(crate) mod utils;
 
+#[cfg_attr(not(windows), allow(dead_code, unused_imports))]
+pub(crate) mod pe;
+
 /// Module containing the error enums used in this crate.
 pub mod error;
 
+#[cfg(windows)]
 /// Module containing traits and types for working with function pointers.
 pub mod function;
 
-#[cfg(feature = "payload-utils")]
+#[cfg(all(windows, feature = "payload-utils"))]
 #[doc(hidden)]
 pub mod payload_utils;
 
-#[cfg(any(feature = "payload-utils", feature = "rpc-payload"))]
+#[cfg(all(windows, any(feature = "payload-utils", feature = "rpc-payload")))]
 #[derive(Debug, Clone, Copy)]
 #[repr(C)]
 pub(crate) struct ArgAndResultBufInfo {
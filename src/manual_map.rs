@@ -0,0 +1,256 @@
+use std::{ffi::OsStr, fs, io, path::Path};
+
+use winapi::{
+    shared::minwindef::{BOOL, FALSE},
+    um::winnt::{
+        DLL_PROCESS_ATTACH, IMAGE_FILE_MACHINE_AMD64, IMAGE_FILE_MACHINE_ARM64,
+        IMAGE_FILE_MACHINE_I386,
+    },
+};
+
+use crate::{
+    error::ManualMapError,
+    function::{FunctionPtr, RawFunctionPtr},
+    pe::{self, DataDirectory, ImportName, PeHeaders, PeLayout, PeView},
+    process::{
+        memory::{ProcessMemoryBuffer, ProcessMemorySlice},
+        BorrowedProcess, ModuleHandle, PageProtection, Process, ProcessAccess, ProcessArchitecture,
+    },
+    rpc::{RemoteRawProcedure, Truncate},
+    Syringe,
+};
+
+type DllEntryPointFn = extern "system" fn(Truncate<usize>, u32, Truncate<usize>) -> BOOL;
+type RtlAddFunctionTableFn = extern "system" fn(u64, u32, u64) -> u8;
+
+/// Size of a `RUNTIME_FUNCTION` entry in the exception directory of an x64 image.
+const RUNTIME_FUNCTION_SIZE_X64: u32 = 12;
+/// Size of a `RUNTIME_FUNCTION` entry in the exception directory of an ARM64 image.
+const RUNTIME_FUNCTION_SIZE_ARM64: u32 = 8;
+
+/// A module that was mapped into a process using [`Syringe::inject_manual_map`].
+///
+/// The module is not registered with the loader of the target process and can therefore not be found using
+/// [`Process::find_module_by_name`] or ejected using [`Syringe::eject`].
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "manual-map")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ManualMappedModule<'a> {
+    base: ModuleHandle,
+    size: usize,
+    entry_point: Option<RawFunctionPtr>,
+    process: BorrowedProcess<'a>,
+}
+
+impl<'a> ManualMappedModule<'a> {
+    /// Returns the base address of the mapped image in the target process.
+    #[must_use]
+    pub fn base(&self) -> ModuleHandle {
+        self.base
+    }
+
+    /// Returns the size of the mapped image in bytes.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the address of the entry point of the mapped image, if it has one.
+    #[must_use]
+    pub fn entry_point(&self) -> Option<RawFunctionPtr> {
+        self.entry_point
+    }
+
+    /// Returns the process the module was mapped into.
+    #[must_use]
+    pub fn process(&self) -> BorrowedProcess<'a> {
+        self.process
+    }
+}
+
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "manual-map")))]
+impl Syringe {
+    /// Maps the module from the given path into the target process without using `LoadLibraryW`.
+    ///
+    /// The image is laid out, relocated and its imports are resolved locally before it is copied into the target process.
+    /// Then the pages of each section are protected according to the characteristics of the section.
+    /// Afterwards the tls callbacks and the entry point of the module are called with `DLL_PROCESS_ATTACH`.
+    /// Dependencies of the module are loaded normally using `LoadLibraryW`.
+    ///
+    /// # Limitations
+    /// - The target process and the given module need to be of the same architecture.
+    /// - The module is not registered with the loader, so it can not be ejected and is not notified about thread creation or process exit.
+    /// - Static thread local storage (`__declspec(thread)` or `#[thread_local]`) of the module is not initialized.
+    pub fn inject_manual_map(
+        &self,
+        payload_path: impl AsRef<Path>,
+    ) -> Result<ManualMappedModule<'_>, ManualMapError> {
+        self.process().ensure_access(ProcessAccess::INJECTION)?;
+
+        let file = fs::read(payload_path.as_ref())?;
+        let view = PeView::parse(&file, PeLayout::File)?;
+        let headers = view.headers();
+
+        let expected_machine = match self.remote_allocator.process_architecture()? {
+            ProcessArchitecture::X86 | ProcessArchitecture::X86OnArm64 => IMAGE_FILE_MACHINE_I386,
+            ProcessArchitecture::X64 | ProcessArchitecture::Arm64EC => IMAGE_FILE_MACHINE_AMD64,
+            ProcessArchitecture::Arm64 => IMAGE_FILE_MACHINE_ARM64,
+        };
+        if headers.machine != expected_machine {
+            return Err(ManualMapError::ArchitectureMismatch);
+        }
+
+        let mut image = pe::map_image(&view)?;
+        let imports = pe::read_imports(&view)?;
+        // the callback addresses are absolute, so they have to be read before the image is relocated.
+        let tls_callbacks = pe::read_tls_callbacks(&view)?;
+
+        let buffer = ProcessMemoryBuffer::allocate_code(self.process(), image.len())?;
+        let base = buffer.as_ptr() as usize;
+
+        pe::relocate_image(&mut image, headers, base as u64)?;
+        self.resolve_imports(&mut image, headers, &imports)?;
+
+        buffer.write(0, &image)?;
+        protect_sections(&buffer, headers)?;
+        buffer.flush_instruction_cache()?;
+
+        // from here on code of the module may run, so the memory must not be freed anymore.
+        let (_, size, process) = buffer.into_raw_parts();
+
+        if headers.is_64 {
+            if let Some(exception_directory) =
+                headers.data_directory(pe::IMAGE_DIRECTORY_ENTRY_EXCEPTION)
+            {
+                self.register_function_table(base, headers.machine, exception_directory)?;
+            }
+        }
+
+        for callback in tls_callbacks {
+            self.call_entry_point(base, callback)?;
+        }
+
+        let entry_point = if headers.address_of_entry_point != 0 {
+            if self.call_entry_point(base, headers.address_of_entry_point)? == FALSE {
+                return Err(ManualMapError::EntryPointFailed);
+            }
+            Some((base + headers.address_of_entry_point as usize) as RawFunctionPtr)
+        } else {
+            None
+        };
+
+        Ok(ManualMappedModule {
+            base: base as ModuleHandle,
+            size,
+            entry_point,
+            process,
+        })
+    }
+
+    fn resolve_imports(
+        &self,
+        image: &mut [u8],
+        headers: &PeHeaders,
+        imports: &[pe::Import],
+    ) -> Result<(), ManualMapError> {
+        for import in imports {
+            let module = match self.process().find_module_by_name(&import.module)? {
+                Some(module) => module,
+                None => self
+                    .load_library(OsStr::new(&import.module))
+                    .map_err(|err| ManualMapError::DependencyLoad {
+                        module: import.module.clone(),
+                        source: Box::new(err),
+                    })?,
+            };
+
+            for symbol in &import.symbols {
+                let address = match &symbol.name {
+                    ImportName::Name { name, .. } => self.get_procedure_address(module, name)?,
+                    ImportName::Ordinal(ordinal) => self.get_procedure_address(module, *ordinal)?,
+                };
+                let address = address.ok_or_else(|| ManualMapError::UnresolvedImport {
+                    module: import.module.clone(),
+                    symbol: symbol.name.to_string(),
+                })?;
+
+                pe::write_pointer(image, headers, symbol.iat_rva, address as u64)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn register_function_table(
+        &self,
+        base: usize,
+        machine: u16,
+        exception_directory: DataDirectory,
+    ) -> Result<(), ManualMapError> {
+        let runtime_function_size = if machine == IMAGE_FILE_MACHINE_ARM64 {
+            RUNTIME_FUNCTION_SIZE_ARM64
+        } else {
+            RUNTIME_FUNCTION_SIZE_X64
+        };
+
+        let kernel32 = self
+            .process()
+            .find_module_by_name("kernel32.dll")?
+            .ok_or(ManualMapError::UnsupportedTarget)?;
+        let rtl_add_function_table = unsafe {
+            self.get_raw_procedure::<RtlAddFunctionTableFn>(kernel32, "RtlAddFunctionTable")
+        }?
+        .ok_or(ManualMapError::UnsupportedTarget)?;
+
+        let registered = rtl_add_function_table.call(
+            (base + exception_directory.virtual_address as usize) as u64,
+            exception_directory.size / runtime_function_size,
+            base as u64,
+        )?;
+        if registered == 0 {
+            return Err(ManualMapError::RemoteIo(io::Error::new(
+                io::ErrorKind::Other,
+                "failed to register function table of mapped module",
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn call_entry_point(&self, base: usize, rva: u32) -> Result<BOOL, ManualMapError> {
+        let entry_point = RemoteRawProcedure::new(
+            unsafe { DllEntryPointFn::from_ptr((base + rva as usize) as RawFunctionPtr) },
+            self.executor.clone(),
+            base as ModuleHandle,
+        );
+        Ok(entry_point.call(Truncate(base), DLL_PROCESS_ATTACH, Truncate(0))?)
+    }
+}
+
+/// Changes the protection of the mapped image from readable, writable and executable to the one requested by its sections.
+fn protect_sections(image: &ProcessMemorySlice<'_>, headers: &PeHeaders) -> Result<(), io::Error> {
+    for (range, characteristics) in
+        pe::page_characteristics(headers, ProcessMemoryBuffer::os_page_size())
+    {
+        image
+            .slice(range)
+            .protect(section_protection(characteristics))?
+            .keep();
+    }
+    Ok(())
+}
+
+/// Returns the page protection matching the given `IMAGE_SCN_MEM_*` flags of a section.
+fn section_protection(characteristics: u32) -> PageProtection {
+    let execute = characteristics & pe::IMAGE_SCN_MEM_EXECUTE != 0;
+    let read = characteristics & pe::IMAGE_SCN_MEM_READ != 0;
+    let write = characteristics & pe::IMAGE_SCN_MEM_WRITE != 0;
+    // pages can not be writable without being readable.
+    match (execute, read, write) {
+        (true, _, true) => PageProtection::EXECUTE_READWRITE,
+        (true, true, false) => PageProtection::EXECUTE_READ,
+        (true, false, false) => PageProtection::EXECUTE,
+        (false, _, true) => PageProtection::READWRITE,
+        (false, true, false) => PageProtection::READONLY,
+        (false, false, false) => PageProtection::NOACCESS,
+    }
+}
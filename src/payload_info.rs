@@ -0,0 +1,201 @@
+use std::path::Path;
+
+use crate::{
+    error::{PeError, ReadImageError},
+    pe::{IMAGE_FILE_MACHINE_AMD64, IMAGE_FILE_MACHINE_ARM64, IMAGE_FILE_MACHINE_I386},
+    process::{ImageExport, ModuleImage, ProcessArchitecture},
+};
+
+/// The prefix of the marker symbols exported by [`payload_procedure!`](crate::payload_procedure) for each procedure.
+/// This has to match the prefix used in the macro.
+const PAYLOAD_PROCEDURE_MARKER_PREFIX: &str = "__dll_syringe_payload_procedure_";
+
+/// Information about a payload module that is read from its file without loading it into any process.
+///
+/// # Example
+/// ```no_run
+/// use dll_syringe::{PayloadInfo, process::ProcessArchitecture};
+///
+/// let payload = PayloadInfo::from_path("payload.dll").unwrap();
+/// assert!(payload.is_compatible_with(ProcessArchitecture::X64));
+/// for procedure in payload.payload_procedures() {
+///     println!("payload procedure: {}", procedure);
+/// }
+/// for module in payload.imported_modules() {
+///     println!("depends on: {}", module);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayloadInfo {
+    image: ModuleImage,
+    payload_procedures: Vec<String>,
+}
+
+impl PayloadInfo {
+    /// Reads and parses the payload at the given path.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, ReadImageError> {
+        Ok(Self::from_image(ModuleImage::from_file(path)?))
+    }
+
+    /// Parses the given bytes of a payload file.
+    pub fn parse(file: &[u8]) -> Result<Self, PeError> {
+        Ok(Self::from_image(ModuleImage::parse_file(file)?))
+    }
+
+    fn from_image(image: ModuleImage) -> Self {
+        let payload_procedures = image
+            .exports()
+            .iter()
+            .filter_map(|export| export.name()?.strip_prefix(PAYLOAD_PROCEDURE_MARKER_PREFIX))
+            .filter(|procedure| image.find_export(*procedure).is_some())
+            .map(ToOwned::to_owned)
+            .collect();
+
+        Self {
+            image,
+            payload_procedures,
+        }
+    }
+
+    /// Returns the machine type the payload was built for (e.g. `IMAGE_FILE_MACHINE_AMD64`).
+    #[must_use]
+    pub fn machine(&self) -> u16 {
+        self.image.machine()
+    }
+
+    /// Returns the architecture the payload was built for or `None` if the machine type is not supported.
+    ///
+    /// # Note
+    /// ARM64EC payloads are reported as [`X64`](ProcessArchitecture::X64) as they share the machine type.
+    #[must_use]
+    pub fn architecture(&self) -> Option<ProcessArchitecture> {
+        match self.machine() {
+            IMAGE_FILE_MACHINE_I386 => Some(ProcessArchitecture::X86),
+            IMAGE_FILE_MACHINE_AMD64 => Some(ProcessArchitecture::X64),
+            IMAGE_FILE_MACHINE_ARM64 => Some(ProcessArchitecture::Arm64),
+            _ => None,
+        }
+    }
+
+    /// Returns whether this is a 64-bit payload.
+    #[must_use]
+    pub fn is_64(&self) -> bool {
+        self.image.is_64()
+    }
+
+    /// Returns whether the payload can be loaded into a process with the given architecture.
+    #[must_use]
+    pub fn is_compatible_with(&self, target: ProcessArchitecture) -> bool {
+        is_compatible_machine(self.machine(), target)
+    }
+
+    /// Returns the symbols exported by the payload ordered by their ordinal.
+    /// The marker symbols emitted by [`payload_procedure!`](crate::payload_procedure) are not included.
+    pub fn exports(&self) -> impl Iterator<Item = &ImageExport> + '_ {
+        self.image.exports().iter().filter(|export| {
+            !export.name().map_or(false, |name| {
+                name.starts_with(PAYLOAD_PROCEDURE_MARKER_PREFIX)
+            })
+        })
+    }
+
+    /// Returns the names of the exports that were declared using [`payload_procedure!`](crate::payload_procedure)
+    /// and can therefore be called using [`Syringe::get_payload_procedure`](crate::Syringe::get_payload_procedure).
+    #[must_use]
+    pub fn payload_procedures(&self) -> &[String] {
+        &self.payload_procedures
+    }
+
+    /// Returns whether the export with the given name was declared using [`payload_procedure!`](crate::payload_procedure).
+    #[must_use]
+    pub fn is_payload_procedure(&self, name: &str) -> bool {
+        self.payload_procedures
+            .iter()
+            .any(|procedure| procedure == name)
+    }
+
+    /// Returns the names of the modules imported by the payload as stored in the payload (e.g. `KERNEL32.dll`).
+    pub fn imported_modules(&self) -> impl Iterator<Item = &str> + '_ {
+        self.image.imports().iter().map(|import| import.module())
+    }
+
+    /// Returns the full image of the payload.
+    #[must_use]
+    pub fn image(&self) -> &ModuleImage {
+        &self.image
+    }
+}
+
+/// Returns whether a module with the given machine type can be loaded into a process with the given architecture.
+pub(crate) fn is_compatible_machine(machine: u16, target: ProcessArchitecture) -> bool {
+    match target {
+        ProcessArchitecture::X86 | ProcessArchitecture::X86OnArm64 => {
+            machine == IMAGE_FILE_MACHINE_I386
+        }
+        ProcessArchitecture::X64 => machine == IMAGE_FILE_MACHINE_AMD64,
+        // ARM64EC processes can load x64 and ARM64EC modules as well as the ARM64EC view of ARM64X modules.
+        ProcessArchitecture::Arm64EC => {
+            machine == IMAGE_FILE_MACHINE_AMD64 || machine == IMAGE_FILE_MACHINE_ARM64
+        }
+        ProcessArchitecture::Arm64 => machine == IMAGE_FILE_MACHINE_ARM64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pe::test_utils::TestPe;
+
+    fn payload_with_procedures(is_64: bool) -> TestPe {
+        let mut pe = TestPe::new(is_64);
+        let text = pe.add_section(".text", 0x20, 0x6000_0020);
+        let rdata = pe.add_section(".rdata", 0x10, 0x4000_0040);
+        pe.add_exports(
+            1,
+            &[
+                (Some("DllMain"), text),
+                (Some("__dll_syringe_payload_procedure_add"), rdata),
+                (Some("__dll_syringe_payload_procedure_missing"), rdata + 1),
+                (Some("add"), text + 0x10),
+                (Some("add_raw"), text + 0x18),
+            ],
+        );
+        pe
+    }
+
+    #[test]
+    fn parse_reports_payload_procedures() {
+        let payload = PayloadInfo::parse(&payload_with_procedures(true).to_file()).unwrap();
+
+        assert_eq!(payload.payload_procedures(), &["add".to_string()]);
+        assert!(payload.is_payload_procedure("add"));
+        assert!(!payload.is_payload_procedure("add_raw"));
+        assert!(!payload.is_payload_procedure("missing"));
+
+        let exports = payload
+            .exports()
+            .map(|export| export.name().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(exports, vec!["DllMain", "add", "add_raw"]);
+        assert_eq!(payload.imported_modules().count(), 0);
+    }
+
+    #[test]
+    fn architecture_matches_machine() {
+        let x64 = PayloadInfo::parse(&payload_with_procedures(true).to_file()).unwrap();
+        assert!(x64.is_64());
+        assert_eq!(x64.architecture(), Some(ProcessArchitecture::X64));
+        assert!(x64.is_compatible_with(ProcessArchitecture::X64));
+        assert!(x64.is_compatible_with(ProcessArchitecture::Arm64EC));
+        assert!(!x64.is_compatible_with(ProcessArchitecture::X86));
+        assert!(!x64.is_compatible_with(ProcessArchitecture::X86OnArm64));
+        assert!(!x64.is_compatible_with(ProcessArchitecture::Arm64));
+
+        let x86 = PayloadInfo::parse(&payload_with_procedures(false).to_file()).unwrap();
+        assert!(!x86.is_64());
+        assert_eq!(x86.architecture(), Some(ProcessArchitecture::X86));
+        assert!(x86.is_compatible_with(ProcessArchitecture::X86));
+        assert!(x86.is_compatible_with(ProcessArchitecture::X86OnArm64));
+        assert!(!x86.is_compatible_with(ProcessArchitecture::X64));
+    }
+}
@@ -35,6 +35,12 @@ macro_rules! payload_procedure {
                 __inner($($name ,)*)
             });
         }
+
+        // Marker export used by `PayloadInfo` to identify payload procedures.
+        const _: () = {
+            #[export_name = concat!("__dll_syringe_payload_procedure_", stringify!($fn))]
+            static __PAYLOAD_PROCEDURE_MARKER: u8 = 0;
+        };
     };
 }
 
@@ -0,0 +1,458 @@
+use std::{cmp::Ordering, ops::Range, str};
+
+use crate::{
+    error::PeError,
+    pe::{read_u16, read_u32, PeView, IMAGE_DIRECTORY_ENTRY_EXPORT},
+};
+
+const IMAGE_SIZEOF_EXPORT_DIRECTORY: usize = 40;
+
+/// A symbol that can be looked up in the export directory of a pe image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum ExportLookup<'a> {
+    Name(&'a [u8]),
+    Ordinal(u16),
+}
+
+/// The location an exported symbol refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum ExportTarget<'a> {
+    /// The symbol is defined in the image itself at the given rva.
+    Rva(u32),
+    /// The symbol is defined in another module and has to be looked up there.
+    Forwarder {
+        module: &'a str,
+        symbol: ExportLookup<'a>,
+    },
+}
+
+/// A symbol exported by a pe image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Export<'a> {
+    pub name: Option<&'a [u8]>,
+    pub ordinal: u16,
+    pub target: ExportTarget<'a>,
+}
+
+/// The export directory of a pe image.
+///
+/// The table only borrows the bytes covered by the export data directory, so it can be parsed from a partial copy
+/// of an image (e.g. read from another process) as well as from a complete image or file.
+/// All tables and names referenced by the directory have to lie inside these bytes, which is the case for images
+/// produced by common linkers.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ExportTable<'a> {
+    data: &'a [u8],
+    rva: u32,
+    ordinal_base: u32,
+    number_of_functions: u32,
+    number_of_names: u32,
+    address_of_functions: u32,
+    address_of_names: u32,
+    address_of_name_ordinals: u32,
+}
+
+impl<'a> ExportTable<'a> {
+    /// Parses the export directory from the given bytes, which are located at the given rva of the image.
+    pub fn parse(data: &'a [u8], rva: u32) -> Result<Self, PeError> {
+        if data.len() < IMAGE_SIZEOF_EXPORT_DIRECTORY {
+            return Err(PeError::InvalidRva(rva));
+        }
+
+        let u32_at = |offset| read_u32(data, offset).unwrap();
+        Ok(Self {
+            data,
+            rva,
+            ordinal_base: u32_at(16),
+            number_of_functions: u32_at(20),
+            number_of_names: u32_at(24),
+            address_of_functions: u32_at(28),
+            address_of_names: u32_at(32),
+            address_of_name_ordinals: u32_at(36),
+        })
+    }
+
+    /// Returns the range of rvas covered by the export directory.
+    /// Exports whose address lies inside this range are forwarders.
+    pub fn directory_range(&self) -> Range<u32> {
+        self.rva..self.rva.saturating_add(self.data.len() as u32)
+    }
+
+    fn offset_of(&self, rva: u32, len: usize) -> Result<usize, PeError> {
+        rva.checked_sub(self.rva)
+            .map(|offset| offset as usize)
+            .filter(|offset| {
+                offset
+                    .checked_add(len)
+                    .map_or(false, |end| end <= self.data.len())
+            })
+            .ok_or(PeError::InvalidRva(rva))
+    }
+
+    fn u16_at(&self, rva: u32) -> Result<u16, PeError> {
+        Ok(read_u16(self.data, self.offset_of(rva, 2)?).unwrap())
+    }
+
+    fn u32_at(&self, rva: u32) -> Result<u32, PeError> {
+        Ok(read_u32(self.data, self.offset_of(rva, 4)?).unwrap())
+    }
+
+    fn c_str_at(&self, rva: u32) -> Result<&'a [u8], PeError> {
+        let bytes = &self.data[self.offset_of(rva, 0)?..];
+        let len = bytes
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or(PeError::InvalidRva(rva))?;
+        Ok(&bytes[..len])
+    }
+
+    fn name_at(&self, index: u32) -> Result<&'a [u8], PeError> {
+        let name = self.u32_at(self.address_of_names.wrapping_add(index.wrapping_mul(4)))?;
+        self.c_str_at(name)
+    }
+
+    fn name_ordinal_at(&self, index: u32) -> Result<u16, PeError> {
+        self.u16_at(
+            self.address_of_name_ordinals
+                .wrapping_add(index.wrapping_mul(2)),
+        )
+    }
+
+    /// Returns the export for the given index into the export address table or `None` if the entry is empty.
+    fn export_at(&self, index: u32, name: Option<&'a [u8]>) -> Result<Option<Export<'a>>, PeError> {
+        if index >= self.number_of_functions {
+            return Ok(None);
+        }
+
+        let address = self.u32_at(
+            self.address_of_functions
+                .wrapping_add(index.wrapping_mul(4)),
+        )?;
+        if address == 0 {
+            return Ok(None);
+        }
+
+        let target = if self.directory_range().contains(&address) {
+            self.parse_forwarder(address)?
+        } else {
+            ExportTarget::Rva(address)
+        };
+
+        Ok(Some(Export {
+            name,
+            ordinal: self.ordinal_base.wrapping_add(index) as u16,
+            target,
+        }))
+    }
+
+    /// Parses a forwarder string of the form `module.name` or `module.#ordinal`.
+    fn parse_forwarder(&self, rva: u32) -> Result<ExportTarget<'a>, PeError> {
+        let forwarder = self.c_str_at(rva)?;
+        let forwarder = str::from_utf8(forwarder).map_err(|_| PeError::InvalidForwarder(rva))?;
+        let (module, symbol) = forwarder
+            .rsplit_once('.')
+            .filter(|(module, symbol)| !module.is_empty() && !symbol.is_empty())
+            .ok_or(PeError::InvalidForwarder(rva))?;
+
+        let symbol = match symbol.strip_prefix('#') {
+            Some(ordinal) => ExportLookup::Ordinal(
+                ordinal
+                    .parse()
+                    .map_err(|_| PeError::InvalidForwarder(rva))?,
+            ),
+            None => ExportLookup::Name(symbol.as_bytes()),
+        };
+
+        Ok(ExportTarget::Forwarder { module, symbol })
+    }
+
+    /// Looks up the given symbol.
+    ///
+    /// Names are looked up using a binary search like the loader does, as the linker sorts the name table.
+    pub fn find(&self, symbol: ExportLookup<'_>) -> Result<Option<Export<'a>>, PeError> {
+        match symbol {
+            ExportLookup::Name(name) => {
+                let (mut low, mut high) = (0, self.number_of_names);
+                while low < high {
+                    let mid = low + (high - low) / 2;
+                    let mid_name = self.name_at(mid)?;
+                    match mid_name.cmp(name) {
+                        Ordering::Less => low = mid + 1,
+                        Ordering::Greater => high = mid,
+                        Ordering::Equal => {
+                            let index = self.name_ordinal_at(mid)?;
+                            return self.export_at(u32::from(index), Some(mid_name));
+                        }
+                    }
+                }
+                Ok(None)
+            }
+            ExportLookup::Ordinal(ordinal) => {
+                match u32::from(ordinal).checked_sub(self.ordinal_base) {
+                    Some(index) => self.export_at(index, None),
+                    None => Ok(None),
+                }
+            }
+        }
+    }
+
+    /// Returns all exported symbols ordered by their ordinal.
+    pub fn exports(&self) -> Result<Vec<Export<'a>>, PeError> {
+        // make sure the address table is valid before allocating space for all of its entries.
+        self.offset_of(
+            self.address_of_functions,
+            self.number_of_functions as usize * 4,
+        )?;
+
+        let mut names = vec![None; self.number_of_functions as usize];
+        for index in 0..self.number_of_names {
+            let function = self.name_ordinal_at(index)? as usize;
+            // a symbol can be exported under multiple names, only the first one is kept.
+            if let Some(name) = names.get_mut(function) {
+                if name.is_none() {
+                    *name = Some(self.name_at(index)?);
+                }
+            }
+        }
+
+        let mut exports = Vec::new();
+        for (index, name) in names.into_iter().enumerate() {
+            if let Some(export) = self.export_at(index as u32, name)? {
+                exports.push(export);
+            }
+        }
+        Ok(exports)
+    }
+}
+
+/// Reads the export directory of the given image, if it has one.
+pub(crate) fn read_export_table<'a>(view: &PeView<'a>) -> Result<Option<ExportTable<'a>>, PeError> {
+    let directory = match view.headers().data_directory(IMAGE_DIRECTORY_ENTRY_EXPORT) {
+        Some(directory) => directory,
+        None => return Ok(None),
+    };
+
+    let data = view.bytes_at(directory.virtual_address, directory.size as usize)?;
+    ExportTable::parse(data, directory.virtual_address).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pe::{
+        test_utils::{map_file, TestPe, FIXTURE_ARM64, FIXTURE_X64, FIXTURE_X86},
+        PeLayout,
+    };
+
+    #[test]
+    fn find_resolves_names_and_ordinals() {
+        let mut pe = TestPe::new(true);
+        let text = pe.add_section(".text", 0x10, 0x6000_0020);
+        pe.add_exports(
+            5,
+            &[
+                (Some("Alpha"), text),
+                (None, text + 4),
+                (Some("Beta"), text + 8),
+                (Some("Gamma"), text + 12),
+            ],
+        );
+
+        for (data, layout) in [
+            (pe.to_file(), PeLayout::File),
+            (pe.to_image(), PeLayout::Image),
+        ] {
+            let view = PeView::parse(&data, layout).unwrap();
+            let table = read_export_table(&view).unwrap().unwrap();
+
+            let beta = table.find(ExportLookup::Name(b"Beta")).unwrap().unwrap();
+            assert_eq!(beta.ordinal, 7);
+            assert_eq!(beta.target, ExportTarget::Rva(text + 8));
+
+            let unnamed = table.find(ExportLookup::Ordinal(6)).unwrap().unwrap();
+            assert_eq!(unnamed.name, None);
+            assert_eq!(unnamed.target, ExportTarget::Rva(text + 4));
+
+            assert_eq!(table.find(ExportLookup::Name(b"Delta")).unwrap(), None);
+            assert_eq!(table.find(ExportLookup::Name(b"beta")).unwrap(), None);
+            assert_eq!(table.find(ExportLookup::Ordinal(4)).unwrap(), None);
+            assert_eq!(table.find(ExportLookup::Ordinal(9)).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn find_parses_forwarders() {
+        let mut pe = TestPe::new(false);
+        let edata = pe.add_exports(1, &[(Some("A"), 0), (Some("B"), 0)]);
+        // place the forwarder strings behind the name table but inside the directory.
+        let forwarder_a = edata + 0x200;
+        let forwarder_b = pe.write_c_str(forwarder_a, "NTDLL.RtlAllocateHeap");
+        let end = pe.write_c_str(forwarder_b, "api-ms-win-core-heap-l1-1-0.#12");
+        pe.write_u32(edata + 0x40, forwarder_a);
+        pe.write_u32(edata + 0x44, forwarder_b);
+        pe.set_directory(IMAGE_DIRECTORY_ENTRY_EXPORT, edata, end - edata);
+
+        let image = pe.to_image();
+        let view = PeView::parse(&image, PeLayout::Image).unwrap();
+        let table = read_export_table(&view).unwrap().unwrap();
+
+        assert_eq!(
+            table
+                .find(ExportLookup::Name(b"A"))
+                .unwrap()
+                .unwrap()
+                .target,
+            ExportTarget::Forwarder {
+                module: "NTDLL",
+                symbol: ExportLookup::Name(b"RtlAllocateHeap"),
+            }
+        );
+        assert_eq!(
+            table
+                .find(ExportLookup::Ordinal(2))
+                .unwrap()
+                .unwrap()
+                .target,
+            ExportTarget::Forwarder {
+                module: "api-ms-win-core-heap-l1-1-0",
+                symbol: ExportLookup::Ordinal(12),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_works_on_partial_copy_of_directory() {
+        let mut pe = TestPe::new(true);
+        let text = pe.add_section(".text", 0x10, 0x6000_0020);
+        let edata = pe.add_exports(1, &[(Some("Export"), text)]);
+
+        let image = pe.to_image();
+        let view = PeView::parse(&image, PeLayout::Image).unwrap();
+        let directory = view
+            .headers()
+            .data_directory(IMAGE_DIRECTORY_ENTRY_EXPORT)
+            .unwrap();
+        let copy = image[edata as usize..(edata + directory.size) as usize].to_vec();
+
+        let table = ExportTable::parse(&copy, edata).unwrap();
+        assert_eq!(
+            table
+                .find(ExportLookup::Name(b"Export"))
+                .unwrap()
+                .unwrap()
+                .target,
+            ExportTarget::Rva(text)
+        );
+        assert_eq!(
+            ExportTable::parse(&copy[..8], edata).unwrap_err(),
+            PeError::InvalidRva(edata)
+        );
+    }
+
+    #[test]
+    fn exports_lists_all_symbols() {
+        let mut pe = TestPe::new(true);
+        let text = pe.add_section(".text", 0x10, 0x6000_0020);
+        pe.add_exports(1, &[(Some("First"), text), (None, 0), (None, text + 8)]);
+
+        let image = pe.to_image();
+        let view = PeView::parse(&image, PeLayout::Image).unwrap();
+        let table = read_export_table(&view).unwrap().unwrap();
+
+        assert_eq!(
+            table.exports().unwrap(),
+            vec![
+                Export {
+                    name: Some(b"First"),
+                    ordinal: 1,
+                    target: ExportTarget::Rva(text),
+                },
+                Export {
+                    name: None,
+                    ordinal: 3,
+                    target: ExportTarget::Rva(text + 8),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn read_export_table_without_directory_returns_none() {
+        let image = TestPe::new(false).to_image();
+        let view = PeView::parse(&image, PeLayout::Image).unwrap();
+        assert!(read_export_table(&view).unwrap().is_none());
+    }
+
+    #[test]
+    fn exports_of_fixture_dlls() {
+        for (file, call_imports) in [
+            (FIXTURE_X86, 0x1009),
+            (FIXTURE_X64, 0x1004),
+            (FIXTURE_ARM64, 0x1008),
+        ] {
+            for (data, layout) in [
+                (file.to_vec(), PeLayout::File),
+                (map_file(file), PeLayout::Image),
+            ] {
+                let view = PeView::parse(&data, layout).unwrap();
+                let table = read_export_table(&view).unwrap().unwrap();
+
+                let forwarder = ExportTarget::Forwarder {
+                    module: "KERNEL32",
+                    symbol: ExportLookup::Name(b"HeapAlloc"),
+                };
+                assert_eq!(
+                    table.exports().unwrap(),
+                    vec![
+                        Export {
+                            name: Some(b"add"),
+                            ordinal: 1,
+                            target: ExportTarget::Rva(0x1000),
+                        },
+                        Export {
+                            name: Some(b"call_imports"),
+                            ordinal: 2,
+                            target: ExportTarget::Rva(call_imports),
+                        },
+                        Export {
+                            name: Some(b"version"),
+                            ordinal: 3,
+                            target: ExportTarget::Rva(0x2000),
+                        },
+                        Export {
+                            name: None,
+                            ordinal: 4,
+                            target: ExportTarget::Rva(0x3000),
+                        },
+                        Export {
+                            name: Some(b"ForwardedHeapAlloc"),
+                            ordinal: 5,
+                            target: forwarder,
+                        },
+                        Export {
+                            name: Some(b"__dll_syringe_payload_procedure_add"),
+                            ordinal: 6,
+                            target: ExportTarget::Rva(0x2008),
+                        },
+                    ]
+                );
+
+                let find = |symbol| table.find(symbol).unwrap().map(|export| export.target);
+                // the name table is sorted by the linker, so every name has to be found by the binary search.
+                for export in table.exports().unwrap() {
+                    if let Some(name) = export.name {
+                        assert_eq!(find(ExportLookup::Name(name)), Some(export.target));
+                    }
+                    assert_eq!(
+                        find(ExportLookup::Ordinal(export.ordinal)),
+                        Some(export.target)
+                    );
+                }
+                assert_eq!(find(ExportLookup::Name(b"Add")), None);
+                assert_eq!(find(ExportLookup::Name(b"zzz")), None);
+                assert_eq!(find(ExportLookup::Ordinal(0)), None);
+                assert_eq!(find(ExportLookup::Ordinal(7)), None);
+            }
+        }
+    }
+}
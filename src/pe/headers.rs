@@ -0,0 +1,472 @@
+use std::{borrow::Cow, cmp};
+
+use crate::error::PeError;
+
+// The constants below mirror the ones from `winnt.h`. They are redefined here so that the parsing code does not
+// depend on the windows api.
+const IMAGE_DOS_SIGNATURE: u16 = 0x5A4D;
+const IMAGE_NT_SIGNATURE: u32 = 0x0000_4550;
+const IMAGE_NT_OPTIONAL_HDR32_MAGIC: u16 = 0x10B;
+const IMAGE_NT_OPTIONAL_HDR64_MAGIC: u16 = 0x20B;
+const IMAGE_SIZEOF_FILE_HEADER: usize = 20;
+const IMAGE_SIZEOF_SECTION_HEADER: usize = 40;
+const IMAGE_NUMBEROF_DIRECTORY_ENTRIES: usize = 16;
+
+pub(crate) const IMAGE_DIRECTORY_ENTRY_EXPORT: usize = 0;
+pub(crate) const IMAGE_DIRECTORY_ENTRY_IMPORT: usize = 1;
+pub(crate) const IMAGE_DIRECTORY_ENTRY_EXCEPTION: usize = 3;
+pub(crate) const IMAGE_DIRECTORY_ENTRY_BASERELOC: usize = 5;
+pub(crate) const IMAGE_DIRECTORY_ENTRY_TLS: usize = 9;
+pub(crate) const IMAGE_DIRECTORY_ENTRY_LOAD_CONFIG: usize = 10;
+
+pub(crate) const IMAGE_FILE_RELOCS_STRIPPED: u16 = 0x0001;
+pub(crate) const IMAGE_FILE_DLL: u16 = 0x2000;
+
+pub(crate) const IMAGE_FILE_MACHINE_I386: u16 = 0x014C;
+pub(crate) const IMAGE_FILE_MACHINE_AMD64: u16 = 0x8664;
+pub(crate) const IMAGE_FILE_MACHINE_ARM64: u16 = 0xAA64;
+
+pub(crate) const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+pub(crate) const IMAGE_SCN_MEM_READ: u32 = 0x4000_0000;
+pub(crate) const IMAGE_SCN_MEM_WRITE: u32 = 0x8000_0000;
+
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+pub(crate) fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    let bytes = data.get(offset..offset.checked_add(8)?)?;
+    Some(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+pub(crate) fn write_u16(data: &mut [u8], offset: usize, value: u16) -> Option<()> {
+    let bytes = data.get_mut(offset..offset.checked_add(2)?)?;
+    bytes.copy_from_slice(&value.to_le_bytes());
+    Some(())
+}
+
+pub(crate) fn write_u32(data: &mut [u8], offset: usize, value: u32) -> Option<()> {
+    let bytes = data.get_mut(offset..offset.checked_add(4)?)?;
+    bytes.copy_from_slice(&value.to_le_bytes());
+    Some(())
+}
+
+pub(crate) fn write_u64(data: &mut [u8], offset: usize, value: u64) -> Option<()> {
+    let bytes = data.get_mut(offset..offset.checked_add(8)?)?;
+    bytes.copy_from_slice(&value.to_le_bytes());
+    Some(())
+}
+
+/// An entry of the data directory of a pe image.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct DataDirectory {
+    pub virtual_address: u32,
+    pub size: u32,
+}
+
+/// A section header of a pe image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SectionHeader {
+    pub name: [u8; 8],
+    pub virtual_size: u32,
+    pub virtual_address: u32,
+    pub size_of_raw_data: u32,
+    pub pointer_to_raw_data: u32,
+    pub characteristics: u32,
+}
+
+impl SectionHeader {
+    /// Returns the name of the section with the padding removed.
+    pub fn name(&self) -> Cow<'_, str> {
+        let len = self.name.iter().position(|&b| b == 0).unwrap_or(self.name.len());
+        String::from_utf8_lossy(&self.name[..len])
+    }
+
+    /// Returns the size of the section once mapped into memory.
+    pub fn mapped_size(&self) -> u32 {
+        if self.virtual_size == 0 {
+            self.size_of_raw_data
+        } else {
+            self.virtual_size
+        }
+    }
+
+    /// Returns the number of bytes of this section that are backed by the file.
+    pub fn file_backed_size(&self) -> u32 {
+        cmp::min(self.size_of_raw_data, self.mapped_size())
+    }
+}
+
+/// The parsed headers of a pe image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PeHeaders {
+    pub machine: u16,
+    pub time_date_stamp: u32,
+    pub characteristics: u16,
+    pub is_64: bool,
+    pub image_base: u64,
+    pub address_of_entry_point: u32,
+    pub section_alignment: u32,
+    pub file_alignment: u32,
+    pub size_of_image: u32,
+    pub size_of_headers: u32,
+    pub dll_characteristics: u16,
+    pub data_directories: Vec<DataDirectory>,
+    pub sections: Vec<SectionHeader>,
+}
+
+impl PeHeaders {
+    /// Parses the headers at the start of the given buffer.
+    /// The headers are laid out the same way in the file and in the mapped image.
+    pub fn parse(data: &[u8]) -> Result<Self, PeError> {
+        if read_u16(data, 0) != Some(IMAGE_DOS_SIGNATURE) {
+            return Err(PeError::InvalidDosHeader);
+        }
+        let nt_headers = read_u32(data, 0x3C).ok_or(PeError::InvalidDosHeader)? as usize;
+        if read_u32(data, nt_headers) != Some(IMAGE_NT_SIGNATURE) {
+            return Err(PeError::InvalidNtHeaders);
+        }
+
+        let u16_at = |offset| read_u16(data, offset).ok_or(PeError::InvalidNtHeaders);
+        let u32_at = |offset| read_u32(data, offset).ok_or(PeError::InvalidNtHeaders);
+        let u64_at = |offset| read_u64(data, offset).ok_or(PeError::InvalidNtHeaders);
+
+        let file_header = nt_headers + 4;
+        let machine = u16_at(file_header)?;
+        let number_of_sections = u16_at(file_header + 2)?;
+        let time_date_stamp = u32_at(file_header + 4)?;
+        let size_of_optional_header = u16_at(file_header + 16)?;
+        let characteristics = u16_at(file_header + 18)?;
+
+        let optional_header = file_header + IMAGE_SIZEOF_FILE_HEADER;
+        let magic = u16_at(optional_header)?;
+        let (is_64, image_base, number_of_rva_and_sizes_offset) = match magic {
+            IMAGE_NT_OPTIONAL_HDR32_MAGIC => (false, u64::from(u32_at(optional_header + 28)?), 92),
+            IMAGE_NT_OPTIONAL_HDR64_MAGIC => (true, u64_at(optional_header + 24)?, 108),
+            _ => return Err(PeError::UnsupportedOptionalHeader(magic)),
+        };
+
+        let number_of_rva_and_sizes = u32_at(optional_header + number_of_rva_and_sizes_offset)?;
+        let data_directories_offset = optional_header + number_of_rva_and_sizes_offset + 4;
+        let data_directories = (0..cmp::min(
+            number_of_rva_and_sizes as usize,
+            IMAGE_NUMBEROF_DIRECTORY_ENTRIES,
+        ))
+            .map(|i| {
+                let entry = data_directories_offset + i * 8;
+                Ok(DataDirectory {
+                    virtual_address: u32_at(entry)?,
+                    size: u32_at(entry + 4)?,
+                })
+            })
+            .collect::<Result<Vec<_>, PeError>>()?;
+
+        let section_table = optional_header + size_of_optional_header as usize;
+        let sections = (0..number_of_sections as usize)
+            .map(|i| {
+                let entry = section_table + i * IMAGE_SIZEOF_SECTION_HEADER;
+                let name = data
+                    .get(entry..entry + 8)
+                    .ok_or(PeError::InvalidNtHeaders)?
+                    .try_into()
+                    .unwrap();
+                Ok(SectionHeader {
+                    name,
+                    virtual_size: u32_at(entry + 8)?,
+                    virtual_address: u32_at(entry + 12)?,
+                    size_of_raw_data: u32_at(entry + 16)?,
+                    pointer_to_raw_data: u32_at(entry + 20)?,
+                    characteristics: u32_at(entry + 36)?,
+                })
+            })
+            .collect::<Result<Vec<_>, PeError>>()?;
+
+        Ok(Self {
+            machine,
+            time_date_stamp,
+            characteristics,
+            is_64,
+            image_base,
+            address_of_entry_point: u32_at(optional_header + 16)?,
+            section_alignment: u32_at(optional_header + 32)?,
+            file_alignment: u32_at(optional_header + 36)?,
+            size_of_image: u32_at(optional_header + 56)?,
+            size_of_headers: u32_at(optional_header + 60)?,
+            dll_characteristics: u16_at(optional_header + 70)?,
+            data_directories,
+            sections,
+        })
+    }
+
+    /// Returns the data directory entry with the given index, if it is present.
+    pub fn data_directory(&self, index: usize) -> Option<DataDirectory> {
+        self.data_directories
+            .get(index)
+            .copied()
+            .filter(|dir| dir.virtual_address != 0 && dir.size != 0)
+    }
+
+    /// Returns the size of a pointer in this image.
+    pub fn pointer_size(&self) -> usize {
+        if self.is_64 {
+            8
+        } else {
+            4
+        }
+    }
+
+    /// Converts an absolute address based on the preferred image base to an rva.
+    pub fn va_to_rva(&self, va: u64) -> Result<u32, PeError> {
+        va.checked_sub(self.image_base)
+            .and_then(|rva| u32::try_from(rva).ok())
+            .ok_or(PeError::InvalidAddress(va))
+    }
+}
+
+/// The way the bytes of a pe image are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum PeLayout {
+    /// The bytes are laid out like the file on disk.
+    File,
+    /// The bytes are laid out like the image after it was mapped into memory.
+    Image,
+}
+
+/// A read-only view of a pe image backed by a byte buffer.
+#[derive(Debug, Clone)]
+pub(crate) struct PeView<'a> {
+    data: &'a [u8],
+    layout: PeLayout,
+    headers: PeHeaders,
+}
+
+impl<'a> PeView<'a> {
+    /// Parses the headers of the given image.
+    pub fn parse(data: &'a [u8], layout: PeLayout) -> Result<Self, PeError> {
+        let headers = PeHeaders::parse(data)?;
+        Ok(Self {
+            data,
+            layout,
+            headers,
+        })
+    }
+
+    /// Returns the underlying buffer.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns the layout of the underlying buffer.
+    pub fn layout(&self) -> PeLayout {
+        self.layout
+    }
+
+    /// Returns the parsed headers of this image.
+    pub fn headers(&self) -> &PeHeaders {
+        &self.headers
+    }
+
+    /// Returns whether this is a 64-bit (PE32+) image.
+    pub fn is_64(&self) -> bool {
+        self.headers.is_64
+    }
+
+    /// Converts the given rva to an offset into the underlying buffer.
+    pub fn rva_to_offset(&self, rva: u32) -> Option<usize> {
+        match self.layout {
+            PeLayout::Image => Some(rva as usize),
+            PeLayout::File => {
+                if rva < self.headers.size_of_headers {
+                    return Some(rva as usize);
+                }
+                self.headers
+                    .sections
+                    .iter()
+                    .find(|section| {
+                        rva >= section.virtual_address
+                            && rva - section.virtual_address < section.file_backed_size()
+                    })
+                    .and_then(|section| {
+                        // the raw data pointer is not validated, so it may point past the end of the address space.
+                        (rva - section.virtual_address)
+                            .checked_add(section.pointer_to_raw_data)
+                            .map(|offset| offset as usize)
+                    })
+            }
+        }
+    }
+
+    /// Returns the bytes at the given rva.
+    pub fn bytes_at(&self, rva: u32, len: usize) -> Result<&'a [u8], PeError> {
+        self.rva_to_offset(rva)
+            .and_then(|offset| self.data.get(offset..offset.checked_add(len)?))
+            .ok_or(PeError::InvalidRva(rva))
+    }
+
+    /// Reads a `u16` at the given rva.
+    pub fn u16_at(&self, rva: u32) -> Result<u16, PeError> {
+        Ok(u16::from_le_bytes(self.bytes_at(rva, 2)?.try_into().unwrap()))
+    }
+
+    /// Reads a `u32` at the given rva.
+    pub fn u32_at(&self, rva: u32) -> Result<u32, PeError> {
+        Ok(u32::from_le_bytes(self.bytes_at(rva, 4)?.try_into().unwrap()))
+    }
+
+    /// Reads a `u64` at the given rva.
+    pub fn u64_at(&self, rva: u32) -> Result<u64, PeError> {
+        Ok(u64::from_le_bytes(self.bytes_at(rva, 8)?.try_into().unwrap()))
+    }
+
+    /// Reads a pointer sized value at the given rva.
+    pub fn pointer_at(&self, rva: u32) -> Result<u64, PeError> {
+        if self.is_64() {
+            self.u64_at(rva)
+        } else {
+            self.u32_at(rva).map(u64::from)
+        }
+    }
+
+    /// Returns the bytes of the nul-terminated string at the given rva (without the nul terminator).
+    pub fn c_str_at(&self, rva: u32) -> Result<&'a [u8], PeError> {
+        let offset = self.rva_to_offset(rva).ok_or(PeError::InvalidRva(rva))?;
+        let bytes = self.data.get(offset..).ok_or(PeError::InvalidRva(rva))?;
+        let len = bytes
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or(PeError::InvalidRva(rva))?;
+        Ok(&bytes[..len])
+    }
+
+    /// Reads the nul-terminated string at the given rva.
+    pub fn string_at(&self, rva: u32) -> Result<String, PeError> {
+        self.c_str_at(rva)
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pe::test_utils::{map_file, TestPe, FIXTURE_ARM64, FIXTURE_X64, FIXTURE_X86};
+
+    #[test]
+    fn parse_reads_headers_of_x86_image() {
+        let pe = TestPe::new(false);
+        let headers = PeHeaders::parse(&pe.to_file()).unwrap();
+        assert!(!headers.is_64);
+        assert_eq!(headers.machine, 0x14C);
+        assert_eq!(headers.image_base, pe.image_base);
+        assert_eq!(headers.size_of_image, pe.size_of_image());
+    }
+
+    #[test]
+    fn parse_reads_headers_of_x64_image() {
+        let mut pe = TestPe::new(true);
+        let text = pe.add_section(".text", 0x10, 0x6000_0020);
+        pe.entry_point = text;
+        let headers = PeHeaders::parse(&pe.to_file()).unwrap();
+        assert!(headers.is_64);
+        assert_eq!(headers.machine, 0x8664);
+        assert_eq!(headers.address_of_entry_point, text);
+        assert_eq!(headers.sections.len(), 1);
+        assert_eq!(headers.sections[0].name(), ".text");
+        assert_eq!(headers.sections[0].virtual_address, text);
+    }
+
+    #[test]
+    fn parse_rejects_invalid_signature() {
+        let mut file = TestPe::new(true).to_file();
+        file[0] = b'X';
+        assert_eq!(PeHeaders::parse(&file), Err(PeError::InvalidDosHeader));
+
+        let mut file = TestPe::new(true).to_file();
+        let nt_headers = read_u32(&file, 0x3C).unwrap() as usize;
+        file[nt_headers] = b'X';
+        assert_eq!(PeHeaders::parse(&file), Err(PeError::InvalidNtHeaders));
+    }
+
+    #[test]
+    fn rva_to_offset_maps_file_layout() {
+        let mut pe = TestPe::new(false);
+        let data = pe.add_section(".data", 0x20, 0xC000_0040);
+        pe.write(data + 4, b"test\0");
+        let file = pe.to_file();
+
+        let view = PeView::parse(&file, PeLayout::File).unwrap();
+        assert_ne!(view.rva_to_offset(data), Some(data as usize));
+        assert_eq!(view.c_str_at(data + 4).unwrap(), b"test");
+        assert_eq!(view.rva_to_offset(data + 0x1000), None);
+
+        let image = pe.to_image();
+        let view = PeView::parse(&image, PeLayout::Image).unwrap();
+        assert_eq!(view.rva_to_offset(data), Some(data as usize));
+        assert_eq!(view.string_at(data + 4).unwrap(), "test");
+    }
+
+    #[test]
+    fn rva_to_offset_rejects_overflowing_raw_data_pointer() {
+        let mut pe = TestPe::new(false);
+        let data = pe.add_section(".data", 0x20, 0xC000_0040);
+        let file = pe.to_file();
+
+        let mut view = PeView::parse(&file, PeLayout::File).unwrap();
+        view.headers.sections[0].pointer_to_raw_data = u32::MAX - 4;
+        assert_eq!(view.rva_to_offset(data + 4), Some(u32::MAX as usize));
+        assert_eq!(view.rva_to_offset(data + 5), None);
+        assert_eq!(view.u32_at(data + 8), Err(PeError::InvalidRva(data + 8)));
+    }
+
+    #[test]
+    fn parse_reads_headers_of_fixture_dlls() {
+        for (file, machine, is_64, image_base) in [
+            (FIXTURE_X86, 0x14C, false, 0x1000_0000),
+            (FIXTURE_X64, 0x8664, true, 0x1_8000_0000),
+            (FIXTURE_ARM64, 0xAA64, true, 0x1_8000_0000),
+        ] {
+            let headers = PeHeaders::parse(file).unwrap();
+            assert_eq!(headers.machine, machine);
+            assert_eq!(headers.is_64, is_64);
+            assert_eq!(headers.image_base, image_base);
+            assert_ne!(headers.characteristics & IMAGE_FILE_DLL, 0);
+            assert_eq!(headers.time_date_stamp, 0);
+            assert_eq!(headers.address_of_entry_point, 0);
+            assert_eq!(headers.size_of_image, 0x5000);
+            assert_eq!(headers.size_of_headers, 0x400);
+            assert_eq!(
+                headers
+                    .sections
+                    .iter()
+                    .map(SectionHeader::name)
+                    .collect::<Vec<_>>(),
+                [".text", ".rdata", ".data", ".reloc"]
+            );
+            assert!(headers
+                .data_directory(IMAGE_DIRECTORY_ENTRY_BASERELOC)
+                .is_some());
+            assert!(headers.data_directory(IMAGE_DIRECTORY_ENTRY_TLS).is_none());
+        }
+    }
+
+    #[test]
+    fn rva_to_offset_maps_fixture_dll() {
+        let view = PeView::parse(FIXTURE_X64, PeLayout::File).unwrap();
+        // `version` is the first symbol in .rdata, which starts at rva 0x2000 and is stored at offset 0x600.
+        assert_eq!(view.rva_to_offset(0x2000), Some(0x600));
+        assert_eq!(view.string_at(0x2000).unwrap(), "fixture");
+        assert_eq!(view.rva_to_offset(0x3C), Some(0x3C));
+        // the rest of the section alignment after .rdata is not backed by the file.
+        assert_eq!(view.rva_to_offset(0x2200), None);
+
+        let image = map_file(FIXTURE_X64);
+        let view = PeView::parse(&image, PeLayout::Image).unwrap();
+        assert_eq!(view.rva_to_offset(0x2000), Some(0x2000));
+        assert_eq!(view.string_at(0x2000).unwrap(), "fixture");
+    }
+}
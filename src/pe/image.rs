@@ -0,0 +1,386 @@
+use std::{cmp, ops::Range};
+
+use crate::{
+    error::PeError,
+    pe::{
+        read_u16, read_u32, read_u64, write_u16, write_u32, write_u64, PeHeaders, PeLayout, PeView,
+        IMAGE_DIRECTORY_ENTRY_BASERELOC, IMAGE_DIRECTORY_ENTRY_TLS, IMAGE_FILE_RELOCS_STRIPPED,
+        IMAGE_SCN_MEM_EXECUTE, IMAGE_SCN_MEM_READ, IMAGE_SCN_MEM_WRITE,
+    },
+};
+
+const IMAGE_REL_BASED_ABSOLUTE: u8 = 0;
+const IMAGE_REL_BASED_HIGH: u8 = 1;
+const IMAGE_REL_BASED_LOW: u8 = 2;
+const IMAGE_REL_BASED_HIGHLOW: u8 = 3;
+const IMAGE_REL_BASED_DIR64: u8 = 10;
+
+/// Lays out the given pe file like the loader would map it into memory.
+/// The returned buffer has a length of `SizeOfImage` and is not relocated.
+pub(crate) fn map_image(file: &PeView<'_>) -> Result<Vec<u8>, PeError> {
+    debug_assert_eq!(file.layout(), PeLayout::File);
+
+    let headers = file.headers();
+    let mut image = vec![0u8; headers.size_of_image as usize];
+
+    let header_len = cmp::min(
+        headers.size_of_headers as usize,
+        cmp::min(file.data().len(), image.len()),
+    );
+    image[..header_len].copy_from_slice(&file.data()[..header_len]);
+
+    for section in &headers.sections {
+        let len = section.file_backed_size() as usize;
+        let src_start = section.pointer_to_raw_data as usize;
+        let dst_start = section.virtual_address as usize;
+        let src = file
+            .data()
+            .get(src_start..src_start + len)
+            .ok_or(PeError::InvalidRva(section.virtual_address))?;
+        let dst = image
+            .get_mut(dst_start..dst_start + len)
+            .ok_or(PeError::InvalidRva(section.virtual_address))?;
+        dst.copy_from_slice(src);
+    }
+
+    Ok(image)
+}
+
+/// Applies the base relocations of the given mapped image so that it can be executed at `new_base`.
+pub(crate) fn relocate_image(
+    image: &mut [u8],
+    headers: &PeHeaders,
+    new_base: u64,
+) -> Result<(), PeError> {
+    let delta = new_base.wrapping_sub(headers.image_base);
+    if delta == 0 {
+        return Ok(());
+    }
+
+    if headers.characteristics & IMAGE_FILE_RELOCS_STRIPPED != 0 {
+        return Err(PeError::RelocationsStripped);
+    }
+    let directory = match headers.data_directory(IMAGE_DIRECTORY_ENTRY_BASERELOC) {
+        Some(directory) => directory,
+        // an image without relocations does not contain any absolute addresses.
+        None => return Ok(()),
+    };
+
+    let mut block = directory.virtual_address;
+    let end = directory
+        .virtual_address
+        .checked_add(directory.size)
+        .ok_or(PeError::InvalidRva(directory.virtual_address))?;
+    // the arithmetic below is checked, as a malformed image must not cause a panic or wrap around.
+    while end.saturating_sub(block) >= 8 {
+        let page = read_u32(image, block as usize).ok_or(PeError::InvalidRva(block))?;
+        let block_size =
+            read_u32(image, block as usize + 4).ok_or(PeError::InvalidRva(block + 4))?;
+        if block_size < 8 {
+            break;
+        }
+        let block_end = block
+            .checked_add(block_size)
+            .ok_or(PeError::InvalidRva(block))?;
+
+        for entry_rva in (block + 8..block_end).step_by(2) {
+            let entry =
+                read_u16(image, entry_rva as usize).ok_or(PeError::InvalidRva(entry_rva))?;
+            let kind = (entry >> 12) as u8;
+            let target = page
+                .checked_add(u32::from(entry & 0x0FFF))
+                .ok_or(PeError::InvalidRva(page))?;
+            let offset = target as usize;
+
+            let result = match kind {
+                IMAGE_REL_BASED_ABSOLUTE => Some(()),
+                IMAGE_REL_BASED_HIGH => read_u16(image, offset).and_then(|value| {
+                    write_u16(image, offset, value.wrapping_add((delta >> 16) as u16))
+                }),
+                IMAGE_REL_BASED_LOW => read_u16(image, offset)
+                    .and_then(|value| write_u16(image, offset, value.wrapping_add(delta as u16))),
+                IMAGE_REL_BASED_HIGHLOW => read_u32(image, offset)
+                    .and_then(|value| write_u32(image, offset, value.wrapping_add(delta as u32))),
+                IMAGE_REL_BASED_DIR64 => read_u64(image, offset)
+                    .and_then(|value| write_u64(image, offset, value.wrapping_add(delta))),
+                _ => return Err(PeError::UnsupportedRelocation(kind)),
+            };
+            result.ok_or(PeError::InvalidRva(target))?;
+        }
+
+        block = block_end;
+    }
+
+    Ok(())
+}
+
+/// Writes a pointer sized value to the given rva of a mapped image.
+pub(crate) fn write_pointer(
+    image: &mut [u8],
+    headers: &PeHeaders,
+    rva: u32,
+    value: u64,
+) -> Result<(), PeError> {
+    let result = if headers.is_64 {
+        write_u64(image, rva as usize, value)
+    } else {
+        write_u32(image, rva as usize, value as u32)
+    };
+    result.ok_or(PeError::InvalidRva(rva))
+}
+
+/// Reads the rvas of the tls callbacks of the given image.
+///
+/// # Note
+/// The callbacks are stored as absolute addresses, so this has to be called before the image is relocated.
+pub(crate) fn read_tls_callbacks(view: &PeView<'_>) -> Result<Vec<u32>, PeError> {
+    let directory = match view.headers().data_directory(IMAGE_DIRECTORY_ENTRY_TLS) {
+        Some(directory) => directory,
+        None => return Ok(Vec::new()),
+    };
+
+    // AddressOfCallBacks is the fourth field of IMAGE_TLS_DIRECTORY
+    let pointer_size = view.headers().pointer_size() as u32;
+    let callbacks = view.pointer_at(directory.virtual_address + 3 * pointer_size)?;
+    if callbacks == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut callback_rvas = Vec::new();
+    let mut entry = view.headers().va_to_rva(callbacks)?;
+    loop {
+        let callback = view.pointer_at(entry)?;
+        if callback == 0 {
+            break;
+        }
+        callback_rvas.push(view.headers().va_to_rva(callback)?);
+        entry += pointer_size;
+    }
+
+    Ok(callback_rvas)
+}
+
+/// Returns the page aligned ranges of a mapped image together with the `IMAGE_SCN_MEM_*` flags of the sections in them.
+///
+/// The headers are only readable and pages that are not part of any section have no flags. Pages shared by multiple
+/// sections (if the section alignment is smaller than a page) get the flags of all of them. Adjacent pages with the same
+/// flags are merged into a single range.
+pub(crate) fn page_characteristics(
+    headers: &PeHeaders,
+    page_size: usize,
+) -> Vec<(Range<usize>, u32)> {
+    let image_size = headers.size_of_image as usize;
+    let mut pages = vec![0u32; image_size.div_ceil(page_size)];
+
+    let mut add_characteristics = |start: usize, len: usize, characteristics: u32| {
+        let first_page = cmp::min(start / page_size, pages.len());
+        let end_page = cmp::min(start.saturating_add(len).div_ceil(page_size), pages.len());
+        for page in &mut pages[first_page..cmp::max(first_page, end_page)] {
+            *page |= characteristics;
+        }
+    };
+    add_characteristics(0, headers.size_of_headers as usize, IMAGE_SCN_MEM_READ);
+    for section in &headers.sections {
+        add_characteristics(
+            section.virtual_address as usize,
+            section.mapped_size() as usize,
+            section.characteristics
+                & (IMAGE_SCN_MEM_EXECUTE | IMAGE_SCN_MEM_READ | IMAGE_SCN_MEM_WRITE),
+        );
+    }
+
+    let mut ranges: Vec<(Range<usize>, u32)> = Vec::new();
+    for (i, &characteristics) in pages.iter().enumerate() {
+        let start = i * page_size;
+        let end = cmp::min(start + page_size, image_size);
+        match ranges.last_mut() {
+            Some((range, last)) if *last == characteristics => range.end = end,
+            _ => ranges.push((start..end, characteristics)),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pe::test_utils::TestPe;
+
+    fn relocation_block(page: u32, entries: &[u16]) -> Vec<u8> {
+        let mut block = Vec::new();
+        block.extend_from_slice(&page.to_le_bytes());
+        block.extend_from_slice(&(8 + entries.len() as u32 * 2).to_le_bytes());
+        for entry in entries {
+            block.extend_from_slice(&entry.to_le_bytes());
+        }
+        block
+    }
+
+    #[test]
+    fn map_image_places_sections_at_their_rva() {
+        let mut pe = TestPe::new(true);
+        let text = pe.add_section(".text", 0x1234, 0x6000_0020);
+        let data = pe.add_section(".data", 0x10, 0xC000_0040);
+        pe.write(text, &[0xCC; 0x1234]);
+        pe.write(data, b"data");
+        let file = pe.to_file();
+
+        let view = PeView::parse(&file, PeLayout::File).unwrap();
+        let image = map_image(&view).unwrap();
+
+        assert_eq!(image.len(), pe.size_of_image() as usize);
+        assert_eq!(image, pe.to_image());
+        assert_eq!(&image[data as usize..data as usize + 4], b"data");
+        assert!(PeView::parse(&image, PeLayout::Image).is_ok());
+    }
+
+    #[test]
+    fn relocate_image_applies_dir64_relocations() {
+        let mut pe = TestPe::new(true);
+        let data = pe.add_section(".data", 0x10, 0xC000_0040);
+        let reloc = pe.add_section(".reloc", 0x10, 0x4200_0040);
+        pe.write_u64(data + 8, pe.image_base + 0x1000);
+        let block = relocation_block(data, &[(10 << 12) | 8, 0]);
+        pe.write(reloc, &block);
+        pe.set_directory(IMAGE_DIRECTORY_ENTRY_BASERELOC, reloc, block.len() as u32);
+
+        let mut image = pe.to_image();
+        let headers = PeHeaders::parse(&image).unwrap();
+        relocate_image(&mut image, &headers, 0x7FF0_0000_0000).unwrap();
+
+        assert_eq!(read_u64(&image, data as usize + 8), Some(0x7FF0_0000_1000));
+    }
+
+    #[test]
+    fn relocate_image_applies_highlow_relocations() {
+        let mut pe = TestPe::new(false);
+        let data = pe.add_section(".data", 0x10, 0xC000_0040);
+        let reloc = pe.add_section(".reloc", 0x10, 0x4200_0040);
+        pe.write_u32(data, pe.image_base as u32 + 0x1004);
+        pe.write_u32(data + 4, 0xAAAA_AAAA);
+        let block = relocation_block(data, &[3 << 12]);
+        pe.write(reloc, &block);
+        pe.set_directory(IMAGE_DIRECTORY_ENTRY_BASERELOC, reloc, block.len() as u32);
+
+        let mut image = pe.to_image();
+        let headers = PeHeaders::parse(&image).unwrap();
+        relocate_image(&mut image, &headers, 0x0040_0000).unwrap();
+
+        assert_eq!(read_u32(&image, data as usize), Some(0x0040_1004));
+        assert_eq!(read_u32(&image, data as usize + 4), Some(0xAAAA_AAAA));
+    }
+
+    #[test]
+    fn relocate_image_to_preferred_base_is_noop() {
+        let mut pe = TestPe::new(true);
+        pe.characteristics |= IMAGE_FILE_RELOCS_STRIPPED;
+        let image = pe.to_image();
+        let headers = PeHeaders::parse(&image).unwrap();
+
+        let mut relocated = image.clone();
+        relocate_image(&mut relocated, &headers, pe.image_base).unwrap();
+        assert_eq!(image, relocated);
+    }
+
+    #[test]
+    fn relocate_image_fails_if_relocations_are_stripped() {
+        let mut pe = TestPe::new(true);
+        pe.characteristics |= IMAGE_FILE_RELOCS_STRIPPED;
+        let mut image = pe.to_image();
+        let headers = PeHeaders::parse(&image).unwrap();
+
+        assert_eq!(
+            relocate_image(&mut image, &headers, 0x1000_0000),
+            Err(PeError::RelocationsStripped)
+        );
+    }
+
+    #[test]
+    fn relocate_image_fails_on_overflowing_relocations() {
+        let mut pe = TestPe::new(false);
+        let reloc = pe.add_section(".reloc", 0x10, 0x4200_0040);
+        let block = relocation_block(0xFFFF_FFF0, &[(3 << 12) | 0x20]);
+        pe.write(reloc, &block);
+        pe.set_directory(IMAGE_DIRECTORY_ENTRY_BASERELOC, reloc, block.len() as u32);
+        let mut image = pe.to_image();
+        let headers = PeHeaders::parse(&image).unwrap();
+        assert_eq!(
+            relocate_image(&mut image, &headers, 0x0040_0000),
+            Err(PeError::InvalidRva(0xFFFF_FFF0))
+        );
+
+        let mut pe = TestPe::new(false);
+        let reloc = pe.add_section(".reloc", 0x10, 0x4200_0040);
+        let mut block = relocation_block(0x1000, &[]);
+        block[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        pe.write(reloc, &block);
+        pe.set_directory(IMAGE_DIRECTORY_ENTRY_BASERELOC, reloc, block.len() as u32);
+        let mut image = pe.to_image();
+        let headers = PeHeaders::parse(&image).unwrap();
+        assert_eq!(
+            relocate_image(&mut image, &headers, 0x0040_0000),
+            Err(PeError::InvalidRva(reloc))
+        );
+    }
+
+    #[test]
+    fn read_tls_callbacks_returns_rvas() {
+        for is_64 in [false, true] {
+            let mut pe = TestPe::new(is_64);
+            let text = pe.add_section(".text", 0x10, 0x6000_0020);
+            let tls = pe.add_section(".tls", 0x100, 0xC000_0040);
+            let pointer_size = pe.pointer_size();
+            let callbacks = tls + 0x40;
+            let image_base = pe.image_base;
+
+            pe.write_pointer(tls + 3 * pointer_size, image_base + u64::from(callbacks));
+            pe.write_pointer(callbacks, image_base + u64::from(text));
+            pe.write_pointer(callbacks + pointer_size, image_base + u64::from(text) + 8);
+            pe.set_directory(IMAGE_DIRECTORY_ENTRY_TLS, tls, 6 * pointer_size);
+
+            let image = pe.to_image();
+            let view = PeView::parse(&image, PeLayout::Image).unwrap();
+            assert_eq!(read_tls_callbacks(&view).unwrap(), vec![text, text + 8]);
+        }
+    }
+
+    #[test]
+    fn page_characteristics_follows_sections() {
+        let mut pe = TestPe::new(true);
+        let text = pe.add_section(".text", 0x1234, 0x6000_0020);
+        let rdata = pe.add_section(".rdata", 0x10, 0x4000_0040);
+        let data = pe.add_section(".data", 0x10, 0xC000_0040);
+        let bss = pe.add_section(".bss", 0x10, 0xC000_0080);
+        let image = pe.to_image();
+        let view = PeView::parse(&image, PeLayout::Image).unwrap();
+
+        let rx = IMAGE_SCN_MEM_EXECUTE | IMAGE_SCN_MEM_READ;
+        let rw = IMAGE_SCN_MEM_READ | IMAGE_SCN_MEM_WRITE;
+        assert_eq!(
+            page_characteristics(view.headers(), 0x1000),
+            vec![
+                (0..text as usize, IMAGE_SCN_MEM_READ),
+                (text as usize..rdata as usize, rx),
+                (rdata as usize..data as usize, IMAGE_SCN_MEM_READ),
+                (data as usize..bss as usize + 0x1000, rw),
+            ]
+        );
+    }
+
+    #[test]
+    fn page_characteristics_combines_sections_sharing_a_page() {
+        let mut pe = TestPe::new(false);
+        pe.add_section(".text", 0x10, 0x6000_0020);
+        pe.add_section(".data", 0x10, 0xC000_0040);
+        let image = pe.to_image();
+        let view = PeView::parse(&image, PeLayout::Image).unwrap();
+
+        assert_eq!(
+            page_characteristics(view.headers(), 0x2000),
+            vec![
+                (0..0x2000, IMAGE_SCN_MEM_EXECUTE | IMAGE_SCN_MEM_READ),
+                (0x2000..0x3000, IMAGE_SCN_MEM_READ | IMAGE_SCN_MEM_WRITE),
+            ]
+        );
+    }
+}
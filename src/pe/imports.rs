@@ -0,0 +1,246 @@
+use std::fmt;
+
+use crate::{
+    error::PeError,
+    pe::{PeView, IMAGE_DIRECTORY_ENTRY_IMPORT},
+};
+
+const IMAGE_SIZEOF_IMPORT_DESCRIPTOR: u32 = 20;
+
+/// A module imported by a pe image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Import {
+    pub module: String,
+    pub symbols: Vec<ImportSymbol>,
+}
+
+/// A symbol imported by a pe image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ImportSymbol {
+    pub name: ImportName,
+    /// The rva of the import address table entry for this symbol.
+    pub iat_rva: u32,
+}
+
+/// The way a symbol is imported.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum ImportName {
+    Name { hint: u16, name: String },
+    Ordinal(u16),
+}
+
+impl fmt::Display for ImportName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name { name, .. } => write!(f, "{}", name),
+            Self::Ordinal(ordinal) => write!(f, "#{}", ordinal),
+        }
+    }
+}
+
+/// Reads the import directory of the given image.
+pub(crate) fn read_imports(view: &PeView<'_>) -> Result<Vec<Import>, PeError> {
+    let directory = match view.headers().data_directory(IMAGE_DIRECTORY_ENTRY_IMPORT) {
+        Some(directory) => directory,
+        None => return Ok(Vec::new()),
+    };
+
+    let pointer_size = view.headers().pointer_size() as u32;
+    let ordinal_flag = 1u64 << (pointer_size * 8 - 1);
+
+    let mut imports = Vec::new();
+    let mut descriptor = directory.virtual_address;
+    loop {
+        let original_first_thunk = view.u32_at(descriptor)?;
+        let name = view.u32_at(offset_rva(descriptor, 12)?)?;
+        let first_thunk = view.u32_at(offset_rva(descriptor, 16)?)?;
+        if name == 0 || first_thunk == 0 {
+            break;
+        }
+
+        // old linkers do not emit an import lookup table, in that case the (unbound) address table is used instead.
+        let lookup_table = if original_first_thunk != 0 {
+            original_first_thunk
+        } else {
+            first_thunk
+        };
+
+        let mut symbols = Vec::new();
+        for index in 0u32.. {
+            let entry_offset = index
+                .checked_mul(pointer_size)
+                .ok_or(PeError::InvalidRva(lookup_table))?;
+            let thunk = view.pointer_at(offset_rva(lookup_table, entry_offset)?)?;
+            if thunk == 0 {
+                break;
+            }
+
+            let name = if thunk & ordinal_flag != 0 {
+                ImportName::Ordinal(thunk as u16)
+            } else {
+                let hint_name = (thunk & 0x7FFF_FFFF) as u32;
+                ImportName::Name {
+                    hint: view.u16_at(hint_name)?,
+                    name: view.string_at(offset_rva(hint_name, 2)?)?,
+                }
+            };
+            symbols.push(ImportSymbol {
+                name,
+                iat_rva: offset_rva(first_thunk, entry_offset)?,
+            });
+        }
+
+        imports.push(Import {
+            module: view.string_at(name)?,
+            symbols,
+        });
+        descriptor = offset_rva(descriptor, IMAGE_SIZEOF_IMPORT_DESCRIPTOR)?;
+    }
+
+    Ok(imports)
+}
+
+/// Adds the given offset to the rva, failing with [`PeError::InvalidRva`] if the result does not fit into an rva.
+fn offset_rva(rva: u32, offset: u32) -> Result<u32, PeError> {
+    rva.checked_add(offset).ok_or(PeError::InvalidRva(rva))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pe::{
+        read_u16, read_u32,
+        test_utils::{map_file, TestPe, FIXTURE_ARM64, FIXTURE_X64, FIXTURE_X86},
+        write_u32, PeLayout,
+    };
+
+    #[test]
+    fn read_imports_reads_names_and_ordinals() {
+        for is_64 in [false, true] {
+            let mut pe = TestPe::new(is_64);
+            let idata = pe.add_section(".idata", 0x200, 0xC000_0040);
+            let pointer_size = pe.pointer_size();
+            let ordinal_flag = 1u64 << (pointer_size * 8 - 1);
+
+            let lookup_table = idata + 0x40;
+            let address_table = idata + 0x80;
+            let hint_name = idata + 0xC0;
+            let module_name = idata + 0x100;
+
+            pe.write_u32(idata, lookup_table);
+            pe.write_u32(idata + 12, module_name);
+            pe.write_u32(idata + 16, address_table);
+            for table in [lookup_table, address_table] {
+                pe.write_pointer(table, u64::from(hint_name));
+                pe.write_pointer(table + pointer_size, ordinal_flag | 42);
+            }
+            pe.write_u16(hint_name, 7);
+            pe.write_c_str(hint_name + 2, "LoadLibraryW");
+            pe.write_c_str(module_name, "KERNEL32.dll");
+            pe.set_directory(IMAGE_DIRECTORY_ENTRY_IMPORT, idata, 40);
+
+            for (data, layout) in [
+                (pe.to_file(), PeLayout::File),
+                (pe.to_image(), PeLayout::Image),
+            ] {
+                let view = PeView::parse(&data, layout).unwrap();
+                let imports = read_imports(&view).unwrap();
+                assert_eq!(
+                    imports,
+                    vec![Import {
+                        module: "KERNEL32.dll".to_string(),
+                        symbols: vec![
+                            ImportSymbol {
+                                name: ImportName::Name {
+                                    hint: 7,
+                                    name: "LoadLibraryW".to_string()
+                                },
+                                iat_rva: address_table,
+                            },
+                            ImportSymbol {
+                                name: ImportName::Ordinal(42),
+                                iat_rva: address_table + pointer_size,
+                            },
+                        ],
+                    }]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn read_imports_of_fixture_dlls() {
+        for (file, kernel32_iat, ws2_32_iat) in [
+            (FIXTURE_X86, 0x2128, 0x2130),
+            (FIXTURE_X64, 0x2138, 0x2148),
+            (FIXTURE_ARM64, 0x2138, 0x2148),
+        ] {
+            for (data, layout) in [
+                (file.to_vec(), PeLayout::File),
+                (map_file(file), PeLayout::Image),
+            ] {
+                let view = PeView::parse(&data, layout).unwrap();
+                assert_eq!(
+                    read_imports(&view).unwrap(),
+                    vec![
+                        Import {
+                            module: "KERNEL32.dll".to_string(),
+                            symbols: vec![ImportSymbol {
+                                name: ImportName::Name {
+                                    hint: 0,
+                                    name: "GetCurrentProcessId".to_string()
+                                },
+                                iat_rva: kernel32_iat,
+                            }],
+                        },
+                        Import {
+                            module: "WS2_32.dll".to_string(),
+                            symbols: vec![ImportSymbol {
+                                name: ImportName::Ordinal(115),
+                                iat_rva: ws2_32_iat,
+                            }],
+                        },
+                    ]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn read_imports_fails_on_overflowing_rvas() {
+        // a section at the very end of the address space, so that the rvas following its contents overflow.
+        let section = u32::MAX - 0x1F;
+        let build = |directory: u32, write: &dyn Fn(&mut TestPe, u32)| {
+            let mut pe = TestPe::new(false);
+            let idata = pe.add_section(".idata", 0x20, 0xC000_0040);
+            write(&mut pe, idata);
+            pe.set_directory(IMAGE_DIRECTORY_ENTRY_IMPORT, directory, 40);
+
+            let mut file = pe.to_file();
+            let nt_headers = read_u32(&file, 0x3C).unwrap() as usize;
+            let size_of_optional_header = read_u16(&file, nt_headers + 20).unwrap() as usize;
+            let section_header = nt_headers + 24 + size_of_optional_header;
+            write_u32(&mut file, section_header + 12, section).unwrap();
+            file
+        };
+
+        // the name of the descriptor lies behind the end of the address space.
+        let file = build(section + 0x18, &|pe, idata| pe.write_u32(idata + 0x18, 1));
+        let view = PeView::parse(&file, PeLayout::File).unwrap();
+        assert_eq!(
+            read_imports(&view),
+            Err(PeError::InvalidRva(section + 0x18))
+        );
+
+        // the second entry of the lookup table lies behind the end of the address space.
+        let lookup_table = section + 0x1C;
+        let file = build(section, &|pe, idata| {
+            pe.write_u32(idata, lookup_table);
+            pe.write_u32(idata + 12, section);
+            pe.write_u32(idata + 16, section);
+            pe.write_u32(idata + 0x1C, 0x8000_0001);
+        });
+        let view = PeView::parse(&file, PeLayout::File).unwrap();
+        assert_eq!(read_imports(&view), Err(PeError::InvalidRva(lookup_table)));
+    }
+}
@@ -0,0 +1,19 @@
+#[allow(dead_code)]
+mod headers;
+pub(crate) use headers::*;
+
+#[cfg(feature = "manual-map")]
+mod image;
+#[cfg(feature = "manual-map")]
+pub(crate) use image::*;
+
+#[allow(dead_code)]
+mod imports;
+pub(crate) use imports::*;
+
+#[allow(dead_code)]
+mod exports;
+pub(crate) use exports::*;
+
+#[cfg(test)]
+pub(crate) mod test_utils;
@@ -0,0 +1,267 @@
+//! Helpers for building small synthetic pe images and for loading the fixture dlls used in tests.
+
+use crate::pe::{PeHeaders, IMAGE_DIRECTORY_ENTRY_EXPORT};
+
+/// Dlls linked from the sources in `tests/fixtures` (see `tests/fixtures/build.sh`).
+///
+/// All of them export `add` (#1), `call_imports` (#2), `version` (#3), an unnamed data symbol (#4),
+/// `ForwardedHeapAlloc` (#5, forwarded to `KERNEL32.HeapAlloc`) and the payload procedure marker for `add` (#6)
+/// and import `GetCurrentProcessId` from `KERNEL32.dll` by name and `WS2_32.dll` #115 by ordinal.
+pub(crate) const FIXTURE_X86: &[u8] = include_bytes!("../../tests/fixtures/fixture_x86.dll");
+pub(crate) const FIXTURE_X64: &[u8] = include_bytes!("../../tests/fixtures/fixture_x64.dll");
+pub(crate) const FIXTURE_ARM64: &[u8] = include_bytes!("../../tests/fixtures/fixture_arm64.dll");
+
+/// Lays out the given pe file like it would be mapped into memory (without relocating it or resolving its imports).
+pub(crate) fn map_file(file: &[u8]) -> Vec<u8> {
+    let headers = PeHeaders::parse(file).unwrap();
+    let mut image = vec![0; headers.size_of_image as usize];
+    let size_of_headers = headers.size_of_headers as usize;
+    image[..size_of_headers].copy_from_slice(&file[..size_of_headers]);
+    for section in &headers.sections {
+        let source = section.pointer_to_raw_data as usize;
+        let target = section.virtual_address as usize;
+        let len = section.file_backed_size() as usize;
+        image[target..target + len].copy_from_slice(&file[source..source + len]);
+    }
+    image
+}
+
+const SECTION_ALIGNMENT: u32 = 0x1000;
+const FILE_ALIGNMENT: u32 = 0x200;
+const SIZE_OF_HEADERS: u32 = 0x400;
+const NT_HEADERS_OFFSET: usize = 0x80;
+
+#[derive(Debug, Clone)]
+struct TestSection {
+    name: [u8; 8],
+    virtual_address: u32,
+    virtual_size: u32,
+    characteristics: u32,
+}
+
+/// A builder for a minimal pe image.
+/// The contents of the image are written in image layout and can then be converted to either layout.
+#[derive(Debug, Clone)]
+pub(crate) struct TestPe {
+    pub is_64: bool,
+    pub image_base: u64,
+    pub entry_point: u32,
+    pub characteristics: u16,
+    pub time_date_stamp: u32,
+    directories: [(u32, u32); 16],
+    sections: Vec<TestSection>,
+    image: Vec<u8>,
+}
+
+impl TestPe {
+    pub fn new(is_64: bool) -> Self {
+        Self {
+            is_64,
+            image_base: if is_64 { 0x1_8000_0000 } else { 0x1000_0000 },
+            entry_point: 0,
+            // IMAGE_FILE_EXECUTABLE_IMAGE | IMAGE_FILE_DLL (| IMAGE_FILE_32BIT_MACHINE)
+            characteristics: if is_64 { 0x2002 } else { 0x2102 },
+            time_date_stamp: 0x5EED_5EED,
+            directories: [(0, 0); 16],
+            sections: Vec::new(),
+            image: vec![0; SECTION_ALIGNMENT as usize],
+        }
+    }
+
+    /// Appends a new section and returns its rva.
+    pub fn add_section(&mut self, name: &str, virtual_size: u32, characteristics: u32) -> u32 {
+        let mut section_name = [0u8; 8];
+        section_name[..name.len()].copy_from_slice(name.as_bytes());
+        let virtual_address = self.image.len() as u32;
+        self.sections.push(TestSection {
+            name: section_name,
+            virtual_address,
+            virtual_size,
+            characteristics,
+        });
+        self.image.resize(
+            (virtual_address + align_up(virtual_size, SECTION_ALIGNMENT)) as usize,
+            0,
+        );
+        virtual_address
+    }
+
+    pub fn set_directory(&mut self, index: usize, rva: u32, size: u32) {
+        self.directories[index] = (rva, size);
+    }
+
+    pub fn size_of_image(&self) -> u32 {
+        self.image.len() as u32
+    }
+
+    pub fn pointer_size(&self) -> u32 {
+        if self.is_64 {
+            8
+        } else {
+            4
+        }
+    }
+
+    pub fn write(&mut self, rva: u32, bytes: &[u8]) {
+        let rva = rva as usize;
+        self.image[rva..rva + bytes.len()].copy_from_slice(bytes);
+    }
+
+    pub fn write_u16(&mut self, rva: u32, value: u16) {
+        self.write(rva, &value.to_le_bytes());
+    }
+
+    pub fn write_u32(&mut self, rva: u32, value: u32) {
+        self.write(rva, &value.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, rva: u32, value: u64) {
+        self.write(rva, &value.to_le_bytes());
+    }
+
+    pub fn write_pointer(&mut self, rva: u32, value: u64) {
+        if self.is_64 {
+            self.write_u64(rva, value);
+        } else {
+            self.write_u32(rva, value as u32);
+        }
+    }
+
+    /// Writes a nul-terminated string and returns the rva directly after it.
+    pub fn write_c_str(&mut self, rva: u32, value: &str) -> u32 {
+        self.write(rva, value.as_bytes());
+        self.write(rva + value.len() as u32, &[0]);
+        rva + value.len() as u32 + 1
+    }
+
+    /// Writes an export directory with the given names (which have to be sorted) and addresses
+    /// to a new section and returns the rva of the section.
+    pub fn add_exports(&mut self, ordinal_base: u32, exports: &[(Option<&str>, u32)]) -> u32 {
+        let edata = self.add_section(".edata", 0x400, 0x4000_0040);
+        let functions = edata + 0x40;
+        let names = functions + 0x40;
+        let name_ordinals = names + 0x40;
+        let mut strings = name_ordinals + 0x40;
+
+        let mut number_of_names = 0;
+        for (index, (name, address)) in exports.iter().enumerate() {
+            self.write_u32(functions + index as u32 * 4, *address);
+            if let Some(name) = name {
+                self.write_u32(names + number_of_names * 4, strings);
+                self.write_u16(name_ordinals + number_of_names * 2, index as u16);
+                strings = self.write_c_str(strings, name);
+                number_of_names += 1;
+            }
+        }
+
+        self.write_u32(edata + 16, ordinal_base);
+        self.write_u32(edata + 20, exports.len() as u32);
+        self.write_u32(edata + 24, number_of_names);
+        self.write_u32(edata + 28, functions);
+        self.write_u32(edata + 32, names);
+        self.write_u32(edata + 36, name_ordinals);
+        self.set_directory(IMAGE_DIRECTORY_ENTRY_EXPORT, edata, strings - edata);
+        edata
+    }
+
+    /// Returns the image laid out like a file on disk.
+    pub fn to_file(&self) -> Vec<u8> {
+        let mut file = self.headers();
+        for (section, pointer_to_raw_data) in self.sections.iter().zip(self.raw_data_pointers()) {
+            let start = section.virtual_address as usize;
+            let raw_size = align_up(section.virtual_size, FILE_ALIGNMENT) as usize;
+            file.resize(pointer_to_raw_data as usize, 0);
+            file.extend_from_slice(&self.image[start..start + raw_size]);
+        }
+        file
+    }
+
+    /// Returns the image laid out like it would be mapped into memory.
+    pub fn to_image(&self) -> Vec<u8> {
+        let mut image = self.image.clone();
+        let headers = self.headers();
+        image[..headers.len()].copy_from_slice(&headers);
+        image
+    }
+
+    fn raw_data_pointers(&self) -> Vec<u32> {
+        let mut pointer = SIZE_OF_HEADERS;
+        self.sections
+            .iter()
+            .map(|section| {
+                let current = pointer;
+                pointer += align_up(section.virtual_size, FILE_ALIGNMENT);
+                current
+            })
+            .collect()
+    }
+
+    fn headers(&self) -> Vec<u8> {
+        let mut headers = vec![0u8; SIZE_OF_HEADERS as usize];
+        let mut put = |offset: usize, bytes: &[u8]| {
+            headers[offset..offset + bytes.len()].copy_from_slice(bytes);
+        };
+
+        put(0, b"MZ");
+        put(0x3C, &(NT_HEADERS_OFFSET as u32).to_le_bytes());
+        put(NT_HEADERS_OFFSET, b"PE\0\0");
+
+        let file_header = NT_HEADERS_OFFSET + 4;
+        let machine: u16 = if self.is_64 { 0x8664 } else { 0x14C };
+        let size_of_optional_header: u16 = if self.is_64 { 240 } else { 224 };
+        put(file_header, &machine.to_le_bytes());
+        put(file_header + 2, &(self.sections.len() as u16).to_le_bytes());
+        put(file_header + 4, &self.time_date_stamp.to_le_bytes());
+        put(file_header + 16, &size_of_optional_header.to_le_bytes());
+        put(file_header + 18, &self.characteristics.to_le_bytes());
+
+        let optional_header = file_header + 20;
+        let magic: u16 = if self.is_64 { 0x20B } else { 0x10B };
+        put(optional_header, &magic.to_le_bytes());
+        put(optional_header + 16, &self.entry_point.to_le_bytes());
+        if self.is_64 {
+            put(optional_header + 24, &self.image_base.to_le_bytes());
+        } else {
+            put(optional_header + 28, &(self.image_base as u32).to_le_bytes());
+        }
+        put(optional_header + 32, &SECTION_ALIGNMENT.to_le_bytes());
+        put(optional_header + 36, &FILE_ALIGNMENT.to_le_bytes());
+        put(optional_header + 56, &self.size_of_image().to_le_bytes());
+        put(optional_header + 60, &SIZE_OF_HEADERS.to_le_bytes());
+        put(optional_header + 68, &2u16.to_le_bytes()); // IMAGE_SUBSYSTEM_WINDOWS_GUI
+        put(optional_header + 70, &0x0140u16.to_le_bytes()); // DYNAMIC_BASE | NX_COMPAT
+
+        let number_of_rva_and_sizes = optional_header + if self.is_64 { 108 } else { 92 };
+        put(number_of_rva_and_sizes, &16u32.to_le_bytes());
+        for (i, (rva, size)) in self.directories.iter().enumerate() {
+            let entry = number_of_rva_and_sizes + 4 + i * 8;
+            put(entry, &rva.to_le_bytes());
+            put(entry + 4, &size.to_le_bytes());
+        }
+
+        let section_table = optional_header + size_of_optional_header as usize;
+        for (i, (section, pointer_to_raw_data)) in self
+            .sections
+            .iter()
+            .zip(self.raw_data_pointers())
+            .enumerate()
+        {
+            let entry = section_table + i * 40;
+            put(entry, &section.name);
+            put(entry + 8, &section.virtual_size.to_le_bytes());
+            put(entry + 12, &section.virtual_address.to_le_bytes());
+            put(
+                entry + 16,
+                &align_up(section.virtual_size, FILE_ALIGNMENT).to_le_bytes(),
+            );
+            put(entry + 20, &pointer_to_raw_data.to_le_bytes());
+            put(entry + 36, &section.characteristics.to_le_bytes());
+        }
+
+        headers
+    }
+}
+
+fn align_up(value: u32, alignment: u32) -> u32 {
+    (value + alignment - 1) & !(alignment - 1)
+}
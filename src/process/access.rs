@@ -0,0 +1,55 @@
+use bitflags::bitflags;
+use winapi::um::winnt::{
+    PROCESS_CREATE_PROCESS, PROCESS_CREATE_THREAD, PROCESS_DUP_HANDLE, PROCESS_QUERY_INFORMATION,
+    PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_SET_INFORMATION, PROCESS_SET_QUOTA,
+    PROCESS_SUSPEND_RESUME, PROCESS_TERMINATE, PROCESS_VM_OPERATION, PROCESS_VM_READ,
+    PROCESS_VM_WRITE, SYNCHRONIZE,
+};
+
+use crate::process::PROCESS_INJECTION_ACCESS;
+
+bitflags! {
+    /// The [access rights](https://docs.microsoft.com/en-us/windows/win32/procthread/process-security-and-access-rights) of a process handle.
+    pub struct ProcessAccess: u32 {
+        /// Required to terminate the process.
+        const TERMINATE = PROCESS_TERMINATE;
+        /// Required to create a thread in the process.
+        const CREATE_THREAD = PROCESS_CREATE_THREAD;
+        /// Required to allocate, free and protect memory of the process.
+        const VM_OPERATION = PROCESS_VM_OPERATION;
+        /// Required to read memory of the process.
+        const VM_READ = PROCESS_VM_READ;
+        /// Required to write memory of the process.
+        const VM_WRITE = PROCESS_VM_WRITE;
+        /// Required to duplicate handles of the process.
+        const DUP_HANDLE = PROCESS_DUP_HANDLE;
+        /// Required to create a process with the process as its parent.
+        const CREATE_PROCESS = PROCESS_CREATE_PROCESS;
+        /// Required to set memory limits of the process.
+        const SET_QUOTA = PROCESS_SET_QUOTA;
+        /// Required to set information about the process, such as its priority class.
+        const SET_INFORMATION = PROCESS_SET_INFORMATION;
+        /// Required to query information about the process, such as its modules.
+        /// Handles with this right also have [`QUERY_LIMITED_INFORMATION`](Self::QUERY_LIMITED_INFORMATION).
+        const QUERY_INFORMATION = PROCESS_QUERY_INFORMATION;
+        /// Required to suspend and resume the process.
+        const SUSPEND_RESUME = PROCESS_SUSPEND_RESUME;
+        /// Required to query basic information about the process, such as its id, path and exit code.
+        const QUERY_LIMITED_INFORMATION = PROCESS_QUERY_LIMITED_INFORMATION;
+        /// Required to wait for the process to exit.
+        const SYNCHRONIZE = SYNCHRONIZE;
+        /// The access rights required for performing dll injection.
+        const INJECTION = PROCESS_INJECTION_ACCESS;
+    }
+}
+
+impl ProcessAccess {
+    /// Returns the rights that are actually granted when requesting these rights.
+    pub(crate) fn granted(self) -> Self {
+        if self.contains(Self::QUERY_INFORMATION) {
+            self | Self::QUERY_LIMITED_INFORMATION
+        } else {
+            self
+        }
+    }
+}
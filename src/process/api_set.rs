@@ -0,0 +1,344 @@
+use std::{io, mem, ptr, slice};
+
+use cstr::cstr;
+use widestring::u16cstr;
+use winapi::{
+    shared::{
+        minwindef::ULONG,
+        ntdef::{HANDLE, NTSTATUS, PVOID},
+    },
+    um::processthreadsapi::GetCurrentProcess,
+};
+
+use crate::process::BorrowedProcessModule;
+
+type NtQueryInformationProcessFn =
+    unsafe extern "system" fn(HANDLE, ULONG, PVOID, ULONG, *mut ULONG) -> NTSTATUS;
+
+const PROCESS_BASIC_INFORMATION_CLASS: ULONG = 0;
+
+/// Offset of `ApiSetMap` in the process environment block of the current process.
+#[cfg(target_pointer_width = "64")]
+const PEB_API_SET_MAP_OFFSET: usize = 0x68;
+#[cfg(target_pointer_width = "32")]
+const PEB_API_SET_MAP_OFFSET: usize = 0x38;
+
+/// The maximum number of forwarders that are followed when resolving an export.
+#[cfg_attr(
+    not(any(
+        feature = "rpc-core",
+        all(target_arch = "x86_64", feature = "into-x86-from-x64")
+    )),
+    allow(dead_code)
+)]
+pub(crate) const MAX_FORWARDER_DEPTH: usize = 16;
+
+/// The only supported version of the schema, which is used since Windows 10.
+const API_SET_SCHEMA_VERSION: u32 = 6;
+const API_SET_NAMESPACE_SIZE: usize = 28;
+const API_SET_NAMESPACE_ENTRY_SIZE: usize = 24;
+const API_SET_VALUE_ENTRY_SIZE: usize = 20;
+
+#[derive(Debug, Default, Clone, Copy)]
+#[repr(C)]
+struct ProcessBasicInformation {
+    exit_status: NTSTATUS,
+    peb_base_address: usize,
+    affinity_mask: usize,
+    base_priority: i32,
+    unique_process_id: usize,
+    inherited_from_unique_process_id: usize,
+}
+
+/// Returns whether the given module name refers to an api set instead of an actual module.
+pub(crate) fn is_api_set_name(module_name: &str) -> bool {
+    let prefix = module_name.get(..4).unwrap_or_default();
+    prefix.eq_ignore_ascii_case("api-") || prefix.eq_ignore_ascii_case("ext-")
+}
+
+/// The api set schema the loader uses to redirect api set names (e.g. `api-ms-win-core-synch-l1-2-0`) to the
+/// modules implementing them.
+///
+/// Only the schema format used since Windows 10 is supported.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ApiSetSchema<'a> {
+    data: &'a [u8],
+    count: u32,
+    entry_offset: u32,
+}
+
+impl ApiSetSchema<'static> {
+    /// Returns the schema of the current process, which is shared by all processes of the system.
+    pub fn current() -> Result<Option<Self>, io::Error> {
+        let ntdll =
+            BorrowedProcessModule::find_local_by_name_or_abs_path_wstr(u16cstr!("ntdll.dll"))?
+                .unwrap();
+        let query_information_process: NtQueryInformationProcessFn = unsafe {
+            mem::transmute(
+                ntdll.get_local_procedure_address_cstr(cstr!("NtQueryInformationProcess"))?,
+            )
+        };
+
+        let mut info = ProcessBasicInformation::default();
+        let status = unsafe {
+            query_information_process(
+                GetCurrentProcess(),
+                PROCESS_BASIC_INFORMATION_CLASS,
+                ptr::addr_of_mut!(info).cast(),
+                mem::size_of::<ProcessBasicInformation>() as ULONG,
+                ptr::null_mut(),
+            )
+        };
+        if status < 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!(
+                    "NtQueryInformationProcess failed with NTSTATUS {:#010x}",
+                    status
+                ),
+            ));
+        }
+
+        let api_set_map = unsafe {
+            ptr::read((info.peb_base_address + PEB_API_SET_MAP_OFFSET) as *const *const u8)
+        };
+        if api_set_map.is_null() {
+            return Ok(None);
+        }
+
+        // the schema is mapped for the whole lifetime of the process and its size is stored in its header.
+        let header = unsafe { slice::from_raw_parts(api_set_map, API_SET_NAMESPACE_SIZE) };
+        let size = u32_at(header, 4).unwrap() as usize;
+        let data = unsafe { slice::from_raw_parts(api_set_map, size) };
+        Ok(Self::parse(data))
+    }
+}
+
+impl<'a> ApiSetSchema<'a> {
+    /// Parses the schema from the given bytes.
+    /// Returns `None` if the schema is malformed or uses an unsupported format.
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        if u32_at(data, 0)? != API_SET_SCHEMA_VERSION {
+            return None;
+        }
+        Some(Self {
+            data,
+            count: u32_at(data, 12)?,
+            entry_offset: u32_at(data, 16)?,
+        })
+    }
+
+    /// Resolves the given api set to the name of the module implementing it for the given importing module.
+    ///
+    /// The name of the api set may include the `.dll` extension. Like the loader, the version after the last
+    /// hyphen is ignored. Returns `None` if the api set is unknown or has no host module.
+    pub fn resolve(&self, api_set: &str, importing_module: Option<&str>) -> Option<String> {
+        let api_set = strip_dll_extension(api_set);
+        // the hashed part of the name excludes the trailing minor version (e.g. "-0").
+        let hashed_name = &api_set[..api_set.rfind('-')?];
+
+        for index in 0..self.count {
+            let entry = self.entry_offset as usize + index as usize * API_SET_NAMESPACE_ENTRY_SIZE;
+            let name_offset = u32_at(self.data, entry + 4)?;
+            let hashed_length = u32_at(self.data, entry + 12)?;
+            let name = self.wide_str_at(name_offset, hashed_length)?;
+            if !name.eq_ignore_ascii_case(hashed_name) {
+                continue;
+            }
+
+            let value_offset = u32_at(self.data, entry + 16)? as usize;
+            let value_count = u32_at(self.data, entry + 20)? as usize;
+            return self.resolve_value(value_offset, value_count, importing_module);
+        }
+
+        None
+    }
+
+    /// Picks the host of an api set. The first value is the default host, the others are exceptions for specific importing modules.
+    fn resolve_value(
+        &self,
+        value_offset: usize,
+        value_count: usize,
+        importing_module: Option<&str>,
+    ) -> Option<String> {
+        if value_count == 0 {
+            return None;
+        }
+
+        let value_at = |index: usize| value_offset + index * API_SET_VALUE_ENTRY_SIZE;
+        let mut host = value_at(0);
+        if let Some(importing_module) = importing_module {
+            for index in 1..value_count {
+                let value = value_at(index);
+                let name =
+                    self.wide_str_at(u32_at(self.data, value + 4)?, u32_at(self.data, value + 8)?)?;
+                if name.eq_ignore_ascii_case(importing_module) {
+                    host = value;
+                    break;
+                }
+            }
+        }
+
+        let host =
+            self.wide_str_at(u32_at(self.data, host + 12)?, u32_at(self.data, host + 16)?)?;
+        if host.is_empty() {
+            None
+        } else {
+            Some(host)
+        }
+    }
+
+    fn wide_str_at(&self, offset: u32, byte_len: u32) -> Option<String> {
+        let offset = offset as usize;
+        let bytes = self
+            .data
+            .get(offset..offset.checked_add(byte_len as usize)?)?;
+        let chars = bytes
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect::<Vec<_>>();
+        String::from_utf16(&chars).ok()
+    }
+}
+
+fn strip_dll_extension(module_name: &str) -> &str {
+    match module_name.len().checked_sub(4) {
+        Some(split)
+            if module_name.is_char_boundary(split)
+                && module_name[split..].eq_ignore_ascii_case(".dll") =>
+        {
+            &module_name[..split]
+        }
+        _ => module_name,
+    }
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a schema with the given api sets, each consisting of its name and its (importing module, host) values.
+    fn build_schema(api_sets: &[(&str, &[(&str, &str)])]) -> Vec<u8> {
+        let entries = API_SET_NAMESPACE_SIZE;
+        let values = entries + api_sets.len() * API_SET_NAMESPACE_ENTRY_SIZE;
+        let value_count = api_sets.iter().map(|(_, v)| v.len()).sum::<usize>();
+        let mut data = vec![0u8; values + value_count * API_SET_VALUE_ENTRY_SIZE];
+
+        let push_str = |data: &mut Vec<u8>, s: &str| {
+            let offset = data.len() as u32;
+            data.extend(s.encode_utf16().flat_map(u16::to_le_bytes));
+            (offset, s.len() as u32 * 2)
+        };
+        let put = |data: &mut Vec<u8>, offset: usize, value: u32| {
+            data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        };
+
+        put(&mut data, 0, API_SET_SCHEMA_VERSION);
+        put(&mut data, 12, api_sets.len() as u32);
+        put(&mut data, 16, entries as u32);
+
+        let mut value = values;
+        for (index, (name, hosts)) in api_sets.iter().enumerate() {
+            let entry = entries + index * API_SET_NAMESPACE_ENTRY_SIZE;
+            let (name_offset, name_length) = push_str(&mut data, name);
+            put(&mut data, entry + 4, name_offset);
+            put(&mut data, entry + 8, name_length);
+            put(&mut data, entry + 12, name.rfind('-').unwrap() as u32 * 2);
+            put(&mut data, entry + 16, value as u32);
+            put(&mut data, entry + 20, hosts.len() as u32);
+
+            for (importing_module, host) in hosts.iter() {
+                let (name_offset, name_length) = push_str(&mut data, importing_module);
+                let (host_offset, host_length) = push_str(&mut data, host);
+                put(&mut data, value + 4, name_offset);
+                put(&mut data, value + 8, name_length);
+                put(&mut data, value + 12, host_offset);
+                put(&mut data, value + 16, host_length);
+                value += API_SET_VALUE_ENTRY_SIZE;
+            }
+        }
+
+        let size = data.len() as u32;
+        put(&mut data, 4, size);
+        data
+    }
+
+    #[test]
+    fn resolve_ignores_version_and_extension() {
+        let data = build_schema(&[
+            ("api-ms-win-core-heap-l1-2-0", &[("", "kernelbase.dll")]),
+            ("api-ms-win-core-synch-l1-2-0", &[("", "kernelbase.dll")]),
+        ]);
+        let schema = ApiSetSchema::parse(&data).unwrap();
+
+        assert_eq!(
+            schema.resolve("api-ms-win-core-synch-l1-2-1", None),
+            Some("kernelbase.dll".to_string())
+        );
+        assert_eq!(
+            schema.resolve("API-MS-WIN-CORE-HEAP-L1-2-0.dll", None),
+            Some("kernelbase.dll".to_string())
+        );
+        assert_eq!(schema.resolve("api-ms-win-core-file-l1-1-0", None), None);
+    }
+
+    #[test]
+    fn resolve_prefers_exception_for_importing_module() {
+        let data = build_schema(&[(
+            "api-ms-win-core-synch-l1-2-0",
+            &[
+                ("", "kernelbase.dll"),
+                ("kernel32.dll", "kernel32legacy.dll"),
+            ],
+        )]);
+        let schema = ApiSetSchema::parse(&data).unwrap();
+
+        assert_eq!(
+            schema.resolve("api-ms-win-core-synch-l1-2-0", Some("KERNEL32.DLL")),
+            Some("kernel32legacy.dll".to_string())
+        );
+        assert_eq!(
+            schema.resolve("api-ms-win-core-synch-l1-2-0", Some("user32.dll")),
+            Some("kernelbase.dll".to_string())
+        );
+    }
+
+    #[test]
+    fn resolve_without_host_returns_none() {
+        let data = build_schema(&[("ext-ms-win-missing-l1-1-0", &[("", "")])]);
+        let schema = ApiSetSchema::parse(&data).unwrap();
+        assert_eq!(schema.resolve("ext-ms-win-missing-l1-1-0", None), None);
+    }
+
+    #[test]
+    fn parse_rejects_unsupported_version() {
+        let mut data = build_schema(&[]);
+        data[0] = 4;
+        assert!(ApiSetSchema::parse(&data).is_none());
+    }
+
+    #[test]
+    fn is_api_set_name_matches_prefixes() {
+        assert!(is_api_set_name("api-ms-win-core-synch-l1-2-0"));
+        assert!(is_api_set_name("EXT-MS-WIN-NTUSER-UICONTEXT-EXT-L1-1-0"));
+        assert!(!is_api_set_name("kernelbase"));
+        assert!(!is_api_set_name("api"));
+    }
+
+    #[test]
+    fn current_schema_resolves_known_api_set() {
+        let schema = ApiSetSchema::current().unwrap().unwrap();
+        let host = schema
+            .resolve("api-ms-win-core-processthreads-l1-1-0", None)
+            .unwrap();
+        assert!(
+            host.eq_ignore_ascii_case("kernel32.dll")
+                || host.eq_ignore_ascii_case("kernelbase.dll")
+        );
+    }
+}
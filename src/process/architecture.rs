@@ -0,0 +1,353 @@
+use std::{
+    fmt::{self, Display},
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+};
+#[cfg(windows)]
+use std::{
+    mem::{self, MaybeUninit},
+    os::windows::prelude::AsRawHandle,
+};
+
+#[cfg(windows)]
+use cstr::cstr;
+#[cfg(windows)]
+use widestring::u16cstr;
+#[cfg(windows)]
+use winapi::{
+    shared::{
+        minwindef::{BOOL, FALSE, USHORT, WORD},
+        ntdef::HANDLE,
+    },
+    um::{
+        processthreadsapi::GetCurrentProcess,
+        sysinfoapi::GetNativeSystemInfo,
+        winnt::{
+            IMAGE_FILE_MACHINE_AMD64, IMAGE_FILE_MACHINE_ARM64, IMAGE_FILE_MACHINE_I386,
+            IMAGE_FILE_MACHINE_UNKNOWN, PROCESSOR_ARCHITECTURE_AMD64, PROCESSOR_ARCHITECTURE_ARM64,
+            PROCESSOR_ARCHITECTURE_INTEL,
+        },
+        wow64apiset::IsWow64Process,
+    },
+};
+
+#[cfg(windows)]
+use crate::process::{BorrowedProcessModule, Process};
+use crate::{
+    error::PeError,
+    pe::{read_u32, read_u64, PeLayout, PeView, IMAGE_DIRECTORY_ENTRY_LOAD_CONFIG},
+};
+
+/// The instruction set architecture a process runs with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProcessArchitecture {
+    /// A 32-bit `x86` process, either on 32-bit windows or under WOW64 on 64-bit `x64` windows.
+    X86,
+    /// A 64-bit `x64` process, either native or emulated on ARM64 windows.
+    X64,
+    /// A native 64-bit ARM64 process.
+    Arm64,
+    /// An `x64` compatible process on ARM64 windows whose executable is compiled to ARM64EC.
+    Arm64EC,
+    /// A 32-bit `x86` process that is emulated under WOW64 on ARM64 windows.
+    X86OnArm64,
+}
+
+impl ProcessArchitecture {
+    /// Returns the architecture of the current process.
+    #[must_use]
+    pub const fn current() -> Self {
+        if cfg!(target_arch = "x86") {
+            Self::X86
+        } else if cfg!(target_arch = "aarch64") {
+            Self::Arm64
+        } else {
+            Self::X64
+        }
+    }
+
+    #[cfg(windows)]
+    /// Returns the native architecture of the host machine, which is either [`X86`](Self::X86), [`X64`](Self::X64) or [`Arm64`](Self::Arm64).
+    pub fn host() -> Result<Self, io::Error> {
+        let native_machine = match is_wow64_process_2(unsafe { GetCurrentProcess() })? {
+            Some((_, native_machine)) => native_machine,
+            None => native_machine_from_system_info(),
+        };
+        match native_machine {
+            IMAGE_FILE_MACHINE_I386 => Ok(Self::X86),
+            IMAGE_FILE_MACHINE_AMD64 => Ok(Self::X64),
+            IMAGE_FILE_MACHINE_ARM64 => Ok(Self::Arm64),
+            machine => Err(unsupported_machine(machine)),
+        }
+    }
+
+    #[cfg(windows)]
+    /// Determines the architecture of the given process.
+    pub(crate) fn of_process(process: &impl Process) -> Result<Self, io::Error> {
+        let (process_machine, native_machine) = match is_wow64_process_2(process.as_raw_handle())? {
+            Some(machines) => machines,
+            None => {
+                // IsWow64Process2 is missing before Windows 10, which also means that there is no ARM64 support.
+                let mut is_wow64 = MaybeUninit::uninit();
+                let result =
+                    unsafe { IsWow64Process(process.as_raw_handle(), is_wow64.as_mut_ptr()) };
+                if result == 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                let process_machine = if unsafe { is_wow64.assume_init() } != FALSE {
+                    IMAGE_FILE_MACHINE_I386
+                } else {
+                    IMAGE_FILE_MACHINE_UNKNOWN
+                };
+                (process_machine, native_machine_from_system_info())
+            }
+        };
+
+        match (process_machine, native_machine) {
+            (IMAGE_FILE_MACHINE_I386, IMAGE_FILE_MACHINE_ARM64) => Ok(Self::X86OnArm64),
+            (IMAGE_FILE_MACHINE_I386, _) => Ok(Self::X86),
+            (IMAGE_FILE_MACHINE_UNKNOWN, IMAGE_FILE_MACHINE_I386) => Ok(Self::X86),
+            (IMAGE_FILE_MACHINE_UNKNOWN, IMAGE_FILE_MACHINE_AMD64) => Ok(Self::X64),
+            (IMAGE_FILE_MACHINE_UNKNOWN, IMAGE_FILE_MACHINE_ARM64) => {
+                // x64 and ARM64EC processes are not run under WOW64 on ARM64, so the executable has to be inspected.
+                match ExecutableInfo::read(&process.path()?)? {
+                    ExecutableInfo {
+                        machine: IMAGE_FILE_MACHINE_ARM64,
+                        ..
+                    } => Ok(Self::Arm64),
+                    ExecutableInfo {
+                        machine: IMAGE_FILE_MACHINE_AMD64,
+                        has_hybrid_metadata: true,
+                    } => Ok(Self::Arm64EC),
+                    ExecutableInfo {
+                        machine: IMAGE_FILE_MACHINE_AMD64,
+                        has_hybrid_metadata: false,
+                    } => Ok(Self::X64),
+                    ExecutableInfo { machine, .. } => Err(unsupported_machine(machine)),
+                }
+            }
+            (IMAGE_FILE_MACHINE_UNKNOWN, machine) | (machine, _) => {
+                Err(unsupported_machine(machine))
+            }
+        }
+    }
+
+    /// Returns whether processes of this architecture use 64-bit pointers.
+    #[must_use]
+    pub const fn is_64_bit(self) -> bool {
+        matches!(self, Self::X64 | Self::Arm64 | Self::Arm64EC)
+    }
+
+    /// Returns whether processes of this architecture run `x86` code (natively or emulated).
+    #[must_use]
+    pub const fn is_x86(self) -> bool {
+        matches!(self, Self::X86 | Self::X86OnArm64)
+    }
+
+    /// Returns whether processes of this architecture use the `x64` abi (natively or emulated).
+    #[must_use]
+    pub const fn is_x64(self) -> bool {
+        matches!(self, Self::X64 | Self::Arm64EC)
+    }
+}
+
+impl Display for ProcessArchitecture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::X86 => "x86",
+            Self::X64 => "x64",
+            Self::Arm64 => "ARM64",
+            Self::Arm64EC => "ARM64EC",
+            Self::X86OnArm64 => "x86 on ARM64",
+        };
+        f.write_str(name)
+    }
+}
+
+#[cfg(windows)]
+fn unsupported_machine(machine: WORD) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("unsupported machine type {:#06x}", machine),
+    )
+}
+
+#[cfg(windows)]
+/// Calls `IsWow64Process2` for the given process and returns the process and native machine or `None`
+/// if the function is not available (before Windows 10).
+fn is_wow64_process_2(process: HANDLE) -> Result<Option<(WORD, WORD)>, io::Error> {
+    type IsWow64Process2Fn = unsafe extern "system" fn(HANDLE, *mut USHORT, *mut USHORT) -> BOOL;
+
+    let kernel32 =
+        BorrowedProcessModule::find_local_by_name_or_abs_path_wstr(u16cstr!("kernel32.dll"))?
+            .unwrap();
+    let is_wow64_process_2 =
+        match kernel32.get_local_procedure_address_cstr(cstr!("IsWow64Process2")) {
+            Ok(f) => unsafe { mem::transmute::<_, IsWow64Process2Fn>(f) },
+            Err(_) => return Ok(None),
+        };
+
+    let mut process_machine = 0;
+    let mut native_machine = 0;
+    let result = unsafe { is_wow64_process_2(process, &mut process_machine, &mut native_machine) };
+    if result == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(Some((process_machine, native_machine)))
+}
+
+#[cfg(windows)]
+fn native_machine_from_system_info() -> WORD {
+    let mut system_info = MaybeUninit::uninit();
+    unsafe { GetNativeSystemInfo(system_info.as_mut_ptr()) };
+    let system_info = unsafe { system_info.assume_init() };
+    match unsafe { system_info.u.s() }.wProcessorArchitecture {
+        PROCESSOR_ARCHITECTURE_INTEL => IMAGE_FILE_MACHINE_I386,
+        PROCESSOR_ARCHITECTURE_AMD64 => IMAGE_FILE_MACHINE_AMD64,
+        PROCESSOR_ARCHITECTURE_ARM64 => IMAGE_FILE_MACHINE_ARM64,
+        _ => IMAGE_FILE_MACHINE_UNKNOWN,
+    }
+}
+
+/// The parts of the headers of an executable needed to tell apart the architectures of processes on ARM64.
+#[cfg_attr(not(windows), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ExecutableInfo {
+    machine: u16,
+    /// Whether the load config contains a CHPE metadata pointer, which marks ARM64EC and ARM64X images.
+    has_hybrid_metadata: bool,
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+impl ExecutableInfo {
+    /// The number of bytes read from the start of the executable, which covers the headers of any sane image.
+    const HEADERS_READ_SIZE: u64 = 0x1000;
+    /// Offset of `CHPEMetadataPointer` in `IMAGE_LOAD_CONFIG_DIRECTORY64`.
+    const CHPE_METADATA_POINTER_OFFSET: usize = 0xC8;
+
+    fn read(path: &Path) -> Result<Self, io::Error> {
+        Self::read_from(File::open(path)?)
+    }
+
+    /// Reads the headers and the start of the load config of the given executable, the rest of it is never read.
+    fn read_from(mut file: impl Read + Seek) -> Result<Self, io::Error> {
+        let mut headers = Vec::new();
+        (&mut file)
+            .take(Self::HEADERS_READ_SIZE)
+            .read_to_end(&mut headers)?;
+        // only the headers are available, which is enough to map rvas to file offsets.
+        let view = PeView::parse(&headers, PeLayout::File).map_err(invalid_data)?;
+        let headers = view.headers();
+
+        let has_hybrid_metadata = match headers.data_directory(IMAGE_DIRECTORY_ENTRY_LOAD_CONFIG) {
+            Some(load_config) if headers.is_64 => {
+                let invalid_rva = || invalid_data(PeError::InvalidRva(load_config.virtual_address));
+                let offset = view
+                    .rva_to_offset(load_config.virtual_address)
+                    .ok_or_else(invalid_rva)?;
+                file.seek(SeekFrom::Start(offset as u64))?;
+
+                let mut load_config = Vec::new();
+                (&mut file)
+                    .take(Self::CHPE_METADATA_POINTER_OFFSET as u64 + 8)
+                    .read_to_end(&mut load_config)?;
+                let size = read_u32(&load_config, 0).ok_or_else(invalid_rva)? as usize;
+                size >= Self::CHPE_METADATA_POINTER_OFFSET + 8
+                    && read_u64(&load_config, Self::CHPE_METADATA_POINTER_OFFSET)
+                        .ok_or_else(invalid_rva)?
+                        != 0
+            }
+            _ => false,
+        };
+
+        Ok(Self {
+            machine: headers.machine,
+            has_hybrid_metadata,
+        })
+    }
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+fn invalid_data(err: PeError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::pe::{
+        test_utils::{TestPe, FIXTURE_ARM64, FIXTURE_X64, FIXTURE_X86},
+        IMAGE_FILE_MACHINE_AMD64, IMAGE_FILE_MACHINE_ARM64, IMAGE_FILE_MACHINE_I386,
+    };
+
+    fn executable_info(file: &[u8]) -> ExecutableInfo {
+        ExecutableInfo::read_from(Cursor::new(file)).unwrap()
+    }
+
+    #[test]
+    fn read_executable_info_of_fixture_dlls() {
+        for (file, machine) in [
+            (FIXTURE_X86, IMAGE_FILE_MACHINE_I386),
+            (FIXTURE_X64, IMAGE_FILE_MACHINE_AMD64),
+            (FIXTURE_ARM64, IMAGE_FILE_MACHINE_ARM64),
+        ] {
+            assert_eq!(
+                executable_info(file),
+                ExecutableInfo {
+                    machine,
+                    has_hybrid_metadata: false
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn read_executable_info_detects_hybrid_metadata() {
+        for (chpe_metadata_pointer, has_hybrid_metadata) in [(0x1_8000_3000, true), (0, false)] {
+            let mut pe = TestPe::new(true);
+            let rdata = pe.add_section(".rdata", 0x140, 0x4000_0040);
+            pe.write_u32(rdata, 0x140);
+            pe.write_u64(
+                rdata + ExecutableInfo::CHPE_METADATA_POINTER_OFFSET as u32,
+                chpe_metadata_pointer,
+            );
+            pe.set_directory(IMAGE_DIRECTORY_ENTRY_LOAD_CONFIG, rdata, 0x140);
+
+            assert_eq!(
+                executable_info(&pe.to_file()),
+                ExecutableInfo {
+                    machine: IMAGE_FILE_MACHINE_AMD64,
+                    has_hybrid_metadata
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn read_executable_info_ignores_load_config_without_chpe_metadata_pointer() {
+        let mut pe = TestPe::new(true);
+        let rdata = pe.add_section(".rdata", 0x100, 0x4000_0040);
+        // the size of the load config ends before the chpe metadata pointer, whose bytes belong to something else.
+        pe.write_u32(rdata, 0x40);
+        pe.write_u64(
+            rdata + ExecutableInfo::CHPE_METADATA_POINTER_OFFSET as u32,
+            u64::MAX,
+        );
+        pe.set_directory(IMAGE_DIRECTORY_ENTRY_LOAD_CONFIG, rdata, 0x40);
+
+        assert!(!executable_info(&pe.to_file()).has_hybrid_metadata);
+    }
+
+    #[test]
+    fn read_executable_info_fails_on_invalid_load_config() {
+        let mut pe = TestPe::new(true);
+        pe.add_section(".rdata", 0x100, 0x4000_0040);
+        pe.set_directory(IMAGE_DIRECTORY_ENTRY_LOAD_CONFIG, 0x10_0000, 0x140);
+
+        let err = ExecutableInfo::read_from(Cursor::new(pe.to_file())).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -5,7 +5,7 @@ use std::{
     io,
     mem::{self, MaybeUninit},
     os::windows::{
-        prelude::{AsHandle, AsRawHandle, BorrowedHandle, FromRawHandle},
+        prelude::{AsHandle, AsRawHandle, BorrowedHandle, FromRawHandle, OwnedHandle},
         raw::HANDLE,
     },
     path::Path,
@@ -18,15 +18,15 @@ use winapi::{
         winerror::ERROR_PARTIAL_COPY,
     },
     um::{
-        handleapi::DuplicateHandle,
-        processthreadsapi::GetCurrentProcess,
-        psapi::{EnumProcessModulesEx, LIST_MODULES_ALL},
-        winnt::DUPLICATE_SAME_ACCESS,
+        handleapi::DuplicateHandle, processthreadsapi::GetCurrentProcess,
+        psapi::EnumProcessModulesEx, winnt::DUPLICATE_SAME_ACCESS,
     },
 };
 
 use crate::{
-    process::{ModuleHandle, OwnedProcess, Process, ProcessModule},
+    process::{
+        ModuleHandle, ModuleListFilter, OwnedProcess, Process, ProcessAccess, ProcessModule,
+    },
     utils::{retry_faillable_until_some_with_timeout, ArrayOrVecBuf},
 };
 
@@ -34,28 +34,25 @@ use crate::{
 /// This struct does **NOT** own the underlying process handle (see also [`OwnedProcess`] for an owned version).
 ///
 /// # Note
-/// The underlying handle has the following [privileges](https://docs.microsoft.com/en-us/windows/win32/procthread/process-security-and-access-rights):
-///  - `PROCESS_CREATE_THREAD`
-///  - `PROCESS_QUERY_INFORMATION`
-///  - `PROCESS_VM_OPERATION`
-///  - `PROCESS_VM_WRITE`
-///  - `PROCESS_VM_READ`
-#[repr(transparent)]
+/// The [access rights](ProcessAccess) of the underlying handle are carried over from the process it was borrowed from (see [`Process::access`]).
 #[derive(Debug, Clone, Copy)]
-pub struct BorrowedProcess<'a>(BorrowedHandle<'a>);
+pub struct BorrowedProcess<'a> {
+    handle: BorrowedHandle<'a>,
+    access: ProcessAccess,
+}
 
 unsafe impl Send for BorrowedProcess<'_> {}
 unsafe impl Sync for BorrowedProcess<'_> {}
 
 impl AsRawHandle for BorrowedProcess<'_> {
     fn as_raw_handle(&self) -> HANDLE {
-        self.0.as_raw_handle()
+        self.handle.as_raw_handle()
     }
 }
 
 impl AsHandle for BorrowedProcess<'_> {
     fn as_handle(&self) -> BorrowedHandle<'_> {
-        self.0.as_handle()
+        self.handle.as_handle()
     }
 }
 
@@ -64,7 +61,7 @@ impl<'a, 'b> PartialEq<BorrowedProcess<'a>> for BorrowedProcess<'b> {
         // TODO: (unsafe { CompareObjectHandles(self.handle(), other.handle()) }) != FALSE
 
         self.as_raw_handle() == other.as_raw_handle()
-            || self.pid().map_or(0, |v| v.get()) == other.pid().map_or(0, |v| v.get())
+            || matches!((self.pid(), other.pid()), (Ok(a), Ok(b)) if a == b)
     }
 }
 
@@ -102,7 +99,7 @@ impl<'a> Process for BorrowedProcess<'a> {
     }
 
     fn into_handle(self) -> Self::Handle {
-        self.0
+        self.handle
     }
 
     fn try_clone(&self) -> Result<Self, io::Error> {
@@ -110,7 +107,11 @@ impl<'a> Process for BorrowedProcess<'a> {
     }
 
     unsafe fn from_handle_unchecked(handle: Self::Handle) -> Self {
-        Self(handle)
+        unsafe { Self::from_handle_with_access(handle, ProcessAccess::all()) }
+    }
+
+    fn access(&self) -> ProcessAccess {
+        self.access
     }
 
     fn current_handle() -> Self::Handle {
@@ -121,27 +122,7 @@ impl<'a> Process for BorrowedProcess<'a> {
         &self,
         module_name: impl AsRef<Path>,
     ) -> Result<Option<ProcessModule<BorrowedProcess<'a>>>, io::Error> {
-        let target_module_name = module_name.as_ref();
-
-        // add default file extension if missing
-        let target_module_name = if target_module_name.extension().is_none() {
-            Cow::Owned(target_module_name.with_extension("dll").into_os_string())
-        } else {
-            Cow::Borrowed(target_module_name.as_os_str())
-        };
-
-        let modules = self.module_handles()?;
-
-        for module_handle in modules {
-            let module = unsafe { ProcessModule::new_unchecked(module_handle, *self) };
-            let module_name = module.base_name()?;
-
-            if module_name.eq_ignore_ascii_case(&target_module_name) {
-                return Ok(Some(module));
-            }
-        }
-
-        Ok(None)
+        self.find_module_by_name_with_filter(module_name, ModuleListFilter::All)
     }
 
     fn find_module_by_path(
@@ -206,6 +187,17 @@ impl<'a> Process for BorrowedProcess<'a> {
 }
 
 impl<'a> BorrowedProcess<'a> {
+    /// Creates a new instance from the given handle that was opened with the given access rights.
+    ///
+    /// # Safety
+    /// The caller must ensure that the handle is a valid process handle and has the given access rights.
+    pub(crate) unsafe fn from_handle_with_access(
+        handle: BorrowedHandle<'a>,
+        access: ProcessAccess,
+    ) -> Self {
+        Self { handle, access }
+    }
+
     /// Tries to create a new [`OwnedProcess`] instance for this process.
     pub fn try_to_owned(&self) -> Result<OwnedProcess, io::Error> {
         let raw_handle = self.as_raw_handle();
@@ -225,7 +217,43 @@ impl<'a> BorrowedProcess<'a> {
         if result == 0 {
             return Err(io::Error::last_os_error());
         }
-        Ok(unsafe { OwnedProcess::from_raw_handle(new_handle.assume_init()) })
+        Ok(unsafe {
+            OwnedProcess::from_handle_with_access(
+                OwnedHandle::from_raw_handle(new_handle.assume_init()),
+                self.access,
+            )
+        })
+    }
+
+    /// Searches the modules in this process that match the given filter for one with the given name.
+    /// The comparison of names is case-insensitive.
+    /// If the extension is omitted, the default library extension `.dll` is appended.
+    pub(crate) fn find_module_by_name_with_filter(
+        &self,
+        module_name: impl AsRef<Path>,
+        filter: ModuleListFilter,
+    ) -> Result<Option<ProcessModule<BorrowedProcess<'a>>>, io::Error> {
+        let target_module_name = module_name.as_ref();
+
+        // add default file extension if missing
+        let target_module_name = if target_module_name.extension().is_none() {
+            Cow::Owned(target_module_name.with_extension("dll").into_os_string())
+        } else {
+            Cow::Borrowed(target_module_name.as_os_str())
+        };
+
+        let modules = self.module_handles_with_filter(filter)?;
+
+        for module_handle in modules {
+            let module = unsafe { ProcessModule::new_unchecked(module_handle, *self) };
+            let module_name = module.base_name()?;
+
+            if module_name.eq_ignore_ascii_case(&target_module_name) {
+                return Ok(Some(module));
+            }
+        }
+
+        Ok(None)
     }
 
     /// Returns a snapshot of the handles of the modules currently loaded in this process.
@@ -234,6 +262,20 @@ impl<'a> BorrowedProcess<'a> {
     /// If the process is currently starting up and has not yet loaded all its modules, the returned list may be incomplete.
     /// This can be worked around by repeatedly calling this method.
     pub fn module_handles(&self) -> Result<impl ExactSizeIterator<Item = ModuleHandle>, io::Error> {
+        self.module_handles_with_filter(ModuleListFilter::All)
+    }
+
+    /// Returns a snapshot of the handles of the modules currently loaded in this process that match the given filter.
+    ///
+    /// # Note
+    /// If the process is currently starting up and has not yet loaded all its modules, the returned list may be incomplete.
+    /// This can be worked around by repeatedly calling this method.
+    pub fn module_handles_with_filter(
+        &self,
+        filter: ModuleListFilter,
+    ) -> Result<impl ExactSizeIterator<Item = ModuleHandle>, io::Error> {
+        self.ensure_access(ProcessAccess::QUERY_INFORMATION | ProcessAccess::VM_READ)?;
+
         let mut module_buf = ArrayOrVecBuf::<ModuleHandle, 1024>::new_uninit_array();
         const HANDLE_SIZE: u32 = mem::size_of::<HMODULE>() as _;
         let mut module_buf_byte_size = HANDLE_SIZE * module_buf.capacity() as u32;
@@ -245,7 +287,7 @@ impl<'a> BorrowedProcess<'a> {
                     module_buf.as_mut_ptr(),
                     module_buf_byte_size,
                     bytes_needed_new.as_mut_ptr(),
-                    LIST_MODULES_ALL,
+                    filter.list_flag(),
                 )
             };
             if result == 0 {
@@ -289,7 +331,7 @@ impl<'a> BorrowedProcess<'a> {
                         module_buf_vec.as_mut_ptr(),
                         module_buf_byte_size,
                         bytes_needed_new.as_mut_ptr(),
-                        LIST_MODULES_ALL,
+                        filter.list_flag(),
                     )
                 };
                 if result == 0 {
@@ -0,0 +1,102 @@
+use std::{
+    borrow::Cow,
+    fmt::{self, Display},
+};
+
+/// A reference to a symbol exported by a module, either by its name or by its ordinal.
+///
+/// # Example
+/// ```
+/// use dll_syringe::process::ExportRef;
+///
+/// assert_eq!(ExportRef::from("GetProcAddress"), ExportRef::Name("GetProcAddress".into()));
+/// assert_eq!(ExportRef::from(String::from("GetProcAddress")).name(), Some("GetProcAddress"));
+/// assert_eq!(ExportRef::from(42), ExportRef::Ordinal(42));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ExportRef<'a> {
+    /// An export referenced by its name.
+    Name(Cow<'a, str>),
+    /// An export referenced by its ordinal.
+    Ordinal(u16),
+}
+
+impl<'a> ExportRef<'a> {
+    /// Returns the name of the export, if it is referenced by name.
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Self::Name(name) => Some(name),
+            Self::Ordinal(_) => None,
+        }
+    }
+
+    /// Returns the ordinal of the export, if it is referenced by ordinal.
+    #[must_use]
+    pub fn ordinal(&self) -> Option<u16> {
+        match self {
+            Self::Name(_) => None,
+            Self::Ordinal(ordinal) => Some(*ordinal),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for ExportRef<'a> {
+    fn from(name: &'a str) -> Self {
+        Self::Name(Cow::Borrowed(name))
+    }
+}
+
+impl<'a> From<&'a String> for ExportRef<'a> {
+    fn from(name: &'a String) -> Self {
+        Self::Name(Cow::Borrowed(name))
+    }
+}
+
+impl From<String> for ExportRef<'_> {
+    fn from(name: String) -> Self {
+        Self::Name(Cow::Owned(name))
+    }
+}
+
+impl From<Box<str>> for ExportRef<'_> {
+    fn from(name: Box<str>) -> Self {
+        Self::Name(Cow::Owned(name.into()))
+    }
+}
+
+impl<'a> From<Cow<'a, str>> for ExportRef<'a> {
+    fn from(name: Cow<'a, str>) -> Self {
+        Self::Name(name)
+    }
+}
+
+impl<'a> From<&'a Cow<'_, str>> for ExportRef<'a> {
+    fn from(name: &'a Cow<'_, str>) -> Self {
+        Self::Name(Cow::Borrowed(name))
+    }
+}
+
+impl<'a> From<&'a ExportRef<'_>> for ExportRef<'a> {
+    fn from(export: &'a ExportRef<'_>) -> Self {
+        match export {
+            ExportRef::Name(name) => Self::Name(Cow::Borrowed(name)),
+            ExportRef::Ordinal(ordinal) => Self::Ordinal(*ordinal),
+        }
+    }
+}
+
+impl From<u16> for ExportRef<'_> {
+    fn from(ordinal: u16) -> Self {
+        Self::Ordinal(ordinal)
+    }
+}
+
+impl Display for ExportRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "{}", name),
+            Self::Ordinal(ordinal) => write!(f, "#{}", ordinal),
+        }
+    }
+}
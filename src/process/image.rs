@@ -0,0 +1,526 @@
+use std::{
+    fmt::{self, Display},
+    fs,
+    path::Path,
+};
+
+use crate::{
+    error::{PeError, ReadImageError},
+    pe::{
+        self, ExportLookup, ExportTarget, ImportName, PeLayout, PeView, IMAGE_FILE_DLL,
+        IMAGE_SCN_MEM_EXECUTE, IMAGE_SCN_MEM_READ, IMAGE_SCN_MEM_WRITE,
+    },
+    process::ExportRef,
+};
+#[cfg(windows)]
+use crate::{
+    pe::PeHeaders,
+    process::{
+        memory::{ProcessMemoryBuffer, ProcessMemorySlice},
+        Process, ProcessModule,
+    },
+};
+
+/// The parsed headers, sections, imports and exports of a pe image.
+///
+/// The image can either be read from a module loaded in a process using [`ProcessModule::image`]
+/// or from a file on disk using [`ModuleImage::from_file`].
+///
+/// # Example
+/// ```no_run
+/// use dll_syringe::process::{ModuleImage, OwnedProcess, Process};
+///
+/// let process = OwnedProcess::find_first_by_name("target_process").unwrap().unwrap();
+/// let module = process.find_module_by_name("kernel32.dll").unwrap().unwrap();
+/// let image = module.image().unwrap();
+/// for export in image.exports() {
+///     println!("{} @ {:?}", export.symbol(), export.target());
+/// }
+///
+/// let payload = ModuleImage::from_file("payload.dll").unwrap();
+/// println!("payload exports {} symbols", payload.exports().len());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleImage {
+    machine: u16,
+    is_64: bool,
+    is_dll: bool,
+    image_base: u64,
+    size_of_image: u32,
+    entry_point: Option<u32>,
+    time_date_stamp: u32,
+    sections: Vec<ImageSection>,
+    imports: Vec<ImageImport>,
+    exports: Vec<ImageExport>,
+}
+
+impl ModuleImage {
+    /// Reads and parses the pe file at the given path.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ReadImageError> {
+        let file = fs::read(path)?;
+        Ok(Self::parse_file(&file)?)
+    }
+
+    /// Parses the given bytes of a pe file.
+    pub fn parse_file(file: &[u8]) -> Result<Self, PeError> {
+        Self::from_view(&PeView::parse(file, PeLayout::File)?)
+    }
+
+    pub(crate) fn from_view(view: &PeView<'_>) -> Result<Self, PeError> {
+        let headers = view.headers();
+
+        let sections = headers
+            .sections
+            .iter()
+            .map(|section| ImageSection {
+                name: section.name().into_owned(),
+                virtual_address: section.virtual_address,
+                virtual_size: section.mapped_size(),
+                characteristics: section.characteristics,
+            })
+            .collect();
+
+        let imports = pe::read_imports(view)?
+            .into_iter()
+            .map(|import| ImageImport {
+                module: import.module,
+                symbols: import
+                    .symbols
+                    .into_iter()
+                    .map(|symbol| match symbol.name {
+                        ImportName::Name { hint, name } => ImportedSymbol::Name { hint, name },
+                        ImportName::Ordinal(ordinal) => ImportedSymbol::Ordinal(ordinal),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let exports = match pe::read_export_table(view)? {
+            Some(table) => table
+                .exports()?
+                .into_iter()
+                .map(|export| ImageExport {
+                    name: export
+                        .name
+                        .map(|name| String::from_utf8_lossy(name).into_owned()),
+                    ordinal: export.ordinal,
+                    target: match export.target {
+                        ExportTarget::Rva(rva) => ImageExportTarget::Rva(rva),
+                        ExportTarget::Forwarder { module, symbol } => {
+                            ImageExportTarget::Forwarder(match symbol {
+                                ExportLookup::Name(name) => {
+                                    format!("{}.{}", module, String::from_utf8_lossy(name))
+                                }
+                                ExportLookup::Ordinal(ordinal) => {
+                                    format!("{}.#{}", module, ordinal)
+                                }
+                            })
+                        }
+                    },
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            machine: headers.machine,
+            is_64: headers.is_64,
+            is_dll: headers.characteristics & IMAGE_FILE_DLL != 0,
+            image_base: headers.image_base,
+            size_of_image: headers.size_of_image,
+            entry_point: Some(headers.address_of_entry_point).filter(|&rva| rva != 0),
+            time_date_stamp: headers.time_date_stamp,
+            sections,
+            imports,
+            exports,
+        })
+    }
+
+    /// Returns the machine type the image was built for (e.g. `IMAGE_FILE_MACHINE_AMD64`).
+    #[must_use]
+    pub fn machine(&self) -> u16 {
+        self.machine
+    }
+
+    /// Returns whether this is a 64-bit (PE32+) image.
+    #[must_use]
+    pub fn is_64(&self) -> bool {
+        self.is_64
+    }
+
+    /// Returns whether the image is a dynamic-link library.
+    #[must_use]
+    pub fn is_dll(&self) -> bool {
+        self.is_dll
+    }
+
+    /// Returns the image base stored in the headers.
+    /// For a file this is the preferred base address, for a loaded module the loader updates it to the actual one.
+    #[must_use]
+    pub fn image_base(&self) -> u64 {
+        self.image_base
+    }
+
+    /// Returns the size of the image once mapped into memory.
+    #[must_use]
+    pub fn size_of_image(&self) -> u32 {
+        self.size_of_image
+    }
+
+    /// Returns the rva of the entry point, if the image has one.
+    #[must_use]
+    pub fn entry_point(&self) -> Option<u32> {
+        self.entry_point
+    }
+
+    /// Returns the timestamp the linker stored in the image, which is not necessarily the actual build time
+    /// (e.g. for reproducible builds).
+    #[must_use]
+    pub fn time_date_stamp(&self) -> u32 {
+        self.time_date_stamp
+    }
+
+    /// Returns the sections of the image.
+    #[must_use]
+    pub fn sections(&self) -> &[ImageSection] {
+        &self.sections
+    }
+
+    /// Returns the section containing the given rva.
+    #[must_use]
+    pub fn section_containing(&self, rva: u32) -> Option<&ImageSection> {
+        self.sections.iter().find(|section| section.contains(rva))
+    }
+
+    /// Returns the modules imported by the image.
+    #[must_use]
+    pub fn imports(&self) -> &[ImageImport] {
+        &self.imports
+    }
+
+    /// Returns the symbols exported by the image ordered by their ordinal.
+    #[must_use]
+    pub fn exports(&self) -> &[ImageExport] {
+        &self.exports
+    }
+
+    /// Returns the export referenced by the given name or ordinal.
+    #[must_use]
+    pub fn find_export<'a>(&self, export: impl Into<ExportRef<'a>>) -> Option<&ImageExport> {
+        let export = export.into();
+        self.exports.iter().find(|candidate| match &export {
+            ExportRef::Name(name) => candidate.name() == Some(&**name),
+            ExportRef::Ordinal(ordinal) => candidate.ordinal() == *ordinal,
+        })
+    }
+}
+
+#[cfg(windows)]
+impl<P: Process> ProcessModule<P> {
+    /// Reads the pe image of this module from the memory of its process.
+    ///
+    /// # Note
+    /// The image reflects the state of the module in memory, so the image base and the import address table
+    /// may differ from the file the module was loaded from.
+    pub fn image(&self) -> Result<ModuleImage, ReadImageError> {
+        let process = self.process().borrowed();
+        let base = self.handle().cast::<u8>();
+
+        // the headers of a loaded module always fit into its first page.
+        let mut header_page = vec![0; ProcessMemoryBuffer::os_page_size()];
+        unsafe { ProcessMemorySlice::from_raw_parts(base, header_page.len(), process) }
+            .read(0, &mut header_page)?;
+        let headers = PeHeaders::parse(&header_page)?;
+
+        // only read the headers and the sections as the gaps between them may not be accessible.
+        let mut image = vec![0; headers.size_of_image as usize];
+        let header_len = (headers.size_of_headers as usize)
+            .min(header_page.len())
+            .min(image.len());
+        image[..header_len].copy_from_slice(&header_page[..header_len]);
+        for section in &headers.sections {
+            let start = section.virtual_address as usize;
+            let end = start
+                .saturating_add(section.mapped_size() as usize)
+                .min(image.len());
+            if start >= end {
+                continue;
+            }
+
+            unsafe {
+                ProcessMemorySlice::from_raw_parts(base.wrapping_add(start), end - start, process)
+            }
+            .read(0, &mut image[start..end])?;
+        }
+
+        Ok(ModuleImage::from_view(&PeView::parse(
+            &image,
+            PeLayout::Image,
+        )?)?)
+    }
+}
+
+/// A section of a pe image.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImageSection {
+    name: String,
+    virtual_address: u32,
+    virtual_size: u32,
+    characteristics: u32,
+}
+
+impl ImageSection {
+    /// Returns the name of the section (e.g. `.text`).
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the rva of the section.
+    #[must_use]
+    pub fn virtual_address(&self) -> u32 {
+        self.virtual_address
+    }
+
+    /// Returns the size of the section once mapped into memory.
+    #[must_use]
+    pub fn virtual_size(&self) -> u32 {
+        self.virtual_size
+    }
+
+    /// Returns the raw characteristics of the section (`IMAGE_SCN_*` flags).
+    #[must_use]
+    pub fn characteristics(&self) -> u32 {
+        self.characteristics
+    }
+
+    /// Returns whether the section contains the given rva.
+    #[must_use]
+    pub fn contains(&self, rva: u32) -> bool {
+        rva >= self.virtual_address && rva - self.virtual_address < self.virtual_size
+    }
+
+    /// Returns whether the section is mapped as executable.
+    #[must_use]
+    pub fn is_executable(&self) -> bool {
+        self.characteristics & IMAGE_SCN_MEM_EXECUTE != 0
+    }
+
+    /// Returns whether the section is mapped as readable.
+    #[must_use]
+    pub fn is_readable(&self) -> bool {
+        self.characteristics & IMAGE_SCN_MEM_READ != 0
+    }
+
+    /// Returns whether the section is mapped as writable.
+    #[must_use]
+    pub fn is_writable(&self) -> bool {
+        self.characteristics & IMAGE_SCN_MEM_WRITE != 0
+    }
+}
+
+/// A module imported by a pe image.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImageImport {
+    module: String,
+    symbols: Vec<ImportedSymbol>,
+}
+
+impl ImageImport {
+    /// Returns the name of the imported module as stored in the image (e.g. `KERNEL32.dll`).
+    #[must_use]
+    pub fn module(&self) -> &str {
+        &self.module
+    }
+
+    /// Returns the symbols imported from the module.
+    #[must_use]
+    pub fn symbols(&self) -> &[ImportedSymbol] {
+        &self.symbols
+    }
+}
+
+/// A symbol imported by a pe image.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ImportedSymbol {
+    /// A symbol imported by its name.
+    Name {
+        /// The index into the export name table of the imported module where the name is expected.
+        hint: u16,
+        /// The name of the symbol.
+        name: String,
+    },
+    /// A symbol imported by its ordinal.
+    Ordinal(u16),
+}
+
+impl ImportedSymbol {
+    /// Returns a reference to the imported symbol that can be used to look it up in the imported module.
+    #[must_use]
+    pub fn export_ref(&self) -> ExportRef<'_> {
+        match self {
+            Self::Name { name, .. } => ExportRef::from(name),
+            Self::Ordinal(ordinal) => ExportRef::Ordinal(*ordinal),
+        }
+    }
+}
+
+impl Display for ImportedSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.export_ref())
+    }
+}
+
+/// A symbol exported by a pe image.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImageExport {
+    name: Option<String>,
+    ordinal: u16,
+    target: ImageExportTarget,
+}
+
+impl ImageExport {
+    /// Returns the name of the export, if it is exported by name.
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the ordinal of the export.
+    #[must_use]
+    pub fn ordinal(&self) -> u16 {
+        self.ordinal
+    }
+
+    /// Returns a reference to the export, which is its name if it has one and its ordinal otherwise.
+    #[must_use]
+    pub fn symbol(&self) -> ExportRef<'_> {
+        match &self.name {
+            Some(name) => ExportRef::from(name),
+            None => ExportRef::Ordinal(self.ordinal),
+        }
+    }
+
+    /// Returns what the export refers to.
+    #[must_use]
+    pub fn target(&self) -> &ImageExportTarget {
+        &self.target
+    }
+
+    /// Returns the rva of the export if it is defined in the image itself.
+    #[must_use]
+    pub fn rva(&self) -> Option<u32> {
+        match self.target {
+            ImageExportTarget::Rva(rva) => Some(rva),
+            ImageExportTarget::Forwarder(_) => None,
+        }
+    }
+}
+
+/// The location an exported symbol refers to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ImageExportTarget {
+    /// The symbol is defined in the image itself at the given rva.
+    Rva(u32),
+    /// The symbol is forwarded to another module (e.g. `NTDLL.RtlAllocateHeap` or `NTDLL.#12`).
+    Forwarder(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pe::{test_utils::TestPe, IMAGE_DIRECTORY_ENTRY_IMPORT};
+
+    #[test]
+    fn parse_file_reads_headers_sections_and_exports() {
+        let mut pe = TestPe::new(true);
+        let text = pe.add_section(".text", 0x10, 0x6000_0020);
+        pe.entry_point = text;
+        let edata = pe.add_exports(1, &[(Some("First"), text), (None, text + 8)]);
+
+        let image = ModuleImage::parse_file(&pe.to_file()).unwrap();
+        assert!(image.is_64());
+        assert!(image.is_dll());
+        assert_eq!(image.machine(), 0x8664);
+        assert_eq!(image.image_base(), pe.image_base);
+        assert_eq!(image.size_of_image(), pe.size_of_image());
+        assert_eq!(image.entry_point(), Some(text));
+        assert_eq!(image.time_date_stamp(), pe.time_date_stamp);
+
+        assert_eq!(image.sections().len(), 2);
+        let text_section = &image.sections()[0];
+        assert_eq!(text_section.name(), ".text");
+        assert_eq!(text_section.virtual_address(), text);
+        assert_eq!(text_section.virtual_size(), 0x10);
+        assert!(text_section.is_executable());
+        assert!(!text_section.is_writable());
+        assert_eq!(
+            image.section_containing(edata + 4).unwrap().name(),
+            ".edata"
+        );
+
+        assert_eq!(
+            image.exports(),
+            &[
+                ImageExport {
+                    name: Some("First".to_string()),
+                    ordinal: 1,
+                    target: ImageExportTarget::Rva(text),
+                },
+                ImageExport {
+                    name: None,
+                    ordinal: 2,
+                    target: ImageExportTarget::Rva(text + 8),
+                },
+            ]
+        );
+        assert_eq!(image.find_export("First").unwrap().rva(), Some(text));
+        assert_eq!(
+            image.find_export(2u16).unwrap().symbol(),
+            ExportRef::Ordinal(2)
+        );
+        assert!(image.find_export("Second").is_none());
+    }
+
+    #[test]
+    fn parse_file_reads_imports() {
+        let mut pe = TestPe::new(false);
+        let idata = pe.add_section(".idata", 0x200, 0xC000_0040);
+        let lookup_table = idata + 0x40;
+        let address_table = idata + 0x80;
+        let hint_name = idata + 0xC0;
+        let module_name = idata + 0x100;
+
+        pe.write_u32(idata, lookup_table);
+        pe.write_u32(idata + 12, module_name);
+        pe.write_u32(idata + 16, address_table);
+        for table in [lookup_table, address_table] {
+            pe.write_u32(table, hint_name);
+            pe.write_u32(table + 4, 0x8000_0000 | 42);
+        }
+        pe.write_u16(hint_name, 7);
+        pe.write_c_str(hint_name + 2, "LoadLibraryW");
+        pe.write_c_str(module_name, "KERNEL32.dll");
+        pe.set_directory(IMAGE_DIRECTORY_ENTRY_IMPORT, idata, 40);
+
+        let image = ModuleImage::parse_file(&pe.to_file()).unwrap();
+        assert!(!image.is_64());
+        assert_eq!(image.entry_point(), None);
+        assert!(image.exports().is_empty());
+
+        let imports = image.imports();
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].module(), "KERNEL32.dll");
+        assert_eq!(
+            imports[0].symbols(),
+            &[
+                ImportedSymbol::Name {
+                    hint: 7,
+                    name: "LoadLibraryW".to_string()
+                },
+                ImportedSymbol::Ordinal(42),
+            ]
+        );
+        assert_eq!(imports[0].symbols()[1].to_string(), "#42");
+    }
+}
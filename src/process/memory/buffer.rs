@@ -10,7 +10,9 @@ use std::{
 use winapi::{
     shared::minwindef::DWORD,
     um::{
-        memoryapi::{ReadProcessMemory, VirtualAllocEx, VirtualFreeEx, WriteProcessMemory},
+        memoryapi::{
+            ReadProcessMemory, VirtualAllocEx, VirtualFreeEx, VirtualProtectEx, WriteProcessMemory,
+        },
         processthreadsapi::FlushInstructionCache,
         sysinfoapi::GetSystemInfo,
         winnt::{MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_EXECUTE_READWRITE, PAGE_READWRITE},
@@ -18,10 +20,15 @@ use winapi::{
 };
 
 use crate::{
-    process::{BorrowedProcess, Process},
+    process::{
+        memory_region_of_process, BorrowedProcess, PageProtection, Pattern, Process, ProcessAccess,
+    },
     utils,
 };
 
+/// The number of bytes read at once when scanning memory for a pattern.
+const SCAN_CHUNK_SIZE: usize = 0x10000;
+
 /// A owned buffer in the memory space of a (remote) process.
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "process-memory")))]
 #[derive(Debug)]
@@ -86,6 +93,8 @@ impl<'a> ProcessMemoryBuffer<'a> {
         allocation_type: DWORD,
         protection: DWORD,
     ) -> Result<Self, io::Error> {
+        process.ensure_access(ProcessAccess::VM_OPERATION)?;
+
         let ptr = unsafe {
             VirtualAllocEx(
                 process.as_raw_handle(),
@@ -179,6 +188,8 @@ impl<'a> ProcessMemoryBuffer<'a> {
         unsafe { self._free() }.map_err(|e| (self, e))
     }
     unsafe fn _free(&mut self) -> Result<(), io::Error> {
+        self.process.ensure_access(ProcessAccess::VM_OPERATION)?;
+
         let result = unsafe {
             VirtualFreeEx(
                 self.process.as_raw_handle(),
@@ -291,6 +302,8 @@ impl<'a> ProcessMemorySlice<'a> {
             return Ok(());
         }
 
+        self.process.ensure_access(ProcessAccess::VM_READ)?;
+
         let mut bytes_read = 0;
         let result = unsafe {
             ReadProcessMemory(
@@ -338,6 +351,9 @@ impl<'a> ProcessMemorySlice<'a> {
             return Ok(());
         }
 
+        self.process
+            .ensure_access(ProcessAccess::VM_WRITE | ProcessAccess::VM_OPERATION)?;
+
         let mut bytes_written = 0;
         let result = unsafe {
             WriteProcessMemory(
@@ -419,4 +435,210 @@ impl<'a> ProcessMemorySlice<'a> {
             Ok(())
         }
     }
+
+    /// Changes the protection of the pages spanned by this slice and returns a guard that restores the previous protection when dropped.
+    ///
+    /// # Note
+    /// The protection is changed for whole pages, so memory adjacent to this slice that lies in the same pages is affected as well.
+    /// If the pages had different protections before, the previous protection of each page is restored.
+    pub fn protect(&self, protection: PageProtection) -> Result<ProtectionGuard<'a>, io::Error> {
+        let previous_protections = self.page_protections()?;
+        let previous_protection = self.change_protection(protection)?;
+        Ok(ProtectionGuard {
+            slice: *self,
+            protection,
+            previous_protection,
+            previous_protections,
+        })
+    }
+
+    /// Returns the parts of this slice whose pages share the same protection, together with that protection.
+    fn page_protections(&self) -> Result<Vec<(Self, PageProtection)>, io::Error> {
+        let end = self.ptr as usize + self.len;
+        let mut protections = Vec::new();
+        let mut address = self.ptr as usize;
+        loop {
+            let region = memory_region_of_process(self.process, address)?;
+            let protection = region.protection().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "slice contains pages that are not committed",
+                )
+            })?;
+            let part_end = region.end().min(end);
+            protections.push((
+                unsafe {
+                    Self::from_raw_parts(address as *mut u8, part_end - address, self.process)
+                },
+                protection,
+            ));
+
+            address = region.end();
+            if address >= end {
+                break;
+            }
+        }
+        Ok(protections)
+    }
+
+    /// Returns the addresses of all (possibly overlapping) matches of the given pattern in this slice in ascending order.
+    ///
+    /// # Note
+    /// The memory is read in chunks of 64KiB, so the whole slice has to be readable.
+    pub fn scan(&self, pattern: &Pattern) -> Result<Vec<usize>, io::Error> {
+        let mut matches = Vec::new();
+        self.scan_with(pattern, |address| {
+            matches.push(address);
+            true
+        })?;
+        Ok(matches)
+    }
+
+    /// Returns the address of the first match of the given pattern in this slice.
+    ///
+    /// # Note
+    /// The memory is read in chunks of 64KiB, so the whole slice has to be readable.
+    pub fn scan_first(&self, pattern: &Pattern) -> Result<Option<usize>, io::Error> {
+        let mut first_match = None;
+        self.scan_with(pattern, |address| {
+            first_match = Some(address);
+            false
+        })?;
+        Ok(first_match)
+    }
+
+    /// Calls the given callback with the address of each match of the given pattern until it returns `false`.
+    fn scan_with(
+        &self,
+        pattern: &Pattern,
+        mut on_match: impl FnMut(usize) -> bool,
+    ) -> Result<(), io::Error> {
+        // matches can cross chunk boundaries, so the last bytes of a chunk are kept in front of the next one.
+        let overlap = pattern.len() - 1;
+        let mut buf = vec![0; SCAN_CHUNK_SIZE.min(self.len) + overlap];
+        let mut kept = 0;
+        let mut offset = 0;
+        while offset < self.len {
+            let chunk_len = SCAN_CHUNK_SIZE.min(self.len - offset);
+            self.read(offset, &mut buf[kept..kept + chunk_len])?;
+
+            let window = &buf[..kept + chunk_len];
+            let window_address = self.ptr as usize + offset - kept;
+            for match_offset in pattern.find_all_in(window) {
+                if !on_match(window_address + match_offset) {
+                    return Ok(());
+                }
+            }
+
+            let window_len = window.len();
+            kept = overlap.min(window_len);
+            buf.copy_within(window_len - kept..window_len, 0);
+            offset += chunk_len;
+        }
+        Ok(())
+    }
+
+    fn change_protection(&self, protection: PageProtection) -> Result<PageProtection, io::Error> {
+        self.process.ensure_access(ProcessAccess::VM_OPERATION)?;
+
+        let mut previous_protection = 0;
+        let result = unsafe {
+            VirtualProtectEx(
+                self.process.as_raw_handle(),
+                self.as_ptr().cast(),
+                self.len,
+                protection.to_raw(),
+                &mut previous_protection,
+            )
+        };
+        if result == 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            PageProtection::from_raw(previous_protection).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown page protection {:#x}", previous_protection),
+                )
+            })
+        }
+    }
+}
+
+/// A guard for a [`ProcessMemorySlice`] whose protection was changed using [`ProcessMemorySlice::protect`].
+/// The previous protection is restored when the guard is dropped.
+///
+/// If the slice was or is made executable, the instruction cache is flushed after restoring the protection
+/// (see [`ProcessMemorySlice::flush_instruction_cache`]).
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "process-memory")))]
+#[derive(Debug)]
+#[must_use = "the previous protection is restored when the guard is dropped"]
+pub struct ProtectionGuard<'a> {
+    slice: ProcessMemorySlice<'a>,
+    protection: PageProtection,
+    previous_protection: PageProtection,
+    /// The parts of the slice with the same previous protection, which are restored one by one.
+    previous_protections: Vec<(ProcessMemorySlice<'a>, PageProtection)>,
+}
+
+impl<'a> Deref for ProtectionGuard<'a> {
+    type Target = ProcessMemorySlice<'a>;
+
+    fn deref(&self) -> &ProcessMemorySlice<'a> {
+        &self.slice
+    }
+}
+
+impl<'a> ProtectionGuard<'a> {
+    /// Returns the protection the slice was changed to.
+    #[must_use]
+    pub fn protection(&self) -> PageProtection {
+        self.protection
+    }
+
+    /// Returns the protection of the first page of the slice before it was changed.
+    #[must_use]
+    pub fn previous_protection(&self) -> PageProtection {
+        self.previous_protection
+    }
+
+    /// Restores the previous protection of the slice.
+    /// In contrast to dropping the guard this allows handling errors.
+    pub fn restore(self) -> Result<(), io::Error> {
+        let this = ManuallyDrop::new(self);
+        this._restore()
+    }
+
+    /// Consumes the guard without restoring the previous protection and returns the underlying slice.
+    #[allow(clippy::must_use_candidate)]
+    pub fn keep(self) -> ProcessMemorySlice<'a> {
+        let this = ManuallyDrop::new(self);
+        this.slice
+    }
+
+    fn _restore(&self) -> Result<(), io::Error> {
+        for (part, previous_protection) in &self.previous_protections {
+            part.change_protection(*previous_protection)?;
+        }
+
+        if self.protection.is_executable()
+            || self
+                .previous_protections
+                .iter()
+                .any(|(_, previous_protection)| previous_protection.is_executable())
+        {
+            self.slice.flush_instruction_cache()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ProtectionGuard<'_> {
+    fn drop(&mut self) {
+        let result = self._restore();
+        debug_assert!(
+            result.is_ok() || !self.slice.process().is_alive(),
+            "Failed to restore memory protection: {:?}",
+            result
+        );
+    }
 }
@@ -1,6 +1,20 @@
 mod buffer;
 pub use buffer::*;
 
+#[cfg(feature = "process-memory")]
+mod pointer_chain;
+#[cfg(feature = "process-memory")]
+pub use pointer_chain::*;
+
+#[cfg(feature = "process-memory")]
+mod remote_ptr;
+#[cfg(feature = "process-memory")]
+pub use remote_ptr::*;
+
+#[cfg(feature = "process-memory")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "process-memory")))]
+pub use dll_syringe_macros::RemoteStruct;
+
 #[cfg(feature = "syringe")]
 #[allow(dead_code)]
 mod raw_allocator;
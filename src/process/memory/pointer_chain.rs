@@ -0,0 +1,262 @@
+use std::{
+    fmt::{self, Display},
+    io, mem,
+};
+
+use crate::{
+    error::PointerChainError,
+    process::{
+        memory::{ProcessMemorySlice, RemotePtr},
+        BorrowedProcess, MemoryState, Process,
+    },
+};
+
+/// A multi-level pointer path that leads from a module or an absolute address to a value in a (remote) process.
+///
+/// Resolving a chain starts at its base address. For each offset the pointer stored at the current address is read
+/// and the offset is added to it. The pointers are read with the pointer width of the target process, so 32-bit
+/// targets can be resolved from 64-bit processes.
+///
+/// # Example
+/// ```no_run
+/// use dll_syringe::process::{memory::PointerChain, OwnedProcess, Process};
+///
+/// let process = OwnedProcess::find_first_by_name("game").unwrap().unwrap();
+///
+/// // "game.dll" + 0x1234 -> +0x10 -> +0x8
+/// let chain = PointerChain::from_module("game.dll", 0x1234)
+///     .with_offset(0x10)
+///     .with_offset(0x8);
+/// let health_address = chain.resolve(process.borrowed()).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "process-memory")))]
+pub struct PointerChain {
+    base: ChainBase,
+    offsets: Vec<isize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ChainBase {
+    Module { name: String, offset: isize },
+    Address(usize),
+}
+
+impl PointerChain {
+    /// Creates a new chain that starts at the given offset from the base of the module with the given name.
+    #[must_use]
+    pub fn from_module(module_name: impl Into<String>, offset: isize) -> Self {
+        Self {
+            base: ChainBase::Module {
+                name: module_name.into(),
+                offset,
+            },
+            offsets: Vec::new(),
+        }
+    }
+
+    /// Creates a new chain that starts at the given absolute address.
+    #[must_use]
+    pub fn from_address(address: usize) -> Self {
+        Self {
+            base: ChainBase::Address(address),
+            offsets: Vec::new(),
+        }
+    }
+
+    /// Appends a hop to the chain that dereferences the current address and adds the given offset to the read pointer.
+    #[must_use]
+    pub fn with_offset(mut self, offset: isize) -> Self {
+        self.offsets.push(offset);
+        self
+    }
+
+    /// Appends a hop for each of the given offsets (see [`PointerChain::with_offset`]).
+    #[must_use]
+    pub fn with_offsets(mut self, offsets: impl IntoIterator<Item = isize>) -> Self {
+        self.offsets.extend(offsets);
+        self
+    }
+
+    /// Returns the offsets of the hops of this chain.
+    #[must_use]
+    pub fn offsets(&self) -> &[isize] {
+        &self.offsets
+    }
+
+    /// Resolves this chain in the given process and returns the final address.
+    /// The final address itself is not dereferenced.
+    pub fn resolve(&self, process: BorrowedProcess<'_>) -> Result<usize, PointerChainError> {
+        let mut address = match &self.base {
+            ChainBase::Module { name, offset } => {
+                let module = process
+                    .find_module_by_name(name)?
+                    .ok_or_else(|| PointerChainError::ModuleNotFound(name.clone()))?;
+                add_offset(module.handle() as usize, *offset)?
+            }
+            ChainBase::Address(address) => *address,
+        };
+
+        if self.offsets.is_empty() {
+            return Ok(address);
+        }
+
+        let is_64_bit = process.architecture()?.is_64_bit();
+        for (hop, &offset) in self.offsets.iter().enumerate() {
+            let pointer = read_pointer(process, address, is_64_bit).map_err(|error| {
+                PointerChainError::Unreadable {
+                    hop,
+                    address,
+                    error,
+                }
+            })?;
+            let pointer = usize::try_from(pointer)
+                .map_err(|_| PointerChainError::UnsupportedPointer { hop, pointer })?;
+            if pointer == 0 {
+                return Err(PointerChainError::NullPointer { hop, address });
+            }
+            address = add_offset(pointer, offset)?;
+        }
+
+        Ok(address)
+    }
+
+    /// Resolves this chain in the given process and returns a typed pointer to the final address.
+    ///
+    /// # Safety
+    /// The caller must ensure that the final address satisfies the requirements of [`RemotePtr::new`].
+    pub unsafe fn resolve_ptr<'a, T>(
+        &self,
+        process: BorrowedProcess<'a>,
+    ) -> Result<RemotePtr<'a, T>, PointerChainError> {
+        let address = self.resolve(process)?;
+        Ok(unsafe { RemotePtr::new(process, address) })
+    }
+}
+
+fn add_offset(address: usize, offset: isize) -> Result<usize, PointerChainError> {
+    let result = if offset >= 0 {
+        address.checked_add(offset as usize)
+    } else {
+        address.checked_sub(offset.unsigned_abs())
+    };
+    result.ok_or(PointerChainError::Overflow { address, offset })
+}
+
+fn read_pointer(
+    process: BorrowedProcess<'_>,
+    address: usize,
+    is_64_bit: bool,
+) -> Result<u64, io::Error> {
+    let size = if is_64_bit {
+        mem::size_of::<u64>()
+    } else {
+        mem::size_of::<u32>()
+    };
+
+    // memory of the current process is accessed directly, so we have to make sure that it is readable first.
+    if process.is_current() {
+        let region = process.memory_region_at(address)?;
+        let is_readable = region.state() == MemoryState::Commit
+            && region.protection().map_or(false, |protection| {
+                protection.is_readable() && !protection.is_guard()
+            })
+            && address
+                .checked_add(size)
+                .map_or(false, |end| end <= region.end());
+        if !is_readable {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "pointer is not readable",
+            ));
+        }
+    }
+
+    let slice = unsafe { ProcessMemorySlice::from_raw_parts(address as *mut u8, size, process) };
+    if is_64_bit {
+        unsafe { slice.read_struct::<u64>(0) }
+    } else {
+        unsafe { slice.read_struct::<u32>(0) }.map(u64::from)
+    }
+}
+
+impl Display for PointerChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_offset(f: &mut fmt::Formatter<'_>, offset: isize) -> fmt::Result {
+            if offset < 0 {
+                write!(f, "-{:#x}", offset.unsigned_abs())
+            } else {
+                write!(f, "+{:#x}", offset)
+            }
+        }
+
+        match &self.base {
+            ChainBase::Module { name, offset } => {
+                write!(f, "{:?} ", name)?;
+                write_offset(f, *offset)?;
+            }
+            ChainBase::Address(address) => write!(f, "{:#x}", address)?,
+        }
+        for &offset in &self.offsets {
+            write!(f, " -> ")?;
+            write_offset(f, offset)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_chain() {
+        let chain = PointerChain::from_module("game.dll", 0x1234)
+            .with_offset(0x10)
+            .with_offset(-0x8);
+        assert_eq!(chain.to_string(), r#""game.dll" +0x1234 -> +0x10 -> -0x8"#);
+        assert_eq!(chain.offsets(), [0x10, -0x8]);
+
+        let chain = PointerChain::from_address(0x1000).with_offsets([0, 4]);
+        assert_eq!(chain.to_string(), "0x1000 -> +0x0 -> +0x4");
+    }
+
+    #[test]
+    fn add_offset_checks_overflow() {
+        assert_eq!(add_offset(0x1000, 0x10).unwrap(), 0x1010);
+        assert_eq!(add_offset(0x1000, -0x10).unwrap(), 0xff0);
+        assert!(matches!(
+            add_offset(0x10, -0x20),
+            Err(PointerChainError::Overflow { .. })
+        ));
+        assert!(matches!(
+            add_offset(usize::MAX, 1),
+            Err(PointerChainError::Overflow { .. })
+        ));
+    }
+
+    #[test]
+    fn resolve_local_chain() {
+        let value = 42u32;
+        let inner = Box::new(&value as *const u32 as usize);
+        let outer = Box::new(&*inner as *const usize as usize - 8);
+        let chain = PointerChain::from_address(&*outer as *const usize as usize)
+            .with_offset(8)
+            .with_offset(0);
+        let address = chain.resolve(BorrowedProcess::current()).unwrap();
+        assert_eq!(address, &value as *const u32 as usize);
+
+        let null = Box::new(0usize);
+        let chain = PointerChain::from_address(&*null as *const usize as usize).with_offset(0);
+        assert!(matches!(
+            chain.resolve(BorrowedProcess::current()),
+            Err(PointerChainError::NullPointer { hop: 0, .. })
+        ));
+
+        let chain = PointerChain::from_address(0x10).with_offset(0);
+        assert!(matches!(
+            chain.resolve(BorrowedProcess::current()),
+            Err(PointerChainError::Unreadable { hop: 0, .. })
+        ));
+    }
+}
@@ -1,8 +1,16 @@
-use std::{cell::RefCell, io, marker::PhantomData, mem, ptr::NonNull, rc::Rc, slice};
+use std::{
+    cell::{OnceCell, RefCell},
+    io,
+    marker::PhantomData,
+    mem,
+    ptr::NonNull,
+    rc::Rc,
+    slice,
+};
 
 use crate::process::{
     memory::{Allocation, DynamicMultiBufferAllocator, ProcessMemorySlice, RawAllocator},
-    BorrowedProcess, OwnedProcess, Process,
+    BorrowedProcess, OwnedProcess, Process, ProcessArchitecture,
 };
 
 #[derive(Debug, Clone)]
@@ -12,6 +20,8 @@ pub struct RemoteBoxAllocator(pub(crate) Rc<RemoteBoxAllocatorInner>);
 pub(crate) struct RemoteBoxAllocatorInner {
     pub(crate) process: OwnedProcess,
     pub(crate) allocator: RefCell<DynamicMultiBufferAllocator<'static>>,
+    /// The architecture of the process, which is cached as determining it may require reading its executable.
+    pub(crate) architecture: OnceCell<ProcessArchitecture>,
 }
 
 impl RemoteBoxAllocator {
@@ -21,6 +31,7 @@ impl RemoteBoxAllocator {
                 process.borrowed_static()
             })),
             process,
+            architecture: OnceCell::new(),
         }))
     }
 
@@ -28,6 +39,14 @@ impl RemoteBoxAllocator {
         self.0.process.borrowed()
     }
 
+    /// Returns the architecture of the target process, which is only determined on the first call.
+    pub fn process_architecture(&self) -> Result<ProcessArchitecture, io::Error> {
+        self.0
+            .architecture
+            .get_or_try_init(|| self.process().architecture())
+            .copied()
+    }
+
     pub fn alloc_raw(&self, size: usize) -> Result<RemoteAllocation, io::Error> {
         // TODO: optimize empty allocations
         let allocation = self.0.allocator.borrow_mut().alloc(size)?;
@@ -82,6 +101,10 @@ impl RemoteAllocation {
         self.allocator.process()
     }
 
+    pub fn process_architecture(&self) -> Result<ProcessArchitecture, io::Error> {
+        self.allocator.process_architecture()
+    }
+
     pub fn memory(&self) -> ProcessMemorySlice<'_> {
         unsafe {
             ProcessMemorySlice::from_raw_parts(
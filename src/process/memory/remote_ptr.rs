@@ -0,0 +1,421 @@
+use std::{
+    fmt::{self, Debug},
+    io,
+    marker::PhantomData,
+    mem,
+};
+
+use crate::process::{memory::ProcessMemorySlice, BorrowedProcess};
+
+/// A typed pointer to a value of type `T` at an arbitrary address in a (remote) process.
+///
+/// Unlike a [`ProcessMemoryBuffer`](crate::process::memory::ProcessMemoryBuffer), a [`RemotePtr`] does not own
+/// the memory it points to, so it can be used to access memory that was allocated by the target itself.
+/// Fields of structs implementing [`RemoteStruct`] can be accessed individually using [`RemotePtr::field`]
+/// without reading the whole struct.
+///
+/// # Layout
+/// The layout of `T` is the layout it has in the current process, which has to match the layout in the target.
+/// `T` should therefore be `#[repr(C)]` and must not contain `usize`, `isize`, references or raw pointers if the
+/// target may have a different pointer width than the current process. Pointers stored in the target should be
+/// declared as [`Ptr32`] for 32-bit targets and [`Ptr64`] for 64-bit targets instead.
+///
+/// # Example
+/// ```no_run
+/// use dll_syringe::process::{
+///     memory::{Ptr32, RemotePtr, RemoteStruct},
+///     OwnedProcess, Process,
+/// };
+///
+/// #[derive(Clone, Copy, RemoteStruct)]
+/// #[repr(C)]
+/// struct Player {
+///     health: u32,
+///     name: Ptr32<u8>,
+/// }
+///
+/// let process = OwnedProcess::find_first_by_name("game").unwrap().unwrap();
+/// let player = unsafe { RemotePtr::<Player>::new(process.borrowed(), 0x1234_5678) };
+/// let health = player.field(|p| &p.health);
+/// health.write(&(health.read().unwrap() + 10)).unwrap();
+/// let first_char = unsafe { player.field(|p| &p.name).read_ptr() }.unwrap().read().unwrap();
+/// ```
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "process-memory")))]
+pub struct RemotePtr<'a, T> {
+    process: BorrowedProcess<'a>,
+    address: usize,
+    data: PhantomData<*mut T>,
+}
+
+impl<'a, T> RemotePtr<'a, T> {
+    /// Creates a new pointer to the value at the given address in the given process.
+    ///
+    /// # Safety
+    /// The caller must ensure that as long as the pointer (or a pointer derived from it) is used, the given address
+    /// - points to a valid instance of type `T` with the layout `T` has in the current process
+    /// - can be read using [`ReadProcessMemory`](winapi::um::memoryapi::ReadProcessMemory)
+    /// - can be written to using [`WriteProcessMemory`](winapi::um::memoryapi::WriteProcessMemory)
+    #[must_use]
+    pub const unsafe fn new(process: BorrowedProcess<'a>, address: usize) -> Self {
+        Self {
+            process,
+            address,
+            data: PhantomData,
+        }
+    }
+
+    /// Returns the process the pointer points into.
+    #[must_use]
+    pub const fn process(&self) -> BorrowedProcess<'a> {
+        self.process
+    }
+
+    /// Returns the address the pointer points to.
+    ///
+    /// # Note
+    /// The returned address is only valid in the target process.
+    #[must_use]
+    pub const fn address(&self) -> usize {
+        self.address
+    }
+
+    /// Returns whether the pointer is null.
+    #[must_use]
+    pub const fn is_null(&self) -> bool {
+        self.address == 0
+    }
+
+    /// Returns the memory slice covering the pointed to value.
+    #[must_use]
+    pub fn as_memory_slice(&self) -> ProcessMemorySlice<'a> {
+        unsafe {
+            ProcessMemorySlice::from_raw_parts(
+                self.address as *mut u8,
+                mem::size_of::<T>(),
+                self.process,
+            )
+        }
+    }
+
+    /// Casts the pointer to a pointer of another type.
+    ///
+    /// # Safety
+    /// The caller must ensure that the address points to a valid instance of type `U` (see [`RemotePtr::new`]).
+    #[must_use]
+    pub const unsafe fn cast<U>(self) -> RemotePtr<'a, U> {
+        unsafe { RemotePtr::new(self.process, self.address) }
+    }
+
+    /// Calculates the offset from the pointer in units of `T`, like [`pointer::offset`].
+    ///
+    /// # Safety
+    /// The caller must ensure that the resulting address points to a valid instance of type `T`
+    /// (see [`RemotePtr::new`]).
+    #[must_use]
+    pub unsafe fn offset(self, count: isize) -> Self {
+        let bytes = count.wrapping_mul(mem::size_of::<T>() as isize);
+        unsafe { Self::new(self.process, self.address.wrapping_add(bytes as usize)) }
+    }
+
+    /// Calculates the offset from the pointer in units of `T`, like [`pointer::add`].
+    ///
+    /// # Safety
+    /// The caller must ensure that the resulting address points to a valid instance of type `T`
+    /// (see [`RemotePtr::new`]).
+    #[must_use]
+    pub unsafe fn add(self, count: usize) -> Self {
+        let bytes = count.wrapping_mul(mem::size_of::<T>());
+        unsafe { Self::new(self.process, self.address.wrapping_add(bytes)) }
+    }
+
+    /// Reads the pointed to value.
+    pub fn read(&self) -> Result<T, io::Error>
+    where
+        T: Copy,
+    {
+        unsafe { self.as_memory_slice().read_struct(0) }
+    }
+
+    /// Overwrites the pointed to value with the given value.
+    pub fn write(&self, value: &T) -> Result<(), io::Error>
+    where
+        T: Copy,
+    {
+        self.as_memory_slice().write_struct(0, value)
+    }
+
+    /// Returns a pointer to a field of the pointed to struct.
+    ///
+    /// The given closure selects the field from the [`RemoteStruct::Fields`] of `T`, e.g. `|s| &s.health`.
+    #[must_use]
+    pub fn field<F>(
+        &self,
+        project: impl FnOnce(&T::Fields) -> &FieldOffset<T, F>,
+    ) -> RemotePtr<'a, F>
+    where
+        T: RemoteStruct,
+    {
+        let fields = T::fields();
+        let offset = project(&fields).offset();
+        unsafe { RemotePtr::new(self.process, self.address.wrapping_add(offset)) }
+    }
+}
+
+impl<'a, T> RemotePtr<'a, Ptr32<T>> {
+    /// Reads the 32-bit pointer stored at this address and returns a pointer to its target.
+    ///
+    /// # Safety
+    /// The caller must ensure that the read pointer is null or satisfies the requirements of [`RemotePtr::new`].
+    pub unsafe fn read_ptr(&self) -> Result<RemotePtr<'a, T>, io::Error> {
+        let pointer = self.read()?;
+        Ok(unsafe { RemotePtr::new(self.process, pointer.address() as usize) })
+    }
+}
+
+impl<'a, T> RemotePtr<'a, Ptr64<T>> {
+    /// Reads the 64-bit pointer stored at this address and returns a pointer to its target.
+    /// Fails if the read pointer cannot be represented in the current process.
+    ///
+    /// # Safety
+    /// The caller must ensure that the read pointer is null or satisfies the requirements of [`RemotePtr::new`].
+    pub unsafe fn read_ptr(&self) -> Result<RemotePtr<'a, T>, io::Error> {
+        let pointer = self.read()?;
+        let address = usize::try_from(pointer.address()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "pointer exceeds the address space of the current process",
+            )
+        })?;
+        Ok(unsafe { RemotePtr::new(self.process, address) })
+    }
+}
+
+impl<T> Clone for RemotePtr<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for RemotePtr<'_, T> {}
+
+impl<T> PartialEq for RemotePtr<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.address == other.address && self.process == other.process
+    }
+}
+
+impl<T> Eq for RemotePtr<'_, T> {}
+
+impl<T> Debug for RemotePtr<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemotePtr")
+            .field("process", &self.process)
+            .field("address", &format_args!("{:#x}", self.address))
+            .finish()
+    }
+}
+
+macro_rules! target_ptr {
+    ($name:ident, $repr:ty, $bits:literal) => {
+        #[doc = concat!("A ", $bits, "-bit pointer to a value of type `T` stored in the memory of a ", $bits, "-bit process.")]
+        ///
+        /// This type has the same layout as the pointer in the target regardless of the pointer width of the current
+        /// process and can be used in structs accessed through a [`RemotePtr`].
+        #[repr(transparent)]
+        #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "process-memory")))]
+        pub struct $name<T> {
+            address: $repr,
+            data: PhantomData<fn() -> T>,
+        }
+
+        impl<T> $name<T> {
+            /// Creates a new pointer to the given address.
+            #[must_use]
+            pub const fn new(address: $repr) -> Self {
+                Self {
+                    address,
+                    data: PhantomData,
+                }
+            }
+
+            /// Creates a new null pointer.
+            #[must_use]
+            pub const fn null() -> Self {
+                Self::new(0)
+            }
+
+            /// Returns the address the pointer points to.
+            #[must_use]
+            pub const fn address(&self) -> $repr {
+                self.address
+            }
+
+            /// Returns whether the pointer is null.
+            #[must_use]
+            pub const fn is_null(&self) -> bool {
+                self.address == 0
+            }
+        }
+
+        impl<T> Clone for $name<T> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<T> Copy for $name<T> {}
+
+        impl<T> PartialEq for $name<T> {
+            fn eq(&self, other: &Self) -> bool {
+                self.address == other.address
+            }
+        }
+
+        impl<T> Eq for $name<T> {}
+
+        impl<T> Debug for $name<T> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}({:#x})", stringify!($name), self.address)
+            }
+        }
+    };
+}
+
+target_ptr!(Ptr32, u32, "32");
+target_ptr!(Ptr64, u64, "64");
+
+/// A struct whose fields can be accessed individually through a [`RemotePtr`] using [`RemotePtr::field`].
+///
+/// This trait should be implemented using `#[derive(RemoteStruct)]`, which generates a
+/// companion struct named `<Name>Fields` containing a [`FieldOffset`] for each field.
+///
+/// # Safety
+/// The offsets returned by [`RemoteStruct::fields`] must be the offsets of fields of `Self` with the given types.
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "process-memory")))]
+pub unsafe trait RemoteStruct: Sized {
+    /// The struct containing the offsets of the fields of `Self`.
+    type Fields;
+
+    /// Returns the offsets of the fields of `Self`.
+    fn fields() -> Self::Fields;
+}
+
+/// The offset of a field of type `F` in a struct of type `S`.
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "process-memory")))]
+pub struct FieldOffset<S, F> {
+    offset: usize,
+    data: PhantomData<fn(S) -> F>,
+}
+
+impl<S, F> FieldOffset<S, F> {
+    /// Creates a new field offset.
+    ///
+    /// # Safety
+    /// The caller must ensure that a struct of type `S` contains a field of type `F` at the given offset.
+    #[must_use]
+    pub const unsafe fn new_unchecked(offset: usize) -> Self {
+        Self {
+            offset,
+            data: PhantomData,
+        }
+    }
+
+    /// Returns the offset of the field in bytes.
+    #[must_use]
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<S, F> Clone for FieldOffset<S, F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S, F> Copy for FieldOffset<S, F> {}
+
+impl<S, F> Debug for FieldOffset<S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FieldOffset").field(&self.offset).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(C)]
+    struct Player {
+        id: u8,
+        health: u32,
+        target: Ptr64<Player>,
+    }
+
+    struct PlayerFields {
+        health: FieldOffset<Player, u32>,
+        target: FieldOffset<Player, Ptr64<Player>>,
+    }
+
+    unsafe impl RemoteStruct for Player {
+        type Fields = PlayerFields;
+
+        fn fields() -> Self::Fields {
+            unsafe {
+                PlayerFields {
+                    health: FieldOffset::new_unchecked(4),
+                    target: FieldOffset::new_unchecked(8),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn target_ptrs_have_target_layout() {
+        assert_eq!(mem::size_of::<Ptr32<Player>>(), 4);
+        assert_eq!(mem::size_of::<Ptr64<Player>>(), 8);
+        assert_eq!(mem::align_of::<Ptr64<Player>>(), mem::align_of::<u64>());
+        assert!(Ptr32::<u8>::null().is_null());
+        assert_eq!(format!("{:?}", Ptr32::<u8>::new(0x10)), "Ptr32(0x10)");
+    }
+
+    #[test]
+    fn offset_and_add_scale_by_size() {
+        let ptr = unsafe { RemotePtr::<u32>::new(BorrowedProcess::current(), 0x1000) };
+        assert_eq!(unsafe { ptr.add(2) }.address(), 0x1008);
+        assert_eq!(unsafe { ptr.offset(-2) }.address(), 0xff8);
+        assert_eq!(unsafe { ptr.cast::<u64>().add(1) }.address(), 0x1008);
+    }
+
+    #[test]
+    fn read_and_write_fields_of_local_struct() {
+        let mut target = Player {
+            id: 2,
+            health: 50,
+            target: Ptr64::null(),
+        };
+        let mut player = Player {
+            id: 1,
+            health: 100,
+            target: Ptr64::new(&mut target as *mut Player as u64),
+        };
+        let ptr = unsafe {
+            RemotePtr::<Player>::new(BorrowedProcess::current(), &mut player as *mut _ as usize)
+        };
+
+        assert_eq!(ptr.read().unwrap().id, 1);
+        let health = ptr.field(|p| &p.health);
+        assert_eq!(health.address(), ptr.address() + 4);
+        assert_eq!(health.read().unwrap(), 100);
+        health.write(&75).unwrap();
+
+        let target_ptr = unsafe { ptr.field(|p| &p.target).read_ptr() }.unwrap();
+        assert_eq!(target_ptr.read().unwrap(), target);
+        assert!(unsafe { target_ptr.field(|p| &p.target).read_ptr() }
+            .unwrap()
+            .is_null());
+
+        assert_eq!(player.health, 75);
+    }
+}
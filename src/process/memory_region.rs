@@ -0,0 +1,633 @@
+use std::{
+    io,
+    mem::{self, MaybeUninit},
+    os::windows::prelude::AsRawHandle,
+};
+
+use bitflags::bitflags;
+use winapi::{
+    shared::{minwindef::DWORD, winerror::ERROR_INVALID_PARAMETER},
+    um::{
+        memoryapi::VirtualQueryEx,
+        winnt::{
+            MEMORY_BASIC_INFORMATION, MEM_COMMIT, MEM_FREE, MEM_IMAGE, MEM_MAPPED, MEM_PRIVATE,
+            MEM_RESERVE, PAGE_EXECUTE, PAGE_EXECUTE_READ, PAGE_EXECUTE_READWRITE,
+            PAGE_EXECUTE_WRITECOPY, PAGE_GUARD, PAGE_NOACCESS, PAGE_NOCACHE, PAGE_READONLY,
+            PAGE_READWRITE, PAGE_WRITECOMBINE, PAGE_WRITECOPY,
+        },
+    },
+};
+
+use crate::process::{BorrowedProcess, ModuleHandle, Process, ProcessAccess, ProcessModule};
+
+/// The state of the pages of a [`MemoryRegion`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoryState {
+    /// The pages are committed and backed by physical memory or a paging file.
+    Commit,
+    /// The pages are reserved but not committed.
+    Reserve,
+    /// The pages are not allocated.
+    Free,
+}
+
+impl MemoryState {
+    fn from_raw(state: DWORD) -> Option<Self> {
+        match state {
+            MEM_COMMIT => Some(Self::Commit),
+            MEM_RESERVE => Some(Self::Reserve),
+            MEM_FREE => Some(Self::Free),
+            _ => None,
+        }
+    }
+}
+
+/// The kind of memory backing the pages of a [`MemoryRegion`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoryType {
+    /// The pages are mapped into the view of an image section, e.g. of a loaded module.
+    Image,
+    /// The pages are mapped into the view of a data section, e.g. of a memory mapped file.
+    Mapped,
+    /// The pages are private to the process, e.g. allocated using `VirtualAllocEx` or part of a heap or stack.
+    Private,
+}
+
+impl MemoryType {
+    fn from_raw(memory_type: DWORD) -> Option<Self> {
+        match memory_type {
+            MEM_IMAGE => Some(Self::Image),
+            MEM_MAPPED => Some(Self::Mapped),
+            MEM_PRIVATE => Some(Self::Private),
+            _ => None,
+        }
+    }
+}
+
+/// The access to memory pages granted by a [`PageProtection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PageAccess {
+    /// The pages cannot be accessed.
+    NoAccess,
+    /// The pages can be read.
+    ReadOnly,
+    /// The pages can be read and written.
+    ReadWrite,
+    /// The pages can be read and are copied on the first write.
+    WriteCopy,
+    /// The pages can be executed.
+    Execute,
+    /// The pages can be executed and read.
+    ExecuteRead,
+    /// The pages can be executed, read and written.
+    ExecuteReadWrite,
+    /// The pages can be executed and read and are copied on the first write.
+    ExecuteWriteCopy,
+}
+
+impl PageAccess {
+    fn from_raw(access: DWORD) -> Option<Self> {
+        match access {
+            PAGE_NOACCESS => Some(Self::NoAccess),
+            PAGE_READONLY => Some(Self::ReadOnly),
+            PAGE_READWRITE => Some(Self::ReadWrite),
+            PAGE_WRITECOPY => Some(Self::WriteCopy),
+            PAGE_EXECUTE => Some(Self::Execute),
+            PAGE_EXECUTE_READ => Some(Self::ExecuteRead),
+            PAGE_EXECUTE_READWRITE => Some(Self::ExecuteReadWrite),
+            PAGE_EXECUTE_WRITECOPY => Some(Self::ExecuteWriteCopy),
+            _ => None,
+        }
+    }
+
+    const fn to_raw(self) -> DWORD {
+        match self {
+            Self::NoAccess => PAGE_NOACCESS,
+            Self::ReadOnly => PAGE_READONLY,
+            Self::ReadWrite => PAGE_READWRITE,
+            Self::WriteCopy => PAGE_WRITECOPY,
+            Self::Execute => PAGE_EXECUTE,
+            Self::ExecuteRead => PAGE_EXECUTE_READ,
+            Self::ExecuteReadWrite => PAGE_EXECUTE_READWRITE,
+            Self::ExecuteWriteCopy => PAGE_EXECUTE_WRITECOPY,
+        }
+    }
+
+    /// Returns whether the pages can be read.
+    #[must_use]
+    pub const fn is_readable(self) -> bool {
+        !matches!(self, Self::NoAccess | Self::Execute)
+    }
+
+    /// Returns whether the pages can be written.
+    #[must_use]
+    pub const fn is_writable(self) -> bool {
+        matches!(
+            self,
+            Self::ReadWrite | Self::WriteCopy | Self::ExecuteReadWrite | Self::ExecuteWriteCopy
+        )
+    }
+
+    /// Returns whether the pages can be executed.
+    #[must_use]
+    pub const fn is_executable(self) -> bool {
+        matches!(
+            self,
+            Self::Execute | Self::ExecuteRead | Self::ExecuteReadWrite | Self::ExecuteWriteCopy
+        )
+    }
+}
+
+bitflags! {
+    /// Modifiers of a [`PageProtection`], which can be combined with any [`PageAccess`] except [`PageAccess::NoAccess`].
+    pub struct PageModifiers: u32 {
+        /// The pages are guard pages that raise an exception on their first access.
+        const GUARD = PAGE_GUARD;
+        /// The pages are not cached.
+        const NOCACHE = PAGE_NOCACHE;
+        /// The pages use write combining.
+        const WRITECOMBINE = PAGE_WRITECOMBINE;
+    }
+}
+
+/// The [protection](https://docs.microsoft.com/en-us/windows/win32/memory/memory-protection-constants) of memory pages,
+/// which consists of exactly one [`PageAccess`] and any number of [`PageModifiers`].
+///
+/// # Example
+/// ```
+/// use dll_syringe::process::{PageAccess, PageModifiers, PageProtection};
+///
+/// let protection = PageProtection::READWRITE.with_modifiers(PageModifiers::GUARD);
+/// assert_eq!(protection.access(), PageAccess::ReadWrite);
+/// assert!(protection.is_guard());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PageProtection {
+    access: PageAccess,
+    modifiers: PageModifiers,
+}
+
+impl PageProtection {
+    /// The pages cannot be accessed.
+    pub const NOACCESS: Self = Self::new(PageAccess::NoAccess);
+    /// The pages can be read.
+    pub const READONLY: Self = Self::new(PageAccess::ReadOnly);
+    /// The pages can be read and written.
+    pub const READWRITE: Self = Self::new(PageAccess::ReadWrite);
+    /// The pages can be read and are copied on the first write.
+    pub const WRITECOPY: Self = Self::new(PageAccess::WriteCopy);
+    /// The pages can be executed.
+    pub const EXECUTE: Self = Self::new(PageAccess::Execute);
+    /// The pages can be executed and read.
+    pub const EXECUTE_READ: Self = Self::new(PageAccess::ExecuteRead);
+    /// The pages can be executed, read and written.
+    pub const EXECUTE_READWRITE: Self = Self::new(PageAccess::ExecuteReadWrite);
+    /// The pages can be executed and read and are copied on the first write.
+    pub const EXECUTE_WRITECOPY: Self = Self::new(PageAccess::ExecuteWriteCopy);
+
+    /// Creates a new protection granting the given access without any modifiers.
+    #[must_use]
+    pub const fn new(access: PageAccess) -> Self {
+        Self {
+            access,
+            modifiers: PageModifiers::empty(),
+        }
+    }
+
+    /// Adds the given modifiers to this protection.
+    #[must_use]
+    pub const fn with_modifiers(mut self, modifiers: PageModifiers) -> Self {
+        self.modifiers = self.modifiers.union(modifiers);
+        self
+    }
+
+    /// The bits of a raw protection value that hold the access, all other bits are modifiers.
+    const ACCESS_MASK: DWORD = 0xFF;
+
+    /// Converts a raw protection value as used by `VirtualProtectEx` or `VirtualQueryEx`, unknown modifiers are ignored.
+    /// Returns `None` if the value does not contain exactly one access value, which is the case for pages that are not committed.
+    pub(crate) fn from_raw(protection: DWORD) -> Option<Self> {
+        let access = PageAccess::from_raw(protection & Self::ACCESS_MASK)?;
+        let modifiers = PageModifiers::from_bits_truncate(protection);
+        Some(Self { access, modifiers })
+    }
+
+    /// Returns the raw protection value as used by `VirtualProtectEx` or `VirtualQueryEx`.
+    pub(crate) const fn to_raw(self) -> DWORD {
+        self.access.to_raw() | self.modifiers.bits()
+    }
+
+    /// Returns the access granted by this protection.
+    #[must_use]
+    pub const fn access(self) -> PageAccess {
+        self.access
+    }
+
+    /// Returns the modifiers of this protection.
+    #[must_use]
+    pub const fn modifiers(self) -> PageModifiers {
+        self.modifiers
+    }
+
+    /// Returns whether the pages are guard pages.
+    #[must_use]
+    pub const fn is_guard(self) -> bool {
+        self.modifiers.contains(PageModifiers::GUARD)
+    }
+
+    /// Returns whether the pages can be read.
+    #[must_use]
+    pub const fn is_readable(self) -> bool {
+        self.access.is_readable()
+    }
+
+    /// Returns whether the pages can be written.
+    #[must_use]
+    pub const fn is_writable(self) -> bool {
+        self.access.is_writable()
+    }
+
+    /// Returns whether the pages can be executed.
+    #[must_use]
+    pub const fn is_executable(self) -> bool {
+        self.access.is_executable()
+    }
+}
+
+/// A snapshot of information about a range of pages in the address space of a process that share the same attributes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MemoryRegion {
+    base: usize,
+    size: usize,
+    state: MemoryState,
+    protection: Option<PageProtection>,
+    memory_type: Option<MemoryType>,
+    allocation_base: Option<usize>,
+    allocation_protection: Option<PageProtection>,
+}
+
+impl MemoryRegion {
+    fn from_raw(info: &MEMORY_BASIC_INFORMATION) -> Self {
+        let state = MemoryState::from_raw(info.State).unwrap_or(MemoryState::Free);
+        let is_free = state == MemoryState::Free;
+        Self {
+            base: info.BaseAddress as usize,
+            size: info.RegionSize,
+            state,
+            protection: PageProtection::from_raw(info.Protect),
+            memory_type: MemoryType::from_raw(info.Type),
+            allocation_base: Some(info.AllocationBase as usize).filter(|_| !is_free),
+            allocation_protection: PageProtection::from_raw(info.AllocationProtect),
+        }
+    }
+
+    /// Returns the address of the first page of this region.
+    #[must_use]
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
+    /// Returns the size of this region in bytes.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the address directly after the last page of this region.
+    #[must_use]
+    pub fn end(&self) -> usize {
+        self.base.wrapping_add(self.size)
+    }
+
+    /// Returns whether the given address lies inside this region.
+    #[must_use]
+    pub fn contains(&self, address: usize) -> bool {
+        address
+            .checked_sub(self.base)
+            .map_or(false, |offset| offset < self.size)
+    }
+
+    /// Returns the state of the pages of this region.
+    #[must_use]
+    pub fn state(&self) -> MemoryState {
+        self.state
+    }
+
+    /// Returns the current protection of the pages of this region or `None` if the pages are not committed.
+    #[must_use]
+    pub fn protection(&self) -> Option<PageProtection> {
+        self.protection
+    }
+
+    /// Returns the kind of memory backing the pages of this region or `None` if the pages are free.
+    #[must_use]
+    pub fn memory_type(&self) -> Option<MemoryType> {
+        self.memory_type
+    }
+
+    /// Returns the base address of the allocation this region is part of or `None` if the pages are free.
+    #[must_use]
+    pub fn allocation_base(&self) -> Option<usize> {
+        self.allocation_base
+    }
+
+    /// Returns the protection the allocation this region is part of was initially created with
+    /// or `None` if the pages are free (or the caller lacks access to it).
+    #[must_use]
+    pub fn allocation_protection(&self) -> Option<PageProtection> {
+        self.allocation_protection
+    }
+
+    /// Returns whether the pages of this region are committed.
+    #[must_use]
+    pub fn is_committed(&self) -> bool {
+        self.state == MemoryState::Commit
+    }
+
+    /// Returns whether the pages of this region are committed and executable.
+    #[must_use]
+    pub fn is_executable(&self) -> bool {
+        self.is_committed() && self.protection.map_or(false, PageProtection::is_executable)
+    }
+
+    /// Returns whether the pages of this region are part of a mapped image.
+    #[must_use]
+    pub fn is_image(&self) -> bool {
+        self.memory_type == Some(MemoryType::Image)
+    }
+
+    /// Returns the handle of the module whose mapped image contains this region, if the region is part of an image.
+    ///
+    /// # Note
+    /// Images can also be mapped without being loaded as a module, see [`MemoryRegion::module`] for a checked version.
+    #[must_use]
+    pub fn module_handle(&self) -> Option<ModuleHandle> {
+        self.allocation_base
+            .filter(|_| self.is_image())
+            .map(|base| base as ModuleHandle)
+    }
+
+    /// Returns the module loaded in the given process whose mapped image contains this region.
+    pub fn module<P: Process>(&self, process: P) -> Result<Option<ProcessModule<P>>, io::Error> {
+        let handle = match self.module_handle() {
+            Some(handle) => handle,
+            None => return Ok(None),
+        };
+
+        if process
+            .borrowed()
+            .module_handles()?
+            .any(|module| module == handle)
+        {
+            Ok(Some(unsafe {
+                ProcessModule::new_unchecked(handle, process)
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// A filter for the memory regions listed for a process (see [`Process::memory_regions_with_filter`]).
+///
+/// # Example
+/// ```no_run
+/// use dll_syringe::process::{BorrowedProcess, MemoryRegionFilter, MemoryType, Process};
+///
+/// let process = BorrowedProcess::current();
+/// let regions = process
+///     .memory_regions_with_filter(
+///         &MemoryRegionFilter::new()
+///             .with_type(MemoryType::Private)
+///             .with_executable(),
+///     )
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct MemoryRegionFilter {
+    state: Option<MemoryState>,
+    memory_type: Option<MemoryType>,
+    executable: bool,
+    allocation_base: Option<usize>,
+}
+
+impl MemoryRegionFilter {
+    /// Creates a new filter that matches all regions.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only matches regions whose pages are in the given state.
+    #[must_use]
+    pub fn with_state(mut self, state: MemoryState) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Only matches regions whose pages are committed.
+    #[must_use]
+    pub fn with_committed(self) -> Self {
+        self.with_state(MemoryState::Commit)
+    }
+
+    /// Only matches regions whose pages are backed by the given kind of memory.
+    #[must_use]
+    pub fn with_type(mut self, memory_type: MemoryType) -> Self {
+        self.memory_type = Some(memory_type);
+        self
+    }
+
+    /// Only matches regions whose pages are part of a mapped image.
+    #[must_use]
+    pub fn with_image(self) -> Self {
+        self.with_type(MemoryType::Image)
+    }
+
+    /// Only matches regions whose pages are committed and executable.
+    #[must_use]
+    pub fn with_executable(mut self) -> Self {
+        self.executable = true;
+        self
+    }
+
+    /// Only matches regions that are part of the mapped image of the given module.
+    #[must_use]
+    pub fn with_module(mut self, module: ModuleHandle) -> Self {
+        self.memory_type = Some(MemoryType::Image);
+        self.allocation_base = Some(module as usize);
+        self
+    }
+
+    /// Returns whether the given region matches all filters.
+    #[must_use]
+    pub fn matches(&self, region: &MemoryRegion) -> bool {
+        if let Some(state) = self.state {
+            if region.state() != state {
+                return false;
+            }
+        }
+        if let Some(memory_type) = self.memory_type {
+            if region.memory_type() != Some(memory_type) {
+                return false;
+            }
+        }
+        if self.executable && !region.is_executable() {
+            return false;
+        }
+        if let Some(allocation_base) = self.allocation_base {
+            if region.allocation_base() != Some(allocation_base) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Returns information about the region of pages containing the given address.
+pub(crate) fn memory_region_of_process(
+    process: BorrowedProcess<'_>,
+    address: usize,
+) -> Result<MemoryRegion, io::Error> {
+    process.ensure_access(ProcessAccess::QUERY_INFORMATION)?;
+
+    let mut info = MaybeUninit::<MEMORY_BASIC_INFORMATION>::uninit();
+    let result = unsafe {
+        VirtualQueryEx(
+            process.as_raw_handle(),
+            address as *const _,
+            info.as_mut_ptr(),
+            mem::size_of::<MEMORY_BASIC_INFORMATION>(),
+        )
+    };
+    if result == 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(MemoryRegion::from_raw(unsafe { info.assume_init_ref() }))
+}
+
+/// Returns information about all regions of pages in the address space of the given process that match the given filter.
+pub(crate) fn memory_regions_of_process(
+    process: BorrowedProcess<'_>,
+    filter: &MemoryRegionFilter,
+) -> Result<Vec<MemoryRegion>, io::Error> {
+    // the regions of an allocation are contiguous, so we can skip everything before it.
+    let mut address = filter.allocation_base.unwrap_or(0);
+    let mut regions = Vec::new();
+    loop {
+        let region = match memory_region_of_process(process, address) {
+            Ok(region) => region,
+            // the address lies beyond the user mode address space of the process.
+            Err(err) if err.raw_os_error() == Some(ERROR_INVALID_PARAMETER as _) => break,
+            Err(err) => return Err(err),
+        };
+
+        if let Some(allocation_base) = filter.allocation_base {
+            if region.allocation_base() != Some(allocation_base) {
+                break;
+            }
+        }
+        if filter.matches(&region) {
+            regions.push(region);
+        }
+
+        address = match region.base().checked_add(region.size()) {
+            Some(next) if region.size() != 0 => next,
+            _ => break,
+        };
+    }
+    Ok(regions)
+}
+
+impl<P: Process> ProcessModule<P> {
+    /// Returns a snapshot of the memory regions that make up the mapped image of this module.
+    pub fn memory_regions(&self) -> Result<Vec<MemoryRegion>, io::Error> {
+        memory_regions_of_process(
+            self.process().borrowed(),
+            &MemoryRegionFilter::new().with_module(self.handle()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_protection_access() {
+        assert!(PageProtection::EXECUTE_READ.is_executable());
+        assert!(PageProtection::EXECUTE_READ.is_readable());
+        assert!(!PageProtection::EXECUTE_READ.is_writable());
+        assert!(PageProtection::READWRITE.is_writable());
+        assert!(!PageProtection::READWRITE.is_executable());
+        assert!(!PageProtection::NOACCESS.is_readable());
+        assert!(!PageProtection::EXECUTE.is_readable());
+
+        let guard = PageProtection::READONLY.with_modifiers(PageModifiers::GUARD);
+        assert!(guard.is_readable());
+        assert!(guard.is_guard());
+        assert_eq!(guard.access(), PageAccess::ReadOnly);
+    }
+
+    #[test]
+    fn page_protection_from_raw() {
+        assert_eq!(
+            PageProtection::from_raw(PAGE_EXECUTE_READ),
+            Some(PageProtection::EXECUTE_READ)
+        );
+        let protection =
+            PageProtection::from_raw(PAGE_READWRITE | PAGE_GUARD | PAGE_NOCACHE).unwrap();
+        assert_eq!(protection.access(), PageAccess::ReadWrite);
+        assert_eq!(
+            protection.modifiers(),
+            PageModifiers::GUARD | PageModifiers::NOCACHE
+        );
+        assert_eq!(
+            protection.to_raw(),
+            PAGE_READWRITE | PAGE_GUARD | PAGE_NOCACHE
+        );
+
+        // uncommitted pages have no protection and a protection has exactly one access value.
+        assert_eq!(PageProtection::from_raw(0), None);
+        assert_eq!(PageProtection::from_raw(PAGE_GUARD), None);
+        assert_eq!(
+            PageProtection::from_raw(PAGE_READONLY | PAGE_READWRITE),
+            None
+        );
+    }
+
+    #[test]
+    fn filter_matches_regions() {
+        let region = MemoryRegion {
+            base: 0x1000,
+            size: 0x2000,
+            state: MemoryState::Commit,
+            protection: Some(PageProtection::EXECUTE_READ),
+            memory_type: Some(MemoryType::Image),
+            allocation_base: Some(0x1000),
+            allocation_protection: Some(PageProtection::EXECUTE_WRITECOPY),
+        };
+        assert!(region.contains(0x1000));
+        assert!(region.contains(0x2fff));
+        assert!(!region.contains(0x3000));
+        assert!(MemoryRegionFilter::new().matches(&region));
+        assert!(MemoryRegionFilter::new()
+            .with_committed()
+            .with_executable()
+            .with_image()
+            .matches(&region));
+        assert!(MemoryRegionFilter::new()
+            .with_module(0x1000 as ModuleHandle)
+            .matches(&region));
+        assert!(!MemoryRegionFilter::new()
+            .with_module(0x2000 as ModuleHandle)
+            .matches(&region));
+        assert!(!MemoryRegionFilter::new()
+            .with_type(MemoryType::Private)
+            .matches(&region));
+        assert!(!MemoryRegionFilter::new()
+            .with_state(MemoryState::Reserve)
+            .matches(&region));
+    }
+}
@@ -1,21 +1,84 @@
+// Only the types describing images, architectures and byte patterns are available on other hosts, everything else
+// interacts with processes and requires windows.
+
+#[cfg(windows)]
 mod process;
+#[cfg(windows)]
 pub use process::*;
 
+#[cfg(windows)]
+mod access;
+#[cfg(windows)]
+pub use access::*;
+
+#[cfg(windows)]
 mod owned;
+#[cfg(windows)]
 pub use owned::*;
 
+#[cfg(windows)]
 mod borrowed;
+#[cfg(windows)]
 pub use borrowed::*;
 
+#[cfg(windows)]
+mod process_info;
+#[cfg(windows)]
+pub use process_info::*;
+
+#[cfg(windows)]
+mod query;
+#[cfg(windows)]
+pub use query::*;
+
+#[cfg(windows)]
 mod module;
+#[cfg(windows)]
 pub use module::*;
 
+#[cfg(windows)]
+mod module_info;
+#[cfg(windows)]
+pub use module_info::*;
+
+#[cfg(windows)]
+mod memory_region;
+#[cfg(windows)]
+pub use memory_region::*;
+
+mod pattern;
+pub use pattern::*;
+
+#[cfg(windows)]
+mod thread;
+#[cfg(windows)]
+pub(crate) use thread::*;
+
+#[cfg(windows)]
+mod suspended;
+#[cfg(windows)]
+pub use suspended::*;
+
+mod architecture;
+pub use architecture::*;
+
+mod export;
+pub use export::*;
+
+mod image;
+pub use image::*;
+
+#[cfg(all(windows, feature = "syringe"))]
+mod api_set;
+#[cfg(all(windows, feature = "syringe"))]
+pub(crate) use api_set::*;
+
 #[cfg_attr(not(feature = "process-memory"), allow(dead_code))]
-#[cfg(feature = "process-memory")]
+#[cfg(all(windows, feature = "process-memory"))]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "process-memory")))]
 /// Module containing utilities for dealing with memory of another process.
 pub mod memory;
 #[cfg_attr(not(feature = "process-memory"), allow(dead_code))]
-#[cfg(not(feature = "process-memory"))]
+#[cfg(all(windows, not(feature = "process-memory")))]
 /// Module containing utilities for dealing with memory of another process.
 pub(crate) mod memory;
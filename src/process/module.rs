@@ -1,7 +1,6 @@
 use std::{
     ffi::{CStr, CString, OsString},
     io,
-    mem::{self, MaybeUninit},
     path::{Path, PathBuf},
     ptr::NonNull,
 };
@@ -9,7 +8,10 @@ use std::{
 use crate::{
     error::{GetLocalProcedureAddressError, IoOrNulError},
     function::{FunctionPtr, RawFunctionPtr},
-    process::{BorrowedProcess, OwnedProcess, Process},
+    process::{
+        memory::ProcessMemorySlice, BorrowedProcess, OwnedProcess, PageProtection, Pattern,
+        Process, ProcessAccess,
+    },
     utils::{win_fill_path_buf_helper, FillPathBufResult},
 };
 use path_absolutize::Absolutize;
@@ -21,9 +23,7 @@ use winapi::{
     },
     um::{
         libloaderapi::{GetModuleFileNameW, GetModuleHandleW, GetProcAddress},
-        memoryapi::VirtualQueryEx,
         psapi::{GetModuleBaseNameW, GetModuleFileNameExW},
-        winnt::{MEMORY_BASIC_INFORMATION, PAGE_NOACCESS},
     },
 };
 
@@ -233,6 +233,8 @@ impl<P: Process> ProcessModule<P> {
                 }
             })
         } else {
+            self.process()
+                .ensure_access(ProcessAccess::QUERY_LIMITED_INFORMATION | ProcessAccess::VM_READ)?;
             win_fill_path_buf_helper(|buf_ptr, buf_size| {
                 let buf_size = buf_size as u32;
                 let result = unsafe {
@@ -266,6 +268,8 @@ impl<P: Process> ProcessModule<P> {
         if self.is_local() {
             self.path().map(|path| path.file_name().unwrap().to_owned())
         } else {
+            self.process()
+                .ensure_access(ProcessAccess::QUERY_LIMITED_INFORMATION | ProcessAccess::VM_READ)?;
             win_fill_path_buf_helper(|buf_ptr, buf_size| {
                 let buf_size = buf_size as u32;
                 let result = unsafe {
@@ -341,6 +345,51 @@ impl<P: Process> ProcessModule<P> {
         }
     }
 
+    /// Returns the addresses of all (possibly overlapping) matches of the given pattern in the mapped image of this module in ascending order.
+    ///
+    /// # Note
+    /// Only the readable memory regions of the image are scanned and matches cannot span multiple regions (e.g. sections with different protections).
+    pub fn scan(&self, pattern: &Pattern) -> Result<Vec<usize>, io::Error> {
+        let mut matches = Vec::new();
+        for region in self.readable_image_slices()? {
+            matches.extend(region.scan(pattern)?);
+        }
+        Ok(matches)
+    }
+
+    /// Returns the address of the first match of the given pattern in the mapped image of this module.
+    ///
+    /// # Note
+    /// Only the readable memory regions of the image are scanned and matches cannot span multiple regions (e.g. sections with different protections).
+    pub fn scan_first(&self, pattern: &Pattern) -> Result<Option<usize>, io::Error> {
+        for region in self.readable_image_slices()? {
+            if let Some(address) = region.scan_first(pattern)? {
+                return Ok(Some(address));
+            }
+        }
+        Ok(None)
+    }
+
+    fn readable_image_slices(&self) -> Result<Vec<ProcessMemorySlice<'_>>, io::Error> {
+        Ok(self
+            .memory_regions()?
+            .into_iter()
+            .filter(|region| {
+                region.is_committed()
+                    && region.protection().map_or(false, |protection| {
+                        protection.is_readable() && !protection.is_guard()
+                    })
+            })
+            .map(|region| unsafe {
+                ProcessMemorySlice::from_raw_parts(
+                    region.base() as *mut u8,
+                    region.size(),
+                    self.process().borrowed(),
+                )
+            })
+            .collect())
+    }
+
     /// Returns whether this module is still loaded in the respective process.
     /// If the operation fails, the module is considered to be unloaded.
     pub fn guess_is_loaded(&self) -> bool {
@@ -353,23 +402,9 @@ impl<P: Process> ProcessModule<P> {
             return Ok(false);
         }
 
-        let mut module_info = MaybeUninit::uninit();
-        let raw_module = self.handle.as_ptr().cast();
-        let result = unsafe {
-            VirtualQueryEx(
-                self.process.as_raw_handle(),
-                raw_module,
-                module_info.as_mut_ptr(),
-                mem::size_of::<MEMORY_BASIC_INFORMATION>(),
-            )
-        };
-
-        if result == 0 {
-            Err(io::Error::last_os_error())
-        } else {
-            let module_info = unsafe { module_info.assume_init() };
-            Ok(module_info.BaseAddress == raw_module && module_info.Protect != PAGE_NOACCESS)
-        }
+        let region = self.process().memory_region_at(self.handle() as usize)?;
+        Ok(region.base() == self.handle() as usize
+            && region.protection() != Some(PageProtection::NOACCESS))
     }
 }
 
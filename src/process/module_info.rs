@@ -0,0 +1,130 @@
+use std::{
+    ffi::OsString,
+    io,
+    mem::{self, MaybeUninit},
+    os::windows::prelude::AsRawHandle,
+    path::{Path, PathBuf},
+};
+
+use winapi::{
+    shared::minwindef::DWORD,
+    um::psapi::{
+        GetModuleInformation, LIST_MODULES_32BIT, LIST_MODULES_64BIT, LIST_MODULES_ALL, MODULEINFO,
+    },
+};
+
+use crate::process::{ModuleHandle, Process, ProcessAccess, ProcessModule};
+
+/// A filter for the modules listed for a process.
+///
+/// This only makes a difference when listing the modules of a WOW64 process, which contains both 32-bit and 64-bit
+/// modules, from a 64-bit process. The filter is ignored if the current process is a WOW64 process itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ModuleListFilter {
+    /// List only the 32-bit modules.
+    Only32Bit,
+    /// List only the 64-bit modules.
+    Only64Bit,
+    /// List all modules.
+    #[default]
+    All,
+}
+
+impl ModuleListFilter {
+    pub(crate) fn list_flag(self) -> DWORD {
+        match self {
+            Self::Only32Bit => LIST_MODULES_32BIT,
+            Self::Only64Bit => LIST_MODULES_64BIT,
+            Self::All => LIST_MODULES_ALL,
+        }
+    }
+}
+
+/// A snapshot of information about a module loaded in a process.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModuleInfo {
+    base: usize,
+    size: usize,
+    entry_point: Option<usize>,
+    name: OsString,
+    path: PathBuf,
+}
+
+impl ModuleInfo {
+    /// Returns the base address of the module.
+    #[must_use]
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
+    /// Returns the handle of the module, which is its base address.
+    #[must_use]
+    pub fn handle(&self) -> ModuleHandle {
+        self.base as ModuleHandle
+    }
+
+    /// Returns the size of the mapped image of the module in bytes.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the address of the entry point of the module, if it has one.
+    #[must_use]
+    pub fn entry_point(&self) -> Option<usize> {
+        self.entry_point
+    }
+
+    /// Returns the base name of the file the module was loaded from.
+    #[must_use]
+    pub fn name(&self) -> &OsString {
+        &self.name
+    }
+
+    /// Returns the path that the module was loaded from.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns whether the given address lies inside the mapped image of the module.
+    #[must_use]
+    pub fn contains(&self, address: usize) -> bool {
+        address
+            .checked_sub(self.base)
+            .map_or(false, |offset| offset < self.size)
+    }
+}
+
+impl<P: Process> ProcessModule<P> {
+    /// Returns a snapshot of information about this module.
+    pub fn info(&self) -> Result<ModuleInfo, io::Error> {
+        self.process()
+            .ensure_access(ProcessAccess::QUERY_INFORMATION | ProcessAccess::VM_READ)?;
+
+        let mut module_info = MaybeUninit::<MODULEINFO>::uninit();
+        let result = unsafe {
+            GetModuleInformation(
+                self.process().as_raw_handle(),
+                self.handle(),
+                module_info.as_mut_ptr(),
+                mem::size_of::<MODULEINFO>() as u32,
+            )
+        };
+        if result == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let module_info = unsafe { module_info.assume_init() };
+
+        let path = self.path()?;
+        let name = path.file_name().unwrap_or_default().to_owned();
+
+        Ok(ModuleInfo {
+            base: module_info.lpBaseOfDll as usize,
+            size: module_info.SizeOfImage as usize,
+            entry_point: Some(module_info.EntryPoint as usize).filter(|&entry| entry != 0),
+            name,
+            path,
+        })
+    }
+}
@@ -13,49 +13,46 @@ use std::{
     time::Duration,
 };
 
-use sysinfo::{PidExt, ProcessExt, SystemExt};
 use winapi::{shared::minwindef::FALSE, um::processthreadsapi::OpenProcess};
 
-use crate::process::{BorrowedProcess, OwnedProcessModule, Process, PROCESS_INJECTION_ACCESS};
+use crate::process::{BorrowedProcess, OwnedProcessModule, Process, ProcessAccess, ProcessInfo};
 
 /// A struct representing a running process.
 /// This struct owns the underlying process handle (see also [`BorrowedProcess`] for a borrowed version).
 ///
 /// # Note
-/// The underlying handle has the following [privileges](https://docs.microsoft.com/en-us/windows/win32/procthread/process-security-and-access-rights):
-///  - `PROCESS_CREATE_THREAD`
-///  - `PROCESS_QUERY_INFORMATION`
-///  - `PROCESS_VM_OPERATION`
-///  - `PROCESS_VM_WRITE`
-///  - `PROCESS_VM_READ`
-#[repr(transparent)]
+/// The [access rights](ProcessAccess) of the underlying handle are recorded when it is opened (see [`Process::access`]).
+/// Instances created from [`OwnedProcess::from_pid`] have the rights required for dll injection ([`ProcessAccess::INJECTION`]).
 #[derive(Debug)]
-pub struct OwnedProcess(OwnedHandle);
+pub struct OwnedProcess {
+    handle: OwnedHandle,
+    access: ProcessAccess,
+}
 
 unsafe impl Send for OwnedProcess {}
 unsafe impl Sync for OwnedProcess {}
 
 impl AsRawHandle for OwnedProcess {
     fn as_raw_handle(&self) -> HANDLE {
-        self.0.as_raw_handle()
+        self.handle.as_raw_handle()
     }
 }
 
 impl AsHandle for OwnedProcess {
     fn as_handle(&self) -> BorrowedHandle<'_> {
-        self.0.as_handle()
+        self.handle.as_handle()
     }
 }
 
 impl IntoRawHandle for OwnedProcess {
     fn into_raw_handle(self) -> RawHandle {
-        self.0.into_raw_handle()
+        self.handle.into_raw_handle()
     }
 }
 
 impl FromRawHandle for OwnedProcess {
     unsafe fn from_raw_handle(handle: HANDLE) -> Self {
-        Self(unsafe { OwnedHandle::from_raw_handle(handle) })
+        unsafe { Self::from_handle_unchecked(OwnedHandle::from_raw_handle(handle)) }
     }
 }
 
@@ -91,7 +88,7 @@ impl Process for OwnedProcess {
     type Handle = OwnedHandle;
 
     fn borrowed(&self) -> BorrowedProcess<'_> {
-        unsafe { BorrowedProcess::from_handle_unchecked(self.as_handle()) }
+        unsafe { BorrowedProcess::from_handle_with_access(self.as_handle(), self.access) }
     }
 
     fn try_clone(&self) -> Result<Self, io::Error> {
@@ -99,11 +96,15 @@ impl Process for OwnedProcess {
     }
 
     fn into_handle(self) -> Self::Handle {
-        self.0
+        self.handle
     }
 
     unsafe fn from_handle_unchecked(handle: Self::Handle) -> Self {
-        Self(handle)
+        unsafe { Self::from_handle_with_access(handle, ProcessAccess::all()) }
+    }
+
+    fn access(&self) -> ProcessAccess {
+        self.access
     }
 
     fn current_handle() -> Self::Handle {
@@ -164,68 +165,77 @@ impl Process for OwnedProcess {
 }
 
 impl OwnedProcess {
-    /// Creates a new instance from the given pid.
+    /// Creates a new instance from the given pid with the access rights required for performing dll injection.
     pub fn from_pid(pid: u32) -> Result<OwnedProcess, io::Error> {
-        let handle = unsafe {
-            OpenProcess(
-                // access required for performing dll injection
-                PROCESS_INJECTION_ACCESS,
-                FALSE,
-                pid,
-            )
-        };
+        Self::open(pid, ProcessAccess::INJECTION)
+    }
+
+    /// Creates a new instance from the given pid with the given access rights.
+    ///
+    /// Operations that require rights that were not requested fail with a [`MissingAccess`](crate::error::MissingAccess) error.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use dll_syringe::process::{OwnedProcess, Process, ProcessAccess};
+    ///
+    /// # let pid = 0;
+    /// let process = OwnedProcess::open(pid, ProcessAccess::QUERY_LIMITED_INFORMATION).unwrap();
+    /// println!("{}", process.path().unwrap().display());
+    /// ```
+    pub fn open(pid: u32, access: ProcessAccess) -> Result<OwnedProcess, io::Error> {
+        let handle = unsafe { OpenProcess(access.bits(), FALSE, pid) };
 
         if handle.is_null() {
             return Err(io::Error::last_os_error());
         }
 
-        Ok(unsafe { OwnedProcess::from_raw_handle(handle) })
+        Ok(unsafe {
+            Self::from_handle_with_access(OwnedHandle::from_raw_handle(handle), access.granted())
+        })
     }
 
-    /// Returns a list of all currently running processes.
-    #[must_use]
-    pub fn all() -> Vec<OwnedProcess> {
-        // TODO: avoid using sysinfo for this
-        // TODO: deduplicate code
-        let mut system = sysinfo::System::new();
-        system.refresh_processes();
-        system
-            .processes()
-            .values()
-            .map(|process| process.pid())
-            .filter_map(|pid| OwnedProcess::from_pid(pid.as_u32()).ok())
-            .collect()
+    /// Creates a new instance from the given handle that was opened with the given access rights.
+    ///
+    /// # Safety
+    /// The caller must ensure that the handle is a valid process handle and has the given access rights.
+    pub(crate) unsafe fn from_handle_with_access(
+        handle: OwnedHandle,
+        access: ProcessAccess,
+    ) -> Self {
+        Self { handle, access }
     }
 
-    /// Finds all processes whose name contains the given string.
-    #[must_use]
-    pub fn find_all_by_name(name: impl AsRef<str>) -> Vec<OwnedProcess> {
-        // TODO: avoid using sysinfo for this
-        // TODO: deduplicate code
-        let mut system = sysinfo::System::new();
-        system.refresh_processes();
-        system
-            .processes()
-            .values()
-            .filter(move |process| process.name().contains(name.as_ref()))
-            .map(|process| process.pid())
-            .filter_map(|pid| OwnedProcess::from_pid(pid.as_u32()).ok())
-            .collect()
+    /// Returns a list of all currently running processes, skipping the processes that cannot be opened.
+    pub fn all() -> Result<Vec<OwnedProcess>, io::Error> {
+        Ok(Self::open_matching(|_| true)?.collect())
     }
 
-    /// Finds the first process whose name contains the given string.
-    #[must_use]
-    pub fn find_first_by_name(name: impl AsRef<str>) -> Option<OwnedProcess> {
-        // TODO: avoid using sysinfo for this
-        // TODO: deduplicate code
-        let mut system = sysinfo::System::new();
-        system.refresh_processes();
-        system
-            .processes()
-            .values()
-            .filter(move |process| process.name().contains(name.as_ref()))
-            .map(|process| process.pid())
-            .find_map(|pid| OwnedProcess::from_pid(pid.as_u32()).ok())
+    /// Finds all processes whose name contains the given string, skipping the processes that cannot be opened.
+    /// See [`ProcessQuery`] for more precise matching.
+    pub fn find_all_by_name(name: impl AsRef<str>) -> Result<Vec<OwnedProcess>, io::Error> {
+        let name = name.as_ref();
+        let processes =
+            Self::open_matching(|process| process.name().to_string_lossy().contains(name))?;
+        Ok(processes.collect())
+    }
+
+    /// Finds the first process whose name contains the given string, skipping the processes that cannot be opened.
+    /// See [`ProcessQuery`] for more precise matching.
+    pub fn find_first_by_name(name: impl AsRef<str>) -> Result<Option<OwnedProcess>, io::Error> {
+        let name = name.as_ref();
+        let mut processes =
+            Self::open_matching(|process| process.name().to_string_lossy().contains(name))?;
+        Ok(processes.next())
+    }
+
+    /// Opens all processes that match the given predicate, skipping the processes that cannot be opened.
+    fn open_matching(
+        mut predicate: impl FnMut(&ProcessInfo) -> bool,
+    ) -> Result<impl Iterator<Item = OwnedProcess>, io::Error> {
+        Ok(ProcessInfo::all()?
+            .into_iter()
+            .filter(move |process| predicate(process))
+            .filter_map(|process| process.open().ok()))
     }
 
     /// Creates a new instance from the given child process.
@@ -242,7 +252,10 @@ impl OwnedProcess {
     #[must_use]
     pub unsafe fn borrowed_static(&self) -> BorrowedProcess<'static> {
         unsafe {
-            BorrowedProcess::from_handle_unchecked(BorrowedHandle::borrow_raw(self.as_raw_handle()))
+            BorrowedProcess::from_handle_with_access(
+                BorrowedHandle::borrow_raw(self.as_raw_handle()),
+                self.access,
+            )
         }
     }
 
@@ -0,0 +1,218 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use crate::error::ParsePatternError;
+
+/// A byte pattern with wildcards that can be searched for in memory, e.g. to locate functions that are not exported.
+///
+/// Patterns are usually written in the IDA style, in which each byte is given as two hexadecimal digits
+/// and wildcard bytes are given as `??` (or `?`), separated by whitespace.
+///
+/// # Example
+/// ```
+/// use dll_syringe::process::Pattern;
+///
+/// let pattern: Pattern = "48 8B ?? ?? E8".parse().unwrap();
+/// let data = [0x90, 0x48, 0x8B, 0x05, 0x10, 0xE8, 0x00];
+/// assert_eq!(pattern.find_in(&data), Some(1));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Pattern {
+    bytes: Vec<Option<u8>>,
+    /// The index of the first byte that is not a wildcard, which is used to quickly find match candidates.
+    anchor: Option<usize>,
+}
+
+impl Pattern {
+    /// Creates a new pattern from the given bytes, where `None` represents a wildcard byte.
+    ///
+    /// # Panics
+    /// This function will panic if the given pattern is empty.
+    #[must_use]
+    pub fn new(bytes: impl Into<Vec<Option<u8>>>) -> Self {
+        let bytes = bytes.into();
+        assert!(!bytes.is_empty(), "pattern is empty");
+        let anchor = bytes.iter().position(Option::is_some);
+        Self { bytes, anchor }
+    }
+
+    /// Creates a new pattern that matches exactly the given bytes.
+    ///
+    /// # Panics
+    /// This function will panic if the given bytes are empty.
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self::new(bytes.iter().copied().map(Some).collect::<Vec<_>>())
+    }
+
+    /// Parses an IDA-style pattern like `48 8B ?? ?? E8`.
+    pub fn parse(pattern: &str) -> Result<Self, ParsePatternError> {
+        let bytes = pattern
+            .split_whitespace()
+            .enumerate()
+            .map(|(position, token)| match token {
+                "?" | "??" => Ok(None),
+                _ if token.len() == 2 && token.bytes().all(|c| c.is_ascii_hexdigit()) => {
+                    Ok(Some(u8::from_str_radix(token, 16).unwrap()))
+                }
+                _ => Err(ParsePatternError::InvalidByte {
+                    token: token.to_string(),
+                    position,
+                }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if bytes.is_empty() {
+            return Err(ParsePatternError::Empty);
+        }
+        Ok(Self::new(bytes))
+    }
+
+    /// Returns the bytes of this pattern, where `None` represents a wildcard byte.
+    #[must_use]
+    pub fn bytes(&self) -> &[Option<u8>] {
+        &self.bytes
+    }
+
+    /// Returns the number of bytes matched by this pattern.
+    #[must_use]
+    #[allow(clippy::len_without_is_empty)] // patterns are never empty.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Returns whether this pattern matches the start of the given data.
+    #[must_use]
+    pub fn matches(&self, data: &[u8]) -> bool {
+        data.len() >= self.len()
+            && self
+                .bytes
+                .iter()
+                .zip(data)
+                .all(|(expected, actual)| expected.map_or(true, |expected| expected == *actual))
+    }
+
+    /// Returns the offset of the first match of this pattern in the given data.
+    #[must_use]
+    pub fn find_in(&self, data: &[u8]) -> Option<usize> {
+        self.find_all_in(data).next()
+    }
+
+    /// Returns the offsets of all (possibly overlapping) matches of this pattern in the given data in ascending order.
+    pub fn find_all_in<'a>(&'a self, data: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        let candidates = data.len().saturating_sub(self.len() - 1);
+        let mut offset = 0;
+        std::iter::from_fn(move || {
+            while offset < candidates {
+                let candidate = match self.anchor {
+                    Some(anchor) => {
+                        let anchor_byte = self.bytes[anchor];
+                        let search = &data[offset + anchor..candidates + anchor];
+                        match search.iter().position(|byte| Some(*byte) == anchor_byte) {
+                            Some(position) => offset + position,
+                            None => {
+                                offset = candidates;
+                                return None;
+                            }
+                        }
+                    }
+                    None => offset,
+                };
+
+                offset = candidate + 1;
+                if self.matches(&data[candidate..]) {
+                    return Some(candidate);
+                }
+            }
+            None
+        })
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = ParsePatternError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, byte) in self.bytes.iter().enumerate() {
+            if i != 0 {
+                write!(f, " ")?;
+            }
+            match byte {
+                Some(byte) => write!(f, "{:02X}", byte)?,
+                None => write!(f, "??")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ida_style_pattern() {
+        let pattern = Pattern::parse("48 8b ?? ? E8").unwrap();
+        assert_eq!(
+            pattern.bytes(),
+            &[Some(0x48), Some(0x8B), None, None, Some(0xE8)]
+        );
+        assert_eq!(pattern.to_string(), "48 8B ?? ?? E8");
+        assert_eq!(Pattern::parse(&pattern.to_string()).unwrap(), pattern);
+    }
+
+    #[test]
+    fn parse_invalid_pattern_fails() {
+        assert_eq!(Pattern::parse("  "), Err(ParsePatternError::Empty));
+        assert_eq!(
+            Pattern::parse("48 8G"),
+            Err(ParsePatternError::InvalidByte {
+                token: "8G".to_string(),
+                position: 1
+            })
+        );
+        assert!(Pattern::parse("488B").is_err());
+        assert!(Pattern::parse("48 ???").is_err());
+        assert!(Pattern::parse("+1 48").is_err());
+    }
+
+    #[test]
+    fn find_matches() {
+        let pattern = Pattern::parse("48 ?? E8").unwrap();
+        let data = [0x48, 0x00, 0xE8, 0x48, 0x48, 0xE8, 0xE8, 0x48, 0x01];
+        assert_eq!(pattern.find_in(&data), Some(0));
+        assert_eq!(pattern.find_all_in(&data).collect::<Vec<_>>(), [0, 3, 4]);
+        assert_eq!(pattern.find_in(&data[1..]), Some(2));
+        assert_eq!(pattern.find_in(&data[..2]), None);
+        assert_eq!(pattern.find_in(&[]), None);
+    }
+
+    #[test]
+    fn find_matches_with_leading_wildcards() {
+        let pattern = Pattern::parse("?? ?? 02").unwrap();
+        let data = [0x02, 0x02, 0x02, 0x02];
+        assert_eq!(pattern.find_all_in(&data).collect::<Vec<_>>(), [0, 1]);
+
+        let pattern = Pattern::parse("?? ??").unwrap();
+        assert_eq!(pattern.find_all_in(&data).collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(pattern.find_in(&data[..1]), None);
+    }
+
+    #[test]
+    fn find_matches_at_end() {
+        let pattern = Pattern::from_bytes(&[0xC3]);
+        let data = [0x90, 0x90, 0xC3];
+        assert_eq!(pattern.find_all_in(&data).collect::<Vec<_>>(), [2]);
+
+        let pattern = Pattern::from_bytes(&[0x90, 0xC3]);
+        assert_eq!(pattern.find_in(&data), Some(1));
+    }
+}
@@ -12,26 +12,29 @@ use std::{
 use winapi::{
     shared::{
         minwindef::{DWORD, FALSE},
-        winerror::{ERROR_CALL_NOT_IMPLEMENTED, ERROR_INSUFFICIENT_BUFFER},
+        winerror::{ERROR_INSUFFICIENT_BUFFER, ERROR_INVALID_HANDLE, ERROR_PARTIAL_COPY},
     },
     um::{
         minwinbase::STILL_ACTIVE,
         processthreadsapi::{
-            CreateRemoteThread, GetCurrentProcess, GetExitCodeProcess, GetExitCodeThread,
-            GetProcessId, TerminateProcess,
+            CreateRemoteThread, GetCurrentProcess, GetExitCodeProcess, GetProcessId,
+            TerminateProcess,
         },
-        synchapi::WaitForSingleObject,
-        winbase::{QueryFullProcessImageNameW, INFINITE, WAIT_FAILED},
+        winbase::QueryFullProcessImageNameW,
         winnt::{
             PROCESS_CREATE_THREAD, PROCESS_QUERY_INFORMATION, PROCESS_VM_OPERATION,
             PROCESS_VM_READ, PROCESS_VM_WRITE,
         },
-        wow64apiset::{GetSystemWow64DirectoryA, IsWow64Process},
     },
 };
 
 use crate::{
-    process::{BorrowedProcess, ProcessModule},
+    error::MissingAccess,
+    process::{
+        memory_region_of_process, memory_regions_of_process, thread_ids_of_process,
+        wait_for_thread_exit_code, BorrowedProcess, MemoryRegion, MemoryRegionFilter, ModuleInfo,
+        ModuleListFilter, ProcessAccess, ProcessArchitecture, ProcessModule,
+    },
     utils::{win_fill_path_buf_helper, FillPathBufResult},
 };
 
@@ -48,12 +51,9 @@ pub const PROCESS_INJECTION_ACCESS: DWORD = PROCESS_CREATE_THREAD
 /// A trait representing a running process.
 ///
 /// # Note
-/// The underlying handle has the following [privileges](https://docs.microsoft.com/en-us/windows/win32/procthread/process-security-and-access-rights):
-///  - `PROCESS_CREATE_THREAD`
-///  - `PROCESS_QUERY_INFORMATION`
-///  - `PROCESS_VM_OPERATION`
-///  - `PROCESS_VM_WRITE`
-///  - `PROCESS_VM_READ`
+/// The [access rights](ProcessAccess) of the underlying handle are recorded alongside it (see [`Process::access`]).
+/// Operations that require rights the handle does not have fail with a [`MissingAccess`] error
+/// (wrapped in an [`io::Error`] of kind [`PermissionDenied`](io::ErrorKind::PermissionDenied)).
 pub trait Process: AsHandle + AsRawHandle {
     /// The underlying handle type.
     type Handle;
@@ -71,12 +71,33 @@ pub trait Process: AsHandle + AsRawHandle {
     fn into_handle(self) -> Self::Handle;
 
     /// Creates a new instance from the given handle.
+    /// As the access rights of the handle are unknown, all operations are assumed to be permitted.
     ///
     /// # Safety
     /// The caller must ensure that the handle is a valid process handle and has the required priviledges.
     #[must_use]
     unsafe fn from_handle_unchecked(handle: Self::Handle) -> Self;
 
+    /// Returns the access rights of the underlying handle.
+    ///
+    /// The default implementation assumes that the handle has all access rights, so that implementors that do not
+    /// track the rights of their handle keep working. Operations then fail with the error reported by windows instead
+    /// of a [`MissingAccess`] error.
+    #[must_use]
+    fn access(&self) -> ProcessAccess {
+        ProcessAccess::all()
+    }
+
+    /// Returns an error if the underlying handle lacks any of the given access rights.
+    fn ensure_access(&self, required: ProcessAccess) -> Result<(), MissingAccess> {
+        let missing = required - self.access();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(MissingAccess(missing))
+        }
+    }
+
     /// Returns the raw pseudo handle representing the current process.
     #[must_use]
     fn raw_current_handle() -> ProcessHandle {
@@ -119,36 +140,20 @@ pub trait Process: AsHandle + AsRawHandle {
 
     /// Returns the id of this process.
     fn pid(&self) -> Result<NonZeroU32, io::Error> {
+        self.ensure_access(ProcessAccess::QUERY_LIMITED_INFORMATION)?;
         let result = unsafe { GetProcessId(self.as_raw_handle()) };
         NonZeroU32::new(result).ok_or_else(io::Error::last_os_error)
     }
 
-    /// Returns whether this process is running under [WOW64](https://docs.microsoft.com/en-us/windows/win32/winprog64/running-32-bit-applications).
-    /// This is the case for 32-bit programs running on a 64-bit platform.
-    ///
-    /// # Note
-    /// This method also returns `false` for a 32-bit process running under 32-bit Windows or 64-bit Windows 10 on ARM.
-    fn runs_under_wow64(&self) -> Result<bool, io::Error> {
-        let mut is_wow64 = MaybeUninit::uninit();
-        let result = unsafe { IsWow64Process(self.as_raw_handle(), is_wow64.as_mut_ptr()) };
-        if result == 0 {
-            return Err(io::Error::last_os_error());
-        }
-        Ok(unsafe { is_wow64.assume_init() } != FALSE)
-    }
-
-    /// Returns whether this process is a 64-bit process.
-    fn is_x64(&self) -> Result<bool, io::Error> {
-        Ok(is_x64_windows()? && !self.runs_under_wow64()?)
-    }
-
-    /// Returns whether this process is a 32-bit process.
-    fn is_x86(&self) -> Result<bool, io::Error> {
-        Ok(is_x32_windows()? || is_x64_windows()? && self.runs_under_wow64()?)
+    /// Returns the [`ProcessArchitecture`] of this process.
+    fn architecture(&self) -> Result<ProcessArchitecture, io::Error> {
+        self.ensure_access(ProcessAccess::QUERY_LIMITED_INFORMATION)?;
+        ProcessArchitecture::of_process(self)
     }
 
     /// Returns the executable path of this process.
     fn path(&self) -> Result<PathBuf, io::Error> {
+        self.ensure_access(ProcessAccess::QUERY_LIMITED_INFORMATION)?;
         win_fill_path_buf_helper(|buf_ptr, buf_size| {
             let mut buf_size = buf_size as u32;
             let result = unsafe {
@@ -184,6 +189,7 @@ pub trait Process: AsHandle + AsRawHandle {
 
     /// Terminates this process with the given exit code.
     fn kill_with_exit_code(&self, exit_code: u32) -> Result<(), io::Error> {
+        self.ensure_access(ProcessAccess::TERMINATE)?;
         let result = unsafe { TerminateProcess(self.as_raw_handle(), exit_code) };
         if result == 0 {
             return Err(io::Error::last_os_error());
@@ -198,24 +204,7 @@ pub trait Process: AsHandle + AsRawHandle {
         parameter: *mut T,
     ) -> Result<u32, io::Error> {
         let thread_handle = self.start_remote_thread(remote_fn, parameter)?;
-
-        let reason = unsafe { WaitForSingleObject(thread_handle.as_raw_handle(), INFINITE) };
-        if reason == WAIT_FAILED {
-            return Err(io::Error::last_os_error());
-        }
-
-        let mut exit_code = MaybeUninit::uninit();
-        let result =
-            unsafe { GetExitCodeThread(thread_handle.as_raw_handle(), exit_code.as_mut_ptr()) };
-        if result == 0 {
-            return Err(io::Error::last_os_error());
-        }
-        debug_assert_ne!(
-            result as u32, STILL_ACTIVE,
-            "GetExitCodeThread returned STILL_ACTIVE after WaitForSingleObject"
-        );
-
-        Ok(unsafe { exit_code.assume_init() })
+        wait_for_thread_exit_code(thread_handle.as_raw_handle())
     }
 
     /// Starts a new thread in this process with the given entry point and argument and returns the thread handle.
@@ -225,6 +214,14 @@ pub trait Process: AsHandle + AsRawHandle {
         remote_fn: unsafe extern "system" fn(*mut T) -> u32,
         parameter: *mut T,
     ) -> Result<OwnedHandle, io::Error> {
+        self.ensure_access(
+            ProcessAccess::CREATE_THREAD
+                | ProcessAccess::QUERY_INFORMATION
+                | ProcessAccess::VM_OPERATION
+                | ProcessAccess::VM_WRITE
+                | ProcessAccess::VM_READ,
+        )?;
+
         // create a remote thread that will call LoadLibraryW with payload_path as its argument.
         let thread_handle = unsafe {
             CreateRemoteThread(
@@ -294,6 +291,11 @@ pub trait Process: AsHandle + AsRawHandle {
     where
         Self: Sized;
 
+    /// Returns the ids of all threads currently running in this process.
+    fn thread_ids(&self) -> Result<Vec<u32>, io::Error> {
+        thread_ids_of_process(self.pid()?.get())
+    }
+
     /// Returns a snapshot of all modules currently loaded in this process.
     ///
     /// # Note
@@ -309,27 +311,59 @@ pub trait Process: AsHandle + AsRawHandle {
         }
         Ok(modules)
     }
-}
 
-fn is_x32_windows() -> Result<bool, io::Error> {
-    // TODO: use GetNativeSystemInfo() instead?
-    let result = unsafe { GetSystemWow64DirectoryA(ptr::null_mut(), 0) };
-    if result == 0 {
-        let err = io::Error::last_os_error();
-        if err.raw_os_error().unwrap() == ERROR_CALL_NOT_IMPLEMENTED as _ {
-            Ok(true)
-        } else {
-            Err(err)
+    /// Returns a snapshot of information about all modules currently loaded in this process.
+    ///
+    /// # Note
+    /// If the process is currently starting up and has not loaded all its modules yet, the returned list may be incomplete.
+    fn module_infos(&self) -> Result<Vec<ModuleInfo>, io::Error> {
+        self.module_infos_with_filter(ModuleListFilter::All)
+    }
+
+    /// Returns a snapshot of information about all modules currently loaded in this process that match the given filter.
+    ///
+    /// # Note
+    /// If the process is currently starting up and has not loaded all its modules yet, the returned list may be incomplete.
+    fn module_infos_with_filter(
+        &self,
+        filter: ModuleListFilter,
+    ) -> Result<Vec<ModuleInfo>, io::Error> {
+        let process = self.borrowed();
+        let module_handles = process.module_handles_with_filter(filter)?;
+        let mut module_infos = Vec::with_capacity(module_handles.len());
+        for module_handle in module_handles {
+            let module = unsafe { ProcessModule::new_unchecked(module_handle, process) };
+            match module.info() {
+                Ok(module_info) => module_infos.push(module_info),
+                // the module was unloaded after the handles were listed.
+                Err(err) if is_unloaded_module_error(&err) && process.is_alive() => {}
+                Err(err) => return Err(err),
+            }
         }
-    } else {
-        Ok(false)
+        Ok(module_infos)
+    }
+
+    /// Returns information about the region of pages in the address space of this process that contains the given address.
+    fn memory_region_at(&self, address: usize) -> Result<MemoryRegion, io::Error> {
+        memory_region_of_process(self.borrowed(), address)
+    }
+
+    /// Returns a snapshot of all regions of pages in the address space of this process, including free and reserved ones.
+    fn memory_regions(&self) -> Result<Vec<MemoryRegion>, io::Error> {
+        self.memory_regions_with_filter(&MemoryRegionFilter::new())
     }
-}
 
-fn is_x64_windows() -> Result<bool, io::Error> {
-    if cfg!(target_arch = "x86_64") {
-        Ok(true)
-    } else {
-        Ok(!is_x32_windows()?)
+    /// Returns a snapshot of all regions of pages in the address space of this process that match the given filter.
+    fn memory_regions_with_filter(
+        &self,
+        filter: &MemoryRegionFilter,
+    ) -> Result<Vec<MemoryRegion>, io::Error> {
+        memory_regions_of_process(self.borrowed(), filter)
     }
 }
+
+/// Returns whether the given error was caused by querying a module that was unloaded in the meantime.
+fn is_unloaded_module_error(err: &io::Error) -> bool {
+    err.raw_os_error() == Some(ERROR_INVALID_HANDLE as i32)
+        || err.raw_os_error() == Some(ERROR_PARTIAL_COPY as i32)
+}
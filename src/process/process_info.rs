@@ -0,0 +1,195 @@
+use std::{
+    ffi::{OsStr, OsString},
+    io, mem,
+};
+
+use cstr::cstr;
+use widestring::{u16cstr, U16Str};
+use winapi::shared::{
+    minwindef::ULONG,
+    ntdef::{HANDLE, LONG, NTSTATUS, PVOID, UNICODE_STRING},
+};
+
+use crate::process::{
+    BorrowedProcessModule, OwnedProcess, Process, ProcessAccess, ProcessArchitecture,
+};
+
+type NtQuerySystemInformationFn =
+    unsafe extern "system" fn(ULONG, PVOID, ULONG, *mut ULONG) -> NTSTATUS;
+
+const SYSTEM_PROCESS_INFORMATION_CLASS: ULONG = 5;
+const STATUS_INFO_LENGTH_MISMATCH: NTSTATUS = 0xC000_0004_u32 as NTSTATUS;
+
+/// The leading part of `SYSTEM_PROCESS_INFORMATION`, which has the same layout on all supported windows versions.
+#[repr(C)]
+struct SystemProcessInformation {
+    next_entry_offset: ULONG,
+    number_of_threads: ULONG,
+    /// `WorkingSetPrivateSize` up to and including `KernelTime`.
+    _times: [u8; 48],
+    image_name: UNICODE_STRING,
+    _base_priority: LONG,
+    unique_process_id: HANDLE,
+    inherited_from_unique_process_id: HANDLE,
+    _handle_count: ULONG,
+    session_id: ULONG,
+}
+
+/// A snapshot of information about a running process.
+///
+/// Creating the snapshot does not open any handles to the listed processes.
+///
+/// # Note
+/// The architecture of a process cannot be determined without opening a handle to it, so it is not part of the
+/// snapshot and has to be queried separately using [`ProcessInfo::architecture`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProcessInfo {
+    pid: u32,
+    parent_pid: u32,
+    name: OsString,
+    thread_count: u32,
+    session_id: u32,
+}
+
+impl ProcessInfo {
+    /// Returns a snapshot of information about all currently running processes.
+    pub fn all() -> Result<Vec<ProcessInfo>, io::Error> {
+        let buf = Self::query_system_process_information()?;
+
+        let mut processes = Vec::new();
+        let mut offset = 0;
+        loop {
+            let entry = unsafe {
+                &*buf
+                    .as_ptr()
+                    .cast::<u8>()
+                    .add(offset)
+                    .cast::<SystemProcessInformation>()
+            };
+            processes.push(Self::from_entry(entry));
+
+            if entry.next_entry_offset == 0 {
+                break;
+            }
+            offset += entry.next_entry_offset as usize;
+        }
+
+        Ok(processes)
+    }
+
+    /// Returns the raw entries of all processes, the buffer is made of `u64`s so that the entries are aligned.
+    fn query_system_process_information() -> Result<Vec<u64>, io::Error> {
+        let ntdll =
+            BorrowedProcessModule::find_local_by_name_or_abs_path_wstr(u16cstr!("ntdll.dll"))?
+                .unwrap();
+        let query_system_information: NtQuerySystemInformationFn = unsafe {
+            mem::transmute(
+                ntdll.get_local_procedure_address_cstr(cstr!("NtQuerySystemInformation"))?,
+            )
+        };
+
+        let mut buf = Vec::<u64>::new();
+        let mut byte_len = 0x40000;
+        loop {
+            // processes may be created between two calls, so leave some room for them.
+            buf.resize((byte_len as usize + 0x10000) / mem::size_of::<u64>(), 0);
+            let status = unsafe {
+                query_system_information(
+                    SYSTEM_PROCESS_INFORMATION_CLASS,
+                    buf.as_mut_ptr().cast(),
+                    (buf.len() * mem::size_of::<u64>()) as ULONG,
+                    &mut byte_len,
+                )
+            };
+            match status {
+                STATUS_INFO_LENGTH_MISMATCH => continue,
+                status if status < 0 => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        format!(
+                            "NtQuerySystemInformation failed with NTSTATUS {:#010x}",
+                            status
+                        ),
+                    ))
+                }
+                _ => return Ok(buf),
+            }
+        }
+    }
+
+    fn from_entry(entry: &SystemProcessInformation) -> Self {
+        // the name is stored in the same buffer as the entries and is missing for the idle process.
+        let name = if entry.image_name.Buffer.is_null() {
+            OsString::new()
+        } else {
+            unsafe {
+                U16Str::from_ptr(
+                    entry.image_name.Buffer,
+                    usize::from(entry.image_name.Length) / mem::size_of::<u16>(),
+                )
+            }
+            .to_os_string()
+        };
+
+        Self {
+            pid: entry.unique_process_id as usize as u32,
+            parent_pid: entry.inherited_from_unique_process_id as usize as u32,
+            name,
+            thread_count: entry.number_of_threads,
+            session_id: entry.session_id,
+        }
+    }
+
+    /// Returns the id of the process.
+    #[must_use]
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// Returns the id of the process that created this process.
+    ///
+    /// # Note
+    /// The parent process may have exited in the meantime and its id may have been reused.
+    #[must_use]
+    pub fn parent_pid(&self) -> u32 {
+        self.parent_pid
+    }
+
+    /// Returns the file name of the executable of the process (e.g. `notepad.exe`).
+    /// The name is empty for the idle process.
+    #[must_use]
+    pub fn name(&self) -> &OsStr {
+        &self.name
+    }
+
+    /// Returns the number of threads of the process at the time of the snapshot.
+    #[must_use]
+    pub fn thread_count(&self) -> u32 {
+        self.thread_count
+    }
+
+    /// Returns the id of the terminal services session the process is running in.
+    #[must_use]
+    pub fn session_id(&self) -> u32 {
+        self.session_id
+    }
+
+    /// Determines the architecture of the process.
+    ///
+    /// # Note
+    /// The architecture is not part of the snapshot, so this opens a handle to the process with
+    /// [`ProcessAccess::QUERY_LIMITED_INFORMATION`], which is granted for most processes.
+    pub fn architecture(&self) -> Result<ProcessArchitecture, io::Error> {
+        OwnedProcess::open(self.pid, ProcessAccess::QUERY_LIMITED_INFORMATION)?.architecture()
+    }
+
+    /// Opens the process with the access required for performing dll injection.
+    pub fn open(&self) -> Result<OwnedProcess, io::Error> {
+        OwnedProcess::from_pid(self.pid)
+    }
+
+    /// Opens the process with the given access rights.
+    pub fn open_with_access(&self, access: ProcessAccess) -> Result<OwnedProcess, io::Error> {
+        OwnedProcess::open(self.pid, access)
+    }
+}
@@ -0,0 +1,226 @@
+use std::{
+    ffi::{OsStr, OsString},
+    io,
+};
+
+use crate::process::{OwnedProcess, ProcessArchitecture, ProcessInfo};
+
+/// A builder for finding running processes by their name, parent and architecture.
+///
+/// The processes are listed using a [`ProcessInfo`] snapshot and only the processes that match all filters are opened.
+///
+/// # Example
+/// ```no_run
+/// use dll_syringe::process::{ProcessArchitecture, ProcessQuery};
+///
+/// let processes = ProcessQuery::new()
+///     .with_name_glob("notepad*.exe")
+///     .with_architecture(ProcessArchitecture::X64)
+///     .find_all()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ProcessQuery {
+    name: Option<NamePattern>,
+    parent_pid: Option<u32>,
+    architecture: Option<ProcessArchitecture>,
+}
+
+#[derive(Debug, Clone)]
+enum NamePattern {
+    Exact(OsString),
+    /// A lowercase name.
+    IgnoreCase(String),
+    /// A lowercase glob pattern.
+    Glob(String),
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+}
+
+impl NamePattern {
+    fn matches(&self, name: &OsStr) -> bool {
+        match self {
+            Self::Exact(expected) => name == expected,
+            Self::IgnoreCase(expected) => name.to_string_lossy().to_lowercase() == *expected,
+            Self::Glob(pattern) => glob_matches(pattern, &name.to_string_lossy().to_lowercase()),
+            #[cfg(feature = "regex")]
+            Self::Regex(regex) => regex.is_match(&name.to_string_lossy()),
+        }
+    }
+}
+
+impl ProcessQuery {
+    /// Creates a new query that matches all processes.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only matches processes whose executable name is exactly the given name (e.g. `notepad.exe`).
+    #[must_use]
+    pub fn with_name(mut self, name: impl Into<OsString>) -> Self {
+        self.name = Some(NamePattern::Exact(name.into()));
+        self
+    }
+
+    /// Only matches processes whose executable name equals the given name ignoring case.
+    #[must_use]
+    pub fn with_name_ignore_case(mut self, name: impl AsRef<str>) -> Self {
+        self.name = Some(NamePattern::IgnoreCase(name.as_ref().to_lowercase()));
+        self
+    }
+
+    /// Only matches processes whose executable name matches the given glob pattern ignoring case.
+    /// The pattern supports `*` for any sequence of characters and `?` for any single character.
+    #[must_use]
+    pub fn with_name_glob(mut self, pattern: impl AsRef<str>) -> Self {
+        self.name = Some(NamePattern::Glob(pattern.as_ref().to_lowercase()));
+        self
+    }
+
+    /// Only matches processes whose executable name matches the given regular expression.
+    #[cfg(feature = "regex")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "regex")))]
+    #[must_use]
+    pub fn with_name_regex(mut self, regex: regex::Regex) -> Self {
+        self.name = Some(NamePattern::Regex(regex));
+        self
+    }
+
+    /// Only matches processes that were created by the process with the given id.
+    #[must_use]
+    pub fn with_parent_pid(mut self, parent_pid: u32) -> Self {
+        self.parent_pid = Some(parent_pid);
+        self
+    }
+
+    /// Only matches processes with the given architecture.
+    ///
+    /// # Note
+    /// Determining the architecture requires opening a handle to the process (see [`ProcessInfo::architecture`]),
+    /// so this filter is only checked for processes that match all other filters.
+    /// Processes whose architecture cannot be determined never match.
+    #[must_use]
+    pub fn with_architecture(mut self, architecture: ProcessArchitecture) -> Self {
+        self.architecture = Some(architecture);
+        self
+    }
+
+    /// Returns whether the given process matches all filters of this query.
+    #[must_use]
+    pub fn matches(&self, process: &ProcessInfo) -> bool {
+        if let Some(name) = &self.name {
+            if !name.matches(process.name()) {
+                return false;
+            }
+        }
+        if let Some(parent_pid) = self.parent_pid {
+            if process.parent_pid() != parent_pid {
+                return false;
+            }
+        }
+        if let Some(architecture) = self.architecture {
+            if process.architecture().ok() != Some(architecture) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns the information about all running processes that match this query.
+    pub fn find_infos(&self) -> Result<Vec<ProcessInfo>, io::Error> {
+        Ok(ProcessInfo::all()?
+            .into_iter()
+            .filter(|process| self.matches(process))
+            .collect())
+    }
+
+    /// Opens all running processes that match this query.
+    /// Processes that cannot be opened are skipped.
+    pub fn find_all(&self) -> Result<Vec<OwnedProcess>, io::Error> {
+        Ok(ProcessInfo::all()?
+            .iter()
+            .filter(|process| self.matches(process))
+            .filter_map(|process| process.open().ok())
+            .collect())
+    }
+
+    /// Opens the first running process that matches this query.
+    /// Processes that cannot be opened are skipped.
+    pub fn find_first(&self) -> Result<Option<OwnedProcess>, io::Error> {
+        Ok(ProcessInfo::all()?
+            .iter()
+            .filter(|process| self.matches(process))
+            .find_map(|process| process.open().ok()))
+    }
+}
+
+/// Matches the given text against a glob pattern supporting `*` and `?`.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+
+    let mut p = 0;
+    let mut t = 0;
+    // the position of the last `*` in the pattern and the position in the text it is matched up to.
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    backtrack = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_matches_literals() {
+        assert!(glob_matches("notepad.exe", "notepad.exe"));
+        assert!(!glob_matches("notepad.exe", "notepad.ex"));
+        assert!(!glob_matches("notepad.ex", "notepad.exe"));
+        assert!(glob_matches("", ""));
+        assert!(!glob_matches("", "a"));
+    }
+
+    #[test]
+    fn glob_matches_wildcards() {
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("*", "notepad.exe"));
+        assert!(glob_matches("note*.exe", "notepad.exe"));
+        assert!(glob_matches("*pad*", "notepad.exe"));
+        assert!(glob_matches("n?tepad.exe", "notepad.exe"));
+        assert!(glob_matches("*.exe", "a.exe.exe"));
+        assert!(glob_matches("a*b*c", "aXbYbZc"));
+        assert!(!glob_matches("a*b*c", "aXbYbZ"));
+        assert!(!glob_matches("?", ""));
+        assert!(!glob_matches("*.dll", "notepad.exe"));
+    }
+
+    #[test]
+    fn name_patterns_ignore_case() {
+        let name = OsStr::new("NotePad.exe");
+        assert!(NamePattern::IgnoreCase("notepad.exe".to_string()).matches(name));
+        assert!(NamePattern::Glob("note*".to_string()).matches(name));
+        assert!(!NamePattern::Exact("notepad.exe".into()).matches(name));
+        assert!(NamePattern::Exact("NotePad.exe".into()).matches(name));
+    }
+}
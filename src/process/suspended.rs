@@ -0,0 +1,124 @@
+use std::{io, mem, os::windows::process::CommandExt, process::Command, ptr};
+
+use winapi::um::{winbase::CREATE_SUSPENDED, winnt::THREAD_SUSPEND_RESUME};
+
+use crate::process::{
+    memory::ProcessMemoryBuffer, thread_ids_of_process, BorrowedProcess, OwnedProcess, Process,
+    ProcessArchitecture, ThreadHandle,
+};
+
+/// A newly spawned process whose main thread has not started running yet.
+///
+/// # Note
+/// If the process is dropped without being [resumed](SuspendedProcess::resume) it is killed, as it never ran any of its own code.
+#[derive(Debug)]
+pub struct SuspendedProcess {
+    process: Option<OwnedProcess>,
+    main_thread: ThreadHandle,
+    main_thread_id: u32,
+}
+
+impl SuspendedProcess {
+    /// Spawns the given command as a new process with a suspended main thread.
+    ///
+    /// # Note
+    /// This overrides any creation flags previously set on the given command.
+    pub fn spawn(command: &mut Command) -> Result<Self, io::Error> {
+        let child = command.creation_flags(CREATE_SUSPENDED).spawn()?;
+        let pid = child.id();
+        let process = OwnedProcess::from_child(child);
+
+        match Self::open_main_thread(pid) {
+            Ok((main_thread_id, main_thread)) => Ok(Self {
+                process: Some(process),
+                main_thread,
+                main_thread_id,
+            }),
+            Err(err) => {
+                let _ = process.kill();
+                Err(err)
+            }
+        }
+    }
+
+    fn open_main_thread(pid: u32) -> Result<(u32, ThreadHandle), io::Error> {
+        // the main thread is the only thread of a process that was just created.
+        let main_thread_id = thread_ids_of_process(pid)?
+            .first()
+            .copied()
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, "spawned process has no threads")
+            })?;
+        let main_thread = ThreadHandle::open(main_thread_id, THREAD_SUSPEND_RESUME)?;
+        Ok((main_thread_id, main_thread))
+    }
+
+    /// Returns a borrowed instance of the suspended process.
+    #[must_use]
+    pub fn process(&self) -> BorrowedProcess<'_> {
+        self.process.as_ref().unwrap().borrowed()
+    }
+
+    /// Returns the id of the suspended main thread.
+    #[must_use]
+    pub fn main_thread_id(&self) -> u32 {
+        self.main_thread_id
+    }
+
+    /// Runs the process initialization of the loader on a temporary thread, while the main thread stays suspended.
+    ///
+    /// A suspended process only has `ntdll.dll` and its executable mapped. Afterwards `kernel32.dll` and all static
+    /// dependencies of the executable are loaded and initialized, so that further modules can be injected.
+    /// The entry point of the executable is not run until the process is [resumed](SuspendedProcess::resume).
+    pub fn initialize_loader(&self) -> Result<(), io::Error> {
+        // an empty thread procedure, the loader initialization runs before it is called.
+        let code: &[u8] = match self.process().architecture()? {
+            ProcessArchitecture::X86 | ProcessArchitecture::X86OnArm64 => &[
+                0x33, 0xC0, // xor eax, eax
+                0xC2, 0x04, 0x00, // ret 4
+            ],
+            ProcessArchitecture::X64 | ProcessArchitecture::Arm64EC => &[
+                0x33, 0xC0, // xor eax, eax
+                0xC3, // ret
+            ],
+            ProcessArchitecture::Arm64 => &[
+                0x00, 0x00, 0x80, 0x52, // mov w0, #0
+                0xC0, 0x03, 0x5F, 0xD6, // ret
+            ],
+        };
+        let buffer = ProcessMemoryBuffer::allocate_code(self.process(), code.len())?;
+        buffer.write(0, code)?;
+        buffer.flush_instruction_cache()?;
+
+        self.process()
+            .run_remote_thread::<u8>(unsafe { mem::transmute(buffer.as_ptr()) }, ptr::null_mut())?;
+
+        Ok(())
+    }
+
+    /// Resumes the main thread of the process and returns the now running process.
+    pub fn resume(mut self) -> Result<OwnedProcess, io::Error> {
+        self.main_thread.resume()?;
+        Ok(self.process.take().unwrap())
+    }
+}
+
+impl Drop for SuspendedProcess {
+    fn drop(&mut self) {
+        if let Some(process) = &self.process {
+            let result = process.kill();
+            debug_assert!(
+                result.is_ok(),
+                "failed to kill suspended process: {:?}",
+                result
+            );
+        }
+    }
+}
+
+impl OwnedProcess {
+    /// Spawns the given command as a new process with a suspended main thread (see [`SuspendedProcess::spawn`]).
+    pub fn spawn_suspended(command: &mut Command) -> Result<SuspendedProcess, io::Error> {
+        SuspendedProcess::spawn(command)
+    }
+}
@@ -0,0 +1,142 @@
+use std::{
+    io,
+    mem::{self, MaybeUninit},
+    os::windows::prelude::{AsRawHandle, FromRawHandle, OwnedHandle, RawHandle},
+};
+
+use winapi::{
+    shared::minwindef::{DWORD, FALSE},
+    um::{
+        handleapi::INVALID_HANDLE_VALUE,
+        minwinbase::STILL_ACTIVE,
+        processthreadsapi::{
+            GetExitCodeThread, GetProcessIdOfThread, OpenThread, ResumeThread, SuspendThread,
+        },
+        synchapi::WaitForSingleObject,
+        tlhelp32::{
+            CreateToolhelp32Snapshot, Thread32First, Thread32Next, TH32CS_SNAPTHREAD, THREADENTRY32,
+        },
+        winbase::{INFINITE, WAIT_FAILED},
+    },
+};
+
+/// Returns the ids of all threads of the process with the given id.
+pub(crate) fn thread_ids_of_process(process_id: u32) -> Result<Vec<u32>, io::Error> {
+    let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0) };
+    if snapshot == INVALID_HANDLE_VALUE {
+        return Err(io::Error::last_os_error());
+    }
+    let snapshot = unsafe { OwnedHandle::from_raw_handle(snapshot) };
+
+    let mut entry = MaybeUninit::<THREADENTRY32>::zeroed();
+    unsafe { (*entry.as_mut_ptr()).dwSize = mem::size_of::<THREADENTRY32>() as DWORD };
+
+    let mut thread_ids = Vec::new();
+    let mut result = unsafe { Thread32First(snapshot.as_raw_handle(), entry.as_mut_ptr()) };
+    while result != FALSE {
+        let entry_ref = unsafe { entry.assume_init_ref() };
+        if entry_ref.th32OwnerProcessID == process_id {
+            thread_ids.push(entry_ref.th32ThreadID);
+        }
+        result = unsafe { Thread32Next(snapshot.as_raw_handle(), entry.as_mut_ptr()) };
+    }
+
+    Ok(thread_ids)
+}
+
+/// Waits for the thread with the given handle to exit and returns its exit code.
+pub(crate) fn wait_for_thread_exit_code(thread_handle: RawHandle) -> Result<u32, io::Error> {
+    let reason = unsafe { WaitForSingleObject(thread_handle, INFINITE) };
+    if reason == WAIT_FAILED {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut exit_code = MaybeUninit::uninit();
+    let result = unsafe { GetExitCodeThread(thread_handle, exit_code.as_mut_ptr()) };
+    if result == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    debug_assert_ne!(
+        result as u32, STILL_ACTIVE,
+        "GetExitCodeThread returned STILL_ACTIVE after WaitForSingleObject"
+    );
+
+    Ok(unsafe { exit_code.assume_init() })
+}
+
+/// An owned handle to a thread of a (remote) process.
+#[derive(Debug)]
+#[cfg_attr(not(feature = "syringe"), allow(dead_code))]
+pub(crate) struct ThreadHandle(OwnedHandle);
+
+#[cfg_attr(not(feature = "syringe"), allow(dead_code))]
+impl ThreadHandle {
+    /// Opens the thread with the given id with the given [access rights](https://docs.microsoft.com/en-us/windows/win32/procthread/thread-security-and-access-rights).
+    pub fn open(thread_id: u32, access: DWORD) -> Result<Self, io::Error> {
+        let handle = unsafe { OpenThread(access, FALSE, thread_id) };
+        if handle.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self(unsafe { OwnedHandle::from_raw_handle(handle) }))
+    }
+
+    /// Returns the id of the process the thread belongs to.
+    /// The handle has to have been opened with `THREAD_QUERY_LIMITED_INFORMATION` access.
+    pub fn process_id(&self) -> Result<u32, io::Error> {
+        let process_id = unsafe { GetProcessIdOfThread(self.as_raw_handle()) };
+        if process_id == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(process_id)
+    }
+
+    /// Suspends the thread until the returned guard is dropped.
+    pub fn suspend(&self) -> Result<SuspendedThread<'_>, io::Error> {
+        let result = unsafe { SuspendThread(self.as_raw_handle()) };
+        if result == DWORD::MAX {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(SuspendedThread(self))
+    }
+
+    /// Decrements the suspend count of the thread, resuming it once the count reaches zero.
+    pub fn resume(&self) -> Result<(), io::Error> {
+        let result = unsafe { ResumeThread(self.as_raw_handle()) };
+        if result == DWORD::MAX {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl AsRawHandle for ThreadHandle {
+    fn as_raw_handle(&self) -> std::os::windows::raw::HANDLE {
+        self.0.as_raw_handle()
+    }
+}
+
+/// A guard that resumes a suspended thread when dropped.
+#[derive(Debug)]
+#[cfg_attr(not(feature = "syringe"), allow(dead_code))]
+pub(crate) struct SuspendedThread<'a>(&'a ThreadHandle);
+
+impl SuspendedThread<'_> {
+    /// Resumes the suspended thread.
+    #[cfg_attr(not(feature = "syringe"), allow(dead_code))]
+    pub fn resume(self) -> Result<(), io::Error> {
+        let thread = self.0;
+        mem::forget(self);
+        thread.resume()
+    }
+}
+
+impl Drop for SuspendedThread<'_> {
+    fn drop(&mut self) {
+        let result = self.0.resume();
+        debug_assert!(
+            result.is_ok(),
+            "failed to resume suspended thread: {:?}",
+            result
+        );
+    }
+}
@@ -3,7 +3,7 @@ use std::io;
 use thiserror::Error;
 use winapi::shared::winerror::ERROR_PARTIAL_COPY;
 
-use crate::error::ExceptionCode;
+use crate::error::{ExceptionCode, MissingAccess};
 
 #[derive(Debug, Error)]
 #[cfg(feature = "rpc-core")]
@@ -21,6 +21,9 @@ pub enum RawRpcError {
     /// This can occur if it crashed or was terminated.
     #[error("inaccessible target process")]
     ProcessInaccessible,
+    /// Variant representing a handle to the target process that lacks the access rights required for the operation.
+    #[error("{}", _0)]
+    MissingAccess(#[from] MissingAccess),
     /// Variant representing an inaccessible target module.
     /// This can occur if the target module was ejected or unloaded.
     #[error("inaccessible target module")]
@@ -31,7 +34,9 @@ pub enum RawRpcError {
 #[cfg_attr(all(feature = "rpc-core", not(feature = "rpc-raw")), doc(hidden))]
 impl From<io::Error> for RawRpcError {
     fn from(err: io::Error) -> Self {
-        if err.raw_os_error() == Some(ERROR_PARTIAL_COPY as _)
+        if let Some(missing) = MissingAccess::from_io_error(&err) {
+            Self::MissingAccess(missing)
+        } else if err.raw_os_error() == Some(ERROR_PARTIAL_COPY as _)
             || err.kind() == io::ErrorKind::PermissionDenied
         {
             Self::ProcessInaccessible
@@ -64,6 +69,9 @@ pub enum PayloadRpcError {
     /// This can occur if it crashed or was terminated.
     #[error("inaccessible target process")]
     ProcessInaccessible,
+    /// Variant representing a handle to the target process that lacks the access rights required for the operation.
+    #[error("{}", _0)]
+    MissingAccess(#[from] MissingAccess),
     /// Variant representing an inaccessible target module.
     /// This can occur if the target module was ejected or unloaded.
     #[error("inaccessible target module")]
@@ -79,7 +87,9 @@ pub enum PayloadRpcError {
 #[cfg(feature = "rpc-payload")]
 impl From<io::Error> for PayloadRpcError {
     fn from(err: io::Error) -> Self {
-        if err.raw_os_error() == Some(ERROR_PARTIAL_COPY as _)
+        if let Some(missing) = MissingAccess::from_io_error(&err) {
+            Self::MissingAccess(missing)
+        } else if err.raw_os_error() == Some(ERROR_PARTIAL_COPY as _)
             || err.kind() == io::ErrorKind::PermissionDenied
         {
             Self::ProcessInaccessible
@@ -96,6 +106,7 @@ impl From<RawRpcError> for PayloadRpcError {
             RawRpcError::Io(err) => Self::Io(err),
             RawRpcError::RemoteException(code) => Self::RemoteException(code),
             RawRpcError::ProcessInaccessible => Self::ProcessInaccessible,
+            RawRpcError::MissingAccess(e) => Self::MissingAccess(e),
             RawRpcError::ModuleInaccessible => Self::ModuleInaccessible,
         }
     }
@@ -4,10 +4,11 @@ use std::marker::PhantomData;
 
 use crate::{
     error::LoadProcedureError,
+    execution::RemoteExecutor,
     function::{FunctionPtr, RawFunctionPtr},
     process::{
-        memory::{ProcessMemoryBuffer, RemoteBoxAllocator},
-        BorrowedProcess, BorrowedProcessModule, ModuleHandle,
+        memory::ProcessMemoryBuffer, BorrowedProcess, BorrowedProcessModule, ExportRef,
+        ModuleHandle,
     },
     rpc::{error::PayloadRpcError, RemoteRawProcedure, Truncate},
     utils::ArrayOrVecBuf,
@@ -22,18 +23,19 @@ impl Syringe {
     /// # Note
     /// The function does not have to be from an injected module.
     /// If the module is not loaded in the target process `Ok(None)` is returned.
+    /// The function can be referenced either by its name or by its ordinal (see [`ExportRef`]).
     ///
     /// # Safety
     /// The target function must abide by the given signature and has to be declared using the [`payload_procedure!`](crate::payload_procedure) macro.
-    pub unsafe fn get_payload_procedure<F: PayloadRpcFunctionPtr>(
+    pub unsafe fn get_payload_procedure<'a, F: PayloadRpcFunctionPtr>(
         &self,
         module: BorrowedProcessModule<'_>,
-        name: &str,
+        export: impl Into<ExportRef<'a>>,
     ) -> Result<Option<RemotePayloadProcedure<F>>, LoadProcedureError> {
-        match self.get_procedure_address(module, name) {
+        match self.get_procedure_address(module, export) {
             Ok(Some(procedure)) => Ok(Some(RemotePayloadProcedure::new(
                 unsafe { RealPayloadRpcFunctionPtr::from_ptr(procedure) },
-                self.remote_allocator.clone(),
+                self.executor.clone(),
                 module.handle(),
             ))),
             Ok(None) => Ok(None),
@@ -62,11 +64,11 @@ where
 {
     pub(crate) fn new(
         ptr: RealPayloadRpcFunctionPtr,
-        remote_allocator: RemoteBoxAllocator,
+        executor: RemoteExecutor,
         module_handle: ModuleHandle,
     ) -> Self {
         Self {
-            f: RemoteRawProcedure::new(ptr, remote_allocator, module_handle),
+            f: RemoteRawProcedure::new(ptr, executor, module_handle),
             phantom: PhantomData,
         }
     }
@@ -7,11 +7,14 @@ use std::{
 };
 
 use crate::{
+    arm64::{registers::*, Arm64Assembler},
     error::LoadProcedureError,
+    execution::RemoteExecutor,
     function::{Abi, FunctionPtr, RawFunctionPtr},
     process::{
         memory::{RemoteAllocation, RemoteBox, RemoteBoxAllocator},
-        BorrowedProcess, BorrowedProcessModule, ModuleHandle, Process, ProcessModule,
+        BorrowedProcess, BorrowedProcessModule, ExportRef, ModuleHandle, Process,
+        ProcessArchitecture, ProcessModule,
     },
     rpc::error::RawRpcError,
     Syringe,
@@ -27,18 +30,19 @@ impl Syringe {
     /// # Note
     /// The function does not have to be from an injected module.
     /// If the module is not loaded in the target process `Ok(None)` is returned.
+    /// The function can be referenced either by its name or by its ordinal (see [`ExportRef`]).
     ///
     /// # Safety
     /// The target function must abide by the given signature.
-    pub unsafe fn get_raw_procedure<F: RawRpcFunctionPtr>(
+    pub unsafe fn get_raw_procedure<'a, F: RawRpcFunctionPtr>(
         &self,
         module: BorrowedProcessModule<'_>,
-        name: &str,
+        export: impl Into<ExportRef<'a>>,
     ) -> Result<Option<RemoteRawProcedure<F>>, LoadProcedureError> {
-        match self.get_procedure_address(module, name) {
+        match self.get_procedure_address(module, export) {
             Ok(Some(procedure)) => Ok(Some(RemoteRawProcedure::new(
                 unsafe { F::from_ptr(procedure) },
-                self.remote_allocator.clone(),
+                self.executor.clone(),
                 module.handle(),
             ))),
             Ok(None) => Ok(None),
@@ -56,6 +60,7 @@ pub trait RawRpcFunctionPtr: FunctionPtr {}
 pub struct RemoteRawProcedure<F> {
     ptr: F,
     pub(crate) remote_allocator: RemoteBoxAllocator,
+    executor: RemoteExecutor,
     stub: OnceCell<RemoteRawProcedureStub>,
     module_handle: ModuleHandle,
 }
@@ -82,14 +87,11 @@ impl<F> RemoteRawProcedure<F>
 where
     F: FunctionPtr,
 {
-    pub(crate) fn new(
-        ptr: F,
-        remote_allocator: RemoteBoxAllocator,
-        module_handle: ModuleHandle,
-    ) -> Self {
+    pub(crate) fn new(ptr: F, executor: RemoteExecutor, module_handle: ModuleHandle) -> Self {
         Self {
             ptr,
-            remote_allocator,
+            remote_allocator: executor.remote_allocator().clone(),
+            executor,
             stub: OnceCell::new(),
             module_handle,
         }
@@ -132,7 +134,7 @@ where
 
         stub.parameter.memory().write_struct(0, args)?;
 
-        let exit_code = stub.code.process().run_remote_thread(
+        let exit_code = self.executor.run(
             unsafe { mem::transmute(stub.code.as_raw_ptr()) },
             stub.parameter.as_raw_ptr(),
         )?;
@@ -159,10 +161,18 @@ where
             let result = self.remote_allocator.alloc_uninit::<usize>()?;
 
             let float_mask = <F::NonExtern>::build_float_mask();
-            let code = if self.process().is_x86()? {
-                Self::build_call_stub_x86(self.ptr, result.as_ptr().as_ptr(), float_mask).unwrap()
-            } else {
-                Self::build_call_stub_x64(self.ptr, result.as_ptr().as_ptr(), float_mask).unwrap()
+            let code = match self.remote_allocator.process_architecture()? {
+                ProcessArchitecture::X86 | ProcessArchitecture::X86OnArm64 => {
+                    Self::build_call_stub_x86(self.ptr, result.as_ptr().as_ptr(), float_mask)
+                        .unwrap()
+                }
+                ProcessArchitecture::X64 | ProcessArchitecture::Arm64EC => {
+                    Self::build_call_stub_x64(self.ptr, result.as_ptr().as_ptr(), float_mask)
+                        .unwrap()
+                }
+                ProcessArchitecture::Arm64 => {
+                    Self::build_call_stub_arm64(self.ptr, result.as_ptr().as_ptr(), float_mask)
+                }
             };
             let code = self.remote_allocator.alloc_and_copy_buf(code.as_slice())?;
             code.memory().flush_instruction_cache()?;
@@ -295,6 +305,60 @@ where
 
         Ok(code)
     }
+
+    fn build_call_stub_arm64(procedure: F, result_buf: *mut usize, float_mask: u32) -> Vec<u8> {
+        assert!(!result_buf.is_null());
+
+        let mut asm = Arm64Assembler::new();
+
+        asm.stp_pre_index(X29, X30, SP, -16); // save frame pointer and link register
+        asm.mov(X29, SP);
+        asm.mov(X9, X0); // arg base ptr
+
+        // integer and floating point arguments are assigned to their registers independently,
+        // the remaining arguments are passed on the stack in 8 byte slots.
+        let mut next_register = 0;
+        let mut next_float_register = 0;
+        let mut stack_args = Vec::new();
+        for i in 0..F::ARITY {
+            let is_float = float_mask & (1 << i) != 0;
+            let offset = (i * mem::size_of::<u64>()) as u32;
+            if is_float && next_float_register < FLOAT_ARGUMENT_REGISTERS.len() {
+                asm.ldr_d(FLOAT_ARGUMENT_REGISTERS[next_float_register], X9, offset);
+                next_float_register += 1;
+            } else if !is_float && next_register < ARGUMENT_REGISTERS.len() {
+                asm.ldr(ARGUMENT_REGISTERS[next_register], X9, offset);
+                next_register += 1;
+            } else {
+                stack_args.push(offset);
+            }
+        }
+        if !stack_args.is_empty() {
+            let stack_size = (stack_args.len() * mem::size_of::<u64>() + 15) & !15; // keep stack 16 byte aligned
+            asm.sub(SP, SP, stack_size as u32);
+            for (slot, offset) in stack_args.into_iter().enumerate() {
+                asm.ldr(X10, X9, offset);
+                asm.str(X10, SP, (slot * mem::size_of::<u64>()) as u32);
+            }
+        }
+
+        asm.mov_imm64(X16, procedure.as_ptr() as u64);
+        asm.blr(X16);
+        asm.mov(SP, X29); // pop stack args
+
+        // write result to result buf
+        if float_mask & 0x8000_0000u32 != 0 {
+            asm.fmov_from_d(X0, D0);
+        }
+        asm.mov_imm64(X17, result_buf as u64);
+        asm.str(X0, X17, 0);
+
+        asm.mov_imm32(X0, 0); // return 0
+        asm.ldp_post_index(X29, X30, SP, 16);
+        asm.ret();
+
+        asm.assemble()
+    }
 }
 
 fn type_eq<T: ?Sized + 'static, U: ?Sized + 'static>() -> bool {
@@ -340,11 +404,11 @@ macro_rules! impl_call {
 
         impl <$($ty,)* Output> RemoteRawProcedure<fn($($ty),*) -> Output> where $($ty : 'static + Copy,)* Output: 'static + Copy  {
             #[allow(clippy::too_many_arguments)]
-            fn build_args_buf(process: BorrowedProcess<'_>, $($nm: $ty),*) -> io::Result<[usize; impl_call!(@count ($($ty)*))]> {
-                let target_pointer_size = if process.is_x86()? {
-                    mem::size_of::<u32>()
-                } else {
+            fn build_args_buf(architecture: ProcessArchitecture, $($nm: $ty),*) -> io::Result<[usize; impl_call!(@count ($($ty)*))]> {
+                let target_pointer_size = if architecture.is_64_bit() {
                     mem::size_of::<u64>()
+                } else {
+                    mem::size_of::<u32>()
                 };
 
                 let truncate_prefix = any::type_name::<Truncate<()>>();
@@ -386,8 +450,13 @@ macro_rules! impl_call {
         impl <$($ty,)* Output> BuildFloatMask for fn($($ty),*) -> Output where $($ty : 'static,)* Output: 'static {
             fn build_float_mask() -> u32 {
                 // calculate a mask denoting which arguments are floats
+                let float_args: [bool; impl_call!(@count ($($ty)*))] = [$(type_eq::<$ty, f32>() || type_eq::<$ty, f64>()),*];
                 let mut float_mask = 0u32;
-                $(float_mask = (float_mask << 1) | if type_eq::<$ty, f32>() || type_eq::<$ty, f64>() { 1 } else { 0 };)*
+                for (i, is_float) in float_args.into_iter().enumerate() {
+                    if is_float {
+                        float_mask |= 1 << i;
+                    }
+                }
                 float_mask |= if type_eq::<Output, f32>() || type_eq::<Output, f64>() { 0x8000_0000u32 } else { 0 };
 
                 float_mask
@@ -399,7 +468,7 @@ macro_rules! impl_call {
             /// The arguments and the return value are copied bytewise.
             #[allow(clippy::too_many_arguments)]
             pub fn call(&self, $($nm: $ty),*) -> Result<Output, RawRpcError> {
-                let args_buf = RemoteRawProcedure::<fn($($ty),*) -> Output>::build_args_buf(self.process(), $($nm),*)?;
+                let args_buf = RemoteRawProcedure::<fn($($ty),*) -> Output>::build_args_buf(self.remote_allocator.process_architecture()?, $($nm),*)?;
                 self.call_with_args(&args_buf)
             }
         }
@@ -408,7 +477,7 @@ macro_rules! impl_call {
             /// The arguments and the return value are copied bytewise.
             #[allow(clippy::too_many_arguments)]
             pub fn call(&self, $($nm: $ty),*) -> Result<Output, RawRpcError> {
-                let args_buf = RemoteRawProcedure::<fn($($ty),*) -> Output>::build_args_buf(self.process(), $($nm),*)?;
+                let args_buf = RemoteRawProcedure::<fn($($ty),*) -> Output>::build_args_buf(self.remote_allocator.process_architecture()?, $($nm),*)?;
                 self.call_with_args(&args_buf)
             }
         }
@@ -420,7 +489,7 @@ macro_rules! impl_call {
             /// The caller must ensure whatever the requirements of the underlying remote procedure are.
             #[allow(clippy::too_many_arguments)]
             pub unsafe fn call(&self, $($nm: $ty),*) -> Result<Output, RawRpcError> {
-                let args_buf = RemoteRawProcedure::<fn($($ty),*) -> Output>::build_args_buf(self.process(), $($nm),*)?;
+                let args_buf = RemoteRawProcedure::<fn($($ty),*) -> Output>::build_args_buf(self.remote_allocator.process_architecture()?, $($nm),*)?;
                 self.call_with_args(&args_buf)
             }
         }
@@ -432,7 +501,7 @@ macro_rules! impl_call {
             /// The caller must ensure whatever the requirements of the underlying remote procedure are.
             #[allow(clippy::too_many_arguments)]
             pub unsafe fn call(&self, $($nm: $ty),*) -> Result<Output, RawRpcError> {
-                let args_buf = RemoteRawProcedure::<fn($($ty),*) -> Output>::build_args_buf(self.process(), $($nm),*)?;
+                let args_buf = RemoteRawProcedure::<fn($($ty),*) -> Output>::build_args_buf(self.remote_allocator.process_architecture()?, $($nm),*)?;
                 self.call_with_args(&args_buf)
             }
         }
@@ -1,49 +1,204 @@
 use iced_x86::{code_asm::*, IcedError};
 
 use std::{
-    ffi::CString,
-    mem,
+    borrow::Cow,
+    ffi::{CString, OsStr},
+    io, mem,
     ptr::{self, NonNull},
 };
 
 use crate::{
+    arm64::{registers::*, Arm64Assembler},
     error::LoadProcedureError,
     function::{FunctionPtr, RawFunctionPtr},
+    pe::{ExportLookup, ExportTable, ExportTarget, PeHeaders, IMAGE_DIRECTORY_ENTRY_EXPORT},
     process::{
-        memory::{RemoteAllocation, RemoteBox},
-        BorrowedProcessModule, Process,
+        is_api_set_name,
+        memory::{ProcessMemoryBuffer, ProcessMemorySlice, RemoteAllocation, RemoteBox},
+        ApiSetSchema, BorrowedProcessModule, ExportRef, ModuleListFilter, Process,
+        ProcessArchitecture, MAX_FORWARDER_DEPTH,
     },
     rpc::error::RawRpcError,
     GetProcAddressFn, Syringe,
 };
 
+/// The result of resolving an export by reading the export directory of a remote module.
+enum LocalExport {
+    /// The export was resolved to the given address.
+    Resolved(RawFunctionPtr),
+    /// The module does not export the symbol.
+    Missing,
+    /// The export can only be resolved by the loader of the target process
+    /// (e.g. because it is forwarded to a module that is not loaded yet).
+    Unresolvable,
+}
+
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "rpc-core")))]
 impl Syringe {
     /// Load the address of the given function from the given module in the remote process.
-    pub fn get_procedure_address(
+    /// The function can be referenced either by its name or by its ordinal (see [`ExportRef`]).
+    ///
+    /// The export directory of the module is read from the memory of the target process, so that most lookups do not
+    /// require running code in the target process. Forwarded exports are followed, including forwarders to api sets,
+    /// which are redirected using the api set schema. Only exports that are forwarded to modules that are not loaded
+    /// yet are resolved using `GetProcAddress` inside the target process.
+    pub fn get_procedure_address<'a>(
         &self,
         module: BorrowedProcessModule<'_>,
-        name: impl AsRef<str>,
+        export: impl Into<ExportRef<'a>>,
     ) -> Result<Option<RawFunctionPtr>, LoadProcedureError> {
         assert!(
             module.process() == &self.process(),
             "trying to get a procedure from a module from a different process"
         );
 
+        let export = export.into();
+        let lookup = match &export {
+            ExportRef::Name(name) => ExportLookup::Name(name.as_bytes()),
+            ExportRef::Ordinal(ordinal) => ExportLookup::Ordinal(*ordinal),
+        };
+        match self.resolve_export_locally(module, lookup, 0)? {
+            LocalExport::Resolved(procedure) => return Ok(Some(procedure)),
+            LocalExport::Missing => return Ok(None),
+            LocalExport::Unresolvable => {}
+        }
+
+        match export {
+            ExportRef::Name(name) => {
+                // a name with an interior nul byte cannot be exported.
+                let name = match CString::new(name.into_owned()) {
+                    Ok(name) => name,
+                    Err(_) => return Ok(None),
+                };
+                let name = self
+                    .remote_allocator
+                    .alloc_and_copy_buf(name.as_bytes_with_nul())?;
+                self.get_procedure_address_raw(module, name.as_raw_ptr() as u64)
+            }
+            // GetProcAddress interprets a name pointer with a zero high-order word as an ordinal.
+            ExportRef::Ordinal(ordinal) => {
+                self.get_procedure_address_raw(module, u64::from(ordinal))
+            }
+        }
+    }
+
+    /// Resolves the given export by reading the export directory of the module from the memory of the target process,
+    /// which is a lot cheaper than running `GetProcAddress` on a remote thread.
+    /// Forwarded exports are followed as long as the module they are forwarded to is already loaded.
+    fn resolve_export_locally(
+        &self,
+        module: BorrowedProcessModule<'_>,
+        symbol: ExportLookup<'_>,
+        depth: usize,
+    ) -> Result<LocalExport, LoadProcedureError> {
+        if depth > MAX_FORWARDER_DEPTH {
+            return Ok(LocalExport::Unresolvable);
+        }
+
+        let (directory, directory_rva) = match Self::read_export_directory(module) {
+            Ok(Some(directory)) => directory,
+            Ok(None) => return Ok(LocalExport::Missing),
+            Err(_) => return Ok(LocalExport::Unresolvable),
+        };
+        let export = match ExportTable::parse(&directory, directory_rva)
+            .and_then(|table| table.find(symbol))
+        {
+            Ok(Some(export)) => export,
+            Ok(None) => return Ok(LocalExport::Missing),
+            Err(_) => return Ok(LocalExport::Unresolvable),
+        };
+
+        match export.target {
+            ExportTarget::Rva(rva) => Ok(LocalExport::Resolved(
+                (module.handle() as usize).wrapping_add(rva as usize) as RawFunctionPtr,
+            )),
+            ExportTarget::Forwarder {
+                module: forwarded_module,
+                symbol,
+            } => {
+                let forwarded_module = if is_api_set_name(forwarded_module) {
+                    // api sets are redirected by the loader using the api set schema, which is shared by all processes.
+                    let importing_module = module.base_name().ok();
+                    let importing_module = importing_module.as_deref().and_then(OsStr::to_str);
+                    match ApiSetSchema::current()
+                        .ok()
+                        .flatten()
+                        .and_then(|schema| schema.resolve(forwarded_module, importing_module))
+                    {
+                        Some(host) => Cow::Owned(host),
+                        None => return Ok(LocalExport::Unresolvable),
+                    }
+                } else {
+                    Cow::Borrowed(forwarded_module)
+                };
+
+                // a wow64 process also contains 64-bit modules with the same names as the ones it uses (e.g. ntdll.dll).
+                let filter = if self.remote_allocator.process_architecture()?.is_64_bit() {
+                    ModuleListFilter::Only64Bit
+                } else {
+                    ModuleListFilter::Only32Bit
+                };
+                match self
+                    .process()
+                    .find_module_by_name_with_filter(&*forwarded_module, filter)?
+                {
+                    Some(forwarded_module) => {
+                        self.resolve_export_locally(forwarded_module, symbol, depth + 1)
+                    }
+                    None => Ok(LocalExport::Unresolvable),
+                }
+            }
+        }
+    }
+
+    /// Reads the bytes of the export directory of the given module and the rva they are located at.
+    /// Returns `None` if the module does not have an export directory.
+    fn read_export_directory(
+        module: BorrowedProcessModule<'_>,
+    ) -> Result<Option<(Vec<u8>, u32)>, io::Error> {
+        let process = *module.process();
+        let base = module.handle().cast::<u8>();
+
+        // the headers of a loaded module always fit into its first page.
+        let mut headers = vec![0; ProcessMemoryBuffer::os_page_size()];
+        unsafe { ProcessMemorySlice::from_raw_parts(base, headers.len(), process) }
+            .read(0, &mut headers)?;
+        let headers = PeHeaders::parse(&headers)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        let directory = match headers.data_directory(IMAGE_DIRECTORY_ENTRY_EXPORT) {
+            Some(directory) => directory,
+            None => return Ok(None),
+        };
+
+        let mut data = vec![0; directory.size as usize];
+        unsafe {
+            ProcessMemorySlice::from_raw_parts(
+                base.wrapping_add(directory.virtual_address as usize),
+                data.len(),
+                process,
+            )
+        }
+        .read(0, &mut data)?;
+
+        Ok(Some((data, directory.virtual_address)))
+    }
+
+    fn get_procedure_address_raw(
+        &self,
+        module: BorrowedProcessModule<'_>,
+        name: u64,
+    ) -> Result<Option<RawFunctionPtr>, LoadProcedureError> {
         let stub = self.build_get_proc_address_stub()?;
-        let name = name.as_ref();
-        let name = self
-            .remote_allocator
-            .alloc_and_copy_buf(CString::new(name).unwrap().as_bytes_with_nul())?;
         stub.parameter.write(&GetProcAddressParams {
             module_handle: module.handle() as u64,
-            name: name.as_raw_ptr() as u64,
+            name,
         })?;
 
         // clear the result
         stub.result.write(&ptr::null_mut())?;
 
-        let exit_code = self.remote_allocator.process().run_remote_thread(
+        let exit_code = self.executor.run(
             unsafe { mem::transmute(stub.code.as_raw_ptr()) },
             stub.parameter.as_raw_ptr(),
         )?;
@@ -57,9 +212,7 @@ impl Syringe {
     ) -> Result<&RemoteProcedureStub<GetProcAddressParams, RawFunctionPtr>, LoadProcedureError>
     {
         self.get_proc_address_stub.get_or_try_init(|| {
-            let inject_data = self.inject_help_data.get_or_try_init(|| {
-                Self::load_inject_help_data_for_process(self.remote_allocator.process())
-            })?;
+            let inject_data = self.inject_help_data()?;
 
             let remote_get_proc_address = inject_data.get_proc_address_fn_ptr();
 
@@ -69,18 +222,25 @@ impl Syringe {
             let result = self.remote_allocator.alloc_uninit::<RawFunctionPtr>()?;
 
             // Allocate memory in remote process and build a method stub.
-            let code = if self.remote_allocator.process().is_x86()? {
-                Syringe::build_get_proc_address_x86(
-                    remote_get_proc_address,
-                    result.as_ptr().as_ptr(),
-                )
-                .unwrap()
-            } else {
-                Syringe::build_get_proc_address_x64(
+            let code = match self.remote_allocator.process_architecture()? {
+                ProcessArchitecture::X86 | ProcessArchitecture::X86OnArm64 => {
+                    Syringe::build_get_proc_address_x86(
+                        remote_get_proc_address,
+                        result.as_ptr().as_ptr(),
+                    )
+                    .unwrap()
+                }
+                ProcessArchitecture::X64 | ProcessArchitecture::Arm64EC => {
+                    Syringe::build_get_proc_address_x64(
+                        remote_get_proc_address,
+                        result.as_ptr().as_ptr(),
+                    )
+                    .unwrap()
+                }
+                ProcessArchitecture::Arm64 => Syringe::build_get_proc_address_arm64(
                     remote_get_proc_address,
                     result.as_ptr().as_ptr(),
-                )
-                .unwrap()
+                ),
             };
             let function_stub = self.remote_allocator.alloc_and_copy_buf(code.as_slice())?;
             function_stub.memory().flush_instruction_cache()?;
@@ -167,6 +327,39 @@ impl Syringe {
 
         Ok(code)
     }
+
+    fn build_get_proc_address_arm64(
+        get_proc_address: GetProcAddressFn,
+        return_buffer: *mut RawFunctionPtr,
+    ) -> Vec<u8> {
+        assert!(!return_buffer.is_null());
+
+        //                                      // CreateRemoteThread lpParameter @ x0
+        // stp x29, x30, [sp, #-16]!            // save frame pointer and link register
+        // mov x29, sp
+        // ldr x1, [x0, #8]                     // lpProcName
+        // ldr x0, [x0, #0]                     // hModule
+        // blr GetProcAddress                   // [address loaded into x16]
+        // str x0, [ReturnAddress]              // [address loaded into x17]
+        // mov w0, #0                           // return 0
+        // ldp x29, x30, [sp], #16
+        // ret
+        let mut asm = Arm64Assembler::new();
+
+        asm.stp_pre_index(X29, X30, SP, -16);
+        asm.mov(X29, SP);
+        asm.ldr(X1, X0, 8); // lpProcName
+        asm.ldr(X0, X0, 0); // hModule
+        asm.mov_imm64(X16, get_proc_address.as_ptr() as u64);
+        asm.blr(X16);
+        asm.mov_imm64(X17, return_buffer as u64);
+        asm.str(X0, X17, 0);
+        asm.mov_imm32(X0, 0); // return 0
+        asm.ldp_post_index(X29, X30, SP, 16);
+        asm.ret();
+
+        asm.assemble()
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -1,7 +1,7 @@
 use cstr::cstr;
 use iced_x86::{
     code_asm::{
-        dword_ptr,
+        dword_ptr, qword_ptr,
         registers::{gpr32::*, gpr64::*},
         CodeAssembler,
     },
@@ -9,25 +9,54 @@ use iced_x86::{
 };
 use num_enum::TryFromPrimitive;
 use path_absolutize::Absolutize;
-use std::{cell::OnceCell, io, mem, path::Path};
+use std::{
+    cell::{Cell, OnceCell, RefCell},
+    ffi::OsStr,
+    io::{self, Write},
+    mem,
+    ops::Deref,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use tempfile::TempPath;
 use widestring::{u16cstr, U16CString};
-use winapi::shared::{
-    minwindef::{BOOL, DWORD, FALSE, HMODULE},
-    ntdef::LPCWSTR,
+use winapi::{
+    shared::{
+        minwindef::{BOOL, DWORD, FALSE, HMODULE},
+        ntdef::{HANDLE, LPCWSTR},
+    },
+    um::libloaderapi::DLL_DIRECTORY_COOKIE,
 };
 
 use crate::{
-    error::{EjectError, ExceptionCode, ExceptionOrIoError, InjectError, LoadInjectHelpDataError},
+    arm64::{registers::*, Arm64Assembler},
+    error::{
+        EjectError, ExceptionCode, ExceptionOrIoError, InjectError, LoadInjectHelpDataError,
+        MissingAccess, ReadImageError,
+    },
+    execution::{ExecutionStrategy, RemoteExecutor},
+    inject_diagnosis::InjectFailureDiagnosis,
+    inject_options::{InjectOptions, LoadLibraryFlags},
+    payload_info::PayloadInfo,
     process::{
         memory::{RemoteAllocation, RemoteBox, RemoteBoxAllocator},
-        BorrowedProcess, BorrowedProcessModule, ModuleHandle, OwnedProcess, Process, ProcessModule,
+        BorrowedProcess, BorrowedProcessModule, ModuleHandle, OwnedProcess, Process,
+        ProcessAccess, ProcessArchitecture, ProcessModule,
     },
 };
 
 #[cfg(all(target_arch = "x86_64", feature = "into-x86-from-x64"))]
 use {
-    goblin::pe::PE,
-    std::{convert::TryInto, fs, mem::MaybeUninit, path::PathBuf, time::Duration},
+    crate::process::{
+        is_api_set_name, ApiSetSchema, ExportRef, ModuleListFilter, MAX_FORWARDER_DEPTH,
+    },
+    goblin::pe::{
+        export::{ExportAddressTableEntry, Reexport},
+        options::ParseOptions,
+        utils::find_offset,
+        PE,
+    },
+    std::{borrow::Cow, convert::TryInto, fs, mem::MaybeUninit, time::Duration},
     widestring::U16Str,
     winapi::{shared::minwindef::MAX_PATH, um::wow64apiset::GetSystemWow64DirectoryW},
 };
@@ -39,6 +68,9 @@ use {
 };
 
 type LoadLibraryWFn = unsafe extern "system" fn(LPCWSTR) -> HMODULE;
+type LoadLibraryExWFn = unsafe extern "system" fn(LPCWSTR, HANDLE, DWORD) -> HMODULE;
+type AddDllDirectoryFn = unsafe extern "system" fn(LPCWSTR) -> DLL_DIRECTORY_COOKIE;
+type RemoveDllDirectoryFn = unsafe extern "system" fn(DLL_DIRECTORY_COOKIE) -> BOOL;
 type FreeLibraryFn = unsafe extern "system" fn(HMODULE) -> BOOL;
 type GetLastErrorFn = unsafe extern "system" fn() -> DWORD;
 #[cfg(feature = "rpc-core")]
@@ -48,6 +80,10 @@ pub(crate) type GetProcAddressFn = unsafe extern "system" fn(HMODULE, LPCSTR) ->
 pub(crate) struct InjectHelpData {
     kernel32_module: ModuleHandle,
     load_library_offset: usize,
+    load_library_ex_offset: usize,
+    /// `AddDllDirectory` and `RemoveDllDirectory` are missing on Windows 7 without KB2533623.
+    add_dll_directory_offset: Option<usize>,
+    remove_dll_directory_offset: Option<usize>,
     free_library_offset: usize,
     get_last_error_offset: usize,
     #[cfg(feature = "rpc-core")]
@@ -57,18 +93,32 @@ pub(crate) struct InjectHelpData {
 unsafe impl Send for InjectHelpData {}
 
 impl InjectHelpData {
+    fn kernel32_address(&self, offset: usize) -> usize {
+        (self.kernel32_module as usize).wrapping_add(offset)
+    }
     pub fn get_load_library_fn_ptr(&self) -> LoadLibraryWFn {
-        unsafe { mem::transmute(self.kernel32_module as usize + self.load_library_offset) }
+        unsafe { mem::transmute(self.kernel32_address(self.load_library_offset)) }
+    }
+    pub fn get_load_library_ex_fn_ptr(&self) -> LoadLibraryExWFn {
+        unsafe { mem::transmute(self.kernel32_address(self.load_library_ex_offset)) }
+    }
+    pub fn get_add_dll_directory_fn_ptr(&self) -> Option<AddDllDirectoryFn> {
+        self.add_dll_directory_offset
+            .map(|offset| unsafe { mem::transmute(self.kernel32_address(offset)) })
+    }
+    pub fn get_remove_dll_directory_fn_ptr(&self) -> Option<RemoveDllDirectoryFn> {
+        self.remove_dll_directory_offset
+            .map(|offset| unsafe { mem::transmute(self.kernel32_address(offset)) })
     }
     pub fn get_free_library_fn_ptr(&self) -> FreeLibraryFn {
-        unsafe { mem::transmute(self.kernel32_module as usize + self.free_library_offset) }
+        unsafe { mem::transmute(self.kernel32_address(self.free_library_offset)) }
     }
     pub fn get_get_last_error(&self) -> GetLastErrorFn {
-        unsafe { mem::transmute(self.kernel32_module as usize + self.get_last_error_offset) }
+        unsafe { mem::transmute(self.kernel32_address(self.get_last_error_offset)) }
     }
     #[cfg(feature = "rpc-core")]
     pub fn get_proc_address_fn_ptr(&self) -> GetProcAddressFn {
-        unsafe { mem::transmute(self.kernel32_module as usize + self.get_proc_address_offset) }
+        unsafe { mem::transmute(self.kernel32_address(self.get_proc_address_offset)) }
     }
 }
 
@@ -79,7 +129,7 @@ impl InjectHelpData {
 /// use dll_syringe::{Syringe, process::OwnedProcess};
 ///
 /// // find target process by name
-/// let target_process = OwnedProcess::find_first_by_name("target_process").unwrap();
+/// let target_process = OwnedProcess::find_first_by_name("target_process").unwrap().unwrap();
 ///
 /// // create a new syringe for the target process
 /// let mut syringe = Syringe::for_process(target_process);
@@ -97,30 +147,135 @@ impl InjectHelpData {
 pub struct Syringe {
     pub(crate) inject_help_data: OnceCell<InjectHelpData>,
     pub(crate) remote_allocator: RemoteBoxAllocator,
+    pub(crate) executor: RemoteExecutor,
     load_library_w_stub: OnceCell<LoadLibraryWStub>,
+    load_library_w_batch_stub: OnceCell<LoadLibraryWBatchStub>,
+    load_library_ex_w_stub: OnceCell<LoadLibraryExWStub>,
+    free_library_loop_stub: OnceCell<FreeLibraryLoopStub>,
     #[cfg(feature = "rpc-core")]
     pub(crate) get_proc_address_stub:
         OnceCell<crate::rpc::RemoteProcedureStub<crate::rpc::GetProcAddressParams, RawFunctionPtr>>,
+    #[cfg(all(target_arch = "x86", feature = "into-x64-from-x86"))]
+    x64_injector: OnceCell<crate::into_x64::X64Injector>,
+    /// The modules injected using this syringe that were not ejected yet, in the order they were injected.
+    injected_modules: RefCell<Vec<ModuleHandle>>,
+    /// The temporary files backing the modules injected using [`Syringe::inject_from_bytes`] that were not ejected yet.
+    temp_payload_files: RefCell<Vec<(ModuleHandle, TempPath)>>,
+    eject_on_drop: Cell<bool>,
 }
 
 impl Syringe {
     /// Creates a new syringe for the given target process.
+    ///
+    /// The access rights of the handle are checked once the first operation runs. If the handle lacks any of the rights
+    /// required for injection ([`ProcessAccess::INJECTION`]), operations fail with a `MissingAccess` error.
+    /// See [`Syringe::try_for_process`] for a version that checks them upfront.
     #[must_use]
     pub fn for_process(process: OwnedProcess) -> Self {
+        Self::with_execution_strategy(process, ExecutionStrategy::default())
+    }
+
+    /// Creates a new syringe for the given target process or returns an error if the handle of the process
+    /// lacks the access rights required for injection ([`ProcessAccess::INJECTION`]).
+    pub fn try_for_process(process: OwnedProcess) -> Result<Self, MissingAccess> {
+        Self::try_with_execution_strategy(process, ExecutionStrategy::default())
+    }
+
+    /// Creates a new syringe for the given target process that executes remote code using the given [`ExecutionStrategy`].
+    ///
+    /// The access rights of the handle are checked once the first operation runs (see [`Syringe::for_process`]).
+    /// See [`Syringe::try_with_execution_strategy`] for a version that checks them upfront.
+    #[must_use]
+    pub fn with_execution_strategy(process: OwnedProcess, strategy: ExecutionStrategy) -> Self {
+        let remote_allocator = RemoteBoxAllocator::new(process);
         Self {
-            remote_allocator: RemoteBoxAllocator::new(process),
+            executor: RemoteExecutor::new(remote_allocator.clone(), strategy),
+            remote_allocator,
             inject_help_data: OnceCell::new(),
             load_library_w_stub: OnceCell::new(),
+            load_library_w_batch_stub: OnceCell::new(),
+            load_library_ex_w_stub: OnceCell::new(),
+            free_library_loop_stub: OnceCell::new(),
             #[cfg(feature = "rpc-core")]
             get_proc_address_stub: OnceCell::new(),
+            #[cfg(all(target_arch = "x86", feature = "into-x64-from-x86"))]
+            x64_injector: OnceCell::new(),
+            injected_modules: RefCell::new(Vec::new()),
+            temp_payload_files: RefCell::new(Vec::new()),
+            eject_on_drop: Cell::new(false),
         }
     }
 
+    /// Creates a new syringe for the given target process that executes remote code using the given [`ExecutionStrategy`]
+    /// or returns an error if the handle of the process lacks the access rights required for injection ([`ProcessAccess::INJECTION`]).
+    pub fn try_with_execution_strategy(
+        process: OwnedProcess,
+        strategy: ExecutionStrategy,
+    ) -> Result<Self, MissingAccess> {
+        process.ensure_access(ProcessAccess::INJECTION)?;
+        Ok(Self::with_execution_strategy(process, strategy))
+    }
+
+    /// Spawns the given command as a new process and injects the given modules before any code of the process itself runs.
+    ///
+    /// The process is created suspended and its loader is initialized on a temporary thread (see [`SuspendedProcess::initialize_loader`](crate::process::SuspendedProcess::initialize_loader)),
+    /// so that `kernel32.dll` and the static dependencies of the executable are loaded. Afterwards the modules are injected in the given order
+    /// and the main thread of the process is resumed. If an injection fails, the process is killed.
+    ///
+    /// # Note
+    /// The returned syringe uses [`ExecutionStrategy::RemoteThread`].
+    ///
+    /// # Limitations
+    /// - The target process and the given modules need to be of the same bitness.
+    /// - If the current process is `x64` the target process can be either `x64` (always available) or `x86` (with the `into_x86_from_x64` feature enabled).
+    /// - If the current process is `x86` the target process can only be `x86`.
+    pub fn launch_and_inject(
+        command: &mut Command,
+        payload_paths: &[impl AsRef<Path>],
+    ) -> Result<Self, InjectError> {
+        let process = OwnedProcess::spawn_suspended(command).map_err(InjectError::Io)?;
+        process.initialize_loader()?;
+
+        let syringe = Self::for_process(process.process().try_to_owned()?);
+        for result in syringe.inject_many(payload_paths)? {
+            result.map_err(InjectError::RemoteIo)?;
+        }
+
+        process.resume()?;
+        Ok(syringe)
+    }
+
     /// Returns the target process for this syringe.
     pub fn process(&self) -> BorrowedProcess<'_> {
         self.remote_allocator.process()
     }
 
+    /// Returns the [`ExecutionStrategy`] used to execute code in the target process.
+    #[must_use]
+    pub fn execution_strategy(&self) -> ExecutionStrategy {
+        self.executor.strategy()
+    }
+
+    /// Sets the [`ExecutionStrategy`] used to execute code in the target process.
+    /// The new strategy is also used by all procedures previously loaded using this syringe.
+    pub fn set_execution_strategy(&self, strategy: ExecutionStrategy) {
+        self.executor.set_strategy(strategy);
+    }
+
+    /// Returns whether all modules injected using this syringe, that were not ejected yet, are ejected when the syringe is dropped.
+    #[must_use]
+    pub fn eject_on_drop(&self) -> bool {
+        self.eject_on_drop.get()
+    }
+
+    /// Sets whether all modules injected using this syringe, that were not ejected yet, are ejected when the syringe is dropped.
+    /// This is disabled by default.
+    ///
+    /// The modules are ejected in the reverse order of their injection and errors during ejection are ignored.
+    pub fn set_eject_on_drop(&self, eject_on_drop: bool) {
+        self.eject_on_drop.set(eject_on_drop);
+    }
+
     /// Injects the module from the given path into the target process.
     ///
     /// # Limitations
@@ -131,24 +286,12 @@ impl Syringe {
         &self,
         payload_path: impl AsRef<Path>,
     ) -> Result<BorrowedProcessModule<'_>, InjectError> {
-        let load_library_w = self.load_library_w_stub.get_or_try_init(|| {
-            let inject_data = self
-                .inject_help_data
-                .get_or_try_init(|| Self::load_inject_help_data_for_process(self.process()))?;
-            LoadLibraryWStub::build(inject_data, &self.remote_allocator)
-        })?;
-
         let module_path = payload_path.as_ref().absolutize()?;
-        let wide_module_path =
-            U16CString::from_os_str(module_path.as_os_str())?.into_vec_with_nul();
-        let remote_wide_module_path = self
-            .remote_allocator
-            .alloc_and_copy_buf(wide_module_path.as_slice())?;
-
-        let injected_module_handle =
-            load_library_w.call(remote_wide_module_path.as_raw_ptr().cast())?;
-        let injected_module =
-            unsafe { ProcessModule::new_unchecked(injected_module_handle, self.process()) };
+        self.check_payload_architecture(&module_path)?;
+        let injected_module = self
+            .load_library(module_path.as_os_str())
+            .map_err(|err| self.diagnose_load_failure(err, &module_path, None))?;
+        self.track_injected_module(injected_module);
 
         debug_assert_eq!(
             Some(injected_module),
@@ -158,6 +301,214 @@ impl Syringe {
         Ok(injected_module)
     }
 
+    /// Injects the module from the given path into the target process and returns a guard that ejects it when dropped.
+    ///
+    /// # Limitations
+    /// - The target process and the given module need to be of the same bitness.
+    /// - If the current process is `x64` the target process can be either `x64` (always available) or `x86` (with the `into_x86_from_x64` feature enabled).
+    /// - If the current process is `x86` the target process can only be `x86`.
+    pub fn inject_scoped(
+        &self,
+        payload_path: impl AsRef<Path>,
+    ) -> Result<InjectedModule<'_>, InjectError> {
+        let module = self.inject(payload_path)?;
+        Ok(InjectedModule {
+            syringe: self,
+            module: Some(module),
+        })
+    }
+
+    /// Injects the module from the given path into the target process using `LoadLibraryExW` with the given [`InjectOptions`].
+    ///
+    /// The directories of the options are registered in the target process using `AddDllDirectory` before the module is loaded
+    /// and removed again using `RemoveDllDirectory` afterwards.
+    ///
+    /// # Limitations
+    /// - The target process and the given module need to be of the same bitness.
+    /// - If the current process is `x64` the target process can be either `x64` (always available) or `x86` (with the `into_x86_from_x64` feature enabled).
+    /// - If the current process is `x86` the target process can only be `x86`.
+    /// - Registering directories requires `AddDllDirectory`, which is not available on Windows 7 without KB2533623.
+    pub fn inject_with_options(
+        &self,
+        payload_path: impl AsRef<Path>,
+        options: &InjectOptions,
+    ) -> Result<BorrowedProcessModule<'_>, InjectError> {
+        let module_path = payload_path.as_ref().absolutize()?;
+        self.check_payload_architecture(&module_path)?;
+        let dll_directories = options
+            .dll_directories()
+            .iter()
+            .map(|directory| directory.absolutize())
+            .collect::<Result<Vec<_>, _>>()?;
+        let dll_directories = dll_directories
+            .iter()
+            .map(|directory| directory.as_os_str())
+            .collect::<Vec<_>>();
+
+        let load_library_ex_w = self.load_library_ex_w_stub.get_or_try_init(|| {
+            let inject_data = self.inject_help_data()?;
+            LoadLibraryExWStub::build(inject_data, &self.executor)
+        })?;
+
+        let module_handle = load_library_ex_w
+            .call(module_path.as_os_str(), options.flags(), &dll_directories)
+            .map_err(|err| self.diagnose_load_failure(err, &module_path, Some(options)))?;
+        let injected_module =
+            unsafe { ProcessModule::new_unchecked(module_handle, self.process()) };
+        self.track_injected_module(injected_module);
+        Ok(injected_module)
+    }
+
+    /// Injects the modules from the given paths into the target process in the given order.
+    ///
+    /// In contrast to calling [`Syringe::inject`] for each module, all modules are loaded by a single remote call.
+    /// Loading stops at the first module that fails to load. The returned list contains the injected module or
+    /// the error reported by `LoadLibraryW` for each module that was attempted to be loaded, so it is shorter than
+    /// the given list if loading stopped early.
+    ///
+    /// # Limitations
+    /// - The target process and the given modules need to be of the same bitness.
+    /// - If the current process is `x64` the target process can be either `x64` (always available) or `x86` (with the `into_x86_from_x64` feature enabled).
+    /// - If the current process is `x86` the target process can only be `x86`.
+    pub fn inject_many(
+        &self,
+        payload_paths: &[impl AsRef<Path>],
+    ) -> Result<Vec<Result<BorrowedProcessModule<'_>, io::Error>>, InjectError> {
+        let module_paths = payload_paths
+            .iter()
+            .map(|payload_path| payload_path.as_ref().absolutize())
+            .collect::<Result<Vec<_>, _>>()?;
+        for module_path in &module_paths {
+            self.check_payload_architecture(module_path)?;
+        }
+        let module_paths = module_paths
+            .iter()
+            .map(|module_path| module_path.as_os_str())
+            .collect::<Vec<_>>();
+
+        let load_library_w_batch = self.load_library_w_batch_stub.get_or_try_init(|| {
+            let inject_data = self.inject_help_data()?;
+            LoadLibraryWBatchStub::build(inject_data, &self.executor)
+        })?;
+
+        let results = load_library_w_batch.call(&module_paths)?;
+        Ok(results
+            .into_iter()
+            .map(|result| {
+                result.map(|module_handle| {
+                    let injected_module =
+                        unsafe { ProcessModule::new_unchecked(module_handle, self.process()) };
+                    self.track_injected_module(injected_module);
+                    injected_module
+                })
+            })
+            .collect())
+    }
+
+    /// Injects the module contained in the given buffer into the target process.
+    ///
+    /// The buffer is written to a newly created temporary file which is then injected like with [`Syringe::inject`].
+    /// The temporary file is deleted once the module is ejected, regardless of whether this happens through
+    /// [`InjectedBytesModule::eject`], [`Syringe::eject`], [`Syringe::eject_fully`] or [`Syringe::set_eject_on_drop`].
+    ///
+    /// # Limitations
+    /// - The target process and the given module need to be of the same bitness.
+    /// - If the current process is `x64` the target process can be either `x64` (always available) or `x86` (with the `into_x86_from_x64` feature enabled).
+    /// - If the current process is `x86` the target process can only be `x86`.
+    pub fn inject_from_bytes(
+        &self,
+        payload: &[u8],
+    ) -> Result<InjectedBytesModule<'_>, InjectError> {
+        let mut file = tempfile::Builder::new()
+            .prefix("dll-syringe-payload-")
+            .suffix(".dll")
+            .tempfile()
+            .map_err(InjectError::Io)?;
+        file.write_all(payload).map_err(InjectError::Io)?;
+        // close our handle so that the file is not locked while it is loaded in the target process.
+        let file = file.into_temp_path();
+
+        let module = self.inject(&file)?;
+        let file_path = file.to_path_buf();
+        self.temp_payload_files
+            .borrow_mut()
+            .push((module.handle(), file));
+
+        Ok(InjectedBytesModule {
+            syringe: self,
+            module,
+            file_path,
+        })
+    }
+
+    /// Checks that the payload at the given path can be loaded into the target process before any remote code is run.
+    /// Payloads that cannot be found or parsed are left for `LoadLibraryW` to report, as it searches for modules
+    /// that do not exist at the given path and produces the more accurate error for invalid images.
+    fn check_payload_architecture(&self, payload_path: &Path) -> Result<(), InjectError> {
+        let payload = match PayloadInfo::from_path(payload_path) {
+            Ok(payload) => payload,
+            Err(ReadImageError::Pe(_)) => return Ok(()),
+            Err(ReadImageError::Io(err)) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            // the payload is a local file, so errors reading it are not caused by the target process.
+            Err(ReadImageError::Io(err)) => return Err(InjectError::Io(err)),
+            Err(err @ ReadImageError::ModuleInaccessible) => {
+                return Err(InjectError::Io(io::Error::new(io::ErrorKind::Other, err)))
+            }
+        };
+
+        if payload.is_compatible_with(self.remote_allocator.process_architecture()?) {
+            Ok(())
+        } else {
+            Err(InjectError::ArchitectureMismatch)
+        }
+    }
+
+    /// Attaches an [`InjectFailureDiagnosis`] to a remote io error of a failed load of the given payload,
+    /// if any issues with its dependencies can be found.
+    fn diagnose_load_failure(
+        &self,
+        err: InjectError,
+        payload_path: &Path,
+        options: Option<&InjectOptions>,
+    ) -> InjectError {
+        let error = match err {
+            InjectError::RemoteIo(error) => error,
+            err => return err,
+        };
+
+        let diagnosis = match options {
+            Some(options) => {
+                InjectFailureDiagnosis::diagnose_with_options(self.process(), payload_path, options)
+            }
+            None => InjectFailureDiagnosis::diagnose(self.process(), payload_path),
+        };
+        match diagnosis {
+            Ok(diagnosis) if !diagnosis.is_empty() => {
+                InjectError::ModuleLoadFailed { error, diagnosis }
+            }
+            _ => InjectError::RemoteIo(error),
+        }
+    }
+
+    /// Loads the given module into the target process by calling `LoadLibraryW` with the given name or path as is.
+    pub(crate) fn load_library(
+        &self,
+        module: &OsStr,
+    ) -> Result<BorrowedProcessModule<'_>, InjectError> {
+        let load_library_w = self.load_library_w_stub.get_or_try_init(|| {
+            let inject_data = self.inject_help_data()?;
+            LoadLibraryWStub::build(inject_data, &self.executor)
+        })?;
+
+        let wide_module_path = U16CString::from_os_str(module)?.into_vec_with_nul();
+        let remote_wide_module_path = self
+            .remote_allocator
+            .alloc_and_copy_buf(wide_module_path.as_slice())?;
+
+        let module_handle = load_library_w.call(remote_wide_module_path.as_raw_ptr().cast())?;
+        Ok(unsafe { ProcessModule::new_unchecked(module_handle, self.process()) })
+    }
+
     /// Injects the module from the given path into the target process, if it is not already loaded.
     ///
     /// # Limitations
@@ -186,19 +537,11 @@ impl Syringe {
             "trying to eject a module from a different process"
         );
 
-        let inject_data = self
-            .inject_help_data
-            .get_or_try_init(|| Self::load_inject_help_data_for_process(self.process()))?;
+        let inject_data = self.inject_help_data()?;
 
-        if !module.guess_is_loaded() {
-            if self.process().is_alive() {
-                return Err(EjectError::ModuleInaccessible);
-            } else {
-                return Err(EjectError::ProcessInaccessible);
-            }
-        }
+        Self::ensure_module_is_loaded(module)?;
 
-        let exit_code = self.process().run_remote_thread(
+        let exit_code = self.executor.run(
             unsafe { mem::transmute(inject_data.get_free_library_fn_ptr()) },
             module.handle(),
         )?;
@@ -215,29 +558,216 @@ impl Syringe {
             return Err(EjectError::RemoteException(exception));
         }
 
+        self.untrack_injected_module(module);
+
+        // a module that was injected multiple times stays loaded until it is ejected as often.
+        let still_injected = self.injected_modules.borrow().contains(&module.handle());
         debug_assert!(
-            !self
-                .remote_allocator
-                .process()
-                .module_handles()?
-                .any(|m| m == module.handle()),
+            still_injected
+                || !self
+                    .remote_allocator
+                    .process()
+                    .module_handles()?
+                    .any(|m| m == module.handle()),
             "ejected module survived"
         );
 
+        if !still_injected {
+            self.delete_temp_payload_file(module)?;
+        }
+
         Ok(())
     }
 
+    /// Ejects a module from the target process by releasing all of its references, regardless of how often it was loaded.
+    /// Returns the number of released references.
+    ///
+    /// `FreeLibrary` is called repeatedly inside the target process until the module is unloaded, but at most
+    /// `u16::MAX` times. If the module is still loaded afterwards (e.g. because it is pinned),
+    /// [`EjectError::ModuleStillLoaded`] is returned.
+    ///
+    /// # Note
+    /// This also releases references held by code of the target process itself, so other modules of the target
+    /// may be left with a dangling dependency.
+    ///
+    /// # Panics
+    /// This method panics if the given module was not loaded in the target process.
+    pub fn eject_fully(&self, module: BorrowedProcessModule<'_>) -> Result<usize, EjectError> {
+        assert!(
+            module.process() == &self.process(),
+            "trying to eject a module from a different process"
+        );
+
+        let free_library_loop = self.free_library_loop_stub.get_or_try_init(|| {
+            let inject_data = self.inject_help_data()?;
+            FreeLibraryLoopStub::build(inject_data, &self.executor)
+        })?;
+
+        Self::ensure_module_is_loaded(module)?;
+
+        let released = free_library_loop.call(module.handle())?;
+
+        self.injected_modules
+            .borrow_mut()
+            .retain(|&handle| handle != module.handle());
+
+        if self
+            .process()
+            .module_handles()?
+            .any(|m| m == module.handle())
+        {
+            return Err(EjectError::ModuleStillLoaded { released });
+        }
+
+        self.delete_temp_payload_file(module)?;
+
+        Ok(released)
+    }
+
+    /// Ejects the module with the given name from the target process by releasing all of its references (see [`Syringe::eject_fully`]).
+    /// Returns the number of released references or `None` if no such module is loaded.
+    ///
+    /// The comparison of names is case-insensitive.
+    pub fn eject_by_name(
+        &self,
+        module_name: impl AsRef<Path>,
+    ) -> Result<Option<usize>, EjectError> {
+        match self.process().find_module_by_name(module_name)? {
+            Some(module) => self.eject_fully(module).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Ejects the module with the given path from the target process by releasing all of its references (see [`Syringe::eject_fully`]).
+    /// Returns the number of released references or `None` if no such module is loaded.
+    pub fn eject_by_path(
+        &self,
+        module_path: impl AsRef<Path>,
+    ) -> Result<Option<usize>, EjectError> {
+        match self.process().find_module_by_path(module_path)? {
+            Some(module) => self.eject_fully(module).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn ensure_module_is_loaded(module: BorrowedProcessModule<'_>) -> Result<(), EjectError> {
+        if module.guess_is_loaded() {
+            Ok(())
+        } else if module.process().is_alive() {
+            Err(EjectError::ModuleInaccessible)
+        } else {
+            Err(EjectError::ProcessInaccessible)
+        }
+    }
+
+    fn track_injected_module(&self, module: BorrowedProcessModule<'_>) {
+        self.injected_modules.borrow_mut().push(module.handle());
+    }
+
+    /// Deletes the temporary file backing the given module if it was injected using [`Syringe::inject_from_bytes`].
+    fn delete_temp_payload_file(
+        &self,
+        module: BorrowedProcessModule<'_>,
+    ) -> Result<(), EjectError> {
+        let file = {
+            let mut files = self.temp_payload_files.borrow_mut();
+            match files
+                .iter()
+                .position(|(handle, _)| *handle == module.handle())
+            {
+                Some(index) => files.swap_remove(index).1,
+                None => return Ok(()),
+            }
+        };
+        file.close().map_err(EjectError::Io)
+    }
+
+    fn untrack_injected_module(&self, module: BorrowedProcessModule<'_>) {
+        let mut injected_modules = self.injected_modules.borrow_mut();
+        if let Some(index) = injected_modules
+            .iter()
+            .rposition(|&handle| handle == module.handle())
+        {
+            injected_modules.remove(index);
+        }
+    }
+
+    /// Injects the module from the given path into the `x64` target process from the current `x86` process and returns the base address of the loaded module.
+    ///
+    /// As the module handles of a 64-bit process do not fit into the pointer sized handles of the current process,
+    /// the injected module is identified by its 64-bit base address and has to be ejected using [`Syringe::eject_x64`].
+    ///
+    /// # Note
+    /// Modules injected this way are always loaded using a new remote thread, regardless of the [`ExecutionStrategy`] of this syringe.
+    ///
+    /// # Limitations
+    /// - The target process and the given module need to be `x64`.
+    /// - The current process needs to run under WOW64 (i.e. on a 64-bit version of windows).
+    #[cfg(all(target_arch = "x86", feature = "into-x64-from-x86"))]
+    #[cfg_attr(
+        feature = "doc-cfg",
+        doc(cfg(all(target_arch = "x86", feature = "into-x64-from-x86")))
+    )]
+    pub fn inject_x64(&self, payload_path: impl AsRef<Path>) -> Result<u64, InjectError> {
+        self.process().ensure_access(ProcessAccess::INJECTION)?;
+        let x64_injector = self
+            .x64_injector
+            .get_or_try_init(|| crate::into_x64::X64Injector::build(&self.remote_allocator))?;
+        x64_injector.inject(
+            self.process(),
+            &self.remote_allocator,
+            payload_path.as_ref(),
+        )
+    }
+
+    /// Ejects the module with the given base address, that was previously injected using [`Syringe::inject_x64`], from the `x64` target process.
+    #[cfg(all(target_arch = "x86", feature = "into-x64-from-x86"))]
+    #[cfg_attr(
+        feature = "doc-cfg",
+        doc(cfg(all(target_arch = "x86", feature = "into-x64-from-x86")))
+    )]
+    pub fn eject_x64(&self, module: u64) -> Result<(), EjectError> {
+        self.process().ensure_access(ProcessAccess::INJECTION)?;
+        let x64_injector = self
+            .x64_injector
+            .get_or_try_init(|| crate::into_x64::X64Injector::build(&self.remote_allocator))?;
+        x64_injector.eject(self.process(), module)
+    }
+
+    /// Returns the data required for injecting into the target process, which is loaded on first use.
+    ///
+    /// Loading it also checks that the handle of the target process has the access rights required for injection,
+    /// so that syringes created using [`Syringe::for_process`] fail with a `MissingAccess` error on their first operation.
+    pub(crate) fn inject_help_data(&self) -> Result<&InjectHelpData, LoadInjectHelpDataError> {
+        self.inject_help_data.get_or_try_init(|| {
+            self.process().ensure_access(ProcessAccess::INJECTION)?;
+            Self::load_inject_help_data_for_process(self.process())
+        })
+    }
+
     pub(crate) fn load_inject_help_data_for_process(
         process: BorrowedProcess<'_>,
     ) -> Result<InjectHelpData, LoadInjectHelpDataError> {
-        let is_target_x64 = process.is_x64()?;
-        let is_self_x64 = cfg!(target_arch = "x86_64");
+        let injector = ProcessArchitecture::current();
+        let target = process.architecture()?;
 
-        match (is_target_x64, is_self_x64) {
-            (true, true) | (false, false) => Self::load_inject_help_data_for_current_target(),
+        match (injector, target) {
+            (ProcessArchitecture::X86, target) if target.is_x86() => {
+                Self::load_inject_help_data_for_current_target()
+            }
+            (ProcessArchitecture::X64, target) if target.is_x64() => {
+                Self::load_inject_help_data_for_current_target()
+            }
+            (ProcessArchitecture::Arm64, ProcessArchitecture::Arm64) => {
+                Self::load_inject_help_data_for_current_target()
+            }
             #[cfg(all(target_arch = "x86_64", feature = "into-x86-from-x64"))]
-            (false, true) => Self::_load_inject_help_data_for_process(process),
-            _ => Err(LoadInjectHelpDataError::UnsupportedTarget),
+            (ProcessArchitecture::X64, target) if target.is_x86() => {
+                Self::_load_inject_help_data_for_process(process)
+            }
+            (injector, target) => {
+                Err(LoadInjectHelpDataError::UnsupportedArchitecture { injector, target })
+            }
         }
     }
 
@@ -276,6 +806,14 @@ impl Syringe {
 
         let load_library_fn_ptr =
             kernel32_module.get_local_procedure_address_cstr(cstr!("LoadLibraryW"))?;
+        let load_library_ex_fn_ptr =
+            kernel32_module.get_local_procedure_address_cstr(cstr!("LoadLibraryExW"))?;
+        let add_dll_directory_fn_ptr = kernel32_module
+            .get_local_procedure_address_cstr(cstr!("AddDllDirectory"))
+            .ok();
+        let remove_dll_directory_fn_ptr = kernel32_module
+            .get_local_procedure_address_cstr(cstr!("RemoveDllDirectory"))
+            .ok();
         let free_library_fn_ptr =
             kernel32_module.get_local_procedure_address_cstr(cstr!("FreeLibrary"))?;
         let get_last_error_fn_ptr =
@@ -284,15 +822,18 @@ impl Syringe {
         let get_proc_address_fn_ptr =
             kernel32_module.get_local_procedure_address_cstr(cstr!("GetProcAddress"))?;
 
+        // exports may be forwarded to other modules, so the offsets can be "negative".
+        let offset = |fn_ptr: usize| fn_ptr.wrapping_sub(kernel32_module.handle() as usize);
         Ok(InjectHelpData {
             kernel32_module: kernel32_module.handle(),
-            load_library_offset: load_library_fn_ptr as usize - kernel32_module.handle() as usize,
-            free_library_offset: free_library_fn_ptr as usize - kernel32_module.handle() as usize,
-            get_last_error_offset: get_last_error_fn_ptr as usize
-                - kernel32_module.handle() as usize,
+            load_library_offset: offset(load_library_fn_ptr as usize),
+            load_library_ex_offset: offset(load_library_ex_fn_ptr as usize),
+            add_dll_directory_offset: add_dll_directory_fn_ptr.map(|f| offset(f as usize)),
+            remove_dll_directory_offset: remove_dll_directory_fn_ptr.map(|f| offset(f as usize)),
+            free_library_offset: offset(free_library_fn_ptr as usize),
+            get_last_error_offset: offset(get_last_error_fn_ptr as usize),
             #[cfg(feature = "rpc-core")]
-            get_proc_address_offset: get_proc_address_fn_ptr as usize
-                - kernel32_module.handle() as usize,
+            get_proc_address_offset: offset(get_proc_address_fn_ptr as usize),
         })
     }
 
@@ -301,59 +842,202 @@ impl Syringe {
     fn _load_inject_help_data_for_process(
         process: BorrowedProcess<'_>,
     ) -> Result<InjectHelpData, LoadInjectHelpDataError> {
-        // get kernel32 handle of target process (may fail if target process is currently starting and has not loaded kernel32 yet)
+        // get kernel32 handle of target process (may fail if target process is currently starting and has not loaded kernel32 yet,
+        // a suspended process has to be initialized using `SuspendedProcess::initialize_loader` first)
         let kernel32_module = process
             .wait_for_module_by_name("kernel32.dll", Duration::from_secs(1))?
-            .unwrap();
-
-        // get path of kernel32 used in target process
-        let kernel32_path = if process.is_x86()? {
-            // We need to manually construct the path to the kernel32.dll used in WOW64 processes.
-            let mut wow64_path = Self::wow64_dir()?;
-            wow64_path.push("kernel32.dll");
-            wow64_path
-        } else {
-            kernel32_module.path()?
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "kernel32.dll is not loaded in the target process",
+                )
+            })?;
+
+        // load the dll as a pe and extract the fn addresses
+        let kernel32_file = fs::read(Self::module_file_path(process, kernel32_module)?)?;
+        let kernel32_pe = PE::parse(&kernel32_file)?;
+        let find_export = |name: &str| {
+            Self::resolve_offline_export(
+                process,
+                kernel32_module,
+                &kernel32_file,
+                &kernel32_pe,
+                ExportRef::from(name),
+                0,
+            )
+        };
+        let required_export = |name: &str| {
+            find_export(name)?.ok_or_else(|| {
+                LoadInjectHelpDataError::from(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{} could not be resolved in the target process", name),
+                ))
+            })
         };
 
-        // load the dll as a pe and extract the fn offsets
-        let module_file_buffer = fs::read(kernel32_path)?;
-        let pe = PE::parse(&module_file_buffer)?;
-        let load_library_export = pe
-            .exports
-            .iter()
-            .find(|export| matches!(export.name, Some("LoadLibraryW")))
-            .unwrap();
-
-        let free_library_export = pe
-            .exports
-            .iter()
-            .find(|export| matches!(export.name, Some("FreeLibrary")))
-            .unwrap();
-
-        let get_last_error_export = pe
-            .exports
-            .iter()
-            .find(|export| matches!(export.name, Some("GetLastError")))
-            .unwrap();
-
+        let load_library = required_export("LoadLibraryW")?;
+        let load_library_ex = required_export("LoadLibraryExW")?;
+        let add_dll_directory = find_export("AddDllDirectory")?;
+        let remove_dll_directory = find_export("RemoveDllDirectory")?;
+        let free_library = required_export("FreeLibrary")?;
+        let get_last_error = required_export("GetLastError")?;
         #[cfg(feature = "rpc-core")]
-        let get_proc_address_export = pe
-            .exports
-            .iter()
-            .find(|export| matches!(export.name, Some("GetProcAddress")))
-            .unwrap();
+        let get_proc_address = required_export("GetProcAddress")?;
 
+        // exports may be forwarded to other modules, so the offsets can be "negative".
+        let offset = |address: usize| address.wrapping_sub(kernel32_module.handle() as usize);
         Ok(InjectHelpData {
             kernel32_module: kernel32_module.handle(),
-            load_library_offset: load_library_export.rva,
-            free_library_offset: free_library_export.rva,
-            get_last_error_offset: get_last_error_export.rva,
+            load_library_offset: offset(load_library),
+            load_library_ex_offset: offset(load_library_ex),
+            add_dll_directory_offset: add_dll_directory.map(offset),
+            remove_dll_directory_offset: remove_dll_directory.map(offset),
+            free_library_offset: offset(free_library),
+            get_last_error_offset: offset(get_last_error),
             #[cfg(feature = "rpc-core")]
-            get_proc_address_offset: get_proc_address_export.rva,
+            get_proc_address_offset: offset(get_proc_address),
         })
     }
 
+    /// Resolves the address of the given export of a module in the target process using the file the module was
+    /// loaded from. Forwarded exports are followed, including forwarders to api sets.
+    /// Returns `None` if the export does not exist or is forwarded to a module that is not loaded.
+    #[cfg(all(target_arch = "x86_64", feature = "into-x86-from-x64"))]
+    fn resolve_offline_export<'a>(
+        process: BorrowedProcess<'_>,
+        module: BorrowedProcessModule<'_>,
+        file: &'a [u8],
+        pe: &PE<'a>,
+        export: ExportRef<'_>,
+        depth: usize,
+    ) -> Result<Option<usize>, LoadInjectHelpDataError> {
+        if depth > MAX_FORWARDER_DEPTH {
+            return Ok(None);
+        }
+
+        let base = module.handle() as usize;
+        let (forwarded_module, forwarded_export) = match export {
+            ExportRef::Name(name) => {
+                match pe.exports.iter().find(|export| export.name == Some(&*name)) {
+                    Some(export) => match &export.reexport {
+                        Some(reexport) => match Self::reexport_target(reexport) {
+                            Some(target) => target,
+                            None => return Ok(None),
+                        },
+                        None => return Ok(Some(base.wrapping_add(export.rva))),
+                    },
+                    None => return Ok(None),
+                }
+            }
+            ExportRef::Ordinal(ordinal) => {
+                let entry = pe.export_data.as_ref().and_then(|export_data| {
+                    let index = u32::from(ordinal)
+                        .checked_sub(export_data.export_directory_table.ordinal_base)?;
+                    export_data.export_address_table.get(index as usize)
+                });
+                let forwarder_rva = match entry {
+                    // unused entries of the address table are zero.
+                    None | Some(ExportAddressTableEntry::ExportRVA(0)) => return Ok(None),
+                    Some(ExportAddressTableEntry::ExportRVA(rva)) => {
+                        return Ok(Some(base.wrapping_add(*rva as usize)))
+                    }
+                    Some(ExportAddressTableEntry::ForwarderRVA(rva)) => *rva,
+                };
+
+                // unnamed forwarders are not included in `pe.exports`, so we have to parse them ourselves.
+                let file_alignment = pe
+                    .header
+                    .optional_header
+                    .map_or(0, |header| header.windows_fields.file_alignment);
+                let offset = find_offset(
+                    forwarder_rva as usize,
+                    &pe.sections,
+                    file_alignment,
+                    &ParseOptions::default(),
+                )
+                .ok_or_else(|| {
+                    goblin::error::Error::Malformed(format!(
+                        "cannot map forwarder rva {:#x} into offset",
+                        forwarder_rva
+                    ))
+                })?;
+                match Self::reexport_target(&Reexport::parse(file, offset)?) {
+                    Some(target) => target,
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        let forwarded_module = if is_api_set_name(forwarded_module) {
+            // api sets are redirected by the loader using the api set schema, which is shared by all processes.
+            let importing_module = module.base_name()?;
+            match ApiSetSchema::current()?
+                .and_then(|schema| schema.resolve(forwarded_module, importing_module.to_str()))
+            {
+                Some(host) => Cow::Owned(host),
+                None => return Ok(None),
+            }
+        } else {
+            Cow::Borrowed(forwarded_module)
+        };
+
+        // the target also contains the 64-bit modules of wow64 (e.g. ntdll.dll), which must not be picked.
+        let forwarded_module = match process
+            .find_module_by_name_with_filter(&*forwarded_module, ModuleListFilter::Only32Bit)?
+        {
+            Some(forwarded_module) => forwarded_module,
+            None => return Ok(None),
+        };
+        let forwarded_file = fs::read(Self::module_file_path(process, forwarded_module)?)?;
+        let forwarded_pe = PE::parse(&forwarded_file)?;
+        Self::resolve_offline_export(
+            process,
+            forwarded_module,
+            &forwarded_file,
+            &forwarded_pe,
+            forwarded_export,
+            depth + 1,
+        )
+    }
+
+    /// Returns the module and the export a forwarder refers to.
+    #[cfg(all(target_arch = "x86_64", feature = "into-x86-from-x64"))]
+    fn reexport_target<'a>(reexport: &Reexport<'a>) -> Option<(&'a str, ExportRef<'a>)> {
+        match *reexport {
+            Reexport::DLLName { export, lib } => Some((lib, ExportRef::from(export))),
+            Reexport::DLLOrdinal { ordinal, lib } => {
+                Some((lib, ExportRef::Ordinal(u16::try_from(ordinal).ok()?)))
+            }
+        }
+    }
+
+    /// Returns the path of the file the given module of the target process was loaded from.
+    #[cfg(all(target_arch = "x86_64", feature = "into-x86-from-x64"))]
+    fn module_file_path(
+        process: BorrowedProcess<'_>,
+        module: BorrowedProcessModule<'_>,
+    ) -> Result<PathBuf, io::Error> {
+        if !process.architecture()?.is_x86() {
+            return module.path();
+        }
+
+        // The paths of system modules of WOW64 processes point to the 64-bit system directory,
+        // so we need to manually construct the path to the modules used in WOW64 processes.
+        let wow64_dir = Self::wow64_dir()?;
+        let system_dir = wow64_dir.with_file_name("System32");
+        let path = module.path()?;
+        match (path.parent(), path.file_name()) {
+            (Some(parent), Some(file_name))
+                if parent
+                    .as_os_str()
+                    .eq_ignore_ascii_case(system_dir.as_os_str()) =>
+            {
+                Ok(wow64_dir.join(file_name))
+            }
+            _ => Ok(path),
+        }
+    }
+
     #[cfg(all(target_arch = "x86_64", feature = "into-x86-from-x64"))]
     fn wow64_dir() -> Result<PathBuf, io::Error> {
         let mut path_buf = MaybeUninit::uninit_array::<MAX_PATH>();
@@ -369,42 +1053,154 @@ impl Syringe {
     }
 }
 
+impl Drop for Syringe {
+    fn drop(&mut self) {
+        if !self.eject_on_drop.get() {
+            return;
+        }
+
+        let injected_modules = mem::take(self.injected_modules.get_mut());
+        for module in injected_modules.into_iter().rev() {
+            let module = unsafe { ProcessModule::new_unchecked(module, self.process()) };
+            let _ = self.eject(module);
+        }
+    }
+}
+
+/// A module that was injected using [`Syringe::inject_scoped`] and is ejected when this guard is dropped.
+///
+/// Errors during the ejection on drop are ignored, use [`InjectedModule::eject`] to handle them.
 #[derive(Debug)]
-struct LoadLibraryWStub {
-    code: RemoteAllocation,
-    result: RemoteBox<ModuleHandle>,
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "syringe")))]
+pub struct InjectedModule<'a> {
+    syringe: &'a Syringe,
+    /// Always `Some` until the module is ejected or leaked.
+    module: Option<BorrowedProcessModule<'a>>,
 }
 
-impl LoadLibraryWStub {
-    fn build(
-        inject_data: &InjectHelpData,
-        remote_allocator: &RemoteBoxAllocator,
-    ) -> Result<Self, InjectError> {
-        let result = remote_allocator.alloc_uninit::<ModuleHandle>()?;
+impl<'a> InjectedModule<'a> {
+    /// Returns the injected module.
+    #[must_use]
+    pub fn module(&self) -> BorrowedProcessModule<'a> {
+        self.module.unwrap()
+    }
 
-        let code = if remote_allocator.process().is_x86()? {
-            Self::build_code_x86(
-                inject_data.get_load_library_fn_ptr(),
-                result.as_raw_ptr().cast(),
-                inject_data.get_get_last_error(),
-            )
-            .unwrap()
-        } else {
-            Self::build_code_x64(
-                inject_data.get_load_library_fn_ptr(),
-                result.as_raw_ptr().cast(),
-                inject_data.get_get_last_error(),
+    /// Ejects the module from the target process.
+    pub fn eject(mut self) -> Result<(), EjectError> {
+        self.syringe.eject(self.module.take().unwrap())
+    }
+
+    /// Releases the module from this guard without ejecting it.
+    ///
+    /// The module is also no longer ejected by the syringe if [`Syringe::set_eject_on_drop`] is enabled.
+    #[must_use]
+    pub fn leak(mut self) -> BorrowedProcessModule<'a> {
+        let module = self.module.take().unwrap();
+        self.syringe.untrack_injected_module(module);
+        module
+    }
+}
+
+impl<'a> Deref for InjectedModule<'a> {
+    type Target = BorrowedProcessModule<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        self.module.as_ref().unwrap()
+    }
+}
+
+impl Drop for InjectedModule<'_> {
+    fn drop(&mut self) {
+        if let Some(module) = self.module.take() {
+            let _ = self.syringe.eject(module);
+        }
+    }
+}
+
+/// A module that was injected from an in-memory buffer using [`Syringe::inject_from_bytes`].
+///
+/// The module is backed by a temporary file that is owned by the syringe and deleted when the module is ejected
+/// through any of the eject methods of the syringe.
+/// If the syringe is dropped while the module is still loaded, the temporary file can not be deleted and is left behind.
+#[derive(Debug)]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "syringe")))]
+pub struct InjectedBytesModule<'a> {
+    module: BorrowedProcessModule<'a>,
+    syringe: &'a Syringe,
+    file_path: PathBuf,
+}
+
+impl<'a> InjectedBytesModule<'a> {
+    /// Returns the injected module.
+    #[must_use]
+    pub fn module(&self) -> BorrowedProcessModule<'a> {
+        self.module
+    }
+
+    /// Returns the path of the temporary file backing the injected module.
+    #[must_use]
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    /// Ejects the module from the target process and deletes the temporary file backing it.
+    pub fn eject(self) -> Result<(), EjectError> {
+        self.syringe.eject(self.module)
+    }
+}
+
+impl<'a> Deref for InjectedBytesModule<'a> {
+    type Target = BorrowedProcessModule<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.module
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct LoadLibraryWStub {
+    code: RemoteAllocation,
+    result: RemoteBox<ModuleHandle>,
+    executor: RemoteExecutor,
+}
+
+impl LoadLibraryWStub {
+    fn build(inject_data: &InjectHelpData, executor: &RemoteExecutor) -> Result<Self, InjectError> {
+        let remote_allocator = executor.remote_allocator();
+        let result = remote_allocator.alloc_uninit::<ModuleHandle>()?;
+
+        let code = match remote_allocator.process_architecture()? {
+            ProcessArchitecture::X86 | ProcessArchitecture::X86OnArm64 => Self::build_code_x86(
+                inject_data.get_load_library_fn_ptr(),
+                result.as_raw_ptr().cast(),
+                inject_data.get_get_last_error(),
             )
-            .unwrap()
+            .unwrap(),
+            ProcessArchitecture::X64 | ProcessArchitecture::Arm64EC => Self::build_code_x64(
+                inject_data.get_load_library_fn_ptr() as usize as u64,
+                result.as_raw_ptr() as usize as u64,
+                inject_data.get_get_last_error() as usize as u64,
+            )
+            .unwrap(),
+            ProcessArchitecture::Arm64 => Self::build_code_arm64(
+                inject_data.get_load_library_fn_ptr() as usize as u64,
+                result.as_raw_ptr() as usize as u64,
+                inject_data.get_get_last_error() as usize as u64,
+            ),
         };
         let code = remote_allocator.alloc_and_copy_buf(code.as_slice())?;
+        code.memory().flush_instruction_cache()?;
 
-        Ok(Self { code, result })
+        Ok(Self {
+            code,
+            result,
+            executor: executor.clone(),
+        })
     }
 
     fn call(&self, remote_wide_module_path: *mut u16) -> Result<ModuleHandle, InjectError> {
-        // creating a thread that will call LoadLibraryW with a pointer to payload_path as argument
-        let exit_code = self.code.process().run_remote_thread(
+        // run the stub that will call LoadLibraryW with a pointer to payload_path as argument
+        let exit_code = self.executor.run(
             unsafe { mem::transmute(self.code.as_raw_ptr()) },
             remote_wide_module_path,
         )?;
@@ -460,28 +1256,28 @@ impl LoadLibraryWStub {
         Ok(code)
     }
 
-    #[allow(clippy::fn_to_numeric_cast, clippy::fn_to_numeric_cast_with_truncation)]
-    fn build_code_x64(
-        load_library_w: LoadLibraryWFn,
-        return_buffer: *mut HMODULE,
-        get_last_error: GetLastErrorFn,
+    /// Builds the x64 stub from raw addresses, so that it can also be built from an `x86` process.
+    pub(crate) fn build_code_x64(
+        load_library_w: u64,
+        return_buffer: u64,
+        get_last_error: u64,
     ) -> Result<Vec<u8>, IcedError> {
-        assert!(!return_buffer.is_null());
+        assert_ne!(return_buffer, 0);
 
         let mut asm = CodeAssembler::new(64)?;
 
         asm.sub(rsp, 40)?; // Re-align stack to 16 byte boundary +32 shadow space
 
         // arg already in rcx
-        asm.mov(rax, load_library_w as u64)?;
+        asm.mov(rax, load_library_w)?;
         asm.call(rax)?;
-        asm.mov(dword_ptr(return_buffer as u64), rax)?; // move result to buffer
+        asm.mov(qword_ptr(return_buffer), rax)?; // move result to buffer
 
         let mut label = asm.create_label();
         asm.test(rax, rax)?;
         asm.mov(rax, 0u64)?;
         asm.jnz(label)?;
-        asm.mov(rax, get_last_error as u64)?;
+        asm.mov(rax, get_last_error)?;
         asm.call(rax)?; // return 0
         asm.set_label(&mut label)?;
 
@@ -497,4 +1293,860 @@ impl LoadLibraryWStub {
 
         Ok(code)
     }
+
+    fn build_code_arm64(load_library_w: u64, return_buffer: u64, get_last_error: u64) -> Vec<u8> {
+        assert_ne!(return_buffer, 0);
+
+        let mut asm = Arm64Assembler::new();
+
+        asm.stp_pre_index(X29, X30, SP, -16); // save frame pointer and link register
+        asm.mov(X29, SP);
+
+        // arg already in x0
+        asm.mov_imm64(X16, load_library_w);
+        asm.blr(X16);
+        asm.mov_imm64(X17, return_buffer);
+        asm.str(X0, X17, 0); // move result to buffer
+
+        let succeeded = asm.create_label();
+        let end = asm.create_label();
+        asm.cbnz(X0, &succeeded);
+        asm.mov_imm64(X16, get_last_error);
+        asm.blr(X16); // return GetLastError()
+        asm.b(&end);
+        asm.set_label(&succeeded);
+        asm.mov_imm32(X0, 0); // return 0
+        asm.set_label(&end);
+
+        asm.ldp_post_index(X29, X30, SP, 16);
+        asm.ret();
+
+        asm.assemble()
+    }
+}
+
+/// A stub that calls `LoadLibraryW` for a list of modules in a single remote call.
+///
+/// The parameter of the stub points to a buffer of pointer sized values in the layout of the target process,
+/// starting with the number of modules followed by a pair of the path of the module and its resulting handle
+/// for each module. The stub stops at the first module that fails to load and returns the result of `GetLastError`
+/// in that case or `0` if all modules were loaded.
+#[derive(Debug)]
+pub(crate) struct LoadLibraryWBatchStub {
+    code: RemoteAllocation,
+    executor: RemoteExecutor,
+}
+
+impl LoadLibraryWBatchStub {
+    fn build(inject_data: &InjectHelpData, executor: &RemoteExecutor) -> Result<Self, InjectError> {
+        let remote_allocator = executor.remote_allocator();
+
+        let load_library_w = inject_data.get_load_library_fn_ptr() as usize as u64;
+        let get_last_error = inject_data.get_get_last_error() as usize as u64;
+        let code = match remote_allocator.process_architecture()? {
+            ProcessArchitecture::X86 | ProcessArchitecture::X86OnArm64 => {
+                Self::build_code_x86(load_library_w as u32, get_last_error as u32).unwrap()
+            }
+            ProcessArchitecture::X64 | ProcessArchitecture::Arm64EC => {
+                Self::build_code_x64(load_library_w, get_last_error).unwrap()
+            }
+            ProcessArchitecture::Arm64 => Self::build_code_arm64(load_library_w, get_last_error),
+        };
+        let code = remote_allocator.alloc_and_copy_buf(code.as_slice())?;
+        code.memory().flush_instruction_cache()?;
+
+        Ok(Self {
+            code,
+            executor: executor.clone(),
+        })
+    }
+
+    fn process(&self) -> BorrowedProcess<'_> {
+        self.code.process()
+    }
+
+    fn call(
+        &self,
+        modules: &[&OsStr],
+    ) -> Result<Vec<Result<ModuleHandle, io::Error>>, InjectError> {
+        let pointer_size = target_pointer_size(self.code.process_architecture()?);
+        let wide_module_paths = modules
+            .iter()
+            .map(|module| U16CString::from_os_str(module).map(U16CString::into_vec_with_nul))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let header_len = pointer_size * (1 + 2 * modules.len());
+        let paths_len = wide_module_paths
+            .iter()
+            .map(|path| path.len() * mem::size_of::<u16>())
+            .sum::<usize>();
+        let parameter = self
+            .executor
+            .remote_allocator()
+            .alloc_raw(header_len + paths_len)?;
+
+        let mut buf = Vec::with_capacity(header_len + paths_len);
+        push_target_pointer(&mut buf, modules.len() as u64, pointer_size);
+        let mut path_address = parameter.as_raw_ptr() as usize as u64 + header_len as u64;
+        for path in &wide_module_paths {
+            push_target_pointer(&mut buf, path_address, pointer_size);
+            push_target_pointer(&mut buf, 0, pointer_size); // resulting module handle
+            path_address += (path.len() * mem::size_of::<u16>()) as u64;
+        }
+        for path in &wide_module_paths {
+            push_wide_str(&mut buf, path);
+        }
+        parameter.write_bytes(&buf)?;
+
+        let exit_code = self.executor.run(
+            unsafe { mem::transmute(self.code.as_raw_ptr()) },
+            parameter.as_raw_ptr(),
+        )?;
+        let mut error = match Syringe::remote_exit_code_to_error_or_exception(exit_code) {
+            Ok(()) => None,
+            Err(ExceptionOrIoError::Exception(exception)) => {
+                return Err(InjectError::RemoteException(exception))
+            }
+            Err(ExceptionOrIoError::Io(err)) => Some(err),
+        };
+
+        let mut header = vec![0; header_len];
+        parameter.read_bytes(&mut header)?;
+
+        let mut results = Vec::with_capacity(modules.len());
+        for entry in header[pointer_size..].chunks_exact(2 * pointer_size) {
+            let module_handle = read_target_pointer(&entry[pointer_size..]);
+
+            if module_handle == 0 {
+                results.push(Err(error.take().unwrap_or_else(|| {
+                    io::Error::new(io::ErrorKind::Other, "failed to load module")
+                })));
+                break;
+            }
+            results.push(Ok(module_handle as usize as ModuleHandle));
+        }
+
+        Ok(results)
+    }
+
+    fn build_code_x86(load_library_w: u32, get_last_error: u32) -> Result<Vec<u8>, IcedError> {
+        let mut asm = CodeAssembler::new(32)?;
+
+        asm.push(ebx)?;
+        asm.push(esi)?;
+        asm.push(edi)?;
+        asm.mov(ebx, dword_ptr(esp + 16))?; // CreateRemoteThread lpParameter
+        asm.mov(esi, dword_ptr(ebx))?; // module count
+        asm.lea(edi, dword_ptr(ebx + 4))?; // first module entry
+
+        let mut next_module = asm.create_label();
+        let mut failed = asm.create_label();
+        let mut succeeded = asm.create_label();
+        let mut end = asm.create_label();
+        asm.set_label(&mut next_module)?;
+        asm.test(esi, esi)?;
+        asm.jz(succeeded)?;
+        asm.push(dword_ptr(edi))?; // lpLibFileName
+        asm.mov(eax, load_library_w)?;
+        asm.call(eax)?;
+        asm.mov(dword_ptr(edi + 4), eax)?; // move result to entry
+        asm.test(eax, eax)?;
+        asm.jz(failed)?;
+        asm.add(edi, 8)?;
+        asm.dec(esi)?;
+        asm.jmp(next_module)?;
+
+        asm.set_label(&mut failed)?;
+        asm.mov(eax, get_last_error)?;
+        asm.call(eax)?; // return GetLastError()
+        asm.jmp(end)?;
+
+        asm.set_label(&mut succeeded)?;
+        asm.xor(eax, eax)?; // return 0
+        asm.set_label(&mut end)?;
+
+        asm.pop(edi)?;
+        asm.pop(esi)?;
+        asm.pop(ebx)?;
+        asm.ret_1(4)?; // Restore stack ptr. (Callee cleanup)
+
+        let code = asm.assemble(0x1234_5678)?;
+        debug_assert_eq!(
+            code,
+            asm.assemble(0x1111_2222)?,
+            "LoadLibraryW x86 batch stub is not location independent"
+        );
+
+        Ok(code)
+    }
+
+    fn build_code_x64(load_library_w: u64, get_last_error: u64) -> Result<Vec<u8>, IcedError> {
+        let mut asm = CodeAssembler::new(64)?;
+
+        asm.push(rbx)?;
+        asm.push(rsi)?;
+        asm.push(rdi)?;
+        asm.sub(rsp, 32)?; // shadow space, the pushes above re-align the stack to a 16 byte boundary
+        asm.mov(rbx, rcx)?; // CreateRemoteThread lpParameter
+        asm.mov(rsi, qword_ptr(rbx))?; // module count
+        asm.lea(rdi, qword_ptr(rbx + 8))?; // first module entry
+
+        let mut next_module = asm.create_label();
+        let mut failed = asm.create_label();
+        let mut succeeded = asm.create_label();
+        let mut end = asm.create_label();
+        asm.set_label(&mut next_module)?;
+        asm.test(rsi, rsi)?;
+        asm.jz(succeeded)?;
+        asm.mov(rcx, qword_ptr(rdi))?; // lpLibFileName
+        asm.mov(rax, load_library_w)?;
+        asm.call(rax)?;
+        asm.mov(qword_ptr(rdi + 8), rax)?; // move result to entry
+        asm.test(rax, rax)?;
+        asm.jz(failed)?;
+        asm.add(rdi, 16)?;
+        asm.dec(rsi)?;
+        asm.jmp(next_module)?;
+
+        asm.set_label(&mut failed)?;
+        asm.mov(rax, get_last_error)?;
+        asm.call(rax)?; // return GetLastError()
+        asm.jmp(end)?;
+
+        asm.set_label(&mut succeeded)?;
+        asm.xor(eax, eax)?; // return 0
+        asm.set_label(&mut end)?;
+
+        asm.add(rsp, 32)?;
+        asm.pop(rdi)?;
+        asm.pop(rsi)?;
+        asm.pop(rbx)?;
+        asm.ret()?;
+
+        let code = asm.assemble(0x1234_5678)?;
+        debug_assert_eq!(
+            code,
+            asm.assemble(0x1111_2222)?,
+            "LoadLibraryW x64 batch stub is not location independent"
+        );
+
+        Ok(code)
+    }
+
+    fn build_code_arm64(load_library_w: u64, get_last_error: u64) -> Vec<u8> {
+        let mut asm = Arm64Assembler::new();
+
+        asm.stp_pre_index(X19, X20, SP, -16); // save callee-saved registers
+        asm.stp_pre_index(X29, X30, SP, -16); // save frame pointer and link register
+        asm.mov(X29, SP);
+        asm.ldr(X20, X0, 0); // module count
+        asm.add(X19, X0, 8); // first module entry
+
+        let next_module = asm.create_label();
+        let failed = asm.create_label();
+        let succeeded = asm.create_label();
+        let end = asm.create_label();
+        asm.set_label(&next_module);
+        asm.cbz(X20, &succeeded);
+        asm.ldr(X0, X19, 0); // lpLibFileName
+        asm.mov_imm64(X16, load_library_w);
+        asm.blr(X16);
+        asm.str(X0, X19, 8); // move result to entry
+        asm.cbz(X0, &failed);
+        asm.add(X19, X19, 16);
+        asm.sub(X20, X20, 1);
+        asm.b(&next_module);
+
+        asm.set_label(&failed);
+        asm.mov_imm64(X16, get_last_error);
+        asm.blr(X16); // return GetLastError()
+        asm.b(&end);
+
+        asm.set_label(&succeeded);
+        asm.mov_imm32(X0, 0); // return 0
+        asm.set_label(&end);
+
+        asm.ldp_post_index(X29, X30, SP, 16);
+        asm.ldp_post_index(X19, X20, SP, 16);
+        asm.ret();
+
+        asm.assemble()
+    }
+}
+
+/// A stub that registers additional dll directories using `AddDllDirectory`, loads a module using `LoadLibraryExW`
+/// and finally removes the registered directories again using `RemoveDllDirectory`.
+///
+/// The parameter of the stub points to a buffer of pointer sized values in the layout of the target process,
+/// consisting of the path of the module, the flags, the resulting module handle and the number of directories
+/// followed by the path of each directory and a slot for its cookie. The stub returns the result of `GetLastError`
+/// if a call fails or `0` otherwise.
+#[derive(Debug)]
+pub(crate) struct LoadLibraryExWStub {
+    code: RemoteAllocation,
+    executor: RemoteExecutor,
+    supports_dll_directories: bool,
+}
+
+impl LoadLibraryExWStub {
+    fn build(inject_data: &InjectHelpData, executor: &RemoteExecutor) -> Result<Self, InjectError> {
+        let remote_allocator = executor.remote_allocator();
+
+        let dll_directory_fns = inject_data
+            .get_add_dll_directory_fn_ptr()
+            .zip(inject_data.get_remove_dll_directory_fn_ptr());
+        let load_library_ex_w = inject_data.get_load_library_ex_fn_ptr() as usize as u64;
+        let get_last_error = inject_data.get_get_last_error() as usize as u64;
+        // the stub never calls AddDllDirectory or RemoveDllDirectory without any directories,
+        // so the addresses do not matter if they are missing.
+        let (add_dll_directory, remove_dll_directory) = dll_directory_fns
+            .map_or((0, 0), |(add, remove)| {
+                (add as usize as u64, remove as usize as u64)
+            });
+        let code = match remote_allocator.process_architecture()? {
+            ProcessArchitecture::X86 | ProcessArchitecture::X86OnArm64 => Self::build_code_x86(
+                add_dll_directory as u32,
+                remove_dll_directory as u32,
+                load_library_ex_w as u32,
+                get_last_error as u32,
+            )
+            .unwrap(),
+            ProcessArchitecture::X64 | ProcessArchitecture::Arm64EC => Self::build_code_x64(
+                add_dll_directory,
+                remove_dll_directory,
+                load_library_ex_w,
+                get_last_error,
+            )
+            .unwrap(),
+            ProcessArchitecture::Arm64 => Self::build_code_arm64(
+                add_dll_directory,
+                remove_dll_directory,
+                load_library_ex_w,
+                get_last_error,
+            ),
+        };
+        let code = remote_allocator.alloc_and_copy_buf(code.as_slice())?;
+        code.memory().flush_instruction_cache()?;
+
+        Ok(Self {
+            code,
+            executor: executor.clone(),
+            supports_dll_directories: dll_directory_fns.is_some(),
+        })
+    }
+
+    fn process(&self) -> BorrowedProcess<'_> {
+        self.code.process()
+    }
+
+    fn call(
+        &self,
+        module: &OsStr,
+        flags: LoadLibraryFlags,
+        dll_directories: &[&OsStr],
+    ) -> Result<ModuleHandle, InjectError> {
+        if !dll_directories.is_empty() && !self.supports_dll_directories {
+            return Err(InjectError::Io(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "AddDllDirectory is not available in the target process",
+            )));
+        }
+
+        let pointer_size = target_pointer_size(self.code.process_architecture()?);
+        let wide_module_path = U16CString::from_os_str(module)?.into_vec_with_nul();
+        let wide_directory_paths = dll_directories
+            .iter()
+            .map(|directory| U16CString::from_os_str(directory).map(U16CString::into_vec_with_nul))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let header_len = pointer_size * (4 + 2 * dll_directories.len());
+        let paths_len = (wide_module_path.len()
+            + wide_directory_paths.iter().map(Vec::len).sum::<usize>())
+            * mem::size_of::<u16>();
+        let parameter = self
+            .executor
+            .remote_allocator()
+            .alloc_raw(header_len + paths_len)?;
+
+        let mut buf = Vec::with_capacity(header_len + paths_len);
+        let mut path_address = parameter.as_raw_ptr() as usize as u64 + header_len as u64;
+        push_target_pointer(&mut buf, path_address, pointer_size);
+        path_address += (wide_module_path.len() * mem::size_of::<u16>()) as u64;
+        push_target_pointer(&mut buf, u64::from(flags.bits()), pointer_size);
+        push_target_pointer(&mut buf, 0, pointer_size); // resulting module handle
+        push_target_pointer(&mut buf, dll_directories.len() as u64, pointer_size);
+        for path in &wide_directory_paths {
+            push_target_pointer(&mut buf, path_address, pointer_size);
+            push_target_pointer(&mut buf, 0, pointer_size); // resulting cookie
+            path_address += (path.len() * mem::size_of::<u16>()) as u64;
+        }
+        push_wide_str(&mut buf, &wide_module_path);
+        for path in &wide_directory_paths {
+            push_wide_str(&mut buf, path);
+        }
+        parameter.write_bytes(&buf)?;
+
+        let exit_code = self.executor.run(
+            unsafe { mem::transmute(self.code.as_raw_ptr()) },
+            parameter.as_raw_ptr(),
+        )?;
+        Syringe::remote_exit_code_to_error_or_exception(exit_code)?;
+
+        let mut header = vec![0; 3 * pointer_size];
+        parameter.read_bytes(&mut header)?;
+        let injected_module_handle = read_target_pointer(&header[2 * pointer_size..]);
+        if injected_module_handle == 0 {
+            // LoadLibraryExW failed without setting a last error.
+            return Err(InjectError::RemoteIo(io::Error::new(
+                io::ErrorKind::Other,
+                "failed to load module",
+            )));
+        }
+
+        Ok(injected_module_handle as usize as ModuleHandle)
+    }
+
+    fn build_code_x86(
+        add_dll_directory: u32,
+        remove_dll_directory: u32,
+        load_library_ex_w: u32,
+        get_last_error: u32,
+    ) -> Result<Vec<u8>, IcedError> {
+        let mut asm = CodeAssembler::new(32)?;
+
+        asm.push(ebx)?;
+        asm.push(esi)?;
+        asm.push(edi)?;
+        asm.push(ebp)?;
+        asm.mov(ebx, dword_ptr(esp + 20))?; // CreateRemoteThread lpParameter
+        asm.xor(ebp, ebp)?; // last error
+        asm.mov(esi, dword_ptr(ebx + 12))?; // directory count
+        asm.lea(edi, dword_ptr(ebx + 16))?; // first directory
+
+        let mut next_directory = asm.create_label();
+        let mut add_failed = asm.create_label();
+        let mut load = asm.create_label();
+        let mut cleanup = asm.create_label();
+        let mut next_cookie = asm.create_label();
+        let mut skip_cookie = asm.create_label();
+        let mut end = asm.create_label();
+        asm.set_label(&mut next_directory)?;
+        asm.test(esi, esi)?;
+        asm.jz(load)?;
+        asm.push(dword_ptr(edi))?; // NewDirectory
+        asm.mov(eax, add_dll_directory)?;
+        asm.call(eax)?;
+        asm.mov(dword_ptr(edi + 4), eax)?; // move cookie to buffer
+        asm.test(eax, eax)?;
+        asm.jz(add_failed)?;
+        asm.add(edi, 8)?;
+        asm.dec(esi)?;
+        asm.jmp(next_directory)?;
+
+        asm.set_label(&mut add_failed)?;
+        asm.mov(eax, get_last_error)?;
+        asm.call(eax)?;
+        asm.mov(ebp, eax)?;
+        asm.jmp(cleanup)?;
+
+        asm.set_label(&mut load)?;
+        asm.push(dword_ptr(ebx + 4))?; // dwFlags
+        asm.push(0)?; // hFile
+        asm.push(dword_ptr(ebx))?; // lpLibFileName
+        asm.mov(eax, load_library_ex_w)?;
+        asm.call(eax)?;
+        asm.mov(dword_ptr(ebx + 8), eax)?; // move result to buffer
+        asm.test(eax, eax)?;
+        asm.jnz(cleanup)?;
+        asm.mov(eax, get_last_error)?;
+        asm.call(eax)?;
+        asm.mov(ebp, eax)?;
+
+        // remove all registered directories, regardless of whether the module was loaded.
+        asm.set_label(&mut cleanup)?;
+        asm.mov(esi, dword_ptr(ebx + 12))?; // directory count
+        asm.lea(edi, dword_ptr(ebx + 16))?; // first directory
+        asm.set_label(&mut next_cookie)?;
+        asm.test(esi, esi)?;
+        asm.jz(end)?;
+        asm.mov(eax, dword_ptr(edi + 4))?; // Cookie
+        asm.test(eax, eax)?;
+        asm.jz(skip_cookie)?;
+        asm.push(eax)?;
+        asm.mov(eax, remove_dll_directory)?;
+        asm.call(eax)?;
+        asm.set_label(&mut skip_cookie)?;
+        asm.add(edi, 8)?;
+        asm.dec(esi)?;
+        asm.jmp(next_cookie)?;
+
+        asm.set_label(&mut end)?;
+        asm.mov(eax, ebp)?; // return GetLastError() of the failed call or 0
+        asm.pop(ebp)?;
+        asm.pop(edi)?;
+        asm.pop(esi)?;
+        asm.pop(ebx)?;
+        asm.ret_1(4)?; // Restore stack ptr. (Callee cleanup)
+
+        let code = asm.assemble(0x1234_5678)?;
+        debug_assert_eq!(
+            code,
+            asm.assemble(0x1111_2222)?,
+            "LoadLibraryExW x86 stub is not location independent"
+        );
+
+        Ok(code)
+    }
+
+    fn build_code_x64(
+        add_dll_directory: u64,
+        remove_dll_directory: u64,
+        load_library_ex_w: u64,
+        get_last_error: u64,
+    ) -> Result<Vec<u8>, IcedError> {
+        let mut asm = CodeAssembler::new(64)?;
+
+        asm.push(rbx)?;
+        asm.push(rsi)?;
+        asm.push(rdi)?;
+        asm.push(rbp)?;
+        asm.sub(rsp, 40)?; // shadow space and padding to re-align the stack to a 16 byte boundary
+        asm.mov(rbx, rcx)?; // CreateRemoteThread lpParameter
+        asm.xor(ebp, ebp)?; // last error
+        asm.mov(rsi, qword_ptr(rbx + 24))?; // directory count
+        asm.lea(rdi, qword_ptr(rbx + 32))?; // first directory
+
+        let mut next_directory = asm.create_label();
+        let mut add_failed = asm.create_label();
+        let mut load = asm.create_label();
+        let mut cleanup = asm.create_label();
+        let mut next_cookie = asm.create_label();
+        let mut skip_cookie = asm.create_label();
+        let mut end = asm.create_label();
+        asm.set_label(&mut next_directory)?;
+        asm.test(rsi, rsi)?;
+        asm.jz(load)?;
+        asm.mov(rcx, qword_ptr(rdi))?; // NewDirectory
+        asm.mov(rax, add_dll_directory)?;
+        asm.call(rax)?;
+        asm.mov(qword_ptr(rdi + 8), rax)?; // move cookie to buffer
+        asm.test(rax, rax)?;
+        asm.jz(add_failed)?;
+        asm.add(rdi, 16)?;
+        asm.dec(rsi)?;
+        asm.jmp(next_directory)?;
+
+        asm.set_label(&mut add_failed)?;
+        asm.mov(rax, get_last_error)?;
+        asm.call(rax)?;
+        asm.mov(ebp, eax)?;
+        asm.jmp(cleanup)?;
+
+        asm.set_label(&mut load)?;
+        asm.mov(rcx, qword_ptr(rbx))?; // lpLibFileName
+        asm.xor(edx, edx)?; // hFile
+        asm.mov(r8, qword_ptr(rbx + 8))?; // dwFlags
+        asm.mov(rax, load_library_ex_w)?;
+        asm.call(rax)?;
+        asm.mov(qword_ptr(rbx + 16), rax)?; // move result to buffer
+        asm.test(rax, rax)?;
+        asm.jnz(cleanup)?;
+        asm.mov(rax, get_last_error)?;
+        asm.call(rax)?;
+        asm.mov(ebp, eax)?;
+
+        // remove all registered directories, regardless of whether the module was loaded.
+        asm.set_label(&mut cleanup)?;
+        asm.mov(rsi, qword_ptr(rbx + 24))?; // directory count
+        asm.lea(rdi, qword_ptr(rbx + 32))?; // first directory
+        asm.set_label(&mut next_cookie)?;
+        asm.test(rsi, rsi)?;
+        asm.jz(end)?;
+        asm.mov(rcx, qword_ptr(rdi + 8))?; // Cookie
+        asm.test(rcx, rcx)?;
+        asm.jz(skip_cookie)?;
+        asm.mov(rax, remove_dll_directory)?;
+        asm.call(rax)?;
+        asm.set_label(&mut skip_cookie)?;
+        asm.add(rdi, 16)?;
+        asm.dec(rsi)?;
+        asm.jmp(next_cookie)?;
+
+        asm.set_label(&mut end)?;
+        asm.mov(eax, ebp)?; // return GetLastError() of the failed call or 0
+        asm.add(rsp, 40)?;
+        asm.pop(rbp)?;
+        asm.pop(rdi)?;
+        asm.pop(rsi)?;
+        asm.pop(rbx)?;
+        asm.ret()?;
+
+        let code = asm.assemble(0x1234_5678)?;
+        debug_assert_eq!(
+            code,
+            asm.assemble(0x1111_2222)?,
+            "LoadLibraryExW x64 stub is not location independent"
+        );
+
+        Ok(code)
+    }
+
+    fn build_code_arm64(
+        add_dll_directory: u64,
+        remove_dll_directory: u64,
+        load_library_ex_w: u64,
+        get_last_error: u64,
+    ) -> Vec<u8> {
+        let mut asm = Arm64Assembler::new();
+
+        asm.stp_pre_index(X19, X20, SP, -16); // save callee-saved registers
+        asm.stp_pre_index(X21, X22, SP, -16);
+        asm.stp_pre_index(X29, X30, SP, -16); // save frame pointer and link register
+        asm.mov(X29, SP);
+        asm.mov(X19, X0); // parameter
+        asm.mov_imm32(X22, 0); // last error
+        asm.ldr(X20, X19, 24); // directory count
+        asm.add(X21, X19, 32); // first directory
+
+        let next_directory = asm.create_label();
+        let add_failed = asm.create_label();
+        let load = asm.create_label();
+        let cleanup = asm.create_label();
+        let next_cookie = asm.create_label();
+        let skip_cookie = asm.create_label();
+        let end = asm.create_label();
+        asm.set_label(&next_directory);
+        asm.cbz(X20, &load);
+        asm.ldr(X0, X21, 0); // NewDirectory
+        asm.mov_imm64(X16, add_dll_directory);
+        asm.blr(X16);
+        asm.str(X0, X21, 8); // move cookie to buffer
+        asm.cbz(X0, &add_failed);
+        asm.add(X21, X21, 16);
+        asm.sub(X20, X20, 1);
+        asm.b(&next_directory);
+
+        asm.set_label(&add_failed);
+        asm.mov_imm64(X16, get_last_error);
+        asm.blr(X16);
+        asm.mov(X22, X0);
+        asm.b(&cleanup);
+
+        asm.set_label(&load);
+        asm.ldr(X0, X19, 0); // lpLibFileName
+        asm.mov_imm32(X1, 0); // hFile
+        asm.ldr(X2, X19, 8); // dwFlags
+        asm.mov_imm64(X16, load_library_ex_w);
+        asm.blr(X16);
+        asm.str(X0, X19, 16); // move result to buffer
+        asm.cbnz(X0, &cleanup);
+        asm.mov_imm64(X16, get_last_error);
+        asm.blr(X16);
+        asm.mov(X22, X0);
+
+        // remove all registered directories, regardless of whether the module was loaded.
+        asm.set_label(&cleanup);
+        asm.ldr(X20, X19, 24); // directory count
+        asm.add(X21, X19, 32); // first directory
+        asm.set_label(&next_cookie);
+        asm.cbz(X20, &end);
+        asm.ldr(X0, X21, 8); // Cookie
+        asm.cbz(X0, &skip_cookie);
+        asm.mov_imm64(X16, remove_dll_directory);
+        asm.blr(X16);
+        asm.set_label(&skip_cookie);
+        asm.add(X21, X21, 16);
+        asm.sub(X20, X20, 1);
+        asm.b(&next_cookie);
+
+        asm.set_label(&end);
+        asm.mov(X0, X22); // return GetLastError() of the failed call or 0
+        asm.ldp_post_index(X29, X30, SP, 16);
+        asm.ldp_post_index(X21, X22, SP, 16);
+        asm.ldp_post_index(X19, X20, SP, 16);
+        asm.ret();
+
+        asm.assemble()
+    }
+}
+
+/// A stub that releases all references to a module by calling `FreeLibrary` until it fails.
+///
+/// The parameter of the stub is the handle of the module. `FreeLibrary` is called at most
+/// [`FreeLibraryLoopStub::MAX_ITERATIONS`] times to avoid looping forever on pinned modules,
+/// and the stub returns the number of successful calls.
+#[derive(Debug)]
+pub(crate) struct FreeLibraryLoopStub {
+    code: RemoteAllocation,
+    executor: RemoteExecutor,
+}
+
+impl FreeLibraryLoopStub {
+    const MAX_ITERATIONS: u16 = u16::MAX;
+
+    fn build(inject_data: &InjectHelpData, executor: &RemoteExecutor) -> Result<Self, EjectError> {
+        let remote_allocator = executor.remote_allocator();
+
+        let free_library = inject_data.get_free_library_fn_ptr() as usize as u64;
+        let code = match remote_allocator.process_architecture()? {
+            ProcessArchitecture::X86 | ProcessArchitecture::X86OnArm64 => {
+                Self::build_code_x86(free_library as u32).unwrap()
+            }
+            ProcessArchitecture::X64 | ProcessArchitecture::Arm64EC => {
+                Self::build_code_x64(free_library).unwrap()
+            }
+            ProcessArchitecture::Arm64 => Self::build_code_arm64(free_library),
+        };
+        let code = remote_allocator.alloc_and_copy_buf(code.as_slice())?;
+        code.memory().flush_instruction_cache()?;
+
+        Ok(Self {
+            code,
+            executor: executor.clone(),
+        })
+    }
+
+    fn call(&self, module: ModuleHandle) -> Result<usize, EjectError> {
+        let exit_code = self
+            .executor
+            .run(unsafe { mem::transmute(self.code.as_raw_ptr()) }, module)?;
+        let released = Syringe::remote_exit_code_to_exception(exit_code)?;
+        Ok(released as usize)
+    }
+
+    fn build_code_x86(free_library: u32) -> Result<Vec<u8>, IcedError> {
+        let mut asm = CodeAssembler::new(32)?;
+
+        asm.push(ebx)?;
+        asm.push(esi)?;
+        asm.mov(ebx, dword_ptr(esp + 12))?; // CreateRemoteThread lpParameter
+        asm.xor(esi, esi)?; // released references
+
+        let mut next_call = asm.create_label();
+        let mut end = asm.create_label();
+        asm.set_label(&mut next_call)?;
+        asm.cmp(esi, u32::from(Self::MAX_ITERATIONS))?;
+        asm.jae(end)?;
+        asm.push(ebx)?; // hLibModule
+        asm.mov(eax, free_library)?;
+        asm.call(eax)?;
+        asm.test(eax, eax)?;
+        asm.jz(end)?;
+        asm.inc(esi)?;
+        asm.jmp(next_call)?;
+
+        asm.set_label(&mut end)?;
+        asm.mov(eax, esi)?; // return released references
+        asm.pop(esi)?;
+        asm.pop(ebx)?;
+        asm.ret_1(4)?; // Restore stack ptr. (Callee cleanup)
+
+        let code = asm.assemble(0x1234_5678)?;
+        debug_assert_eq!(
+            code,
+            asm.assemble(0x1111_2222)?,
+            "FreeLibrary x86 loop stub is not location independent"
+        );
+
+        Ok(code)
+    }
+
+    fn build_code_x64(free_library: u64) -> Result<Vec<u8>, IcedError> {
+        let mut asm = CodeAssembler::new(64)?;
+
+        asm.push(rbx)?;
+        asm.push(rsi)?;
+        asm.sub(rsp, 40)?; // shadow space + re-align the stack to a 16 byte boundary
+        asm.mov(rbx, rcx)?; // CreateRemoteThread lpParameter
+        asm.xor(esi, esi)?; // released references
+
+        let mut next_call = asm.create_label();
+        let mut end = asm.create_label();
+        asm.set_label(&mut next_call)?;
+        asm.cmp(esi, u32::from(Self::MAX_ITERATIONS))?;
+        asm.jae(end)?;
+        asm.mov(rcx, rbx)?; // hLibModule
+        asm.mov(rax, free_library)?;
+        asm.call(rax)?;
+        asm.test(eax, eax)?;
+        asm.jz(end)?;
+        asm.inc(esi)?;
+        asm.jmp(next_call)?;
+
+        asm.set_label(&mut end)?;
+        asm.mov(eax, esi)?; // return released references
+        asm.add(rsp, 40)?;
+        asm.pop(rsi)?;
+        asm.pop(rbx)?;
+        asm.ret()?;
+
+        let code = asm.assemble(0x1234_5678)?;
+        debug_assert_eq!(
+            code,
+            asm.assemble(0x1111_2222)?,
+            "FreeLibrary x64 loop stub is not location independent"
+        );
+
+        Ok(code)
+    }
+
+    fn build_code_arm64(free_library: u64) -> Vec<u8> {
+        let mut asm = Arm64Assembler::new();
+
+        asm.stp_pre_index(X19, X20, SP, -16); // save callee-saved registers
+        asm.stp_pre_index(X21, X22, SP, -16);
+        asm.stp_pre_index(X29, X30, SP, -16); // save frame pointer and link register
+        asm.mov(X29, SP);
+        asm.mov(X19, X0); // hLibModule
+        asm.mov_imm32(X20, 0); // released references
+        asm.mov_imm32(X21, Self::MAX_ITERATIONS); // remaining calls
+
+        let next_call = asm.create_label();
+        let end = asm.create_label();
+        asm.set_label(&next_call);
+        asm.cbz(X21, &end);
+        asm.mov(X0, X19);
+        asm.mov_imm64(X16, free_library);
+        asm.blr(X16);
+        asm.cbz(X0, &end);
+        asm.add(X20, X20, 1);
+        asm.sub(X21, X21, 1);
+        asm.b(&next_call);
+
+        asm.set_label(&end);
+        asm.mov(X0, X20); // return released references
+
+        asm.ldp_post_index(X29, X30, SP, 16);
+        asm.ldp_post_index(X21, X22, SP, 16);
+        asm.ldp_post_index(X19, X20, SP, 16);
+        asm.ret();
+
+        asm.assemble()
+    }
+}
+
+/// Returns the size of pointers in target processes of the given architecture.
+fn target_pointer_size(architecture: ProcessArchitecture) -> usize {
+    if architecture.is_64_bit() {
+        mem::size_of::<u64>()
+    } else {
+        mem::size_of::<u32>()
+    }
+}
+
+/// Appends the given value as a pointer of the given size of the target process to the given buffer.
+fn push_target_pointer(buf: &mut Vec<u8>, value: u64, pointer_size: usize) {
+    buf.extend_from_slice(&value.to_le_bytes()[..pointer_size]);
+}
+
+/// Reads a pointer of the target process from the given bytes, whose length is the pointer size of the target process.
+fn read_target_pointer(bytes: &[u8]) -> u64 {
+    let mut value = [0; mem::size_of::<u64>()];
+    value[..bytes.len()].copy_from_slice(bytes);
+    u64::from_le_bytes(value)
+}
+
+/// Appends the given nul-terminated wide string to the given buffer.
+fn push_wide_str(buf: &mut Vec<u8>, str: &[u16]) {
+    buf.extend(str.iter().flat_map(|c| c.to_le_bytes()));
 }
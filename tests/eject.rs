@@ -1,4 +1,4 @@
-#![cfg(feature = "syringe")]
+#![cfg(all(windows, feature = "syringe"))]
 
 use dll_syringe::{error::EjectError, process::Process, Syringe};
 
@@ -32,3 +32,87 @@ syringe_test! {
         assert!(matches!(err, EjectError::ProcessInaccessible), "{:?}", err);
     }
 }
+
+syringe_test! {
+    fn inject_scoped_ejects_on_drop(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        let syringe = Syringe::for_process(process);
+        let module = syringe.inject_scoped(payload_path).unwrap();
+        assert!(syringe.process().find_module_by_path(payload_path).unwrap().is_some());
+
+        drop(module);
+        assert!(syringe.process().find_module_by_path(payload_path).unwrap().is_none());
+    }
+}
+
+syringe_test! {
+    fn inject_scoped_does_not_eject_leaked(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        let syringe = Syringe::for_process(process);
+        let module = syringe.inject_scoped(payload_path).unwrap().leak();
+        assert_eq!(
+            Some(module),
+            syringe.process().find_module_by_path(payload_path).unwrap()
+        );
+    }
+}
+
+syringe_test! {
+    fn syringe_with_eject_on_drop_ejects_injected_modules(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        let target = process.try_clone().unwrap();
+
+        let syringe = Syringe::for_process(process);
+        syringe.set_eject_on_drop(true);
+        syringe.inject(payload_path).unwrap();
+        drop(syringe);
+
+        assert!(target.find_module_by_path(payload_path).unwrap().is_none());
+    }
+}
+
+syringe_test! {
+    fn eject_fully_releases_all_references(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        let syringe = Syringe::for_process(process);
+        let module = syringe.inject(payload_path).unwrap();
+        syringe.inject(payload_path).unwrap();
+
+        let released = syringe.eject_fully(module).unwrap();
+        assert_eq!(released, 2);
+        assert!(syringe.process().find_module_by_path(payload_path).unwrap().is_none());
+    }
+}
+
+syringe_test! {
+    fn eject_by_path_ejects_injected_module(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        let syringe = Syringe::for_process(process);
+        syringe.inject(payload_path).unwrap();
+
+        let released = syringe.eject_by_path(payload_path).unwrap();
+        assert_eq!(released, Some(1));
+        assert!(syringe.process().find_module_by_path(payload_path).unwrap().is_none());
+    }
+}
+
+syringe_test! {
+    fn eject_by_name_with_missing_module_returns_none(
+        process: OwnedProcess,
+        _payload_path: &Path,
+    ) {
+        let syringe = Syringe::for_process(process);
+        let released = syringe.eject_by_name("not_a_loaded_module.dll").unwrap();
+        assert_eq!(released, None);
+    }
+}
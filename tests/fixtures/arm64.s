@@ -0,0 +1,37 @@
+// Source of fixture_arm64.dll, see build.sh.
+// `call_imports` only exists to reference the imports and is not meant to be called.
+
+    .text
+    .globl add
+add:
+    add w0, w0, w1
+    ret
+
+    .globl call_imports
+call_imports:
+    stp x29, x30, [sp, #-16]!
+    adrp x16, __imp_GetCurrentProcessId
+    ldr x16, [x16, :lo12:__imp_GetCurrentProcessId]
+    blr x16
+    adrp x16, __imp_WSAStartup
+    ldr x16, [x16, :lo12:__imp_WSAStartup]
+    blr x16
+    ldp x29, x30, [sp], #16
+    ret
+
+    .data
+    .globl counter
+counter:
+    .word 0
+    .p2align 3
+    .globl counter_ptr
+counter_ptr:
+    .xword counter
+
+    .section .rdata,"dr"
+    .globl version
+version:
+    .asciz "fixture"
+    .globl __dll_syringe_payload_procedure_add
+__dll_syringe_payload_procedure_add:
+    .byte 0
@@ -0,0 +1,34 @@
+#!/bin/sh
+# Rebuilds the fixture dlls used by the pe parser tests from the assembly sources in this directory.
+# Requires llvm-mc, llvm-dlltool and lld-link (e.g. `rust-lld -flavor link`) but no windows toolchain.
+set -eu
+
+cd "$(dirname "$0")"
+LLVM_MC=${LLVM_MC:-llvm-mc}
+DLLTOOL=${DLLTOOL:-llvm-dlltool}
+LLD_LINK=${LLD_LINK:-lld-link}
+BUILD_DIR=$(mktemp -d)
+trap 'rm -rf "$BUILD_DIR"' EXIT
+
+# build <name> <llvm-mc triple> <dlltool machine> <lld-link machine> <import def suffix> [extra lld-link flags...]
+build() {
+    name=$1
+    triple=$2
+    dlltool_machine=$3
+    link_machine=$4
+    def_suffix=$5
+    shift 5
+
+    "$LLVM_MC" -triple "$triple" -filetype=obj "$name.s" -o "$BUILD_DIR/$name.obj"
+    # x86 imports are decorated (e.g. `GetCurrentProcessId@0`), -k strips the decoration from the imported name.
+    "$DLLTOOL" -m "$dlltool_machine" -k -d "kernel32$def_suffix.def" -l "$BUILD_DIR/kernel32_$name.lib"
+    "$DLLTOOL" -m "$dlltool_machine" -k -d "ws2_32$def_suffix.def" -l "$BUILD_DIR/ws2_32_$name.lib"
+    # a zero timestamp keeps the output reproducible.
+    $LLD_LINK /nologo /dll /noentry /timestamp:0 "/machine:$link_machine" /def:fixture.def \
+        "/out:fixture_$name.dll" "/implib:$BUILD_DIR/fixture_$name.lib" "$@" \
+        "$BUILD_DIR/$name.obj" "$BUILD_DIR/kernel32_$name.lib" "$BUILD_DIR/ws2_32_$name.lib"
+}
+
+build x64 x86_64-pc-windows-msvc i386:x86-64 x64 ""
+build x86 i686-pc-windows-msvc i386 x86 _x86 /safeseh:no
+build arm64 aarch64-pc-windows-msvc arm64 arm64 ""
@@ -0,0 +1,32 @@
+# Source of fixture_x64.dll, see build.sh.
+# `call_imports` only exists to reference the imports and is not meant to be called.
+
+    .text
+    .globl add
+add:
+    leal (%rcx,%rdx), %eax
+    retq
+
+    .globl call_imports
+call_imports:
+    subq $40, %rsp
+    callq *__imp_GetCurrentProcessId(%rip)
+    callq *__imp_WSAStartup(%rip)
+    addq $40, %rsp
+    retq
+
+    .data
+    .globl counter
+counter:
+    .long 0
+    .globl counter_ptr
+counter_ptr:
+    .quad counter
+
+    .section .rdata,"dr"
+    .globl version
+version:
+    .asciz "fixture"
+    .globl __dll_syringe_payload_procedure_add
+__dll_syringe_payload_procedure_add:
+    .byte 0
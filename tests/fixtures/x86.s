@@ -0,0 +1,31 @@
+# Source of fixture_x86.dll, see build.sh.
+# `call_imports` only exists to reference the imports and is not meant to be called.
+
+    .text
+    .globl _add
+_add:
+    movl 4(%esp), %eax
+    addl 8(%esp), %eax
+    retl
+
+    .globl _call_imports
+_call_imports:
+    calll *__imp__GetCurrentProcessId@0
+    calll *__imp__WSAStartup@8
+    retl
+
+    .data
+    .globl _counter
+_counter:
+    .long 0
+    .globl _counter_ptr
+_counter_ptr:
+    .long _counter
+
+    .section .rdata,"dr"
+    .globl _version
+_version:
+    .asciz "fixture"
+    .globl ___dll_syringe_payload_procedure_add
+___dll_syringe_payload_procedure_add:
+    .byte 0
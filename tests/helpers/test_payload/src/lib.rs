@@ -73,6 +73,11 @@ pub extern "system" fn sub_float_raw(a: f32, b: f32) -> f32 {
     a - b
 }
 
+#[no_mangle]
+pub extern "system" fn mul_add_mixed_raw(a: u32, b: f32, c: u32, d: f32) -> u32 {
+    (a as f32 * b + c as f32 * d) as u32
+}
+
 #[no_mangle]
 pub extern "C" fn sub_float_raw_c(a: f32, b: f32) -> f32 {
     a - b
@@ -1,7 +1,13 @@
+#[link(name = "kernel32")]
+extern "system" {
+    fn SleepEx(milliseconds: u32, alertable: i32) -> u32;
+}
+
 fn main() {
     // this loop keeps the process alive for a while, so that the tests can run.
     // we dont want to wait indefinitely to avoid creating sleeping zombies.
+    // the sleep is alertable, so that apcs queued by the tests get executed.
     for _ in 0..120 {
-        std::thread::sleep(std::time::Duration::from_secs(1));
+        unsafe { SleepEx(1000, 1) };
     }
 }
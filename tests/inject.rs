@@ -1,6 +1,11 @@
-#![cfg(feature = "syringe")]
+#![cfg(all(windows, feature = "syringe"))]
 
-use dll_syringe::{error::InjectError, process::Process, Syringe};
+use dll_syringe::{
+    error::{InjectError, MissingAccess},
+    process::{Process, ProcessAccess},
+    DependencyIssue, ExecutionStrategy, InjectOptions, LoadLibraryFlags, Syringe,
+};
+use std::{io, time::Duration};
 
 #[allow(unused)]
 mod common;
@@ -32,6 +37,169 @@ process_test! {
     }
 }
 
+process_test! {
+    fn inject_with_unreadable_path_fails_with_io(
+        process: OwnedProcess,
+    ) {
+        let syringe = Syringe::for_process(process);
+        let dir = tempfile::tempdir().unwrap();
+        // a directory exists but can not be read as a file.
+        let result = syringe.inject(dir.path());
+        let err = result.unwrap_err();
+        assert!(matches!(err, InjectError::Io(_)), "{:?}", err);
+    }
+}
+
+process_test! {
+    fn syringe_for_process_with_limited_access_fails_with_missing_access(
+        process: OwnedProcess,
+    ) {
+        let pid = process.pid().unwrap().get();
+        let limited = OwnedProcess::open(
+            pid,
+            ProcessAccess::QUERY_LIMITED_INFORMATION | ProcessAccess::VM_READ,
+        )
+        .unwrap();
+        let err = Syringe::try_for_process(limited).unwrap_err();
+        assert_eq!(
+            err,
+            MissingAccess(ProcessAccess::INJECTION - ProcessAccess::VM_READ)
+        );
+        assert!(Syringe::try_for_process(process).is_ok());
+    }
+}
+
+syringe_test! {
+    fn inject_with_limited_access_fails_with_missing_access(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        let pid = process.pid().unwrap().get();
+        let limited = OwnedProcess::open(
+            pid,
+            ProcessAccess::QUERY_LIMITED_INFORMATION | ProcessAccess::VM_READ,
+        )
+        .unwrap();
+        // the access rights are only checked once the first operation runs.
+        let syringe = Syringe::for_process(limited);
+        let err = syringe.inject(payload_path).unwrap_err();
+        assert!(
+            matches!(err, InjectError::MissingAccess(MissingAccess(access)) if access == ProcessAccess::INJECTION - ProcessAccess::VM_READ),
+            "{:?}",
+            err
+        );
+    }
+}
+
+#[test]
+fn missing_access_is_not_reported_as_inaccessible_process() {
+    let err = InjectError::from(io::Error::from(MissingAccess(ProcessAccess::VM_WRITE)));
+    assert!(
+        matches!(err, InjectError::MissingAccess(MissingAccess(access)) if access == ProcessAccess::VM_WRITE),
+        "{:?}",
+        err
+    );
+}
+
+syringe_test! {
+    fn inject_with_missing_dependency_fails_with_diagnosis(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        // redirect the kernel32 import of the payload to a module that does not exist.
+        let mut payload = std::fs::read(payload_path).unwrap();
+        let mut patched = false;
+        while let Some(pos) = payload.windows(12).position(|name| name == b"KERNEL32.dll") {
+            payload[pos..pos + 12].copy_from_slice(b"MISSING1.dll");
+            patched = true;
+        }
+        assert!(patched);
+
+        let dir = tempfile::tempdir().unwrap();
+        let patched_path = dir.path().join("missing_dependency.dll");
+        std::fs::write(&patched_path, payload).unwrap();
+
+        let syringe = Syringe::for_process(process);
+        let err = syringe.inject(&patched_path).unwrap_err();
+        let diagnosis = match err {
+            InjectError::ModuleLoadFailed { error, diagnosis } => {
+                assert_eq!(error.raw_os_error(), Some(126));
+                diagnosis
+            }
+            err => panic!("unexpected error: {:?}", err),
+        };
+        assert!(diagnosis.issues().contains(&DependencyIssue::MissingModule {
+            importer: "missing_dependency.dll".to_string(),
+            module: "MISSING1.dll".to_string(),
+        }));
+    }
+}
+
+syringe_test! {
+    fn inject_with_options_succeeds(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        let options = InjectOptions::new()
+            .with_flags(
+                LoadLibraryFlags::LOAD_LIBRARY_SEARCH_DEFAULT_DIRS
+                    | LoadLibraryFlags::LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR,
+            )
+            .with_dll_directory(payload_path.parent().unwrap());
+
+        let syringe = Syringe::for_process(process);
+        let module = syringe.inject_with_options(payload_path, &options).unwrap();
+        assert_eq!(
+            Some(module),
+            syringe.process().find_module_by_path(payload_path).unwrap()
+        );
+    }
+}
+
+process_test! {
+    fn inject_with_options_with_invalid_path_fails_with_remote_io(
+        process: OwnedProcess,
+    ) {
+        let syringe = Syringe::for_process(process);
+        let result = syringe.inject_with_options("invalid path", &InjectOptions::new());
+        let err = result.unwrap_err();
+        assert!(matches!(err, InjectError::RemoteIo(_)), "{:?}", err);
+    }
+}
+
+syringe_test! {
+    fn inject_many_with_valid_paths_succeeds(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        let syringe = Syringe::for_process(process);
+        let results = syringe.inject_many(&[payload_path, payload_path]).unwrap();
+        assert_eq!(results.len(), 2);
+        let first = results[0].as_ref().unwrap();
+        let second = results[1].as_ref().unwrap();
+        assert_eq!(first, second);
+        assert_eq!(
+            Some(*first),
+            syringe.process().find_module_by_path(payload_path).unwrap()
+        );
+    }
+}
+
+syringe_test! {
+    fn inject_many_stops_at_invalid_path(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        let syringe = Syringe::for_process(process);
+        let results = syringe
+            .inject_many(&[payload_path, Path::new("invalid path"), payload_path])
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert_eq!(results[1].as_ref().unwrap_err().raw_os_error(), Some(126));
+    }
+}
+
 syringe_test! {
     fn inject_with_crashed_process_fails_with_process_inaccessible(
         process: OwnedProcess,
@@ -46,3 +214,208 @@ syringe_test! {
         assert!(matches!(err, InjectError::ProcessInaccessible), "{:?}", err);
     }
 }
+
+syringe_test! {
+    fn inject_from_bytes_succeeds_and_cleans_up_on_eject(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        let payload = std::fs::read(payload_path).unwrap();
+
+        let syringe = Syringe::for_process(process);
+        let module = syringe.inject_from_bytes(&payload).unwrap();
+        let file_path = module.file_path().to_path_buf();
+        assert!(file_path.exists());
+        assert_eq!(
+            Some(module.module()),
+            syringe.process().find_module_by_path(&file_path).unwrap()
+        );
+
+        module.eject().unwrap();
+        assert!(!file_path.exists());
+    }
+}
+
+syringe_test! {
+    fn inject_from_bytes_cleans_up_on_eject_through_syringe(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        let payload = std::fs::read(payload_path).unwrap();
+
+        let syringe = Syringe::for_process(process);
+        let module = syringe.inject_from_bytes(&payload).unwrap();
+        let file_path = module.file_path().to_path_buf();
+
+        syringe.eject(module.module()).unwrap();
+        assert!(!file_path.exists());
+
+        let module = syringe.inject_from_bytes(&payload).unwrap();
+        let file_path = module.file_path().to_path_buf();
+
+        syringe.eject_by_path(&file_path).unwrap();
+        assert!(!file_path.exists());
+    }
+}
+
+syringe_test! {
+    fn inject_from_bytes_cleans_up_on_eject_on_drop(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        let payload = std::fs::read(payload_path).unwrap();
+
+        let syringe = Syringe::for_process(process);
+        syringe.set_eject_on_drop(true);
+        let file_path = syringe.inject_from_bytes(&payload).unwrap().file_path().to_path_buf();
+
+        drop(syringe);
+        assert!(!file_path.exists());
+    }
+}
+
+syringe_test! {
+    fn inject_and_eject_with_thread_hijacking_succeeds(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        let syringe = Syringe::with_execution_strategy(
+            process,
+            ExecutionStrategy::ThreadHijacking { thread_id: None, timeout: Some(Duration::from_secs(5)) },
+        );
+        let module = syringe.inject(payload_path).unwrap();
+        assert!(syringe.process().find_module_by_path(payload_path).unwrap().is_some());
+        syringe.eject(module).unwrap();
+    }
+}
+
+syringe_test! {
+    fn inject_with_thread_hijacking_of_foreign_thread_fails(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        let foreign_thread_id = dll_syringe::process::BorrowedProcess::current().thread_ids().unwrap()[0];
+        let syringe = Syringe::with_execution_strategy(
+            process,
+            ExecutionStrategy::ThreadHijacking { thread_id: Some(foreign_thread_id), timeout: Some(Duration::from_secs(5)) },
+        );
+        let result = syringe.inject(payload_path);
+        assert!(
+            matches!(&result, Err(InjectError::Io(err)) if err.kind() == std::io::ErrorKind::InvalidInput),
+            "{:?}",
+            result
+        );
+    }
+}
+
+syringe_test! {
+    fn inject_and_eject_with_apc_succeeds(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        let main_thread_id = process.thread_ids().unwrap()[0];
+        let syringe = Syringe::with_execution_strategy(
+            process,
+            ExecutionStrategy::Apc { thread_id: main_thread_id, timeout: Some(Duration::from_secs(5)) },
+        );
+        let module = syringe.inject(payload_path).unwrap();
+        assert!(syringe.process().find_module_by_path(payload_path).unwrap().is_some());
+        syringe.eject(module).unwrap();
+    }
+}
+
+mod launch_and_inject_succeeds {
+    use super::*;
+    use std::{
+        path::Path,
+        process::{Command, Stdio},
+    };
+
+    #[test]
+    #[cfg(any(
+        target_arch = "x86",
+        all(target_arch = "x86_64", feature = "into-x86-from-x64")
+    ))]
+    fn x86() {
+        test(
+            common::build_test_payload_x86().unwrap(),
+            common::build_test_target_x86().unwrap(),
+        )
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn x86_64() {
+        test(
+            common::build_test_payload_x64().unwrap(),
+            common::build_test_target_x64().unwrap(),
+        )
+    }
+
+    fn test(payload_path: impl AsRef<Path>, target_path: impl AsRef<Path>) {
+        let mut command = Command::new(target_path.as_ref());
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        let syringe = Syringe::launch_and_inject(&mut command, &[payload_path.as_ref()]).unwrap();
+        let _guard = syringe.process().try_to_owned().unwrap().kill_on_drop();
+
+        assert!(syringe.process().is_alive());
+        assert!(syringe
+            .process()
+            .find_module_by_path(payload_path.as_ref())
+            .unwrap()
+            .is_some());
+    }
+}
+
+#[test]
+#[cfg(all(target_arch = "x86", feature = "into-x64-from-x86"))]
+fn inject_x64_and_eject_x64_succeeds() {
+    use dll_syringe::process::OwnedProcess;
+    use std::process::{Command, Stdio};
+
+    let payload_path = common::build_test_payload_x64().unwrap();
+    let target_path = common::build_test_target_x64().unwrap();
+
+    let process: OwnedProcess = Command::new(target_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap()
+        .into();
+    let _guard = process.try_clone().unwrap().kill_on_drop();
+
+    let syringe = Syringe::for_process(process);
+    let module = syringe.inject_x64(payload_path).unwrap();
+    assert_ne!(module, 0);
+    syringe.eject_x64(module).unwrap();
+}
+
+#[test]
+#[cfg(all(target_arch = "x86_64", feature = "into-x86-from-x64"))]
+fn inject_x64_payload_into_x86_target_fails_with_architecture_mismatch() {
+    use dll_syringe::process::OwnedProcess;
+    use std::process::{Command, Stdio};
+
+    let payload_path = common::build_test_payload_x64().unwrap();
+    let target_path = common::build_test_target_x86().unwrap();
+
+    let process: OwnedProcess = Command::new(target_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap()
+        .into();
+    let _guard = process.try_clone().unwrap().kill_on_drop();
+
+    let syringe = Syringe::for_process(process);
+    let err = syringe.inject(&payload_path).unwrap_err();
+    assert!(matches!(err, InjectError::ArchitectureMismatch), "{:?}", err);
+    let err = syringe.inject_many(&[&payload_path]).unwrap_err();
+    assert!(matches!(err, InjectError::ArchitectureMismatch), "{:?}", err);
+}
@@ -0,0 +1,63 @@
+#![cfg(all(windows, feature = "manual-map"))]
+
+use dll_syringe::{
+    error::ManualMapError,
+    process::{PageProtection, Process},
+    Syringe,
+};
+
+#[allow(unused)]
+mod common;
+
+syringe_test! {
+    fn inject_manual_map_with_valid_path_succeeds(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        let syringe = Syringe::for_process(process);
+        let module = syringe.inject_manual_map(payload_path).unwrap();
+        assert!(!module.base().is_null());
+        assert!(module.entry_point().is_some());
+        assert!(syringe.process().is_alive());
+    }
+}
+
+syringe_test! {
+    fn inject_manual_map_does_not_register_module(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        let syringe = Syringe::for_process(process);
+        syringe.inject_manual_map(payload_path).unwrap();
+        assert!(syringe.process().find_module_by_path(payload_path).unwrap().is_none());
+    }
+}
+
+syringe_test! {
+    fn inject_manual_map_protects_sections(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        let syringe = Syringe::for_process(process);
+        let module = syringe.inject_manual_map(payload_path).unwrap();
+
+        let headers = syringe.process().memory_region_at(module.base() as usize).unwrap();
+        assert_eq!(headers.protection(), Some(PageProtection::READONLY));
+
+        let code = syringe
+            .process()
+            .memory_region_at(module.entry_point().unwrap() as usize)
+            .unwrap();
+        assert_eq!(code.protection(), Some(PageProtection::EXECUTE_READ));
+    }
+}
+
+process_test! {
+    fn inject_manual_map_with_invalid_path_fails_with_io(
+        process: OwnedProcess,
+    ) {
+        let syringe = Syringe::for_process(process);
+        let result = syringe.inject_manual_map("invalid path");
+        assert!(matches!(result, Err(ManualMapError::Io(_))), "{:?}", result);
+    }
+}
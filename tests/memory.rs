@@ -0,0 +1,230 @@
+#![cfg(all(windows, feature = "process-memory"))]
+
+use dll_syringe::{
+    error::PointerChainError,
+    process::{
+        memory::{PointerChain, ProcessMemoryBuffer, Ptr32, Ptr64, RemotePtr, RemoteStruct},
+        PageProtection, Pattern, Process,
+    },
+};
+use std::time::Duration;
+
+#[allow(unused)]
+mod common;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, RemoteStruct)]
+#[repr(C)]
+struct Node32 {
+    value: u32,
+    next: Ptr32<Node32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, RemoteStruct)]
+#[repr(C)]
+struct Node64 {
+    value: u32,
+    next: Ptr64<Node64>,
+}
+
+process_test! {
+    fn protect_changes_and_restores_protection(
+        process: OwnedProcess
+    ) {
+        let buffer = ProcessMemoryBuffer::allocate_data_page(process.borrowed()).unwrap();
+        buffer.write(0, &[1, 2, 3, 4]).unwrap();
+
+        let guard = buffer.protect(PageProtection::READONLY).unwrap();
+        assert_eq!(guard.previous_protection(), PageProtection::READWRITE);
+        let region = process.memory_region_at(buffer.as_ptr() as usize).unwrap();
+        assert_eq!(region.protection(), Some(PageProtection::READONLY));
+        assert!(guard.write(0, &[5, 6, 7, 8]).is_err());
+
+        let mut data = [0; 4];
+        guard.read(0, &mut data).unwrap();
+        assert_eq!(data, [1, 2, 3, 4]);
+        drop(guard);
+
+        let region = process.memory_region_at(buffer.as_ptr() as usize).unwrap();
+        assert_eq!(region.protection(), Some(PageProtection::READWRITE));
+        buffer.write(0, &[5, 6, 7, 8]).unwrap();
+    }
+}
+
+process_test! {
+    fn protect_restores_protection_of_each_page(
+        process: OwnedProcess
+    ) {
+        let page_size = ProcessMemoryBuffer::os_page_size();
+        let buffer = ProcessMemoryBuffer::allocate_data(process.borrowed(), 2 * page_size).unwrap();
+        buffer.slice(page_size..).protect(PageProtection::READONLY).unwrap().keep();
+
+        let guard = buffer.protect(PageProtection::EXECUTE_READWRITE).unwrap();
+        assert_eq!(guard.previous_protection(), PageProtection::READWRITE);
+        guard.write(page_size, &[1, 2, 3, 4]).unwrap();
+        drop(guard);
+
+        let first_page = process.memory_region_at(buffer.as_ptr() as usize).unwrap();
+        assert_eq!(first_page.protection(), Some(PageProtection::READWRITE));
+        let second_page = process.memory_region_at(buffer.as_ptr() as usize + page_size).unwrap();
+        assert_eq!(second_page.protection(), Some(PageProtection::READONLY));
+    }
+}
+
+process_test! {
+    fn protect_allows_writing_to_readonly_memory(
+        process: OwnedProcess
+    ) {
+        let buffer = ProcessMemoryBuffer::allocate_data_page(process.borrowed()).unwrap();
+        let readonly = buffer.protect(PageProtection::READONLY).unwrap().keep();
+        assert!(readonly.write(0, &[1, 2, 3, 4]).is_err());
+
+        let guard = readonly.protect(PageProtection::READWRITE).unwrap();
+        assert_eq!(guard.previous_protection(), PageProtection::READONLY);
+        guard.write(0, &[1, 2, 3, 4]).unwrap();
+        guard.restore().unwrap();
+
+        let region = process.memory_region_at(buffer.as_ptr() as usize).unwrap();
+        assert_eq!(region.protection(), Some(PageProtection::READONLY));
+    }
+}
+
+process_test! {
+    fn scan_finds_matches_across_chunks(
+        process: OwnedProcess
+    ) {
+        let buffer = ProcessMemoryBuffer::allocate_data(process.borrowed(), 0x30000).unwrap();
+        let base = buffer.as_ptr() as usize;
+        buffer.write(0x100, &[0x48, 0x8B, 0x01, 0x02, 0xE8]).unwrap();
+        buffer.write(0x10000 - 2, &[0x48, 0x8B, 0x03, 0x04, 0xE8]).unwrap();
+        buffer.write(0x30000 - 5, &[0x48, 0x8B, 0x05, 0x06, 0xE8]).unwrap();
+
+        let pattern = Pattern::parse("48 8B ?? ?? E8").unwrap();
+        assert_eq!(
+            buffer.scan(&pattern).unwrap(),
+            [base + 0x100, base + 0x10000 - 2, base + 0x30000 - 5]
+        );
+        assert_eq!(buffer.scan_first(&pattern).unwrap(), Some(base + 0x100));
+        assert_eq!(
+            buffer.slice(0x200..).scan_first(&pattern).unwrap(),
+            Some(base + 0x10000 - 2)
+        );
+        assert_eq!(buffer.slice(..0x100 + 4).scan_first(&pattern).unwrap(), None);
+    }
+}
+
+process_test! {
+    fn pointer_chain_resolves_with_target_pointer_width(
+        process: OwnedProcess
+    ) {
+        let is_64_bit = process.architecture().unwrap().is_64_bit();
+        let buffer = ProcessMemoryBuffer::allocate_data_page(process.borrowed()).unwrap();
+        let base = buffer.as_ptr() as usize;
+        let write_pointer = |offset: usize, pointer: usize| {
+            if is_64_bit {
+                buffer.write_struct(offset, &(pointer as u64)).unwrap();
+            } else {
+                buffer.write_struct(offset, &(pointer as u32)).unwrap();
+            }
+        };
+        // base -> base + 0x100 - 0x10, base + 0x100 -> base + 0x200, base + 0x300 -> null
+        write_pointer(0, base + 0x100 - 0x10);
+        write_pointer(0x100, base + 0x200);
+        write_pointer(0x300, 0);
+
+        let chain = PointerChain::from_address(base)
+            .with_offset(0x10)
+            .with_offset(0x8);
+        assert_eq!(chain.resolve(process.borrowed()).unwrap(), base + 0x208);
+
+        let chain = PointerChain::from_address(base + 0x300).with_offset(0);
+        assert!(matches!(
+            chain.resolve(process.borrowed()),
+            Err(PointerChainError::NullPointer { hop: 0, address }) if address == base + 0x300
+        ));
+
+        // the second hop leads to the unmapped address 0x10.
+        let chain = PointerChain::from_address(base).with_offsets([0x10, 0x10 - (base as isize + 0x200), 0]);
+        assert!(matches!(
+            chain.resolve(process.borrowed()),
+            Err(PointerChainError::Unreadable { hop: 2, address: 0x10, .. })
+        ));
+    }
+}
+
+process_test! {
+    fn pointer_chain_resolves_module_base(
+        process: OwnedProcess
+    ) {
+        let kernel32 = process.wait_for_module_by_name("kernel32.dll", Duration::from_secs(1)).unwrap().unwrap();
+        let chain = PointerChain::from_module("kernel32.dll", 0x3c);
+        assert_eq!(chain.resolve(process.borrowed()).unwrap(), kernel32.handle() as usize + 0x3c);
+
+        let chain = PointerChain::from_module("missing.dll", 0).with_offset(0);
+        assert!(matches!(
+            chain.resolve(process.borrowed()),
+            Err(PointerChainError::ModuleNotFound(_))
+        ));
+    }
+}
+
+process_test! {
+    fn remote_ptr_reads_and_writes_fields(
+        process: OwnedProcess
+    ) {
+        let buffer = ProcessMemoryBuffer::allocate_data_page(process.borrowed()).unwrap();
+        let base = buffer.as_ptr() as usize;
+
+        let values = [1u32, 2, 3];
+        buffer.write_struct(0x100, &values).unwrap();
+        let first = unsafe { RemotePtr::<u32>::new(process.borrowed(), base + 0x100) };
+        assert_eq!(unsafe { first.add(2) }.read().unwrap(), 3);
+        assert_eq!(unsafe { first.add(2).offset(-1) }.read().unwrap(), 2);
+        unsafe { first.add(1) }.write(&5).unwrap();
+        assert_eq!(unsafe { buffer.read_struct::<[u32; 3]>(0x100) }.unwrap(), [1, 5, 3]);
+
+        if process.architecture().unwrap().is_64_bit() {
+            let second = Node64 { value: 2, next: Ptr64::null() };
+            buffer.write_struct(0x20, &second).unwrap();
+            buffer.write_struct(0, &Node64 { value: 1, next: Ptr64::new((base + 0x20) as u64) }).unwrap();
+
+            let first = unsafe { RemotePtr::<Node64>::new(process.borrowed(), base) };
+            let value = first.field(|node| &node.value);
+            assert_eq!(value.read().unwrap(), 1);
+            value.write(&10).unwrap();
+            let next = unsafe { first.field(|node| &node.next).read_ptr() }.unwrap();
+            assert_eq!(next.address(), base + 0x20);
+            assert_eq!(next.read().unwrap(), second);
+            assert!(unsafe { next.field(|node| &node.next).read_ptr() }.unwrap().is_null());
+            assert_eq!(first.read().unwrap().value, 10);
+        } else {
+            let second = Node32 { value: 2, next: Ptr32::null() };
+            buffer.write_struct(0x20, &second).unwrap();
+            buffer.write_struct(0, &Node32 { value: 1, next: Ptr32::new((base + 0x20) as u32) }).unwrap();
+
+            let first = unsafe { RemotePtr::<Node32>::new(process.borrowed(), base) };
+            let value = first.field(|node| &node.value);
+            assert_eq!(value.read().unwrap(), 1);
+            value.write(&10).unwrap();
+            let next = unsafe { first.field(|node| &node.next).read_ptr() }.unwrap();
+            assert_eq!(next.address(), base + 0x20);
+            assert_eq!(next.read().unwrap(), second);
+            assert!(unsafe { next.field(|node| &node.next).read_ptr() }.unwrap().is_null());
+            assert_eq!(first.read().unwrap().value, 10);
+        }
+    }
+}
+
+process_test! {
+    fn pointer_chain_resolves_remote_ptr(
+        process: OwnedProcess
+    ) {
+        let buffer = ProcessMemoryBuffer::allocate_data_page(process.borrowed()).unwrap();
+        let base = buffer.as_ptr() as usize;
+        buffer.write_struct(0x10, &42u32).unwrap();
+
+        let chain = PointerChain::from_address(base + 0x10);
+        let ptr = unsafe { chain.resolve_ptr::<u32>(process.borrowed()) }.unwrap();
+        assert_eq!(ptr.address(), base + 0x10);
+        assert_eq!(ptr.read().unwrap(), 42);
+    }
+}
@@ -1,4 +1,6 @@
-use dll_syringe::process::Process;
+#![cfg(windows)]
+
+use dll_syringe::process::{Pattern, Process};
 use std::time::Duration;
 
 #[allow(unused)]
@@ -24,8 +26,62 @@ process_test! {
     }
 }
 
+process_test! {
+    fn image_of_kernel32_contains_exports(
+        process: OwnedProcess
+    ) {
+        let kernel32 = process.borrowed().wait_for_module_by_name("kernel32.dll", Duration::from_secs(1)).unwrap().unwrap();
+        let image = kernel32.image().unwrap();
+        assert!(image.is_dll());
+        assert!(image.size_of_image() > 0);
+        assert!(image.sections().iter().any(|section| section.name() == ".text" && section.is_executable()));
+        assert!(image.imports().iter().any(|import| import.module().to_ascii_lowercase().starts_with("ntdll")
+            || import.module().to_ascii_lowercase().starts_with("api-")));
+
+        let load_library = image.find_export("LoadLibraryW").unwrap();
+        assert!(load_library.rva().is_some());
+        assert_eq!(image.find_export(load_library.ordinal()), Some(load_library));
+    }
+}
+
+process_test! {
+    fn scan_kernel32_finds_dos_header(
+        process: OwnedProcess
+    ) {
+        let kernel32 = process.borrowed().wait_for_module_by_name("kernel32.dll", Duration::from_secs(1)).unwrap().unwrap();
+        let pattern = Pattern::parse("4D 5A ?? 00").unwrap();
+        assert_eq!(kernel32.scan_first(&pattern).unwrap(), Some(kernel32.handle() as usize));
+        assert_eq!(kernel32.scan(&pattern).unwrap().first(), Some(&(kernel32.handle() as usize)));
+
+        let pattern = Pattern::parse("DE AD BE EF ?? DE AD BE EF").unwrap();
+        assert!(kernel32.scan(&pattern).unwrap().is_empty());
+    }
+}
+
+#[cfg(feature = "syringe")]
+use dll_syringe::{process::ModuleImage, Syringe};
+
 #[cfg(feature = "syringe")]
-use dll_syringe::Syringe;
+syringe_test! {
+    fn image_of_injected_module_matches_file(
+        process: OwnedProcess,
+        payload_path: &Path,
+    ) {
+        let syringe = Syringe::for_process(process);
+        let module = syringe.inject(payload_path).unwrap();
+
+        let image = module.image().unwrap();
+        let file = ModuleImage::from_file(payload_path).unwrap();
+        assert_eq!(image.image_base(), module.handle() as u64);
+        assert_eq!(image.size_of_image(), file.size_of_image());
+        assert_eq!(image.entry_point(), file.entry_point());
+        assert_eq!(image.time_date_stamp(), file.time_date_stamp());
+        assert_eq!(image.sections(), file.sections());
+        assert_eq!(image.imports(), file.imports());
+        assert_eq!(image.exports(), file.exports());
+        assert!(image.find_export("DllMain").is_some());
+    }
+}
 
 #[cfg(feature = "syringe")]
 syringe_test! {
@@ -0,0 +1,93 @@
+use dll_syringe::{process::ProcessArchitecture, PayloadInfo};
+use std::path::{Path, PathBuf};
+
+#[cfg(windows)]
+#[allow(unused)]
+mod common;
+
+fn fixture_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+#[test]
+fn payload_info_of_fixture_dlls() {
+    for (name, architecture, is_64) in [
+        ("fixture_x86.dll", ProcessArchitecture::X86, false),
+        ("fixture_x64.dll", ProcessArchitecture::X64, true),
+        ("fixture_arm64.dll", ProcessArchitecture::Arm64, true),
+    ] {
+        let payload = PayloadInfo::from_path(fixture_path(name)).unwrap();
+        assert_eq!(payload.architecture(), Some(architecture));
+        assert_eq!(payload.is_64(), is_64);
+        assert!(payload.is_compatible_with(architecture));
+
+        assert_eq!(payload.payload_procedures(), &["add".to_string()]);
+        assert!(!payload.is_payload_procedure("version"));
+        assert_eq!(
+            payload
+                .exports()
+                .map(|export| export.symbol().to_string())
+                .collect::<Vec<_>>(),
+            ["add", "call_imports", "version", "#4", "ForwardedHeapAlloc"]
+        );
+        assert_eq!(
+            payload.imported_modules().collect::<Vec<_>>(),
+            ["KERNEL32.dll", "WS2_32.dll"]
+        );
+    }
+}
+
+#[test]
+fn payload_info_of_arm64_fixture_is_compatible_with_arm64ec() {
+    let payload = PayloadInfo::from_path(fixture_path("fixture_arm64.dll")).unwrap();
+    assert!(payload.is_compatible_with(ProcessArchitecture::Arm64EC));
+    assert!(!payload.is_compatible_with(ProcessArchitecture::X64));
+}
+
+#[test]
+fn payload_info_of_invalid_file_fails() {
+    let file = std::fs::read(fixture_path("fixture_x64.dll")).unwrap();
+    assert!(PayloadInfo::parse(&file[..0x40]).is_err());
+    assert!(PayloadInfo::from_path(fixture_path("missing.dll")).is_err());
+}
+
+#[test]
+#[cfg(windows)]
+fn payload_info_of_test_payload_x64() {
+    let payload = PayloadInfo::from_path(common::build_test_payload_x64().unwrap()).unwrap();
+    assert!(payload.is_64());
+    assert_eq!(payload.architecture(), Some(ProcessArchitecture::X64));
+    assert!(payload.is_compatible_with(ProcessArchitecture::X64));
+    assert!(!payload.is_compatible_with(ProcessArchitecture::X86));
+
+    assert!(payload.is_payload_procedure("add"));
+    assert!(payload.is_payload_procedure("sum"));
+    assert!(payload.is_payload_procedure("does_panic"));
+    assert!(!payload.is_payload_procedure("add_raw"));
+    assert!(payload
+        .exports()
+        .any(|export| export.name() == Some("add_raw")));
+    assert!(payload
+        .imported_modules()
+        .any(|module| module.eq_ignore_ascii_case("kernel32.dll")));
+}
+
+#[test]
+#[cfg(all(
+    windows,
+    any(
+        target_arch = "x86",
+        all(target_arch = "x86_64", feature = "into-x86-from-x64")
+    )
+))]
+fn payload_info_of_test_payload_x86() {
+    let payload = PayloadInfo::from_path(common::build_test_payload_x86().unwrap()).unwrap();
+    assert!(!payload.is_64());
+    assert_eq!(payload.architecture(), Some(ProcessArchitecture::X86));
+    assert!(payload.is_compatible_with(ProcessArchitecture::X86));
+    assert!(!payload.is_compatible_with(ProcessArchitecture::X64));
+    assert!(payload.is_payload_procedure("add"));
+}
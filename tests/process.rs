@@ -1,5 +1,13 @@
-use dll_syringe::process::{BorrowedProcess, OwnedProcess, Process};
-use std::{fs, time::Duration};
+#![cfg(windows)]
+
+use dll_syringe::{
+    error::MissingAccess,
+    process::{
+        BorrowedProcess, MemoryRegionFilter, MemoryState, MemoryType, ModuleListFilter,
+        OwnedProcess, Process, ProcessAccess, ProcessArchitecture, ProcessInfo, ProcessQuery,
+    },
+};
+use std::{fs, io, time::Duration};
 
 #[allow(unused)]
 mod common;
@@ -20,6 +28,37 @@ process_test! {
     }
 }
 
+process_test! {
+    fn list_module_infos_contains_kernel32(
+        process: OwnedProcess
+    ) {
+        let kernel32 = process.wait_for_module_by_name("kernel32.dll", Duration::from_secs(1)).unwrap().unwrap();
+        let module_infos = process.module_infos().unwrap();
+        let kernel32_info = module_infos
+            .iter()
+            .find(|info| info.handle() == kernel32.handle())
+            .unwrap();
+        assert!(kernel32_info.name().eq_ignore_ascii_case("kernel32.dll"));
+        assert_eq!(kernel32_info.path(), kernel32.path().unwrap());
+        assert!(kernel32_info.size() > 0);
+        assert!(kernel32_info.contains(kernel32_info.entry_point().unwrap()));
+    }
+}
+
+process_test! {
+    fn list_module_infos_with_filter_matches_architecture(
+        process: OwnedProcess
+    ) {
+        let filter = if process.architecture().unwrap().is_x86() {
+            ModuleListFilter::Only32Bit
+        } else {
+            ModuleListFilter::Only64Bit
+        };
+        let matching = process.module_infos_with_filter(filter).unwrap();
+        assert!(matching.iter().any(|info| info.name().eq_ignore_ascii_case("kernel32.dll")));
+    }
+}
+
 process_test! {
     fn wait_for_module_with_kernel32_succeeds(
         process: OwnedProcess
@@ -122,7 +161,7 @@ fn current_process_is_current() {
 
 #[test]
 fn remote_process_is_not_current() {
-    let mut all = OwnedProcess::all().into_iter();
+    let mut all = OwnedProcess::all().unwrap().into_iter();
     let process_a = all.next().unwrap();
     let process_b = all.next().unwrap();
     assert!(!process_a.is_current() || !process_b.is_current());
@@ -138,3 +177,151 @@ fn current_pseudo_process_eq_current_process() {
     assert_eq!(pseudo.try_to_owned().unwrap(), normal);
     assert_eq!(pseudo, normal.try_clone().unwrap());
 }
+
+#[test]
+fn current_process_architecture_is_current() {
+    let architecture = BorrowedProcess::current().architecture().unwrap();
+    assert_eq!(architecture.is_64_bit(), ProcessArchitecture::current().is_64_bit());
+}
+
+process_test! {
+    fn architecture_of_running_succeeds(
+        process: OwnedProcess
+    ) {
+        let architecture = process.architecture().unwrap();
+        let host = ProcessArchitecture::host().unwrap();
+        assert!(host.is_64_bit() || !architecture.is_64_bit());
+    }
+}
+
+process_test! {
+    fn process_info_all_contains_process(
+        process: OwnedProcess
+    ) {
+        let pid = process.pid().unwrap().get();
+        let infos = ProcessInfo::all().unwrap();
+        let info = infos.iter().find(|info| info.pid() == pid).unwrap();
+        assert_eq!(info.parent_pid(), std::process::id());
+        assert_eq!(info.name(), process.base_name().unwrap());
+        assert!(info.thread_count() > 0);
+        // child processes inherit the session of their parent.
+        let current = infos.iter().find(|info| info.pid() == std::process::id()).unwrap();
+        assert_eq!(info.session_id(), current.session_id());
+        // the architecture is not part of the snapshot, but determined by opening the process.
+        assert_eq!(info.architecture().unwrap(), process.architecture().unwrap());
+    }
+}
+
+#[test]
+fn process_info_all_contains_session_of_current_process() {
+    let mut session_id = 0;
+    let result = unsafe {
+        winapi::um::processthreadsapi::ProcessIdToSessionId(std::process::id(), &mut session_id)
+    };
+    assert_ne!(result, 0);
+
+    let infos = ProcessInfo::all().unwrap();
+    let current = infos.iter().find(|info| info.pid() == std::process::id()).unwrap();
+    assert_eq!(current.session_id(), session_id);
+    assert_eq!(
+        current.name(),
+        std::env::current_exe().unwrap().file_name().unwrap()
+    );
+    // the idle process has no name.
+    assert!(infos.iter().any(|info| info.pid() == 0 && info.name().is_empty()));
+}
+
+process_test! {
+    fn process_query_finds_process_by_name_and_parent(
+        process: OwnedProcess
+    ) {
+        let name = process.base_name().unwrap().to_string_lossy().into_owned();
+        let query = ProcessQuery::new()
+            .with_name_glob(format!("{}*", &name[..name.len() - 4].to_uppercase()))
+            .with_parent_pid(std::process::id())
+            .with_architecture(process.architecture().unwrap());
+        let found = query.find_all().unwrap();
+        assert!(found.contains(&process));
+
+        let found = ProcessQuery::new()
+            .with_name_ignore_case(name.to_uppercase())
+            .with_parent_pid(std::process::id())
+            .find_first()
+            .unwrap();
+        assert!(found.is_some());
+
+        let found = ProcessQuery::new()
+            .with_name(name)
+            .with_parent_pid(0)
+            .find_infos()
+            .unwrap();
+        assert!(found.iter().all(|info| info.pid() != process.pid().unwrap().get()));
+    }
+}
+
+process_test! {
+    fn open_with_limited_access_records_access(
+        process: OwnedProcess
+    ) {
+        let pid = process.pid().unwrap().get();
+        let limited = OwnedProcess::open(pid, ProcessAccess::QUERY_LIMITED_INFORMATION).unwrap();
+        assert_eq!(limited.access(), ProcessAccess::QUERY_LIMITED_INFORMATION);
+        assert_eq!(limited.path().unwrap(), process.path().unwrap());
+        assert_eq!(limited.architecture().unwrap(), process.architecture().unwrap());
+        assert_eq!(limited.try_clone().unwrap().access(), limited.access());
+
+        let injection = OwnedProcess::from_pid(pid).unwrap();
+        assert!(injection.access().contains(ProcessAccess::INJECTION));
+    }
+}
+
+process_test! {
+    fn operations_without_access_fail_with_missing_access(
+        process: OwnedProcess
+    ) {
+        let pid = process.pid().unwrap().get();
+        let limited = OwnedProcess::open(pid, ProcessAccess::QUERY_LIMITED_INFORMATION).unwrap();
+
+        let err = limited.kill().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert_eq!(
+            MissingAccess::from_io_error(&err),
+            Some(MissingAccess(ProcessAccess::TERMINATE))
+        );
+
+        let err = limited.modules().unwrap_err();
+        assert_eq!(
+            MissingAccess::from_io_error(&err),
+            Some(MissingAccess(ProcessAccess::QUERY_INFORMATION | ProcessAccess::VM_READ))
+        );
+        assert!(process.is_alive());
+    }
+}
+
+process_test! {
+    fn memory_regions_contain_kernel32_image(
+        process: OwnedProcess
+    ) {
+        let kernel32 = process.wait_for_module_by_name("kernel32.dll", Duration::from_secs(1)).unwrap().unwrap();
+        let kernel32_info = kernel32.info().unwrap();
+
+        let regions = process.memory_regions().unwrap();
+        assert!(regions.windows(2).all(|pair| pair[0].end() == pair[1].base()));
+        assert!(regions.iter().any(|region| region.state() == MemoryState::Free));
+
+        let kernel32_regions = kernel32.memory_regions().unwrap();
+        assert_eq!(kernel32_regions.first().unwrap().base(), kernel32_info.base());
+        assert!(kernel32_regions.iter().all(|region| kernel32_info.contains(region.base())));
+        assert!(kernel32_regions.iter().any(|region| region.is_executable()));
+
+        let executable_regions = process
+            .memory_regions_with_filter(&MemoryRegionFilter::new().with_image().with_executable())
+            .unwrap();
+        let region = executable_regions
+            .iter()
+            .find(|region| kernel32_info.contains(region.base()))
+            .unwrap();
+        assert_eq!(region.memory_type(), Some(MemoryType::Image));
+        assert_eq!(region.module(process.borrowed()).unwrap().unwrap().handle(), kernel32.handle());
+    }
+}
@@ -1,6 +1,9 @@
-#![cfg(feature = "rpc-core")]
+#![cfg(all(windows, feature = "rpc-core"))]
 
-use dll_syringe::{process::Process, Syringe};
+use dll_syringe::{
+    process::{ExportRef, ModuleListFilter, Process},
+    Syringe,
+};
 
 #[allow(unused)]
 mod common;
@@ -37,6 +40,89 @@ mod core {
         }
     }
 
+    process_test! {
+        fn get_procedure_address_of_forwarded_fn(
+            process: OwnedProcess,
+        ) {
+            let syringe = Syringe::for_process(process);
+
+            let kernel32 = syringe.process().wait_for_module_by_name("kernel32.dll", Duration::from_secs(1)).unwrap().unwrap();
+            let ntdll = syringe.process().find_module_by_name("ntdll.dll").unwrap().unwrap();
+            // HeapAlloc is forwarded to NTDLL.RtlAllocateHeap
+            let heap_alloc = syringe.get_procedure_address(kernel32, "HeapAlloc").unwrap();
+            let rtl_allocate_heap = syringe.get_procedure_address(ntdll, "RtlAllocateHeap").unwrap();
+            assert!(heap_alloc.is_some());
+            assert_eq!(heap_alloc, rtl_allocate_heap);
+        }
+    }
+
+    process_test! {
+        fn get_procedure_address_of_api_set_forwarded_fn(
+            process: OwnedProcess,
+        ) {
+            let syringe = Syringe::for_process(process);
+
+            let kernel32 = syringe.process().wait_for_module_by_name("kernel32.dll", Duration::from_secs(1)).unwrap().unwrap();
+            let kernelbase = syringe.process().find_module_by_name("kernelbase.dll").unwrap().unwrap();
+            // WaitOnAddress is forwarded to api-ms-win-core-synch-l1-2-0.WaitOnAddress, which is implemented by kernelbase
+            let kernel32_wait_on_address = syringe.get_procedure_address(kernel32, "WaitOnAddress").unwrap();
+            let kernelbase_wait_on_address = syringe.get_procedure_address(kernelbase, "WaitOnAddress").unwrap();
+            assert!(kernel32_wait_on_address.is_some());
+            assert_eq!(kernel32_wait_on_address, kernelbase_wait_on_address);
+        }
+    }
+
+    process_test! {
+        fn get_procedure_address_of_forwarded_fn_in_module_of_target_bitness(
+            process: OwnedProcess,
+        ) {
+            let syringe = Syringe::for_process(process);
+
+            let kernel32 = syringe.process().wait_for_module_by_name("kernel32.dll", Duration::from_secs(1)).unwrap().unwrap();
+            // HeapAlloc is forwarded to NTDLL.RtlAllocateHeap, but wow64 processes contain a 64-bit ntdll as well.
+            let heap_alloc = syringe.get_procedure_address(kernel32, String::from("HeapAlloc")).unwrap().unwrap();
+            let filter = if syringe.process().architecture().unwrap().is_64_bit() {
+                ModuleListFilter::Only64Bit
+            } else {
+                ModuleListFilter::Only32Bit
+            };
+            let ntdll = syringe.process().module_infos_with_filter(filter).unwrap()
+                .into_iter()
+                .find(|module| module.name().eq_ignore_ascii_case("ntdll.dll"))
+                .unwrap();
+            assert!(ntdll.contains(heap_alloc as usize));
+        }
+    }
+
+    process_test! {
+        fn get_procedure_address_by_ordinal(
+            process: OwnedProcess,
+        ) {
+            let syringe = Syringe::for_process(process);
+
+            let kernel32 = syringe.process().wait_for_module_by_name("kernel32.dll", Duration::from_secs(1)).unwrap().unwrap();
+            let open_process = syringe.get_procedure_address(kernel32, "OpenProcess").unwrap();
+            assert!(open_process.is_some());
+
+            // the ordinals of kernel32 differ between windows versions, so we have to search for the one of OpenProcess.
+            let ordinal = (1..=4096).find(|&ordinal| {
+                syringe.get_procedure_address(kernel32, ExportRef::Ordinal(ordinal)).unwrap() == open_process
+            });
+            assert!(ordinal.is_some());
+        }
+    }
+
+    process_test! {
+        fn get_procedure_address_by_invalid_ordinal(
+            process: OwnedProcess,
+        ) {
+            let syringe = Syringe::for_process(process);
+            let module = syringe.process().wait_for_module_by_name("kernel32.dll", Duration::from_secs(1)).unwrap().unwrap();
+            let invalid = syringe.get_procedure_address(module, u16::MAX).unwrap();
+            assert!(invalid.is_none());
+        }
+    }
+
     process_test! {
         fn get_procedure_address_of_invalid(
             process: OwnedProcess,
@@ -193,6 +279,20 @@ mod raw {
         }
     }
 
+    syringe_test! {
+        fn call_mixed_float_and_int_args(
+            process: OwnedProcess,
+            payload_path: &Path,
+        ) {
+            let syringe = Syringe::for_process(process);
+            let module = syringe.inject(payload_path).unwrap();
+
+            let remote_mul_add = unsafe { syringe.get_raw_procedure::<extern "system" fn(u32, f32, u32, f32) -> u32>(module, "mul_add_mixed_raw") }.unwrap().unwrap();
+            let mul_add_result = remote_mul_add.call(2, 1.5, 3, 2.5).unwrap();
+            assert_eq!(mul_add_result, 10);
+        }
+    }
+
     syringe_test! {
         fn call_simple_c_call(
             process: OwnedProcess,